@@ -45,7 +45,7 @@ async fn session(address: impl ToSocketAddrs) -> Result<(), SqlError> {
         .await
         .map_err(SqlError::CouldNotWriteToConnection)?;
 
-    loop {
+    'session: loop {
         let input = rep_without_the_l();
 
         let message = if let Some(command) = parse_command(&input) {
@@ -56,20 +56,29 @@ async fn session(address: impl ToSocketAddrs) -> Result<(), SqlError> {
 
         message.write(&mut stream, serialisation_manager).await?;
 
-        let response = Message::read(&mut stream, serialisation_manager).await?;
-
-        match response.body {
-            // TODO: This doesn't work, because we're waiting on user input synchronously
-            // That has to be made async
-            MessageBody::Close => break,
-            MessageBody::Ok => (),
-            MessageBody::Str(message) => println!("{message}"),
-            MessageBody::Command(uhoh) => {
-                panic!("Client received a command? What is going on ({uhoh:?})")
+        // The rows of a query come in as many rowsets as it takes, followed by the actual response
+        loop {
+            let response = Message::read(&mut stream, serialisation_manager).await?;
+
+            match response.body {
+                // TODO: This doesn't work, because we're waiting on user input synchronously
+                // That has to be made async
+                MessageBody::Close => break 'session,
+                MessageBody::Ok => (),
+                MessageBody::Str(message) => println!("{message}"),
+                MessageBody::Command(uhoh) => {
+                    panic!("Client received a command? What is going on ({uhoh:?})")
+                }
+                // Actually this never gets sent, errors get sent as a Str because serialisation is hard
+                MessageBody::Error(error) => println!("ERROR: {error:?}"),
+                MessageBody::RowSet(rowset) => {
+                    println!("{rowset:?}");
+
+                    continue;
+                }
             }
-            // Actually this never gets sent, errors get sent as a Str because serialisation is hard
-            MessageBody::Error(error) => println!("ERROR: {error:?}"),
-            MessageBody::RowSet(rowset) => println!("{rowset:?}"),
+
+            break;
         }
     }
 
@@ -0,0 +1,258 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use sql_parse::parser::{ArithmeticOperator, ColumnType};
+
+use super::index::IndexKey;
+use super::{Row, StatementContext};
+use crate::types::{
+    AggregateFunction, ColumnValue, PreparedValueExpression, PreparedWhere, ValueExpression, Where,
+};
+use crate::{Result, SqlError};
+
+impl AggregateFunction {
+    pub fn result_type(&self, argument: ColumnType) -> Result<ColumnType> {
+        return match (self, argument) {
+            (AggregateFunction::Count, _) => Ok(ColumnType::BigInt),
+            (
+                AggregateFunction::Sum,
+                ColumnType::SmallInt | ColumnType::Int | ColumnType::BigInt,
+            ) => Ok(ColumnType::BigInt),
+            // Like arithmetic, these aren't limited to the size of the column
+            (
+                AggregateFunction::Sum | AggregateFunction::Average,
+                ColumnType::SmallInt
+                | ColumnType::Int
+                | ColumnType::BigInt
+                | ColumnType::Decimal(_),
+            ) => Ok(ColumnType::Decimal(None)),
+            (AggregateFunction::Sum | AggregateFunction::Average, ColumnType::Float) => {
+                Ok(ColumnType::Float)
+            }
+            (AggregateFunction::Sum, ColumnType::Interval) => Ok(ColumnType::Interval),
+            (AggregateFunction::Min | AggregateFunction::Max, _) => Ok(argument),
+            _ => Err(SqlError::InvalidAggregate(*self, argument)),
+        };
+    }
+
+    /// Combines the values of a group of rows, ignoring NULLs.
+    fn apply(&self, values: Vec<ColumnValue>) -> Result<ColumnValue> {
+        let values: Vec<_> = values
+            .into_iter()
+            .filter(|value| !value.is_null())
+            .collect();
+
+        if let AggregateFunction::Count = self {
+            return Ok(ColumnValue::Int(values.len() as i64));
+        }
+
+        let count = values.len();
+
+        let mut values = values.into_iter();
+
+        // Only COUNT gives a value for no rows, even the sum of nothing is NULL rather than 0
+        let Some(first) = values.next() else {
+            return Ok(ColumnValue::Null);
+        };
+
+        return match self {
+            AggregateFunction::Count => unreachable!(),
+            AggregateFunction::Sum => values.try_fold(first, |total, value| {
+                total.apply(ArithmeticOperator::Add, &value)
+            }),
+            AggregateFunction::Average => {
+                let total = values.try_fold(first, |total, value| {
+                    total.apply(ArithmeticOperator::Add, &value)
+                })?;
+
+                // Make sure ints don't get integer division
+                let total = match total {
+                    ColumnValue::Int(total) => ColumnValue::Decimal(total.into()),
+                    other => other,
+                };
+
+                total.apply(ArithmeticOperator::Divide, &ColumnValue::Int(count as i64))
+            }
+            AggregateFunction::Min => {
+                Ok(
+                    values.fold(first, |minimum, value| match value.total_cmp(&minimum) {
+                        Ordering::Less => value,
+                        _ => minimum,
+                    }),
+                )
+            }
+            AggregateFunction::Max => {
+                Ok(
+                    values.fold(first, |maximum, value| match value.total_cmp(&maximum) {
+                        Ordering::Greater => value,
+                        _ => maximum,
+                    }),
+                )
+            }
+        };
+    }
+}
+
+impl<Column> ValueExpression<Column> {
+    pub fn first_aggregate(&self) -> Option<AggregateFunction> {
+        return match self {
+            ValueExpression::Column(_)
+            | ValueExpression::Value(_)
+            | ValueExpression::Function(_) => None,
+            ValueExpression::Binary { left, right, .. } => {
+                left.first_aggregate().or_else(|| right.first_aggregate())
+            }
+            ValueExpression::Unary { operand, .. } => operand.first_aggregate(),
+            ValueExpression::Aggregate { function, .. } => Some(*function),
+            ValueExpression::Extract { source, .. } => source.first_aggregate(),
+        };
+    }
+}
+
+impl<Column> Where<Column> {
+    pub fn first_aggregate(&self) -> Option<AggregateFunction> {
+        return match self {
+            Where::Comparison { left, right, .. } => {
+                left.first_aggregate().or_else(|| right.first_aggregate())
+            }
+            Where::And(left, right) | Where::Or(left, right) => {
+                left.first_aggregate().or_else(|| right.first_aggregate())
+            }
+            Where::IsNull { expression, .. } => expression.first_aggregate(),
+            Where::Not(inner) => inner.first_aggregate(),
+        };
+    }
+}
+
+impl PreparedValueExpression {
+    /// Finds a column that is used outside of an aggregate and isn't one of the `grouped` columns,
+    /// which would have a different value for each row in the group.
+    pub fn ungrouped_column(&self, grouped: &[usize]) -> Option<usize> {
+        return match self {
+            ValueExpression::Column(index) if !grouped.contains(index) => Some(*index),
+            ValueExpression::Column(_)
+            | ValueExpression::Value(_)
+            | ValueExpression::Function(_) => None,
+            ValueExpression::Binary { left, right, .. } => left
+                .ungrouped_column(grouped)
+                .or_else(|| right.ungrouped_column(grouped)),
+            ValueExpression::Unary { operand, .. } => operand.ungrouped_column(grouped),
+            ValueExpression::Aggregate { .. } => None,
+            ValueExpression::Extract { source, .. } => source.ungrouped_column(grouped),
+        };
+    }
+
+    /// Replaces all aggregates with their value over `rows`.
+    pub fn resolve_aggregates(
+        &self,
+        rows: &[&Row],
+        context: &StatementContext,
+    ) -> Result<PreparedValueExpression> {
+        return match self {
+            ValueExpression::Column(_)
+            | ValueExpression::Value(_)
+            | ValueExpression::Function(_) => Ok(self.clone()),
+            ValueExpression::Binary {
+                left,
+                operator,
+                right,
+            } => Ok(ValueExpression::Binary {
+                left: Box::new(left.resolve_aggregates(rows, context)?),
+                operator: *operator,
+                right: Box::new(right.resolve_aggregates(rows, context)?),
+            }),
+            ValueExpression::Unary { operator, operand } => Ok(ValueExpression::Unary {
+                operator: *operator,
+                operand: Box::new(operand.resolve_aggregates(rows, context)?),
+            }),
+            ValueExpression::Aggregate { function, argument } => {
+                let values = match argument {
+                    Some(argument) => rows
+                        .iter()
+                        .map(|row| row.evaluate(argument, context))
+                        .collect::<Result<Vec<_>>>()?,
+                    // `COUNT(*)` just counts rows
+                    None => rows.iter().map(|_| ColumnValue::Bool(true)).collect(),
+                };
+
+                Ok(ValueExpression::Value(function.apply(values)?))
+            }
+            ValueExpression::Extract { field, source } => Ok(ValueExpression::Extract {
+                field: *field,
+                source: Box::new(source.resolve_aggregates(rows, context)?),
+            }),
+        };
+    }
+}
+
+impl PreparedWhere {
+    pub fn ungrouped_column(&self, grouped: &[usize]) -> Option<usize> {
+        return match self {
+            Where::Comparison { left, right, .. } => left
+                .ungrouped_column(grouped)
+                .or_else(|| right.ungrouped_column(grouped)),
+            Where::And(left, right) | Where::Or(left, right) => left
+                .ungrouped_column(grouped)
+                .or_else(|| right.ungrouped_column(grouped)),
+            Where::IsNull { expression, .. } => expression.ungrouped_column(grouped),
+            Where::Not(inner) => inner.ungrouped_column(grouped),
+        };
+    }
+
+    pub fn resolve_aggregates(
+        &self,
+        rows: &[&Row],
+        context: &StatementContext,
+    ) -> Result<PreparedWhere> {
+        let resolve =
+            |clause: &PreparedWhere| clause.resolve_aggregates(rows, context).map(Box::new);
+
+        return match self {
+            Where::Comparison {
+                left,
+                operator,
+                right,
+            } => Ok(Where::Comparison {
+                left: left.resolve_aggregates(rows, context)?,
+                operator: *operator,
+                right: right.resolve_aggregates(rows, context)?,
+            }),
+            Where::IsNull {
+                expression,
+                negated,
+            } => Ok(Where::IsNull {
+                expression: expression.resolve_aggregates(rows, context)?,
+                negated: *negated,
+            }),
+            Where::And(left, right) => Ok(Where::And(resolve(left)?, resolve(right)?)),
+            Where::Or(left, right) => Ok(Where::Or(resolve(left)?, resolve(right)?)),
+            Where::Not(inner) => Ok(Where::Not(resolve(inner)?)),
+        };
+    }
+}
+
+/// Splits rows into groups with equal values for `group_by`, ordered by those values.
+///
+/// Without any `group_by` expressions, all rows form a single (possibly empty) group.
+pub fn group_rows<'a>(
+    rows: Vec<&'a Row>,
+    group_by: &[PreparedValueExpression],
+    context: &StatementContext,
+) -> Result<Vec<Vec<&'a Row>>> {
+    if group_by.is_empty() {
+        return Ok(vec![rows]);
+    }
+
+    let mut groups: BTreeMap<IndexKey, Vec<&Row>> = BTreeMap::new();
+
+    for row in rows {
+        let key = group_by
+            .iter()
+            .map(|expression| row.evaluate(expression, context))
+            .collect::<Result<Vec<_>>>()?;
+
+        groups.entry(IndexKey(key)).or_default().push(row);
+    }
+
+    return Ok(groups.into_values().collect());
+}
@@ -0,0 +1,363 @@
+use sql_parse::parser::{ColumnConstraint, Expression};
+
+use super::expression::{operands, try_map_expression};
+use super::{serial_sequences, Database, StatementContext, Table};
+use crate::types::{ColumnDefinition, ColumnName, ColumnValue, TableName};
+use crate::{Result, SqlError};
+
+impl Database {
+    /// Adds a column to a table, whose existing rows get the default of the column, or NULL.
+    pub fn add_column(&mut self, table_name: &TableName, column: ColumnDefinition) -> Result<()> {
+        let (columns, sequences) = serial_sequences(table_name, vec![column])?;
+
+        return self.with_sequences(sequences, |database| {
+            // Not through `Database::table`, since the sequences are borrowed at the same time
+            let table = database
+                .tables
+                .get(&table_name.0)
+                .ok_or_else(|| SqlError::TableDoesNotExist(table_name.clone()))?;
+
+            let mut definitions = table.column_definitions();
+
+            definitions.extend(columns);
+
+            let mut altered = Table::new(
+                table_name.clone(),
+                definitions,
+                table.constraints.clone(),
+                table.schema.unique.clone(),
+                table.schema.checks.clone(),
+            )?;
+
+            for index in &table.schema.indexes {
+                altered.create_index(index.clone())?;
+            }
+
+            database.validate_sequences(&altered)?;
+
+            let default = altered.schema.defaults.last().cloned().flatten();
+
+            let context = StatementContext::new(database);
+
+            let mut rows = vec![];
+
+            for row in &table.values {
+                let value = match &default {
+                    Some(default) => context.evaluate(default)?,
+                    None => ColumnValue::Null,
+                };
+
+                rows.push(row.0.iter().cloned().chain([value]).collect());
+            }
+
+            database.keep_sequence_values(&context);
+
+            return database.replace_table(altered, rows, &context);
+        });
+    }
+
+    /// Drops a column of a table, together with the keys, foreign keys, checks and indexes that use it.
+    ///
+    /// Fails if a foreign key of another table references the column.
+    pub fn drop_column(&mut self, table_name: &TableName, column: &ColumnName) -> Result<()> {
+        let table = self.table(table_name)?;
+
+        let index = table.column_index(column.clone())?;
+
+        let name = table.schema.column_names[index].clone();
+
+        for (child, constraint) in self.references_to(table_name) {
+            if child.0 != table_name.0 && constraint.foreign_column == name {
+                return Err(SqlError::ColumnIsReferenced(name, child));
+            }
+        }
+
+        let mut definitions = table.column_definitions();
+
+        definitions.remove(index);
+
+        let constraints = table
+            .constraints
+            .iter()
+            .filter(|constraint| {
+                let is_own = constraint.foreign_table.0 == table_name.0;
+
+                constraint.column != name && !(is_own && constraint.foreign_column == name)
+            })
+            .cloned()
+            .collect();
+
+        let keys = table
+            .schema
+            .unique
+            .iter()
+            .filter(|key| !key.columns.contains(&name))
+            .cloned()
+            .collect();
+
+        let checks = table
+            .schema
+            .checks
+            .iter()
+            .filter(|check| !table.mentions(check, &name))
+            .cloned()
+            .collect();
+
+        let mut altered = Table::new(table_name.clone(), definitions, constraints, keys, checks)?;
+
+        for index in &table.schema.indexes {
+            if !index.columns.contains(&name) {
+                altered.create_index(index.clone())?;
+            }
+        }
+
+        let rows = table
+            .values
+            .iter()
+            .map(|row| {
+                let mut values = row.0.clone();
+
+                values.remove(index);
+
+                values
+            })
+            .collect();
+
+        self.replace_table(altered, rows, &StatementContext::default())?;
+
+        // The sequence of a dropped `SERIAL` column isn't used anymore
+        let unused = self
+            .sequences
+            .values()
+            .filter(|sequence| {
+                sequence
+                    .owner
+                    .as_ref()
+                    .is_some_and(|owner| owner.0 == table_name.0)
+            })
+            .filter(|sequence| {
+                !self
+                    .tables
+                    .values()
+                    .any(|table| table.uses_sequence(&sequence.name))
+            })
+            .map(|sequence| sequence.name.0.clone())
+            .collect::<Vec<_>>();
+
+        for name in unused {
+            self.sequences.remove(&name);
+        }
+
+        return Ok(());
+    }
+
+    /// Renames a column of a table, including where its keys, foreign keys, checks and indexes
+    /// and the foreign keys of other tables refer to it.
+    pub fn rename_column(
+        &mut self,
+        table_name: &TableName,
+        from: &ColumnName,
+        to: ColumnName,
+    ) -> Result<()> {
+        let table = self.table(table_name)?;
+
+        let index = table.column_index(from.clone())?;
+
+        let from = table.schema.column_names[index].clone();
+
+        if table.schema.column_names.contains(&to) {
+            return Err(SqlError::ColumnNameNotUnique(to));
+        }
+
+        let checks = table
+            .schema
+            .checks
+            .iter()
+            .map(|check| {
+                try_map_expression(check, &mut |part| {
+                    let renamed = match part {
+                        Expression::Ident(_) => Expression::Ident(to.0.clone()),
+                        Expression::QualifiedIdent(table_name, _) => {
+                            Expression::QualifiedIdent(table_name.clone(), to.0.clone())
+                        }
+                        _ => return Ok(None),
+                    };
+
+                    return Ok(table.mentions(part, &from).then_some(renamed));
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let table = self.table_mut(table_name)?;
+
+        table.schema.column_names[index] = to.clone();
+
+        table.schema.checks = checks;
+
+        for key in &mut table.schema.unique {
+            for column in &mut key.columns {
+                if *column == from {
+                    *column = to.clone();
+                }
+            }
+        }
+
+        for index in &mut table.schema.indexes {
+            for column in &mut index.columns {
+                if *column == from {
+                    *column = to.clone();
+                }
+            }
+        }
+
+        for constraint in &mut table.constraints {
+            if constraint.column == from {
+                constraint.column = to.clone();
+            }
+        }
+
+        // Including the foreign keys of the table itself
+        for table in self.tables.values_mut() {
+            for constraint in &mut table.constraints {
+                if constraint.foreign_table.0 == table_name.0 && constraint.foreign_column == from {
+                    constraint.foreign_column = to.clone();
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Renames a table, including where foreign keys and sequences refer to it.
+    pub fn rename_table(&mut self, from: &TableName, to: TableName) -> Result<()> {
+        if self.tables.contains_key(&to.0) {
+            return Err(SqlError::DuplicateTable(to.0));
+        }
+
+        let mut table = self
+            .tables
+            .remove(&from.0)
+            .ok_or_else(|| SqlError::TableDoesNotExist(from.clone()))?;
+
+        // Checks can qualify columns with the name of their table
+        table.schema.checks = table
+            .schema
+            .checks
+            .iter()
+            .map(|check| {
+                try_map_expression(check, &mut |part| {
+                    return Ok(match part {
+                        Expression::QualifiedIdent(table_name, column) if *table_name == from.0 => {
+                            Some(Expression::QualifiedIdent(to.0.clone(), column.clone()))
+                        }
+                        _ => None,
+                    });
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        table.schema.name = to.clone();
+
+        self.tables.insert(to.0.clone(), table);
+
+        // Including the foreign keys of the table itself
+        for table in self.tables.values_mut() {
+            for constraint in &mut table.constraints {
+                if constraint.foreign_table.0 == from.0 {
+                    constraint.foreign_table = to.clone();
+                }
+            }
+        }
+
+        // Owned sequences keep their name, since the defaults of the table call nextval with it
+        for sequence in self.sequences.values_mut() {
+            if sequence
+                .owner
+                .as_ref()
+                .is_some_and(|owner| owner.0 == from.0)
+            {
+                sequence.owner = Some(to.clone());
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn table(&self, name: &TableName) -> Result<&Table> {
+        return self
+            .tables
+            .get(&name.0)
+            .ok_or_else(|| SqlError::TableDoesNotExist(name.clone()));
+    }
+
+    fn table_mut(&mut self, name: &TableName) -> Result<&mut Table> {
+        return self
+            .tables
+            .get_mut(&name.0)
+            .ok_or_else(|| SqlError::TableDoesNotExist(name.clone()));
+    }
+
+    /// Replaces the table with the same name as `table`, which is still empty, by it with `rows` as its values.
+    ///
+    /// The rows are checked against all constraints of the new table, like when inserting them.
+    fn replace_table(
+        &mut self,
+        mut table: Table,
+        rows: Vec<Vec<ColumnValue>>,
+        context: &StatementContext,
+    ) -> Result<()> {
+        let checks = table.prepare_checks()?;
+
+        for row in rows {
+            // All columns have a value, so there are no defaults to evaluate
+            let row = table.prepare_row(&None, row, &checks, context)?;
+
+            table.push_row(row);
+        }
+
+        self.validate_foreign_keys(&table)?;
+
+        self.tables.insert(table.schema.name.0.clone(), table);
+
+        return Ok(());
+    }
+}
+
+impl Table {
+    /// The definitions the columns of the table could have been created with,
+    /// without the keys and checks, which are part of the schema separately.
+    fn column_definitions(&self) -> Vec<ColumnDefinition> {
+        let schema = &self.schema;
+
+        return schema
+            .column_names
+            .iter()
+            .zip(&schema.types)
+            .zip(schema.nullable.iter().zip(&schema.defaults))
+            .map(|((name, column_type), (nullable, default))| {
+                let mut constraints = vec![];
+
+                if !nullable {
+                    constraints.push(ColumnConstraint::NotNull);
+                }
+
+                if let Some(default) = default {
+                    constraints.push(ColumnConstraint::Default(default.clone()));
+                }
+
+                ColumnDefinition(name.clone(), *column_type, constraints)
+            })
+            .collect();
+    }
+
+    /// Whether `expression` refers to the column `name` of the table.
+    fn mentions(&self, expression: &Expression, name: &ColumnName) -> bool {
+        if let Ok(reference) = ColumnName::try_from(expression) {
+            return self.refers_to(name, &reference);
+        }
+
+        return operands(expression)
+            .into_iter()
+            .any(|operand| self.mentions(operand, name));
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use sql_parse::parser::{ColumnType, Expression};
+
+use super::{Database, Sequence};
+use crate::types::{
+    ColumnName, ColumnValue, SequenceName, StatementFunction, Timestamp, ValueExpression,
+};
+use crate::{Result, SqlError};
+
+/// What the functions of a statement that don't depend on a row are evaluated with, see [`StatementFunction`].
+///
+/// Made once for every statement, so e.g. every `NOW()` in it is the same time.
+#[derive(Debug, Default)]
+pub struct StatementContext {
+    now: OnceLock<Timestamp>,
+    // Behind a mutex, since rows are evaluated through shared references
+    sequences: Mutex<HashMap<String, Sequence>>,
+}
+
+impl StatementContext {
+    /// A context for a statement on `database`, which can use its sequences.
+    ///
+    /// The values they get have to be kept afterwards, see [`Database::keep_sequence_values`].
+    pub fn new(database: &Database) -> Self {
+        return Self {
+            now: OnceLock::new(),
+            sequences: Mutex::new(database.sequences.clone()),
+        };
+    }
+
+    pub fn call(&self, function: &StatementFunction) -> Result<ColumnValue> {
+        return match function {
+            StatementFunction::Now => Ok(ColumnValue::Timestamp(
+                *self.now.get_or_init(Timestamp::now),
+            )),
+            StatementFunction::NextValue(name) => {
+                self.sequence(name, |sequence| sequence.next_value())
+            }
+            StatementFunction::CurrentValue(name) => {
+                self.sequence(name, |sequence| sequence.current_value())
+            }
+        };
+    }
+
+    fn sequence(
+        &self,
+        name: &SequenceName,
+        value: impl FnOnce(&mut Sequence) -> Result<i64>,
+    ) -> Result<ColumnValue> {
+        let mut sequences = self.sequences();
+
+        let sequence = sequences
+            .get_mut(&name.0)
+            .ok_or_else(|| SqlError::SequenceDoesNotExist(name.clone()))?;
+
+        return value(sequence).map(ColumnValue::Int);
+    }
+
+    fn sequences(&self) -> MutexGuard<'_, HashMap<String, Sequence>> {
+        // A sequence is changed all at once, so it can't be left half changed
+        return self
+            .sequences
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+    }
+
+    /// Evaluates an expression that can't refer to columns, like a value of an `INSERT` or a default.
+    pub fn evaluate(&self, expression: &Expression) -> Result<ColumnValue> {
+        let lookup = |name: &ColumnName| Err(SqlError::NameDoesNotExist(name.clone(), vec![]));
+
+        return ValueExpression::try_from(expression)?.evaluate(&lookup, self);
+    }
+}
+
+impl StatementFunction {
+    pub fn is_same(&self, other: &Self) -> bool {
+        use StatementFunction::*;
+
+        return match (self, other) {
+            (Now, Now) => true,
+            (NextValue(name), NextValue(other_name))
+            | (CurrentValue(name), CurrentValue(other_name)) => name.0 == other_name.0,
+            _ => false,
+        };
+    }
+
+    pub fn result_type(&self) -> ColumnType {
+        return match self {
+            StatementFunction::Now => ColumnType::Timestamp,
+            StatementFunction::NextValue(_) | StatementFunction::CurrentValue(_) => {
+                ColumnType::BigInt
+            }
+        };
+    }
+}
+
+impl Database {
+    /// Keeps the values `nextval` gave during the statement of `context`.
+    ///
+    /// This is done even if the statement failed, since sequences aren't rolled back,
+    /// so a value of a sequence is never given out twice.
+    ///
+    /// Returns whether `nextval` gave any.
+    pub fn keep_sequence_values(&mut self, context: &StatementContext) -> bool {
+        let mut changed = false;
+
+        // Only the values, the statement may have created or dropped sequences in the meantime
+        for (name, sequence) in context.sequences().iter() {
+            if let Some(kept) = self.sequences.get_mut(name) {
+                changed |= kept.last_value != sequence.last_value;
+
+                kept.last_value = sequence.last_value;
+            }
+        }
+
+        return changed;
+    }
+}
@@ -0,0 +1,118 @@
+use std::cmp::Ordering;
+
+use sql_parse::parser::MAX_DECIMAL_PRECISION;
+
+use crate::types::Decimal;
+
+// Minimum number of fractional digits kept when dividing decimals
+const DIVISION_SCALE: u32 = 6;
+
+pub fn unit(scale: u32) -> i128 {
+    return 10i128.pow(scale);
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        return Decimal {
+            mantissa: value as i128,
+            scale: 0,
+        };
+    }
+}
+
+impl Decimal {
+    /// Changes the number of fractional digits, rounding half away from zero if digits are dropped.
+    ///
+    /// Returns `None` if the result doesn't fit.
+    pub fn rescale(self, scale: u32) -> Option<Decimal> {
+        if scale > MAX_DECIMAL_PRECISION {
+            return None;
+        }
+
+        let mantissa = match scale.cmp(&self.scale) {
+            Ordering::Greater => self.mantissa.checked_mul(unit(scale - self.scale))?,
+            Ordering::Equal => self.mantissa,
+            Ordering::Less => {
+                let divisor = unit(self.scale - scale);
+
+                let rounded = self.mantissa / divisor;
+
+                // Division truncates towards zero, so rounding away from it goes in the direction of the sign
+                match (self.mantissa % divisor).abs() >= divisor / 2 {
+                    true => rounded + self.mantissa.signum(),
+                    false => rounded,
+                }
+            }
+        };
+
+        return Some(Decimal { mantissa, scale });
+    }
+
+    /// Rescales both decimals to the larger of their scales.
+    fn aligned_with(self, other: Decimal) -> Option<(Decimal, Decimal)> {
+        let scale = self.scale.max(other.scale);
+
+        return Some((self.rescale(scale)?, other.rescale(scale)?));
+    }
+
+    pub fn checked_add(self, other: Decimal) -> Option<Decimal> {
+        let (left, right) = self.aligned_with(other)?;
+
+        return Some(Decimal {
+            mantissa: left.mantissa.checked_add(right.mantissa)?,
+            scale: left.scale,
+        });
+    }
+
+    pub fn checked_sub(self, other: Decimal) -> Option<Decimal> {
+        let (left, right) = self.aligned_with(other)?;
+
+        return Some(Decimal {
+            mantissa: left.mantissa.checked_sub(right.mantissa)?,
+            scale: left.scale,
+        });
+    }
+
+    pub fn checked_mul(self, other: Decimal) -> Option<Decimal> {
+        let product = Decimal {
+            mantissa: self.mantissa.checked_mul(other.mantissa)?,
+            scale: self.scale + other.scale,
+        };
+
+        return product.rescale(product.scale.min(MAX_DECIMAL_PRECISION));
+    }
+
+    /// Divides, truncating the result to at least [`DIVISION_SCALE`] fractional digits.
+    ///
+    /// Returns `None` if `other` is zero or the result doesn't fit.
+    pub fn checked_div(self, other: Decimal) -> Option<Decimal> {
+        let scale = self.scale.max(other.scale).max(DIVISION_SCALE);
+
+        let (left, right) = self.aligned_with(other)?;
+
+        // Both sides have the same scale, so it cancels out
+        return Some(Decimal {
+            mantissa: left
+                .mantissa
+                .checked_mul(unit(scale))?
+                .checked_div(right.mantissa)?,
+            scale,
+        });
+    }
+
+    /// Compares by numeric value, so `1.5` and `1.50` are equal.
+    pub fn compare(&self, other: &Decimal) -> Ordering {
+        let whole = |value: &Decimal| value.mantissa / unit(value.scale);
+
+        // Both parts have the sign of the whole number, so comparing them in order works for negative numbers too
+        return whole(self).cmp(&whole(other)).then_with(|| {
+            let scale = self.scale.max(other.scale);
+
+            // Fractional parts are less than 10^scale, so aligning them can't overflow
+            let fractional =
+                |value: &Decimal| value.mantissa % unit(value.scale) * unit(scale - value.scale);
+
+            fractional(self).cmp(&fractional(other))
+        });
+    }
+}
@@ -0,0 +1,468 @@
+use std::cmp::Ordering;
+
+use sql_parse::parser::{
+    ArithmeticOperator, ColumnType, Expression, UnaryOperator, MAX_DECIMAL_PRECISION,
+};
+
+use super::context::StatementContext;
+use super::decimal::unit;
+use super::float::float_cmp;
+use super::temporal::{extract_type, temporal_arithmetic_type};
+use crate::types::{AggregateFunction, ColumnValue, Decimal, Interval, ValueExpression};
+use crate::{Result, SqlError};
+
+impl<Column> ValueExpression<Column> {
+    pub fn evaluate(
+        &self,
+        lookup: &impl Fn(&Column) -> Result<ColumnValue>,
+        context: &StatementContext,
+    ) -> Result<ColumnValue> {
+        return match self {
+            ValueExpression::Column(column) => lookup(column),
+            ValueExpression::Value(value) => Ok(value.clone()),
+            ValueExpression::Binary {
+                left,
+                operator,
+                right,
+            } => left
+                .evaluate(lookup, context)?
+                .apply(*operator, &right.evaluate(lookup, context)?),
+            ValueExpression::Unary {
+                operator: UnaryOperator::Minus,
+                operand,
+            } => operand.evaluate(lookup, context)?.negate(),
+            // Aggregates are replaced by their value before evaluating, see Table::query
+            ValueExpression::Aggregate { function, .. } => {
+                Err(SqlError::MisplacedAggregate(*function))
+            }
+            ValueExpression::Extract { field, source } => {
+                source.evaluate(lookup, context)?.extract(*field)
+            }
+            ValueExpression::Function(function) => context.call(function),
+        };
+    }
+
+    /// Determines the type the expression evaluates to without evaluating it,
+    /// so that type errors are caught even if no rows match.
+    ///
+    /// `None` means the expression is always NULL.
+    pub fn result_type(
+        &self,
+        lookup: &impl Fn(&Column) -> Result<ColumnType>,
+    ) -> Result<Option<ColumnType>> {
+        return match self {
+            ValueExpression::Column(column) => lookup(column).map(Some),
+            ValueExpression::Value(value) => Ok(value.column_type()),
+            ValueExpression::Binary {
+                left,
+                operator,
+                right,
+            } => arithmetic_type(
+                *operator,
+                left.result_type(lookup)?,
+                right.result_type(lookup)?,
+            ),
+            ValueExpression::Unary { operand, .. } => match operand.result_type(lookup)? {
+                Some(ColumnType::Interval) => Ok(Some(ColumnType::Interval)),
+                operand => arithmetic_type(
+                    ArithmeticOperator::Subtract,
+                    Some(ColumnType::BigInt),
+                    operand,
+                ),
+            },
+            ValueExpression::Aggregate {
+                function,
+                argument: Some(argument),
+            } => match argument.result_type(lookup)? {
+                Some(argument) => function.result_type(argument).map(Some),
+                None if *function == AggregateFunction::Count => Ok(Some(ColumnType::BigInt)),
+                None => Ok(None),
+            },
+            ValueExpression::Aggregate { argument: None, .. } => Ok(Some(ColumnType::BigInt)),
+            ValueExpression::Extract { field, source } => match source.result_type(lookup)? {
+                Some(source) => extract_type(*field, source).map(Some),
+                None => Ok(None),
+            },
+            ValueExpression::Function(function) => Ok(Some(function.result_type())),
+        };
+    }
+
+    /// Whether both expressions are written the same, with values compared by type and [`ColumnValue::total_cmp`].
+    pub fn is_same(&self, other: &Self) -> bool
+    where
+        Column: PartialEq,
+    {
+        use ValueExpression::*;
+
+        return match (self, other) {
+            (Column(left), Column(right)) => left == right,
+            (Value(left), Value(right)) => {
+                left.column_type() == right.column_type() && left.total_cmp(right).is_eq()
+            }
+            (
+                Binary {
+                    left,
+                    operator,
+                    right,
+                },
+                Binary {
+                    left: other_left,
+                    operator: other_operator,
+                    right: other_right,
+                },
+            ) => {
+                operator == other_operator && left.is_same(other_left) && right.is_same(other_right)
+            }
+            (
+                Unary { operator, operand },
+                Unary {
+                    operator: other_operator,
+                    operand: other_operand,
+                },
+            ) => operator == other_operator && operand.is_same(other_operand),
+            (
+                Aggregate { function, argument },
+                Aggregate {
+                    function: other_function,
+                    argument: other_argument,
+                },
+            ) => {
+                function == other_function
+                    && match (argument, other_argument) {
+                        (Some(argument), Some(other_argument)) => argument.is_same(other_argument),
+                        (None, None) => true,
+                        _ => false,
+                    }
+            }
+            (
+                Extract { field, source },
+                Extract {
+                    field: other_field,
+                    source: other_source,
+                },
+            ) => field == other_field && source.is_same(other_source),
+            (Function(function), Function(other_function)) => function.is_same(other_function),
+            _ => false,
+        };
+    }
+}
+
+fn arithmetic_type(
+    operator: ArithmeticOperator,
+    left: Option<ColumnType>,
+    right: Option<ColumnType>,
+) -> Result<Option<ColumnType>> {
+    use ColumnType::*;
+
+    let is_number =
+        |column_type| matches!(column_type, SmallInt | Int | BigInt | Decimal(_) | Float);
+
+    return match (left, right) {
+        // Integer arithmetic is always done with 64 bits
+        (Some(SmallInt | Int | BigInt), Some(SmallInt | Int | BigInt)) => Ok(Some(BigInt)),
+        // Floats are inexact, so anything they meet becomes inexact too
+        (Some(left), Some(right))
+            if is_number(left) && is_number(right) && (left == Float || right == Float) =>
+        {
+            Ok(Some(Float))
+        }
+        // The result of arithmetic isn't limited to the size of the columns it uses
+        (Some(left), Some(right)) if is_number(left) && is_number(right) => Ok(Some(Decimal(None))),
+        // Anything involving NULL is NULL
+        (None, _) | (_, None) => Ok(None),
+        (Some(left), Some(right)) => match temporal_arithmetic_type(operator, left, right) {
+            Some(result) => Ok(Some(result)),
+            None => Err(SqlError::InvalidOperation(operator, left, right)),
+        },
+    };
+}
+
+impl ColumnValue {
+    pub fn apply(&self, operator: ArithmeticOperator, other: &ColumnValue) -> Result<ColumnValue> {
+        arithmetic_type(operator, self.column_type(), other.column_type())?;
+
+        if self.is_null() || other.is_null() {
+            return Ok(ColumnValue::Null);
+        }
+
+        let overflow = || SqlError::ArithmeticOverflow(self.clone(), operator, other.clone());
+
+        let is_temporal = |value: &ColumnValue| {
+            matches!(
+                value,
+                ColumnValue::Date(_)
+                    | ColumnValue::Time(_)
+                    | ColumnValue::Timestamp(_)
+                    | ColumnValue::Interval(_)
+            )
+        };
+
+        if is_temporal(self) || is_temporal(other) {
+            return self.apply_temporal(operator, other).ok_or_else(overflow);
+        }
+
+        if matches!(self, ColumnValue::Float(_)) || matches!(other, ColumnValue::Float(_)) {
+            return self.apply_float(operator, other);
+        }
+
+        if let (ColumnValue::Int(left), ColumnValue::Int(right)) = (self, other) {
+            let result = match operator {
+                ArithmeticOperator::Add => left.checked_add(*right),
+                ArithmeticOperator::Subtract => left.checked_sub(*right),
+                ArithmeticOperator::Multiply => left.checked_mul(*right),
+                ArithmeticOperator::Divide => {
+                    if *right == 0 {
+                        return Err(SqlError::DivisionByZero);
+                    }
+
+                    left.checked_div(*right)
+                }
+            };
+
+            return result.map(ColumnValue::Int).ok_or_else(overflow);
+        }
+
+        // At least one side is a decimal, the other one gets promoted
+        let (left, right) = (Decimal::from(self), Decimal::from(other));
+
+        let result = match operator {
+            ArithmeticOperator::Add => left.checked_add(right),
+            ArithmeticOperator::Subtract => left.checked_sub(right),
+            ArithmeticOperator::Multiply => left.checked_mul(right),
+            ArithmeticOperator::Divide => {
+                if right.mantissa == 0 {
+                    return Err(SqlError::DivisionByZero);
+                }
+
+                left.checked_div(right)
+            }
+        };
+
+        return result.map(ColumnValue::Decimal).ok_or_else(overflow);
+    }
+
+    pub fn negate(&self) -> Result<ColumnValue> {
+        let zero = match self {
+            ColumnValue::Interval(_) => ColumnValue::Interval(Interval {
+                months: 0,
+                micros: 0,
+            }),
+            _ => ColumnValue::Int(0),
+        };
+
+        return zero.apply(ArithmeticOperator::Subtract, self);
+    }
+
+    /// Converts the value into one that can be stored in a column of `column_type`,
+    /// which its type has to be [assignable](is_assignable) to.
+    ///
+    /// Integers have to fit in the number of bits of the column.
+    /// Decimals are rounded to the scale of the column, and have to fit in its precision.
+    pub fn conform_to(self, column_type: ColumnType) -> Result<ColumnValue> {
+        let out_of_range = || SqlError::ValueOutOfRange(self.clone(), column_type);
+
+        return match (&self, column_type) {
+            (ColumnValue::Int(value), ColumnType::SmallInt) => match i16::try_from(*value) {
+                Ok(_) => Ok(self),
+                Err(_) => Err(out_of_range()),
+            },
+            (ColumnValue::Int(value), ColumnType::Int) => match i32::try_from(*value) {
+                Ok(_) => Ok(self),
+                Err(_) => Err(out_of_range()),
+            },
+            (ColumnValue::Int(_) | ColumnValue::Decimal(_), ColumnType::Float) => {
+                Ok(ColumnValue::Float(self.as_float().unwrap()))
+            }
+            (ColumnValue::Date(date), ColumnType::Timestamp) => {
+                Ok(ColumnValue::Timestamp((*date).into()))
+            }
+            (ColumnValue::Int(_) | ColumnValue::Decimal(_), ColumnType::Decimal(size)) => {
+                let decimal = Decimal::from(&self);
+
+                let (precision, scale) = size.unwrap_or((MAX_DECIMAL_PRECISION, decimal.scale));
+
+                match decimal.rescale(scale) {
+                    Some(decimal) if decimal.mantissa.abs() < unit(precision) => {
+                        Ok(ColumnValue::Decimal(decimal))
+                    }
+                    _ => Err(out_of_range()),
+                }
+            }
+            _ => Ok(self),
+        };
+    }
+}
+
+/// Whether values of type `from` can be stored in a column of type `to`,
+/// as long as they are in its range, see [`ColumnValue::conform_to`].
+pub fn is_assignable(from: ColumnType, to: ColumnType) -> bool {
+    use ColumnType::*;
+
+    return matches!(
+        (from, to),
+        (
+            SmallInt | Int | BigInt,
+            SmallInt | Int | BigInt | Decimal(_) | Float
+        ) | (Decimal(_), Decimal(_) | Float)
+            | (Float, Float)
+            | (Text, Text)
+            | (Blob, Blob)
+            | (Bool, Bool)
+            | (Date, Date | Timestamp)
+            | (Time, Time)
+            | (Timestamp, Timestamp)
+            | (Interval, Interval)
+    );
+}
+
+impl ColumnValue {
+    /// Compares two values, failing if their types can't be compared.
+    ///
+    /// NULL isn't comparable to anything, callers have to check for it first.
+    pub fn compare(&self, other: &ColumnValue) -> Result<Ordering> {
+        if self.type_rank() != other.type_rank() {
+            return Err(SqlError::ImpossibleComparison(self.clone(), other.clone()));
+        }
+
+        return Ok(self.total_cmp(other));
+    }
+
+    /// A total ordering over all values, used for sorting.
+    ///
+    /// Numbers are compared by their numeric value, with NaN after all others, and dates by the midnight at their start.
+    /// Values of types that can't be compared are ordered by type, so that sorting never fails.
+    /// NULLs are equal to each other and sort after everything else.
+    pub fn total_cmp(&self, other: &ColumnValue) -> Ordering {
+        use ColumnValue::*;
+
+        return match (self, other) {
+            (Int(_) | Decimal(_), Int(_) | Decimal(_)) => {
+                // `Decimal` is the variant here, not the type
+                let (left, right) = (crate::types::Decimal::from(self), other.into());
+
+                left.compare(&right)
+            }
+            (Float(_), Int(_) | Decimal(_) | Float(_)) | (Int(_) | Decimal(_), Float(_)) => {
+                float_cmp(self.as_float().unwrap(), other.as_float().unwrap())
+            }
+            (Str(left), Str(right)) => left.cmp(right),
+            // Byte by byte, so a prefix comes first
+            (Blob(left), Blob(right)) => left.0.cmp(&right.0),
+            (Bool(left), Bool(right)) => left.cmp(right),
+            _ => match self.compare_temporal(other) {
+                Some(ordering) => ordering,
+                None => self.type_rank().cmp(&other.type_rank()),
+            },
+        };
+    }
+
+    fn type_rank(&self) -> u8 {
+        return match self {
+            ColumnValue::Bool(_) => 0,
+            ColumnValue::Int(_) | ColumnValue::Decimal(_) | ColumnValue::Float(_) => 1,
+            ColumnValue::Date(_) | ColumnValue::Timestamp(_) => 2,
+            ColumnValue::Time(_) => 3,
+            ColumnValue::Interval(_) => 4,
+            ColumnValue::Str(_) => 5,
+            ColumnValue::Blob(_) => 6,
+            ColumnValue::Null => 7,
+        };
+    }
+}
+
+impl From<&ColumnValue> for Decimal {
+    // Only called for numbers
+    fn from(value: &ColumnValue) -> Self {
+        return match value {
+            ColumnValue::Int(value) => Decimal::from(*value),
+            ColumnValue::Decimal(value) => *value,
+            _ => unreachable!(),
+        };
+    }
+}
+
+/// The direct operands of an expression, for the kinds of expressions that can be stored in a schema.
+pub(super) fn operands(expression: &Expression) -> Vec<&Expression> {
+    use Expression as E;
+
+    return match expression {
+        E::Binary { left, right, .. } | E::Comparison { left, right, .. } => vec![left, right],
+        E::And(left, right) | E::Or(left, right) => vec![left, right],
+        E::Unary { operand, .. } => vec![operand],
+        E::Not(inner) => vec![inner],
+        E::IsNull { expression, .. } => vec![expression],
+        E::Extract { source, .. } => vec![source],
+        E::FunctionCall {
+            argument: Some(argument),
+            ..
+        } => vec![argument],
+        _ => vec![],
+    };
+}
+
+/// Copies `expression`, replacing the parts for which `replace` returns something.
+///
+/// The operands (see [`operands`]) of the parts it returns `None` for are replaced in the same way.
+pub(super) fn try_map_expression(
+    expression: &Expression,
+    replace: &mut impl FnMut(&Expression) -> Result<Option<Expression>>,
+) -> Result<Expression> {
+    use Expression as E;
+
+    if let Some(replacement) = replace(expression)? {
+        return Ok(replacement);
+    }
+
+    let mut map = |expression: &Expression| {
+        return try_map_expression(expression, replace).map(Box::new);
+    };
+
+    let result = match expression {
+        E::Binary {
+            left,
+            operator,
+            right,
+        } => E::Binary {
+            left: map(left)?,
+            operator: *operator,
+            right: map(right)?,
+        },
+        E::Comparison {
+            left,
+            operator,
+            right,
+        } => E::Comparison {
+            left: map(left)?,
+            operator: *operator,
+            right: map(right)?,
+        },
+        E::And(left, right) => E::And(map(left)?, map(right)?),
+        E::Or(left, right) => E::Or(map(left)?, map(right)?),
+        E::Unary { operator, operand } => E::Unary {
+            operator: *operator,
+            operand: map(operand)?,
+        },
+        E::Not(inner) => E::Not(map(inner)?),
+        E::IsNull {
+            expression,
+            negated,
+        } => E::IsNull {
+            expression: map(expression)?,
+            negated: *negated,
+        },
+        E::Extract { field, source } => E::Extract {
+            field: *field,
+            source: map(source)?,
+        },
+        E::FunctionCall {
+            name,
+            argument: Some(argument),
+        } => E::FunctionCall {
+            name: name.clone(),
+            argument: Some(map(argument)?),
+        },
+        other => other.clone(),
+    };
+
+    return Ok(result);
+}
@@ -0,0 +1,69 @@
+use std::cmp::Ordering;
+
+use sql_parse::parser::ArithmeticOperator;
+
+use crate::types::{ColumnValue, Decimal};
+use crate::{Result, SqlError};
+
+impl From<Decimal> for f64 {
+    fn from(value: Decimal) -> Self {
+        return value.mantissa as f64 / 10f64.powi(value.scale as i32);
+    }
+}
+
+/// A total order over floats, where NaN is equal to itself and larger than everything else,
+/// so it sorts last in ascending order. Unlike [`f64::total_cmp`], `-0.0` and `0.0` are equal.
+pub fn float_cmp(left: f64, right: f64) -> Ordering {
+    return match (left.is_nan(), right.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => left.partial_cmp(&right).unwrap(),
+    };
+}
+
+impl ColumnValue {
+    /// The value of a number as a float, which ints and decimals get promoted to when they meet one.
+    pub(super) fn as_float(&self) -> Option<f64> {
+        return match self {
+            ColumnValue::Int(value) => Some(*value as f64),
+            ColumnValue::Decimal(value) => Some((*value).into()),
+            ColumnValue::Float(value) => Some(*value),
+            _ => None,
+        };
+    }
+
+    /// Arithmetic where both sides are numbers and at least one of them is a float.
+    pub(super) fn apply_float(
+        &self,
+        operator: ArithmeticOperator,
+        other: &ColumnValue,
+    ) -> Result<ColumnValue> {
+        // Only called for numbers
+        let (left, right) = (self.as_float().unwrap(), other.as_float().unwrap());
+
+        let result = match operator {
+            ArithmeticOperator::Add => left + right,
+            ArithmeticOperator::Subtract => left - right,
+            ArithmeticOperator::Multiply => left * right,
+            ArithmeticOperator::Divide => {
+                if right == 0.0 {
+                    return Err(SqlError::DivisionByZero);
+                }
+
+                left / right
+            }
+        };
+
+        // Infinity can only be written as a literal, not reached by accident
+        if result.is_infinite() && left.is_finite() && right.is_finite() {
+            return Err(SqlError::ArithmeticOverflow(
+                self.clone(),
+                operator,
+                other.clone(),
+            ));
+        }
+
+        return Ok(ColumnValue::Float(result));
+    }
+}
@@ -0,0 +1,392 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+
+use sql_parse::parser::ReferentialAction;
+
+use super::expression::is_assignable;
+use super::index::IndexKey;
+use super::{Database, Row, StatementContext, Table};
+use crate::types::{ColumnValue, ForeignKeyConstraint, TableName};
+use crate::{Result, SqlError};
+
+/// A row before it was changed and after, or `None` if it was deleted.
+pub type RowChange = (Row, Option<Row>);
+
+impl Database {
+    /// Checks that every foreign key of a new table references a key column of an existing table,
+    /// or of the table itself.
+    pub(crate) fn validate_foreign_keys(&self, table: &Table) -> Result<()> {
+        for constraint in &table.constraints {
+            let column = table.column_index(constraint.column.clone())?;
+
+            let parent = if constraint.foreign_table.0 == table.schema.name.0 {
+                table
+            } else {
+                self.tables
+                    .get(&constraint.foreign_table.0)
+                    .ok_or_else(|| SqlError::TableDoesNotExist(constraint.foreign_table.clone()))?
+            };
+
+            let parent_column = parent.column_index(constraint.foreign_column.clone())?;
+
+            let parent_name = &parent.schema.column_names[parent_column];
+
+            // Otherwise deleting one row could leave another one with the same key behind
+            let is_key = parent
+                .schema
+                .unique
+                .iter()
+                .any(|key| key.columns == std::slice::from_ref(parent_name));
+
+            if !is_key {
+                return Err(SqlError::NotAKey(
+                    parent.schema.name.clone(),
+                    parent_name.clone(),
+                ));
+            }
+
+            let (own_type, parent_type) = (
+                table.schema.types[column],
+                parent.schema.types[parent_column],
+            );
+
+            if !is_assignable(own_type, parent_type) {
+                return Err(SqlError::IncompatibleTypes(
+                    vec![own_type],
+                    vec![parent_type],
+                ));
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Fails if any of `rows` of `table` references a row that doesn't exist.
+    pub(crate) fn check_references(&self, table: &Table, rows: &[Row]) -> Result<()> {
+        return Staged::new(self).check_references(table, rows);
+    }
+
+    /// Fails if another table has a foreign key referencing `table_name`.
+    pub(crate) fn check_not_referenced(&self, table_name: &TableName) -> Result<()> {
+        for (child, _) in self.references_to(table_name) {
+            if child.0 != table_name.0 {
+                return Err(SqlError::TableIsReferenced(table_name.clone(), child));
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Changes the rows of a table with `change`, then applies the `ON DELETE` and `ON UPDATE` actions
+    /// of the foreign keys referencing them and checks the table's own foreign keys.
+    ///
+    /// If any of that fails, none of the tables are changed.
+    pub(crate) fn change_rows(
+        &mut self,
+        table_name: &TableName,
+        context: &StatementContext,
+        change: impl FnOnce(&mut Table) -> Result<Vec<RowChange>>,
+    ) -> Result<()> {
+        let table = self
+            .tables
+            .get(&table_name.0)
+            .ok_or_else(|| SqlError::TableDoesNotExist(table_name.clone()))?;
+
+        // No other tables are involved, so there's no need for copies
+        if table.constraints.is_empty() && self.references_to(table_name).is_empty() {
+            let table = self
+                .tables
+                .get_mut(&table_name.0)
+                .ok_or_else(|| SqlError::TableDoesNotExist(table_name.clone()))?;
+
+            return change(table).map(|_| ());
+        }
+
+        let mut staged = Staged::new(self);
+
+        let changes = change(staged.get_mut(table_name)?)?;
+
+        let updated = changes.iter().any(|(_, new)| new.is_some());
+
+        staged.propagate(table_name, changes, context)?;
+
+        // Rows that were only deleted can't reference anything that's missing
+        if updated {
+            let table = staged.get(table_name)?;
+
+            staged.check_references(table, &table.values)?;
+        }
+
+        let tables = staged.tables;
+
+        self.tables.extend(tables);
+
+        return Ok(());
+    }
+
+    /// The foreign keys referencing `table_name`, together with the name of their table.
+    pub(super) fn references_to(
+        &self,
+        table_name: &TableName,
+    ) -> Vec<(TableName, ForeignKeyConstraint)> {
+        let mut result = vec![];
+
+        for table in self.tables.values() {
+            for constraint in &table.constraints {
+                if constraint.foreign_table.0 == table_name.0 {
+                    result.push((table.schema.name.clone(), constraint.clone()));
+                }
+            }
+        }
+
+        // So that errors don't depend on the order of the HashMap
+        result.sort_by(|(left, _), (right, _)| left.0.cmp(&right.0));
+
+        return result;
+    }
+
+    /// `table_name` and the tables referencing it, directly or through other tables,
+    /// which is how far the actions of foreign keys can reach.
+    pub(crate) fn referencing_tables(&self, table_name: &TableName) -> Vec<TableName> {
+        let mut result = vec![table_name.clone()];
+
+        let mut next = 0;
+
+        while let Some(table_name) = result.get(next).cloned() {
+            for (child, _) in self.references_to(&table_name) {
+                if !result.iter().any(|name| name.0 == child.0) {
+                    result.push(child);
+                }
+            }
+
+            next += 1;
+        }
+
+        return result;
+    }
+}
+
+/// Copies of the tables a statement changes, which replace the originals once all of it succeeded.
+struct Staged<'a> {
+    database: &'a Database,
+    tables: HashMap<String, Table>,
+}
+
+impl<'a> Staged<'a> {
+    fn new(database: &'a Database) -> Self {
+        return Self {
+            database,
+            tables: HashMap::new(),
+        };
+    }
+
+    /// The table with the changes made to it so far.
+    fn get(&self, table_name: &TableName) -> Result<&Table> {
+        return self
+            .tables
+            .get(&table_name.0)
+            .or_else(|| self.database.tables.get(&table_name.0))
+            .ok_or_else(|| SqlError::TableDoesNotExist(table_name.clone()));
+    }
+
+    /// The copy of the table, which is made the first time it's changed.
+    fn get_mut(&mut self, table_name: &TableName) -> Result<&mut Table> {
+        return match self.tables.entry(table_name.0.clone()) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                let table = self
+                    .database
+                    .tables
+                    .get(&table_name.0)
+                    .ok_or_else(|| SqlError::TableDoesNotExist(table_name.clone()))?;
+
+                Ok(entry.insert(table.clone()))
+            }
+        };
+    }
+
+    fn check_references(&self, table: &Table, rows: &[Row]) -> Result<()> {
+        for constraint in &table.constraints {
+            let column = table.column_index(constraint.column.clone())?;
+
+            let parent = if constraint.foreign_table.0 == table.schema.name.0 {
+                table
+            } else {
+                self.get(&constraint.foreign_table)?
+            };
+
+            let parent_column = parent.column_index(constraint.foreign_column.clone())?;
+
+            for row in rows {
+                let value = &row.0[column];
+
+                // NULL doesn't reference anything, so it never violates the constraint
+                if !value.is_null() && !parent.has_key(parent_column, value) {
+                    return Err(SqlError::ForeignKeyViolation(
+                        constraint.column.clone(),
+                        value.clone(),
+                        constraint.foreign_table.clone(),
+                    ));
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Applies the actions of the foreign keys referencing the changed rows of `table_name`,
+    /// and then of the ones referencing the rows changed by that, and so on.
+    fn propagate(
+        &mut self,
+        table_name: &TableName,
+        changes: Vec<RowChange>,
+        context: &StatementContext,
+    ) -> Result<()> {
+        let mut pending = vec![(table_name.clone(), changes)];
+
+        while let Some((parent_name, changes)) = pending.pop() {
+            for (child_name, constraint) in self.database.references_to(&parent_name) {
+                let parent_column = self
+                    .get(&parent_name)?
+                    .column_index(constraint.foreign_column.clone())?;
+
+                let changed_keys = changed_keys(&changes, parent_column);
+
+                if changed_keys.is_empty() {
+                    continue;
+                }
+
+                let child_changes = self.get_mut(&child_name)?.follow_references(
+                    &constraint,
+                    &changed_keys,
+                    context,
+                )?;
+
+                if !child_changes.is_empty() {
+                    pending.push((child_name, child_changes));
+                }
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+/// Maps the old values of `column` to their new ones, or to `None` if their row was deleted,
+/// leaving out the ones that didn't change.
+fn changed_keys(changes: &[RowChange], column: usize) -> BTreeMap<IndexKey, Option<ColumnValue>> {
+    let mut result = BTreeMap::new();
+
+    for (old, new) in changes {
+        let old_key = &old.0[column];
+
+        // Nothing can reference NULL
+        if old_key.is_null() {
+            continue;
+        }
+
+        let new_key = new.as_ref().map(|new| &new.0[column]);
+
+        if new_key.is_some_and(|new_key| new_key.total_cmp(old_key).is_eq()) {
+            continue;
+        }
+
+        result.insert(IndexKey(vec![old_key.clone()]), new_key.cloned());
+    }
+
+    return result;
+}
+
+impl Table {
+    /// Whether any row has `value` in `column`.
+    fn has_key(&self, column: usize, value: &ColumnValue) -> bool {
+        let key = IndexKey(vec![value.clone()]);
+
+        return match self
+            .indexes
+            .iter()
+            .find(|index| index.columns() == [column])
+        {
+            Some(index) => index.contains(&key),
+            None => self
+                .values
+                .iter()
+                .any(|row| row.0[column].total_cmp(value).is_eq()),
+        };
+    }
+
+    /// Applies the action of `constraint` to the rows referencing one of the `changed` keys,
+    /// see [`changed_keys`], and returns how they changed.
+    fn follow_references(
+        &mut self,
+        constraint: &ForeignKeyConstraint,
+        changed: &BTreeMap<IndexKey, Option<ColumnValue>>,
+        context: &StatementContext,
+    ) -> Result<Vec<RowChange>> {
+        let column = self.column_index(constraint.column.clone())?;
+
+        let checks = self.prepare_checks()?;
+
+        let mut updated = vec![];
+
+        let mut removed = vec![];
+
+        let mut changes = vec![];
+
+        for (position, row) in self.values.iter().enumerate() {
+            let Some(new_key) = changed.get(&IndexKey(vec![row.0[column].clone()])) else {
+                continue;
+            };
+
+            let action = match new_key {
+                Some(_) => constraint.on_update,
+                None => constraint.on_delete,
+            };
+
+            let new_value = match (action, new_key) {
+                (ReferentialAction::Restrict, _) => {
+                    return Err(SqlError::RowIsReferenced(
+                        self.schema.name.clone(),
+                        constraint.column.clone(),
+                        row.0[column].clone(),
+                    ))
+                }
+                (ReferentialAction::Cascade, None) => None,
+                (ReferentialAction::Cascade, Some(new_key)) => Some(new_key.clone()),
+                (ReferentialAction::SetNull, _) => Some(ColumnValue::Null),
+            };
+
+            let new_row = match new_value {
+                Some(value) => {
+                    let mut new_row = row.clone();
+
+                    new_row.0[column] = value;
+
+                    new_row.check_not_null(&self.schema)?;
+
+                    new_row.conform_to(&self.schema)?;
+
+                    self.check_row(&new_row, &checks, context)?;
+
+                    updated.push((position, new_row.clone()));
+
+                    Some(new_row)
+                }
+                None => {
+                    removed.push(position);
+
+                    None
+                }
+            };
+
+            changes.push((row.clone(), new_row));
+        }
+
+        // Updated first, since removing rows moves the others
+        self.update_rows(updated)?;
+
+        self.remove_rows(removed);
+
+        return Ok(changes);
+    }
+}
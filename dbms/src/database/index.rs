@@ -0,0 +1,627 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+
+use sql_parse::parser::InfixOperator;
+
+use super::expression::is_assignable;
+use super::{Database, Row, Table};
+use crate::types::{
+    ColumnName, ColumnValue, IndexDefinition, IndexName, PreparedWhere, TableName, TableSchema,
+    ValueExpression, Where,
+};
+use crate::{Result, SqlError};
+
+/// Values of a few columns, ordered with [`ColumnValue::total_cmp`], so they can be used as keys of groups and indexes.
+///
+/// A key comes right before the longer keys it is the start of, so a range can start at a single value.
+#[derive(Debug, Clone)]
+pub struct IndexKey(pub Vec<ColumnValue>);
+
+impl Ord for IndexKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        for (left, right) in self.0.iter().zip(&other.0) {
+            let ordering = left.total_cmp(right);
+
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+
+        return self.0.len().cmp(&other.0.len());
+    }
+}
+
+impl PartialOrd for IndexKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl PartialEq for IndexKey {
+    fn eq(&self, other: &Self) -> bool {
+        return self.cmp(other) == Ordering::Equal;
+    }
+}
+
+impl Eq for IndexKey {}
+
+/// The keys of all rows for one `PRIMARY KEY` or `UNIQUE` constraint,
+/// so a new key can be checked without looking at every row.
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct UniqueIndex {
+    columns: Vec<usize>,
+    keys: BTreeSet<IndexKey>,
+}
+
+impl UniqueIndex {
+    /// Builds an index for each unique constraint of the schema, failing if two of the rows have the same key.
+    pub fn build_all(schema: &TableSchema, rows: &[Row]) -> Result<Vec<UniqueIndex>> {
+        let mut result = vec![];
+
+        for constraint in &schema.unique {
+            let columns = constraint
+                .columns
+                .iter()
+                .map(|name| {
+                    schema
+                        .column_names
+                        .iter()
+                        .position(|column| column == name)
+                        .ok_or_else(|| {
+                            SqlError::NameDoesNotExist(name.clone(), schema.column_names.clone())
+                        })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let mut index = UniqueIndex {
+                columns,
+                keys: BTreeSet::new(),
+            };
+
+            for row in rows {
+                index.check(row, &constraint.columns)?;
+
+                index.insert(row);
+            }
+
+            result.push(index);
+        }
+
+        return Ok(result);
+    }
+
+    /// The key of `row`, or `None` if any of its values are NULL,
+    /// since NULLs aren't equal to anything, not even other NULLs.
+    fn key(&self, row: &Row) -> Option<IndexKey> {
+        let values: Vec<_> = self
+            .columns
+            .iter()
+            .map(|index| row.0[*index].clone())
+            .collect();
+
+        if values.iter().any(ColumnValue::is_null) {
+            return None;
+        }
+
+        return Some(IndexKey(values));
+    }
+
+    /// Fails if another row already has the key of `row`, where `names` are the names of the key's columns.
+    pub fn check(&self, row: &Row, names: &[ColumnName]) -> Result<()> {
+        return match self.key(row) {
+            Some(key) if self.keys.contains(&key) => {
+                Err(SqlError::DuplicateKey(names.to_vec(), key.0))
+            }
+            _ => Ok(()),
+        };
+    }
+
+    /// The indices of the key's columns.
+    pub fn columns(&self) -> &[usize] {
+        return &self.columns;
+    }
+
+    pub fn contains(&self, key: &IndexKey) -> bool {
+        return self.keys.contains(key);
+    }
+
+    pub fn insert(&mut self, row: &Row) {
+        if let Some(key) = self.key(row) {
+            self.keys.insert(key);
+        }
+    }
+
+    pub fn remove(&mut self, row: &Row) {
+        if let Some(key) = self.key(row) {
+            self.keys.remove(&key);
+        }
+    }
+
+    /// Replaces the keys of the old rows of `changed` by those of the new rows,
+    /// failing if another row already has one of the new keys, in which case the index is left as it was.
+    fn replace(&mut self, changed: &[ChangedRow], names: &[ColumnName]) -> Result<()> {
+        // All old keys go first, so rows can swap keys
+        for (_, old, _) in changed {
+            self.remove(old);
+        }
+
+        for (done, (_, _, new)) in changed.iter().enumerate() {
+            if let Err(error) = self.check(new, names) {
+                self.revert(changed, done);
+
+                return Err(error);
+            }
+
+            self.insert(new);
+        }
+
+        return Ok(());
+    }
+
+    /// Undoes [`UniqueIndex::replace`], which got as far as the first `done` new rows.
+    fn revert(&mut self, changed: &[ChangedRow], done: usize) {
+        for (_, _, new) in &changed[..done] {
+            self.remove(new);
+        }
+
+        for (_, old, _) in changed {
+            self.insert(old);
+        }
+    }
+}
+
+/// The positions of all rows in a table ordered by the values of a few of its columns,
+/// for an index made by `CREATE [UNIQUE] INDEX`.
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct SecondaryIndex {
+    columns: Vec<usize>,
+    unique: bool,
+    entries: BTreeMap<IndexKey, Vec<usize>>,
+}
+
+impl SecondaryIndex {
+    /// Builds an index for each index of the schema, failing if two of the rows have the same key of a unique index.
+    pub fn build_all(schema: &TableSchema, rows: &[Row]) -> Result<Vec<SecondaryIndex>> {
+        let mut result = vec![];
+
+        for definition in &schema.indexes {
+            let columns = definition
+                .columns
+                .iter()
+                .map(|name| {
+                    schema
+                        .column_names
+                        .iter()
+                        .position(|column| column == name)
+                        .ok_or_else(|| {
+                            SqlError::NameDoesNotExist(name.clone(), schema.column_names.clone())
+                        })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let mut index = SecondaryIndex {
+                columns,
+                unique: definition.unique,
+                entries: BTreeMap::new(),
+            };
+
+            for (position, row) in rows.iter().enumerate() {
+                index.check(row, &definition.columns)?;
+
+                index.insert(row, position);
+            }
+
+            result.push(index);
+        }
+
+        return Ok(result);
+    }
+
+    fn key(&self, row: &Row) -> IndexKey {
+        return IndexKey(
+            self.columns
+                .iter()
+                .map(|index| row.0[*index].clone())
+                .collect(),
+        );
+    }
+
+    /// Fails if the index is unique and another row already has the key of `row`,
+    /// where `names` are the names of the key's columns.
+    ///
+    /// Like for `UNIQUE` constraints, keys with NULLs never clash.
+    pub fn check(&self, row: &Row, names: &[ColumnName]) -> Result<()> {
+        let key = self.key(row);
+
+        if !self.unique || key.0.iter().any(ColumnValue::is_null) {
+            return Ok(());
+        }
+
+        return match self.entries.contains_key(&key) {
+            true => Err(SqlError::DuplicateKey(names.to_vec(), key.0)),
+            false => Ok(()),
+        };
+    }
+
+    /// Adds `row`, which is at `position` in the table.
+    pub fn insert(&mut self, row: &Row, position: usize) {
+        let positions = self.entries.entry(self.key(row)).or_default();
+
+        // Kept in order, which is at the end unless a row is changed in place
+        let at = positions.partition_point(|other| *other < position);
+
+        positions.insert(at, position);
+    }
+
+    /// Removes `row`, which is at `position` in the table, without moving the other rows.
+    fn remove_entry(&mut self, row: &Row, position: usize) {
+        let key = self.key(row);
+
+        if let Some(positions) = self.entries.get_mut(&key) {
+            positions.retain(|other| *other != position);
+
+            if positions.is_empty() {
+                self.entries.remove(&key);
+            }
+        }
+    }
+
+    /// Removes the rows that were at the `removed` positions, in ascending order,
+    /// and moves the positions of the other rows up to where they are once those are gone.
+    pub fn remove(&mut self, removed: &[(usize, &Row)]) {
+        for (position, row) in removed {
+            self.remove_entry(row, *position);
+        }
+
+        for positions in self.entries.values_mut() {
+            for position in positions {
+                *position -= removed.partition_point(|(other, _)| other < position);
+            }
+        }
+    }
+
+    /// Like [`UniqueIndex::replace`], the rows stay where they are.
+    fn replace(&mut self, changed: &[ChangedRow], names: &[ColumnName]) -> Result<()> {
+        for (position, old, _) in changed {
+            self.remove_entry(old, *position);
+        }
+
+        for (done, (position, _, new)) in changed.iter().enumerate() {
+            if let Err(error) = self.check(new, names) {
+                self.revert(changed, done);
+
+                return Err(error);
+            }
+
+            self.insert(new, *position);
+        }
+
+        return Ok(());
+    }
+
+    /// Undoes [`SecondaryIndex::replace`], which got as far as the first `done` new rows.
+    fn revert(&mut self, changed: &[ChangedRow], done: usize) {
+        for (position, _, new) in &changed[..done] {
+            self.remove_entry(new, *position);
+        }
+
+        for (position, old, _) in changed {
+            self.insert(old, *position);
+        }
+    }
+
+    /// The positions of the rows whose first key column compares to `value` with `operator`,
+    /// grouped by key, or `None` if the index can't tell.
+    fn matching<'a>(
+        &'a self,
+        operator: InfixOperator,
+        value: &'a ColumnValue,
+    ) -> Option<impl Iterator<Item = &'a Vec<usize>> + 'a> {
+        use InfixOperator::*;
+
+        let start = match operator {
+            Equals | GreaterThan | GreaterThanEqual => {
+                Bound::Included(IndexKey(vec![value.clone()]))
+            }
+            LessThan | LessThanEqual => Bound::Unbounded,
+            NotEqual => return None,
+        };
+
+        let positions = self
+            .entries
+            .range((start, Bound::Unbounded))
+            .map(move |(key, positions)| (key.0[0].total_cmp(value), &key.0[0], positions))
+            // Everything after the first value that is too large is as well
+            .take_while(move |(ordering, _, _)| match operator {
+                Equals | LessThanEqual => ordering.is_le(),
+                LessThan => ordering.is_lt(),
+                _ => true,
+            })
+            // NULLs are ordered last, but never match
+            .filter(move |(ordering, first, _)| {
+                !first.is_null() && (operator != GreaterThan || ordering.is_gt())
+            })
+            .map(|(_, _, positions)| positions);
+
+        return Some(positions);
+    }
+
+    /// The positions of the rows [`SecondaryIndex::matching`] finds, in no particular order.
+    fn lookup(&self, operator: InfixOperator, value: &ColumnValue) -> Option<Vec<usize>> {
+        return self
+            .matching(operator, value)
+            .map(|matching| matching.flatten().copied().collect());
+    }
+
+    /// How many rows [`SecondaryIndex::lookup`] would find, without counting further than `limit`.
+    fn count(&self, operator: InfixOperator, value: &ColumnValue, limit: usize) -> Option<usize> {
+        let mut count = 0;
+
+        for positions in self.matching(operator, value)? {
+            count += positions.len();
+
+            if count >= limit {
+                break;
+            }
+        }
+
+        return Some(count);
+    }
+}
+
+/// A row that is changed in place: its position in the table, and its old and new values.
+pub(super) type ChangedRow<'a> = (usize, &'a Row, &'a Row);
+
+/// Replaces the keys of the `changed` rows in all indexes of a table.
+///
+/// Keys only have to be unique once all rows are changed, so e.g. `SET id = id + 1` works.
+/// If they aren't, all indexes are left as they were.
+pub(super) fn replace_keys(
+    indexes: &mut [UniqueIndex],
+    secondary_indexes: &mut [SecondaryIndex],
+    schema: &TableSchema,
+    changed: &[ChangedRow],
+) -> Result<()> {
+    for (position, constraint) in schema.unique.iter().enumerate() {
+        if let Err(error) = indexes[position].replace(changed, &constraint.columns) {
+            for index in &mut indexes[..position] {
+                index.revert(changed, changed.len());
+            }
+
+            return Err(error);
+        }
+    }
+
+    for (position, definition) in schema.indexes.iter().enumerate() {
+        if let Err(error) = secondary_indexes[position].replace(changed, &definition.columns) {
+            for index in &mut *indexes {
+                index.revert(changed, changed.len());
+            }
+
+            for index in &mut secondary_indexes[..position] {
+                index.revert(changed, changed.len());
+            }
+
+            return Err(error);
+        }
+    }
+
+    return Ok(());
+}
+
+/// Finds the rows whose value in the first column of one of the indexes of a table compares to `value` with `operator`.
+#[derive(Debug, Clone)]
+pub struct IndexLookup {
+    pub index: usize, // Of `schema.indexes`
+    pub operator: InfixOperator,
+    pub value: ColumnValue,
+}
+
+impl Table {
+    /// Adds an index on some of the columns of the table, failing if it's unique and the existing rows have duplicates.
+    pub fn create_index(&mut self, definition: IndexDefinition) -> Result<()> {
+        let columns = definition
+            .columns
+            .into_iter()
+            .map(|name| Ok(self.schema.column_names[self.column_index(name)?].clone()))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut schema = self.schema.clone();
+
+        schema.indexes.push(IndexDefinition {
+            columns,
+            ..definition
+        });
+
+        self.secondary_indexes = SecondaryIndex::build_all(&schema, &self.values)?;
+        self.schema = schema;
+
+        return Ok(());
+    }
+
+    /// Removes the index called `name`, returning whether the table had it.
+    pub fn drop_index(&mut self, name: &IndexName) -> bool {
+        let Some(position) = self
+            .schema
+            .indexes
+            .iter()
+            .position(|definition| definition.name.0 == name.0)
+        else {
+            return false;
+        };
+
+        self.schema.indexes.remove(position);
+        self.secondary_indexes.remove(position);
+
+        return true;
+    }
+
+    /// The positions of the rows that could match `condition`, in ascending order.
+    pub fn candidate_rows(&self, condition: &Option<PreparedWhere>) -> Vec<usize> {
+        return match condition
+            .as_ref()
+            .and_then(|condition| self.choose_index(condition))
+        {
+            Some(lookup) => self.look_up(&lookup),
+            None => (0..self.values.len()).collect(),
+        };
+    }
+
+    /// The index lookup that narrows down the rows that could match `condition` the most,
+    /// using a comparison of a column with a value that has to be true for the entire condition to be.
+    ///
+    /// The rows it finds still need to be checked against all of the condition.
+    pub fn choose_index(&self, condition: &PreparedWhere) -> Option<IndexLookup> {
+        let mut best: Option<(usize, IndexLookup)> = None;
+
+        for (column, operator, value) in indexable_comparisons(condition) {
+            // Values of other types fail to compare, which the full scan reports
+            let comparable = match (value.column_type(), self.schema.types.get(column)) {
+                (Some(value_type), Some(column_type)) => {
+                    is_assignable(value_type, *column_type)
+                        || is_assignable(*column_type, value_type)
+                }
+                _ => false,
+            };
+
+            if !comparable {
+                continue;
+            }
+
+            for (position, index) in self.secondary_indexes.iter().enumerate() {
+                if index.columns[0] != column {
+                    continue;
+                }
+
+                let limit = best.as_ref().map_or(usize::MAX, |(count, _)| *count);
+
+                // Only counted, the rows are looked up once the lookup is chosen
+                let Some(count) = index.count(operator, value, limit) else {
+                    continue;
+                };
+
+                if count < limit {
+                    let lookup = IndexLookup {
+                        index: position,
+                        operator,
+                        value: value.clone(),
+                    };
+
+                    best = Some((count, lookup));
+                }
+            }
+        }
+
+        return best.map(|(_, lookup)| lookup);
+    }
+
+    /// The positions of the rows `lookup` finds, in ascending order.
+    pub fn look_up(&self, lookup: &IndexLookup) -> Vec<usize> {
+        let mut positions = self.secondary_indexes[lookup.index]
+            .lookup(lookup.operator, &lookup.value)
+            .unwrap_or_default();
+
+        positions.sort_unstable();
+
+        return positions;
+    }
+
+    /// The name of the index `lookup` uses, and the comparison it looks up.
+    pub fn describe_lookup(&self, lookup: &IndexLookup) -> (IndexName, Where) {
+        let definition = &self.schema.indexes[lookup.index];
+
+        let comparison = Where::Comparison {
+            left: ValueExpression::Column(definition.columns[0].clone()),
+            operator: lookup.operator,
+            right: ValueExpression::Value(lookup.value.clone()),
+        };
+
+        return (definition.name.clone(), comparison);
+    }
+}
+
+/// The comparisons of a column with a value that all have to be true for `condition` to be,
+/// with the column on the left.
+fn indexable_comparisons(condition: &PreparedWhere) -> Vec<(usize, InfixOperator, &ColumnValue)> {
+    use InfixOperator::*;
+
+    return match condition {
+        Where::And(left, right) => {
+            let mut result = indexable_comparisons(left);
+
+            result.extend(indexable_comparisons(right));
+
+            result
+        }
+        Where::Comparison {
+            left: ValueExpression::Column(column),
+            operator,
+            right: ValueExpression::Value(value),
+        } => vec![(*column, *operator, value)],
+        Where::Comparison {
+            left: ValueExpression::Value(value),
+            operator,
+            right: ValueExpression::Column(column),
+        } => {
+            let operator = match operator {
+                LessThan => GreaterThan,
+                LessThanEqual => GreaterThanEqual,
+                GreaterThan => LessThan,
+                GreaterThanEqual => LessThanEqual,
+                other => *other,
+            };
+
+            vec![(*column, operator, value)]
+        }
+        _ => vec![],
+    };
+}
+
+impl Database {
+    /// Adds an index to a table, with a name that no other index in the database has.
+    pub fn create_index(
+        &mut self,
+        table_name: &TableName,
+        definition: IndexDefinition,
+    ) -> Result<()> {
+        if self.index_table(&definition.name).is_some() {
+            return Err(SqlError::DuplicateIndex(definition.name));
+        }
+
+        return self
+            .tables
+            .get_mut(&table_name.0)
+            .ok_or_else(|| SqlError::TableDoesNotExist(table_name.clone()))?
+            .create_index(definition);
+    }
+
+    pub fn drop_index(&mut self, name: &IndexName) -> Result<()> {
+        let table_name = self
+            .index_table(name)
+            .ok_or_else(|| SqlError::IndexDoesNotExist(name.clone()))?;
+
+        self.tables
+            .get_mut(&table_name.0)
+            .map(|table| table.drop_index(name));
+
+        return Ok(());
+    }
+
+    /// The table that has the index called `name`.
+    pub(crate) fn index_table(&self, name: &IndexName) -> Option<TableName> {
+        return self
+            .tables
+            .values()
+            .find(|table| {
+                table
+                    .schema
+                    .indexes
+                    .iter()
+                    .any(|definition| definition.name.0 == name.0)
+            })
+            .map(|table| table.schema.name.clone());
+    }
+}
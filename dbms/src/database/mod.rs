@@ -1,165 +1,261 @@
+mod aggregate;
+mod alter;
+mod context;
+mod decimal;
+mod expression;
+mod float;
+mod foreign_key;
+mod index;
+mod plan;
+mod sequence;
+mod snapshot;
+mod temporal;
 #[cfg(test)]
 mod tests;
 
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
-use sql_parse::parser::{ColumnType, InfixOperator};
+use sql_parse::parser::{ColumnConstraint, ColumnType, Expression, InfixOperator, JoinType};
 
 use super::types::{
-    ColumnDefinition, ColumnName, ColumnSelector, ColumnValue, DatabaseName, PreparedWhere,
-    TableName, Where,
+    ColumnDefinition, ColumnName, ColumnSelector, ColumnValue, DatabaseName,
+    PreparedValueExpression, PreparedWhere, SelectQuery, TableName, ValueExpression, Where,
 };
 use super::SqlError;
-use crate::types::{ForeignKeyConstraint, TableSchema};
+use crate::types::{ForeignKeyConstraint, TableSchema, UniqueConstraint};
 use crate::Result;
-
-#[derive(Debug)]
-#[cfg_attr(test, derive(Clone, PartialEq))]
+pub use context::StatementContext;
+use expression::is_assignable;
+pub use foreign_key::RowChange;
+use index::{replace_keys, SecondaryIndex, UniqueIndex};
+pub use plan::RowStream;
+use plan::{fold_condition, LogicalPlan, QueryPlan};
+pub use sequence::{serial_sequences, Sequence};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
 pub struct Row(pub Vec<ColumnValue>);
 
 impl Row {
-    fn select(&self, columns: &[usize]) -> Result<Row> {
-        for index in columns {
-            if *index >= self.0.len() {
-                return Err(SqlError::IndexOutOfBounds(*index, self.0.len()));
-            }
-        }
-
-        let values = self
-            .0
-            .iter()
-            .enumerate()
-            .filter_map(|(index, values)| {
-                if columns.contains(&index) {
-                    Some(values)
-                } else {
-                    None
-                }
-            })
-            .cloned()
-            .collect();
+    fn evaluate(
+        &self,
+        expression: &PreparedValueExpression,
+        context: &StatementContext,
+    ) -> Result<ColumnValue> {
+        let lookup = |index: &usize| {
+            self.0
+                .get(*index)
+                .cloned()
+                .ok_or(SqlError::IndexOutOfBounds(*index, self.0.len()))
+        };
 
-        return Ok(Row(values));
+        return expression.evaluate(&lookup, context);
     }
 
-    fn update(
-        &mut self,
+    /// The row with the `new_values` in `columns`, or `None` if it doesn't match `condition`.
+    fn updated(
+        &self,
         columns: &[usize],
-        new_values: Vec<ColumnValue>,
+        new_values: &[PreparedValueExpression],
         condition: &Option<PreparedWhere>,
-    ) -> Result<()> {
+        context: &StatementContext,
+    ) -> Result<Option<Row>> {
         assert_eq!(columns.len(), new_values.len());
 
-        if !self.matches(condition)? {
-            return Ok(());
+        if !self.matches(condition, context)? {
+            return Ok(None);
         }
 
-        let self_length = self.0.len();
+        // Evaluate everything first, so that `SET a = b, b = a` uses the old values
+        let new_values = new_values
+            .iter()
+            .map(|expression| self.evaluate(expression, context))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut row = self.clone();
 
         for (index, new_value) in columns.iter().zip(new_values) {
-            *self
-                .0
+            *row.0
                 .get_mut(*index)
-                .ok_or(SqlError::IndexOutOfBounds(*index, self_length))? = new_value;
+                .ok_or(SqlError::IndexOutOfBounds(*index, self.0.len()))? = new_value;
         }
 
-        return Ok(());
+        return Ok(Some(row));
     }
 
-    fn matches(&self, condition: &Option<PreparedWhere>) -> Result<bool> {
+    fn matches(
+        &self,
+        condition: &Option<PreparedWhere>,
+        context: &StatementContext,
+    ) -> Result<bool> {
         if let Some(where_clause) = condition {
-            let PreparedWhere {
-                left,
-                operator,
-                right,
-            } = where_clause;
-
-            return match operator {
-                InfixOperator::Equals => self.evaluate_equal(*left, right),
-                InfixOperator::NotEqual => self.evaluate_not_equal(*left, right),
-                InfixOperator::LessThan => self.evaluate_less_than(*left, right),
-                InfixOperator::LessThanEqual => self.evaluate_less_than_equal(*left, right),
-                InfixOperator::GreaterThan => self.evaluate_greater_than(*left, right),
-                InfixOperator::GreaterThanEqual => self.evaluate_greater_than_equal(*left, right),
-            };
+            // Unknown (NULL) counts as not matching
+            return Ok(self.evaluate_condition(where_clause, context)? == Some(true));
         } else {
             return Ok(true);
         }
     }
 
-    fn evaluate_equal(&self, left: usize, right: &ColumnValue) -> Result<bool> {
-        use ColumnValue::*;
-
-        let left = self.select(&[left])?;
-
-        // left will always have length one
-        let value = left.0.first().unwrap();
-
-        let result = match (value, right) {
-            (Int(left), Int(right)) => Ok(left == right),
-            (Int(left), Decimal(whole, fractional)) => Ok(left == whole && fractional == &0),
-            (Decimal(whole, fractional), Int(right)) => Ok(whole == right && fractional == &0),
-            (Decimal(left_whole, left_fractional), Decimal(right_whole, right_fractional)) => {
-                if left_whole == right_whole {
-                    Ok(true)
-                } else {
-                    Ok(left_fractional == right_fractional)
+    /// Evaluates the condition using three-valued logic, where `None` means unknown,
+    /// e.g. because a NULL was compared.
+    fn evaluate_condition(
+        &self,
+        condition: &PreparedWhere,
+        context: &StatementContext,
+    ) -> Result<Option<bool>> {
+        return match condition {
+            PreparedWhere::Comparison {
+                left,
+                operator,
+                right,
+            } => match operator {
+                InfixOperator::Equals => self.evaluate_equal(left, right, context),
+                InfixOperator::NotEqual => self.evaluate_not_equal(left, right, context),
+                InfixOperator::LessThan => self.evaluate_less_than(left, right, context),
+                InfixOperator::LessThanEqual => self.evaluate_less_than_equal(left, right, context),
+                InfixOperator::GreaterThan => self.evaluate_greater_than(left, right, context),
+                InfixOperator::GreaterThanEqual => {
+                    self.evaluate_greater_than_equal(left, right, context)
                 }
+            },
+            PreparedWhere::IsNull {
+                expression,
+                negated,
+            } => Ok(Some(
+                self.evaluate(expression, context)?.is_null() != *negated,
+            )),
+            // Short-circuits, so the right hand side might not get type-checked
+            PreparedWhere::And(left, right) => match self.evaluate_condition(left, context)? {
+                Some(false) => Ok(Some(false)),
+                left => match (left, self.evaluate_condition(right, context)?) {
+                    (_, Some(false)) => Ok(Some(false)),
+                    (Some(true), Some(true)) => Ok(Some(true)),
+                    _ => Ok(None),
+                },
+            },
+            PreparedWhere::Or(left, right) => match self.evaluate_condition(left, context)? {
+                Some(true) => Ok(Some(true)),
+                left => match (left, self.evaluate_condition(right, context)?) {
+                    (_, Some(true)) => Ok(Some(true)),
+                    (Some(false), Some(false)) => Ok(Some(false)),
+                    _ => Ok(None),
+                },
+            },
+            PreparedWhere::Not(inner) => {
+                Ok(self.evaluate_condition(inner, context)?.map(|value| !value))
             }
-            (Bool(left), Bool(right)) => Ok(left == right),
-            _ => Err(SqlError::ImpossibleComparison(value.clone(), right.clone())),
         };
+    }
+
+    /// Compares both sides, or returns `None` if either of them is NULL.
+    fn evaluate_comparison(
+        &self,
+        left: &PreparedValueExpression,
+        right: &PreparedValueExpression,
+        context: &StatementContext,
+    ) -> Result<Option<Ordering>> {
+        let left = self.evaluate(left, context)?;
+
+        let right = self.evaluate(right, context)?;
 
-        return result;
+        if left.is_null() || right.is_null() {
+            return Ok(None);
+        }
+
+        return left.compare(&right).map(Some);
     }
 
-    fn evaluate_not_equal(&self, left: usize, right: &ColumnValue) -> Result<bool> {
-        return Ok(!self.evaluate_equal(left, right)?);
+    fn evaluate_equal(
+        &self,
+        left: &PreparedValueExpression,
+        right: &PreparedValueExpression,
+        context: &StatementContext,
+    ) -> Result<Option<bool>> {
+        let ordering = self.evaluate_comparison(left, right, context)?;
+
+        return Ok(ordering.map(|ordering| ordering == Ordering::Equal));
     }
 
-    fn evaluate_less_than(&self, left: usize, right: &ColumnValue) -> Result<bool> {
-        let equal = self.evaluate_equal(left, right)?;
+    fn evaluate_not_equal(
+        &self,
+        left: &PreparedValueExpression,
+        right: &PreparedValueExpression,
+        context: &StatementContext,
+    ) -> Result<Option<bool>> {
+        let ordering = self.evaluate_comparison(left, right, context)?;
 
-        if equal {
-            return Ok(false);
-        }
+        return Ok(ordering.map(|ordering| ordering != Ordering::Equal));
+    }
 
-        let less_than_or_equal = self.evaluate_less_than_equal(left, right)?;
+    fn evaluate_less_than(
+        &self,
+        left: &PreparedValueExpression,
+        right: &PreparedValueExpression,
+        context: &StatementContext,
+    ) -> Result<Option<bool>> {
+        let ordering = self.evaluate_comparison(left, right, context)?;
 
-        return Ok(less_than_or_equal);
+        return Ok(ordering.map(|ordering| ordering == Ordering::Less));
     }
 
-    fn evaluate_less_than_equal(&self, left: usize, right: &ColumnValue) -> Result<bool> {
-        use ColumnValue::*;
+    fn evaluate_less_than_equal(
+        &self,
+        left: &PreparedValueExpression,
+        right: &PreparedValueExpression,
+        context: &StatementContext,
+    ) -> Result<Option<bool>> {
+        let ordering = self.evaluate_comparison(left, right, context)?;
 
-        let left = self.select(&[left])?;
+        return Ok(ordering.map(|ordering| ordering != Ordering::Greater));
+    }
 
-        let value = left.0.first().unwrap();
+    fn evaluate_greater_than(
+        &self,
+        left: &PreparedValueExpression,
+        right: &PreparedValueExpression,
+        context: &StatementContext,
+    ) -> Result<Option<bool>> {
+        let ordering = self.evaluate_comparison(left, right, context)?;
 
-        let result = match (value, right) {
-            (Int(left), Int(right)) => Ok(left <= right),
-            (Int(left), Decimal(whole, _)) => Ok(left <= whole),
-            (Decimal(whole, _), Int(right)) => Ok(whole <= right),
-            (Decimal(left_whole, left_fractional), Decimal(right_whole, right_fractional)) => {
-                if left_whole <= right_whole {
-                    Ok(true)
-                } else {
-                    Ok(left_fractional <= right_fractional)
-                }
-            }
-            (Bool(left), Bool(right)) => Ok(!left || *right),
-            _ => Err(SqlError::ImpossibleComparison(value.clone(), right.clone())),
-        };
+        return Ok(ordering.map(|ordering| ordering == Ordering::Greater));
+    }
 
-        return result;
+    fn evaluate_greater_than_equal(
+        &self,
+        left: &PreparedValueExpression,
+        right: &PreparedValueExpression,
+        context: &StatementContext,
+    ) -> Result<Option<bool>> {
+        let ordering = self.evaluate_comparison(left, right, context)?;
+
+        return Ok(ordering.map(|ordering| ordering != Ordering::Less));
     }
 
-    fn evaluate_greater_than(&self, left: usize, right: &ColumnValue) -> Result<bool> {
-        return Ok(!self.evaluate_less_than_equal(left, right)?);
+    /// Makes sure that none of the `NOT NULL` columns are NULL.
+    fn check_not_null(&self, schema: &TableSchema) -> Result<()> {
+        for ((value, nullable), name) in self
+            .0
+            .iter()
+            .zip(&schema.nullable)
+            .zip(&schema.column_names)
+        {
+            if value.is_null() && !nullable {
+                return Err(SqlError::NullNotAllowed(name.clone()));
+            }
+        }
+
+        return Ok(());
     }
 
-    fn evaluate_greater_than_equal(&self, left: usize, right: &ColumnValue) -> Result<bool> {
-        return Ok(!self.evaluate_less_than(left, right)?);
+    /// Converts all values to the types of their columns, see [`ColumnValue::conform_to`].
+    fn conform_to(&mut self, schema: &TableSchema) -> Result<()> {
+        for (value, column_type) in self.0.iter_mut().zip(&schema.types) {
+            *value = value.clone().conform_to(*column_type)?;
+        }
+
+        return Ok(());
     }
 }
 
@@ -177,12 +273,14 @@ impl PartialEq for RowSet {
     }
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(Clone, PartialEq))]
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
 pub struct Table {
     pub schema: TableSchema,
     pub values: Vec<Row>,
     pub constraints: Vec<ForeignKeyConstraint>,
+    indexes: Vec<UniqueIndex>, // One for each of `schema.unique`
+    secondary_indexes: Vec<SecondaryIndex>, // One for each of `schema.indexes`
 }
 
 impl Table {
@@ -190,11 +288,79 @@ impl Table {
         name: TableName,
         columns: Vec<ColumnDefinition>,
         constraints: Vec<ForeignKeyConstraint>,
+        keys: Vec<UniqueConstraint>,
+        checks: Vec<Expression>,
     ) -> Result<Self> {
-        let (column_names, types): (Vec<_>, Vec<_>) = columns
-            .into_iter()
-            .map(|column_definition| (column_definition.0, column_definition.1))
-            .unzip();
+        let mut column_names = vec![];
+        let mut types = vec![];
+        let mut nullable = vec![];
+        let mut unique = vec![];
+        let mut defaults = vec![];
+        let mut column_checks = vec![];
+
+        for ColumnDefinition(name, column_type, constraints) in columns {
+            let mut default = None;
+
+            for constraint in &constraints {
+                let primary = match constraint {
+                    ColumnConstraint::NotNull => continue,
+                    ColumnConstraint::PrimaryKey => true,
+                    ColumnConstraint::Unique => false,
+                    ColumnConstraint::Default(expression) => {
+                        default = Some(expression.clone());
+
+                        continue;
+                    }
+                    ColumnConstraint::Check(condition) => {
+                        column_checks.push(condition.clone());
+
+                        continue;
+                    }
+                    // Turned into a default by `serial_sequences` before the table is made
+                    ColumnConstraint::Serial => return Err(SqlError::InvalidParameter),
+                };
+
+                unique.push(UniqueConstraint {
+                    columns: vec![name.clone()],
+                    primary,
+                });
+            }
+
+            if let Some(expression) = &default {
+                let uses_sequences = !sequence::used_sequences(expression).is_empty();
+
+                // Sequences only have values at insert, which are always integers,
+                // other defaults can't refer to columns, so any mistakes in them show up right away
+                let value = if uses_sequences {
+                    None
+                } else {
+                    Some(ColumnValue::try_from(expression)?)
+                };
+
+                let value_type = match &value {
+                    Some(value) => value.column_type(),
+                    None => Some(ColumnType::BigInt),
+                };
+
+                if let Some(value_type) = value_type {
+                    if !is_assignable(value_type, column_type) {
+                        return Err(SqlError::IncompatibleTypes(
+                            vec![value_type],
+                            vec![column_type],
+                        ));
+                    }
+                }
+
+                if let Some(value) = value {
+                    value.conform_to(column_type)?;
+                }
+            }
+
+            column_names.push(name);
+            types.push(column_type);
+            nullable.push(!constraints.contains(&ColumnConstraint::NotNull));
+            defaults.push(default);
+        }
 
         let mut unique_names = std::collections::HashSet::new();
         for name in column_names.iter() {
@@ -203,17 +369,74 @@ impl Table {
             }
         }
 
+        unique.extend(keys);
+
+        if unique
+            .iter()
+            .filter(|constraint| constraint.primary)
+            .count()
+            > 1
+        {
+            return Err(SqlError::MultiplePrimaryKeys(name));
+        }
+
+        for constraint in &unique {
+            let mut key_names = std::collections::HashSet::new();
+
+            for name in &constraint.columns {
+                let index = column_names
+                    .iter()
+                    .position(|column| column == name)
+                    .ok_or_else(|| {
+                        SqlError::NameDoesNotExist(name.clone(), column_names.clone())
+                    })?;
+
+                if !key_names.insert(name) {
+                    return Err(SqlError::ColumnNameNotUnique(name.clone()));
+                }
+
+                // PRIMARY KEY implies NOT NULL, UNIQUE allows any number of NULLs
+                if constraint.primary {
+                    nullable[index] = false;
+                }
+            }
+        }
+
         let schema = TableSchema {
             name,
             column_names,
             types,
+            nullable,
+            unique,
+            defaults,
+            checks: column_checks.into_iter().chain(checks).collect(),
+            indexes: vec![],
         };
 
-        return Ok(Table {
+        return Table::from_parts(schema, vec![], constraints);
+    }
+
+    /// Puts a table back together from what gets stored, rebuilding its indexes.
+    pub fn from_parts(
+        schema: TableSchema,
+        values: Vec<Row>,
+        constraints: Vec<ForeignKeyConstraint>,
+    ) -> Result<Self> {
+        let indexes = UniqueIndex::build_all(&schema, &values)?;
+
+        let secondary_indexes = SecondaryIndex::build_all(&schema, &values)?;
+
+        let table = Table {
             schema,
-            values: vec![],
+            values,
             constraints,
-        });
+            indexes,
+            secondary_indexes,
+        };
+
+        table.prepare_checks()?;
+
+        return Ok(table);
     }
 
     pub fn insert(
@@ -221,24 +444,194 @@ impl Table {
         columns: &Option<Vec<ColumnName>>,
         row: Vec<ColumnValue>,
     ) -> Result<()> {
-        let types = row.iter().map(|row| row.into()).collect::<Vec<_>>();
+        let checks = self.prepare_checks()?;
 
-        if types != self.schema.types {
+        // Without a database there are no sequences, so defaults using them fail
+        let row = self.prepare_row(columns, row, &checks, &StatementContext::default())?;
+
+        self.push_row(row);
+
+        return Ok(());
+    }
+
+    /// Turns the values of an `INSERT` into a row that fits the table, without inserting it yet.
+    ///
+    /// Columns that aren't listed get their default, which is evaluated with `context`,
+    /// so that it can e.g. take the next value of a sequence.
+    /// The `checks` are the ones returned by [`Table::prepare_checks`], which only need to be prepared once per statement.
+    pub fn prepare_row(
+        &self,
+        columns: &Option<Vec<ColumnName>>,
+        row: Vec<ColumnValue>,
+        checks: &[PreparedWhere],
+        context: &StatementContext,
+    ) -> Result<Row> {
+        let row = match columns {
+            Some(columns) => {
+                if columns.len() != row.len() {
+                    return Err(SqlError::UnequalLengths(columns.len(), row.len()));
+                }
+
+                let mut listed = vec![None; self.schema.types.len()];
+
+                for (name, value) in columns.iter().zip(row) {
+                    let index = self.column_index(name.clone())?;
+
+                    if listed[index].is_some() {
+                        return Err(SqlError::ColumnNameNotUnique(name.clone()));
+                    }
+
+                    listed[index] = Some(value);
+                }
+
+                // Only evaluated for the columns that need them, so no values of a sequence are skipped
+                listed
+                    .into_iter()
+                    .zip(&self.schema.defaults)
+                    .map(|(value, default)| match (value, default) {
+                        (Some(value), _) => Ok(value),
+                        (None, Some(expression)) => context.evaluate(expression),
+                        (None, None) => Ok(ColumnValue::Null),
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            }
+            None => row,
+        };
+
+        // NULL fits in any column, whether it's allowed is checked below
+        let types_match = row.len() == self.schema.types.len()
+            && row
+                .iter()
+                .zip(&self.schema.types)
+                .all(|(value, column_type)| match value.column_type() {
+                    Some(value_type) => is_assignable(value_type, *column_type),
+                    None => true,
+                });
+
+        if !types_match {
             return Err(SqlError::IncompatibleTypes(
-                types,
+                row.iter().filter_map(ColumnValue::column_type).collect(),
                 self.schema.types.clone(),
             ));
         }
 
-        // TODO: Check that all non-nullable columns were passed
-        // But that implies first implementing nullable
-        if let Some(columns) = columns {
-            if columns.len() != row.len() {
-                return Err(SqlError::UnequalLengths(columns.len(), row.len()));
+        let mut row = Row(row);
+
+        row.check_not_null(&self.schema)?;
+
+        row.conform_to(&self.schema)?;
+
+        self.check_row(&row, checks, context)?;
+
+        for (index, constraint) in self.indexes.iter().zip(&self.schema.unique) {
+            index.check(&row, &constraint.columns)?;
+        }
+
+        for (index, definition) in self.secondary_indexes.iter().zip(&self.schema.indexes) {
+            index.check(&row, &definition.columns)?;
+        }
+
+        return Ok(row);
+    }
+
+    /// Inserts a row returned by [`Table::prepare_row`].
+    pub fn push_row(&mut self, row: Row) {
+        for index in &mut self.indexes {
+            index.insert(&row);
+        }
+
+        for index in &mut self.secondary_indexes {
+            index.insert(&row, self.values.len());
+        }
+
+        self.values.push(row);
+    }
+
+    /// Replaces the rows at the given positions, which only happens if all rows are unique where they have to be
+    /// afterwards, see [`replace_keys`].
+    fn update_rows(&mut self, updated: Vec<(usize, Row)>) -> Result<()> {
+        let changed: Vec<_> = updated
+            .iter()
+            .map(|(position, row)| (*position, &self.values[*position], row))
+            .collect();
+
+        replace_keys(
+            &mut self.indexes,
+            &mut self.secondary_indexes,
+            &self.schema,
+            &changed,
+        )?;
+
+        for (position, row) in updated {
+            self.values[position] = row;
+        }
+
+        return Ok(());
+    }
+
+    /// Removes the rows at `positions`, which are in ascending order, and returns them in that order.
+    fn remove_rows(&mut self, positions: Vec<usize>) -> Vec<Row> {
+        let removed: Vec<_> = positions
+            .iter()
+            .map(|position| (*position, &self.values[*position]))
+            .collect();
+
+        for index in &mut self.secondary_indexes {
+            index.remove(&removed);
+        }
+
+        let mut rows = vec![];
+
+        for position in positions.into_iter().rev() {
+            let row = self.values.remove(position);
+
+            for unique_index in &mut self.indexes {
+                unique_index.remove(&row);
             }
+
+            rows.push(row);
         }
 
-        self.values.push(Row(row));
+        rows.reverse();
+
+        return rows;
+    }
+
+    /// The `CHECK` constraints of the table, with their columns resolved.
+    pub fn prepare_checks(&self) -> Result<Vec<PreparedWhere>> {
+        return self
+            .schema
+            .checks
+            .iter()
+            .map(|check| {
+                let check = self.prepare_where_clause(Where::try_from(check)?)?;
+
+                // Checks are about a single row
+                if let Some(function) = check.first_aggregate() {
+                    return Err(SqlError::MisplacedAggregate(function));
+                }
+
+                Ok(check)
+            })
+            .collect();
+    }
+
+    /// Fails if any of the `checks` returned by [`Table::prepare_checks`] is false for `row`.
+    fn check_row(
+        &self,
+        row: &Row,
+        checks: &[PreparedWhere],
+        context: &StatementContext,
+    ) -> Result<()> {
+        for (check, condition) in checks.iter().zip(&self.schema.checks) {
+            // Only false violates a check, unknown (NULL) passes it
+            if row.evaluate_condition(check, context)? == Some(false) {
+                return Err(SqlError::CheckViolation(
+                    self.schema.name.clone(),
+                    condition.to_string(),
+                ));
+            }
+        }
 
         return Ok(());
     }
@@ -248,166 +641,244 @@ impl Table {
         columns: &Option<Vec<ColumnName>>,
         values: Vec<Vec<ColumnValue>>,
     ) -> Result<()> {
+        let checks = self.prepare_checks()?;
+
         for row in values {
-            self.insert(columns, row)?;
+            let row = self.prepare_row(columns, row, &checks, &StatementContext::default())?;
+
+            self.push_row(row);
         }
 
         return Ok(());
     }
 
-    fn prepare_where_clause(&self, clause: Where) -> Result<PreparedWhere> {
-        let Where {
-            left,
-            operator,
-            right,
-        } = clause;
+    /// Whether `name` refers to `column`, either exactly or by qualifying/unqualifying it with a table name.
+    fn refers_to(&self, column: &ColumnName, name: &ColumnName) -> bool {
+        if column == name {
+            return true;
+        }
+
+        return match column.0.split_once('.') {
+            // Joined tables have qualified column names, but can be referred to unqualified
+            Some((_, unqualified)) => unqualified == name.0,
+            None => format!("{}.{}", self.schema.name.0, column.0) == name.0,
+        };
+    }
 
-        let left_index = self
+    fn column_index(&self, name: ColumnName) -> Result<usize> {
+        let mut candidates = self
             .schema
             .column_names
             .iter()
-            .position(|self_name| self_name == &left)
-            .ok_or(SqlError::NameDoesNotExist(
-                left,
+            .enumerate()
+            .filter(|(_, column)| self.refers_to(column, &name))
+            .map(|(index, _)| index);
+
+        return match (candidates.next(), candidates.next()) {
+            (Some(index), None) => Ok(index),
+            (Some(_), Some(_)) => Err(SqlError::AmbiguousColumnName(name)),
+            (None, _) => Err(SqlError::NameDoesNotExist(
+                name,
                 self.schema.column_names.clone(),
-            ))?;
+            )),
+        };
+    }
 
-        return Ok(PreparedWhere {
-            left: left_index,
-            operator,
-            right,
-        });
+    fn column_type(&self, index: &usize) -> Result<ColumnType> {
+        return self
+            .schema
+            .types
+            .get(*index)
+            .copied()
+            .ok_or(SqlError::IndexOutOfBounds(*index, self.schema.types.len()));
+    }
+
+    fn prepare_expression(&self, expression: ValueExpression) -> Result<PreparedValueExpression> {
+        return expression.map_columns(&|name| self.column_index(name));
+    }
+
+    fn prepare_where_clause(&self, clause: Where) -> Result<PreparedWhere> {
+        return clause.map_columns(&|name| self.column_index(name));
     }
 
-    // I don't like that columns is necessarily a vec, it should be a vec of identifiers or an Expression::AllColumns
-    // Also this whole method kinda sucks donkey dick, wtf am I looking at
     pub fn select(&self, columns: ColumnSelector, condition: Option<Where>) -> Result<RowSet> {
-        let column_indices: Vec<_> = match columns {
-            ColumnSelector::AllColumns => (0..self.schema.types.len()).collect(),
-            ColumnSelector::Name(names) => names
-                .iter()
-                .flat_map(|name| {
-                    self.schema
-                        .column_names
-                        .iter()
-                        .position(|self_name| self_name == name)
-                })
-                .collect(),
-        };
+        return self.query(SelectQuery::new(columns, condition));
+    }
 
-        let types = self
+    pub fn query(&self, query: SelectQuery) -> Result<RowSet> {
+        return QueryPlan::new(LogicalPlan::scan(self), query)?
+            .execute(&StatementContext::default());
+    }
+
+    fn qualified_columns(&self) -> Vec<ColumnDefinition> {
+        return self
             .schema
-            .types
+            .column_names
             .iter()
-            .enumerate()
-            .filter_map(|(index, value)| match column_indices.contains(&index) {
-                true => Some(value),
-                false => None,
+            .zip(&self.schema.types)
+            .map(|(name, column_type)| {
+                let name = match name.0.contains('.') {
+                    true => name.clone(),
+                    false => ColumnName(format!("{}.{}", self.schema.name.0, name.0)),
+                };
+
+                // Left joins pad with NULLs, so no column keeps its constraints
+                ColumnDefinition(name, *column_type, vec![])
             })
-            .cloned()
             .collect();
+    }
 
-        let prepared_condition = if let Some(condition) = condition {
-            Some(self.prepare_where_clause(condition)?)
-        } else {
-            None
-        };
+    /// An empty table with the columns of both tables,
+    /// qualified by the table they came from, like `orders.id`.
+    fn joined_schema(&self, other: &Table) -> Result<Table> {
+        let columns = self
+            .qualified_columns()
+            .into_iter()
+            .chain(other.qualified_columns())
+            .collect();
 
-        let mut rows = vec![];
+        let name = TableName(format!(
+            "{} JOIN {}",
+            self.schema.name.0, other.schema.name.0
+        ));
 
-        for row in &self.values {
-            if row.matches(&prepared_condition)? {
-                rows.push(row.select(&column_indices)?);
-            }
-        }
+        return Table::new(name, columns, vec![], vec![], vec![]);
+    }
 
-        return Ok(RowSet {
-            types,
-            names: self.schema.column_names.clone(),
-            values: rows,
-        });
+    /// Combines the rows of both tables into a new table, see [`Table::joined_schema`].
+    pub fn join(&self, other: &Table, kind: JoinType, on: Option<Where>) -> Result<Table> {
+        let plan = LogicalPlan::join(LogicalPlan::scan(self), LogicalPlan::scan(other), kind, on)?;
+
+        let mut result = self.joined_schema(other)?;
+
+        result.values = plan::optimise(plan)
+            .into_physical()
+            .open(&StatementContext::default())?
+            .map(|row| row.map(Cow::into_owned))
+            .collect::<Result<_>>()?;
+
+        return Ok(result);
     }
 
     pub fn update(
         &mut self,
         columns: Vec<ColumnName>,
-        new_values: Vec<ColumnValue>,
+        new_values: Vec<ValueExpression>,
         condition: Option<Where>,
-    ) -> Result<()> {
-        let new_types: Vec<ColumnType> = new_values.iter().map(|value| value.into()).collect();
+        context: &StatementContext,
+    ) -> Result<Vec<RowChange>> {
+        if columns.len() != new_values.len() {
+            return Err(SqlError::UnequalLengths(columns.len(), new_values.len()));
+        }
 
         let column_indices = columns
+            .into_iter()
+            .map(|name| self.column_index(name))
+            .collect::<Result<Vec<_>>>()?;
+
+        let new_values = new_values
+            .into_iter()
+            .map(|expression| self.prepare_expression(expression))
+            .collect::<Result<Vec<_>>>()?;
+
+        let self_types = column_indices
             .iter()
-            .flat_map(|name| {
-                self.schema
-                    .column_names
-                    .iter()
-                    .position(|self_name| self_name == name)
-            })
-            .collect::<Vec<_>>();
+            .map(|index| self.column_type(index))
+            .collect::<Result<Vec<_>>>()?;
 
-        let self_types: Vec<_> = self
-            .schema
-            .types
+        // NULL fits any type, whether it's allowed is checked per row
+        let new_types = new_values
             .iter()
-            .enumerate()
-            .filter_map(|(index, value)| {
-                if column_indices.contains(&index) {
-                    Some(*value)
-                } else {
-                    None
-                }
+            .zip(&self_types)
+            .map(|(expression, self_type)| {
+                Ok(expression
+                    .result_type(&|index| self.column_type(index))?
+                    .unwrap_or(*self_type))
             })
-            .collect();
+            .collect::<Result<Vec<_>>>()?;
 
-        if self_types != new_types {
-            return Err(SqlError::IncompatibleTypes(
-                new_types,
-                self.schema.types.clone(),
-            ));
+        let types_match = new_types
+            .iter()
+            .zip(&self_types)
+            .all(|(new_type, self_type)| is_assignable(*new_type, *self_type));
+
+        if !types_match {
+            return Err(SqlError::IncompatibleTypes(new_types, self_types));
         }
 
-        let prepared_condition = if let Some(condition) = condition {
-            Some(self.prepare_where_clause(condition)?)
-        } else {
-            None
+        // Folded like `SELECT` does, so the same indexes get used
+        let prepared_condition = match condition {
+            Some(condition) => Some(fold_condition(self.prepare_where_clause(condition)?)),
+            None => None,
         };
 
-        for row in &mut self.values {
-            row.update(&column_indices, new_values.clone(), &prepared_condition)?;
+        let checks = self.prepare_checks()?;
+
+        // Nothing is changed until all rows are updated, so the table is left as it was if any of them fails
+        let mut updated = vec![];
+
+        let mut changes = vec![];
+
+        for position in self.candidate_rows(&prepared_condition) {
+            let old_row = &self.values[position];
+
+            let Some(mut row) =
+                old_row.updated(&column_indices, &new_values, &prepared_condition, context)?
+            else {
+                continue;
+            };
+
+            row.check_not_null(&self.schema)?;
+
+            row.conform_to(&self.schema)?;
+
+            self.check_row(&row, &checks, context)?;
+
+            changes.push((old_row.clone(), Some(row.clone())));
+
+            updated.push((position, row));
         }
 
-        return Ok(());
+        self.update_rows(updated)?;
+
+        return Ok(changes);
     }
 
-    pub fn delete(&mut self, condition: Option<Where>) -> Result<()> {
+    pub fn delete(
+        &mut self,
+        condition: Option<Where>,
+        context: &StatementContext,
+    ) -> Result<Vec<RowChange>> {
         let mut remove_indices = vec![];
 
-        let prepared_condition = if let Some(condition) = condition {
-            Some(self.prepare_where_clause(condition)?)
-        } else {
-            None
+        // Folded like `SELECT` does, so the same indexes get used
+        let prepared_condition = match condition {
+            Some(condition) => Some(fold_condition(self.prepare_where_clause(condition)?)),
+            None => None,
         };
 
-        for (index, row) in self.values.iter().enumerate() {
-            if row.matches(&prepared_condition)? {
+        for index in self.candidate_rows(&prepared_condition) {
+            if self.values[index].matches(&prepared_condition, context)? {
                 remove_indices.push(index);
             }
         }
 
-        for index in remove_indices.into_iter().rev() {
-            self.values.remove(index);
-        }
+        let changes = self
+            .remove_rows(remove_indices)
+            .into_iter()
+            .map(|row| (row, None))
+            .collect();
 
-        return Ok(());
+        return Ok(changes);
     }
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(Clone, PartialEq))]
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
 pub struct Database {
     pub name: DatabaseName,
     pub tables: HashMap<String, Table>,
+    pub sequences: HashMap<String, Sequence>,
 }
 
 impl Database {
@@ -415,6 +886,7 @@ impl Database {
         return Self {
             name,
             tables: HashMap::new(),
+            sequences: HashMap::new(),
         };
     }
 }
@@ -0,0 +1,549 @@
+//! Turns queries into plans, which are rewritten to be faster before they are executed.
+//!
+//! A [`LogicalPlan`] says what a query does, see [`optimise`] for the rewrites,
+//! and a [`PhysicalPlan`] how it gets done.
+
+mod optimise;
+mod physical;
+#[cfg(test)]
+mod tests;
+
+use std::borrow::Cow;
+
+use sql_parse::parser::{ColumnType, JoinType, SortDirection};
+
+use super::index::IndexLookup;
+use super::{Database, Row, RowSet, StatementContext, Table};
+use crate::types::{
+    ColumnName, ColumnSelector, ColumnValue, PreparedValueExpression, PreparedWhere, SelectQuery,
+    TableName, TableReference, ValueExpression, Where,
+};
+use crate::{Result, SqlError};
+pub use optimise::{fold_condition, optimise};
+use physical::Rows;
+
+#[derive(Debug)]
+pub enum LogicalPlan<'a> {
+    /// The rows of a table, or only the ones the lookup finds
+    Scan {
+        table: &'a Table,
+        index: Option<IndexLookup>,
+    },
+    Filter {
+        input: Box<LogicalPlan<'a>>,
+        condition: PreparedWhere,
+    },
+    /// The rows of both sides next to each other, with the columns of `schema`
+    Join {
+        left: Box<LogicalPlan<'a>>,
+        right: Box<LogicalPlan<'a>>,
+        kind: JoinType,
+        on: Option<PreparedWhere>,
+        schema: Box<Table>,
+    },
+    /// One row for each group, with the values of one of its rows followed by the values of the aggregates
+    Aggregate {
+        input: Box<LogicalPlan<'a>>,
+        group_by: Vec<PreparedValueExpression>,
+        aggregates: Vec<PreparedValueExpression>,
+    },
+    Sort {
+        input: Box<LogicalPlan<'a>>,
+        keys: Vec<(PreparedValueExpression, SortDirection)>,
+    },
+    Limit {
+        input: Box<LogicalPlan<'a>>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    },
+    Project {
+        input: Box<LogicalPlan<'a>>,
+        expressions: Vec<PreparedValueExpression>,
+    },
+}
+
+impl<'a> LogicalPlan<'a> {
+    pub fn scan(table: &'a Table) -> Self {
+        return LogicalPlan::Scan { table, index: None };
+    }
+
+    /// Joins the rows of two plans, where the columns of the result are qualified by the table they came from.
+    pub fn join(
+        left: LogicalPlan<'a>,
+        right: LogicalPlan<'a>,
+        kind: JoinType,
+        on: Option<Where>,
+    ) -> Result<Self> {
+        let schema = left.schema().joined_schema(right.schema())?;
+
+        let on = match on {
+            Some(condition) => Some(schema.prepare_where_clause(condition)?),
+            None => None,
+        };
+
+        return Ok(LogicalPlan::Join {
+            left: Box::new(left),
+            right: Box::new(right),
+            kind,
+            on,
+            schema: Box::new(schema),
+        });
+    }
+
+    /// The table whose columns the rows of a scan or join have, which the rows of the plans above them keep.
+    fn schema(&self) -> &Table {
+        return match self {
+            LogicalPlan::Scan { table, .. } => table,
+            LogicalPlan::Join { schema, .. } => schema,
+            LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Aggregate { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Limit { input, .. }
+            | LogicalPlan::Project { input, .. } => input.schema(),
+        };
+    }
+
+    /// Replaces the plans this one uses the rows of with what `map` makes of them.
+    fn map_inputs(self, map: &mut impl FnMut(LogicalPlan<'a>) -> LogicalPlan<'a>) -> Self {
+        let mut map = |input: Box<LogicalPlan<'a>>| Box::new(map(*input));
+
+        return match self {
+            LogicalPlan::Scan { .. } => self,
+            LogicalPlan::Filter { input, condition } => LogicalPlan::Filter {
+                input: map(input),
+                condition,
+            },
+            LogicalPlan::Join {
+                left,
+                right,
+                kind,
+                on,
+                schema,
+            } => LogicalPlan::Join {
+                left: map(left),
+                right: map(right),
+                kind,
+                on,
+                schema,
+            },
+            LogicalPlan::Aggregate {
+                input,
+                group_by,
+                aggregates,
+            } => LogicalPlan::Aggregate {
+                input: map(input),
+                group_by,
+                aggregates,
+            },
+            LogicalPlan::Sort { input, keys } => LogicalPlan::Sort {
+                input: map(input),
+                keys,
+            },
+            LogicalPlan::Limit {
+                input,
+                limit,
+                offset,
+            } => LogicalPlan::Limit {
+                input: map(input),
+                limit,
+                offset,
+            },
+            LogicalPlan::Project { input, expressions } => LogicalPlan::Project {
+                input: map(input),
+                expressions,
+            },
+        };
+    }
+
+    /// The number of columns of a scan or join, see [`LogicalPlan::schema`].
+    fn width(&self) -> usize {
+        return self.schema().schema.types.len();
+    }
+}
+
+/// A `SELECT` as a plan, with the names and types of the columns it returns.
+#[derive(Debug)]
+pub struct QueryPlan<'a> {
+    pub plan: LogicalPlan<'a>,
+    pub names: Vec<ColumnName>,
+    pub types: Vec<ColumnType>,
+}
+
+impl<'a> QueryPlan<'a> {
+    /// Plans `query` over the rows of `source`, which is a scan or a join.
+    ///
+    /// Everything that can be checked without looking at the rows is checked here,
+    /// so the same mistakes are found however the plan ends up being executed.
+    pub fn new(source: LogicalPlan<'a>, query: SelectQuery) -> Result<Self> {
+        let SelectQuery {
+            columns,
+            condition,
+            group_by,
+            having,
+            order_by,
+            limit,
+            offset,
+        } = query;
+
+        let table = source.schema();
+
+        let width = table.schema.types.len();
+
+        let (names, expressions): (Vec<ColumnName>, Vec<PreparedValueExpression>) = match columns {
+            ColumnSelector::AllColumns => (
+                table.schema.column_names.clone(),
+                (0..width).map(ValueExpression::Column).collect(),
+            ),
+            ColumnSelector::Expressions(expressions) => expressions
+                .into_iter()
+                .map(|expression| {
+                    // Plain columns keep their name, anything else is named after the expression
+                    let name = match &expression {
+                        ValueExpression::Column(name) => name.clone(),
+                        other => ColumnName(other.to_string()),
+                    };
+
+                    Ok((name, table.prepare_expression(expression)?))
+                })
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .unzip(),
+        };
+
+        let column_type = |index: &usize| table.column_type(index);
+
+        let types = expressions
+            .iter()
+            .map(|expression| {
+                // A column that is always NULL could have any type
+                Ok(expression
+                    .result_type(&column_type)?
+                    .unwrap_or(ColumnType::Int))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let condition = match condition {
+            Some(condition) => Some(table.prepare_where_clause(condition)?),
+            None => None,
+        };
+
+        // WHERE picks the rows that get aggregated, so it can't contain aggregates itself
+        if let Some(function) = condition
+            .as_ref()
+            .and_then(|condition| condition.first_aggregate())
+        {
+            return Err(SqlError::MisplacedAggregate(function));
+        }
+
+        let group_by = group_by
+            .into_iter()
+            .map(|expression| table.prepare_expression(expression))
+            .collect::<Result<Vec<_>>>()?;
+
+        for expression in &group_by {
+            if let Some(function) = expression.first_aggregate() {
+                return Err(SqlError::MisplacedAggregate(function));
+            }
+
+            expression.result_type(&column_type)?;
+        }
+
+        let having = match having {
+            Some(having) => Some(table.prepare_where_clause(having)?),
+            None => None,
+        };
+
+        let order_by = order_by
+            .into_iter()
+            .map(|key| Ok((table.prepare_expression(key.expression)?, key.direction)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let grouped = !group_by.is_empty()
+            || having.is_some()
+            || expressions
+                .iter()
+                .chain(order_by.iter().map(|(expression, _)| expression))
+                .any(|expression| expression.first_aggregate().is_some());
+
+        if grouped {
+            // Only columns that are grouped by have the same value for the entire group
+            let grouped_columns: Vec<usize> = group_by
+                .iter()
+                .filter_map(|expression| match expression {
+                    ValueExpression::Column(index) => Some(*index),
+                    _ => None,
+                })
+                .collect();
+
+            let ungrouped = expressions
+                .iter()
+                .chain(order_by.iter().map(|(expression, _)| expression))
+                .find_map(|expression| expression.ungrouped_column(&grouped_columns))
+                .or_else(|| {
+                    having
+                        .as_ref()
+                        .and_then(|having| having.ungrouped_column(&grouped_columns))
+                });
+
+            if let Some(index) = ungrouped {
+                return Err(SqlError::NotGrouped(
+                    table.schema.column_names[index].clone(),
+                ));
+            }
+        }
+
+        let mut plan = source;
+
+        if let Some(condition) = condition {
+            plan = LogicalPlan::Filter {
+                input: Box::new(plan),
+                condition,
+            };
+        }
+
+        let (expressions, order_by) = match grouped {
+            true => {
+                // Aggregates become columns after the ones of the rows, which the aggregation adds
+                let mut aggregates = vec![];
+
+                let mut extract = |expression: PreparedValueExpression| {
+                    extract_aggregates(expression, width, &mut aggregates)
+                };
+
+                let expressions: Vec<_> = expressions.into_iter().map(&mut extract).collect();
+
+                let order_by: Vec<_> = order_by
+                    .into_iter()
+                    .map(|(expression, direction)| (extract(expression), direction))
+                    .collect();
+
+                let having = having.map(|having| having.map_expressions(&mut extract));
+
+                plan = LogicalPlan::Aggregate {
+                    input: Box::new(plan),
+                    group_by,
+                    aggregates,
+                };
+
+                if let Some(having) = having {
+                    plan = LogicalPlan::Filter {
+                        input: Box::new(plan),
+                        condition: having,
+                    };
+                }
+
+                (expressions, order_by)
+            }
+            false => (expressions, order_by),
+        };
+
+        if !order_by.is_empty() {
+            plan = LogicalPlan::Sort {
+                input: Box::new(plan),
+                keys: order_by,
+            };
+        }
+
+        if limit.is_some() || offset.is_some() {
+            plan = LogicalPlan::Limit {
+                input: Box::new(plan),
+                limit,
+                offset,
+            };
+        }
+
+        plan = LogicalPlan::Project {
+            input: Box::new(plan),
+            expressions,
+        };
+
+        return Ok(QueryPlan { plan, names, types });
+    }
+
+    /// Rewrites the plan and starts executing it, see [`RowStream`].
+    pub fn stream(self, context: &'a StatementContext) -> Result<RowStream<'a>> {
+        return Ok(RowStream {
+            rows: optimise(self.plan).into_physical().open(context)?,
+            types: self.types,
+            names: self.names,
+        });
+    }
+
+    /// Rewrites the plan and executes it.
+    pub fn execute(self, context: &StatementContext) -> Result<RowSet> {
+        return self.stream(context)?.into_row_set();
+    }
+
+    /// The plan [`QueryPlan::execute`] would execute, one operator per row, indented below the operator using its rows.
+    pub fn explain(self) -> Result<RowSet> {
+        return Ok(explanation(optimise(self.plan).into_physical().explain()));
+    }
+}
+
+/// The rows of a query, which are found one at a time as they are asked for,
+/// so a `LIMIT` stops the query once it has enough and scans don't need memory for every row.
+pub struct RowStream<'a> {
+    pub types: Vec<ColumnType>,
+    pub names: Vec<ColumnName>,
+    rows: Rows<'a>,
+}
+
+impl RowStream<'_> {
+    /// Collects the rest of the rows, or the first error.
+    pub fn into_row_set(mut self) -> Result<RowSet> {
+        let values = self.by_ref().collect::<Result<Vec<_>>>()?;
+
+        return Ok(RowSet {
+            types: self.types,
+            names: self.names,
+            values,
+        });
+    }
+}
+
+impl Iterator for RowStream<'_> {
+    type Item = Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        return self.rows.next().map(|row| row.map(Cow::into_owned));
+    }
+}
+
+impl std::fmt::Debug for RowStream<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return f
+            .debug_struct("RowStream")
+            .field("types", &self.types)
+            .field("names", &self.names)
+            .finish_non_exhaustive();
+    }
+}
+
+/// Replaces the aggregates in `expression` with the columns they become after aggregating rows with `width` columns,
+/// adding them to `aggregates` unless the same aggregate is there already.
+fn extract_aggregates(
+    expression: PreparedValueExpression,
+    width: usize,
+    aggregates: &mut Vec<PreparedValueExpression>,
+) -> PreparedValueExpression {
+    let mut extract = |expression: Box<PreparedValueExpression>| {
+        Box::new(extract_aggregates(*expression, width, aggregates))
+    };
+
+    return match expression {
+        ValueExpression::Aggregate { .. } => {
+            let position = aggregates
+                .iter()
+                .position(|aggregate| aggregate.is_same(&expression));
+
+            let position = match position {
+                Some(position) => position,
+                None => {
+                    aggregates.push(expression);
+
+                    aggregates.len() - 1
+                }
+            };
+
+            ValueExpression::Column(width + position)
+        }
+        ValueExpression::Column(_) | ValueExpression::Value(_) | ValueExpression::Function(_) => {
+            expression
+        }
+        ValueExpression::Binary {
+            left,
+            operator,
+            right,
+        } => ValueExpression::Binary {
+            left: extract(left),
+            operator,
+            right: extract(right),
+        },
+        ValueExpression::Unary { operator, operand } => ValueExpression::Unary {
+            operator,
+            operand: extract(operand),
+        },
+        ValueExpression::Extract { field, source } => ValueExpression::Extract {
+            field,
+            source: extract(source),
+        },
+    };
+}
+
+/// The lines of an `EXPLAIN` as a single column of text.
+fn explanation(lines: Vec<String>) -> RowSet {
+    let values = lines
+        .into_iter()
+        .map(|line| Row(vec![ColumnValue::Str(line)]))
+        .collect();
+
+    return RowSet {
+        types: vec![ColumnType::Text],
+        names: vec![ColumnName("plan".into())],
+        values,
+    };
+}
+
+impl Database {
+    /// The scan or join of the tables `from` refers to.
+    pub fn plan_source(&self, from: TableReference) -> Result<LogicalPlan<'_>> {
+        let table = |name: TableName| {
+            return self
+                .tables
+                .get(&name.0)
+                .map(LogicalPlan::scan)
+                .ok_or(SqlError::TableDoesNotExist(name));
+        };
+
+        return match from {
+            TableReference::Table(name) => table(name),
+            TableReference::Join {
+                left,
+                right,
+                kind,
+                on,
+            } => LogicalPlan::join(self.plan_source(*left)?, table(right)?, kind, on),
+        };
+    }
+
+    pub fn plan_query(&self, from: TableReference, query: SelectQuery) -> Result<QueryPlan<'_>> {
+        return QueryPlan::new(self.plan_source(from)?, query);
+    }
+
+    /// Explains how an `UPDATE` or `DELETE` finds the rows of `table_name` it changes.
+    pub fn explain_change(
+        &self,
+        change: &str,
+        table_name: TableName,
+        condition: Option<Where>,
+    ) -> Result<RowSet> {
+        let table = self
+            .tables
+            .get(&table_name.0)
+            .ok_or(SqlError::TableDoesNotExist(table_name.clone()))?;
+
+        let mut lines = vec![format!("{change} on {}", table_name.0)];
+
+        let plan = optimise(table.plan_rows(condition)?).into_physical();
+
+        lines.extend(plan.explain().into_iter().map(|line| format!("  {line}")));
+
+        return Ok(explanation(lines));
+    }
+}
+
+impl Table {
+    /// Plans finding the rows of the table that match `condition`, like `UPDATE` and `DELETE` do.
+    pub fn plan_rows(&self, condition: Option<Where>) -> Result<LogicalPlan<'_>> {
+        let mut plan = LogicalPlan::scan(self);
+
+        if let Some(condition) = condition {
+            plan = LogicalPlan::Filter {
+                input: Box::new(plan),
+                condition: self.prepare_where_clause(condition)?,
+            };
+        }
+
+        return Ok(plan);
+    }
+}
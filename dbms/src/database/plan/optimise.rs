@@ -0,0 +1,327 @@
+//! Rewrites of logical plans that make them faster without changing what they return.
+
+use sql_parse::parser::JoinType;
+
+use super::LogicalPlan;
+use crate::database::StatementContext;
+use crate::types::{PreparedValueExpression, PreparedWhere, ValueExpression, Where};
+use crate::SqlError;
+
+/// Folds constants, pushes conditions down to the tables they are about and picks indexes to find rows with.
+pub fn optimise(plan: LogicalPlan) -> LogicalPlan {
+    let plan = fold_constants(plan);
+
+    let plan = push_down_conditions(plan);
+
+    return select_indexes(plan);
+}
+
+/// Evaluates the parts of expressions that are the same for every row.
+fn fold_constants(plan: LogicalPlan) -> LogicalPlan {
+    let fold_all = |expressions: Vec<PreparedValueExpression>| {
+        expressions.into_iter().map(fold_expression).collect()
+    };
+
+    return match plan {
+        LogicalPlan::Scan { .. } => plan,
+        LogicalPlan::Filter { input, condition } => LogicalPlan::Filter {
+            input: Box::new(fold_constants(*input)),
+            condition: fold_condition(condition),
+        },
+        LogicalPlan::Join {
+            left,
+            right,
+            kind,
+            on,
+            schema,
+        } => LogicalPlan::Join {
+            left: Box::new(fold_constants(*left)),
+            right: Box::new(fold_constants(*right)),
+            kind,
+            on: on.map(fold_condition),
+            schema,
+        },
+        LogicalPlan::Aggregate {
+            input,
+            group_by,
+            aggregates,
+        } => LogicalPlan::Aggregate {
+            input: Box::new(fold_constants(*input)),
+            group_by: fold_all(group_by),
+            aggregates: fold_all(aggregates),
+        },
+        LogicalPlan::Sort { input, keys } => LogicalPlan::Sort {
+            input: Box::new(fold_constants(*input)),
+            keys: keys
+                .into_iter()
+                .map(|(expression, direction)| (fold_expression(expression), direction))
+                .collect(),
+        },
+        LogicalPlan::Limit {
+            input,
+            limit,
+            offset,
+        } => LogicalPlan::Limit {
+            input: Box::new(fold_constants(*input)),
+            limit,
+            offset,
+        },
+        LogicalPlan::Project { input, expressions } => LogicalPlan::Project {
+            input: Box::new(fold_constants(*input)),
+            expressions: fold_all(expressions),
+        },
+    };
+}
+
+/// Evaluates the parts of `condition` that are the same for every row, like `UPDATE` and `DELETE` do too.
+pub fn fold_condition(condition: PreparedWhere) -> PreparedWhere {
+    return condition.map_expressions(&mut fold_expression);
+}
+
+fn fold_expression(expression: PreparedValueExpression) -> PreparedValueExpression {
+    let fold = |expression: Box<PreparedValueExpression>| Box::new(fold_expression(*expression));
+
+    let folded = match expression {
+        // Functions can't be folded, since they are evaluated when the statement is executed
+        ValueExpression::Column(_) | ValueExpression::Value(_) | ValueExpression::Function(_) => {
+            return expression
+        }
+        ValueExpression::Binary {
+            left,
+            operator,
+            right,
+        } => ValueExpression::Binary {
+            left: fold(left),
+            operator,
+            right: fold(right),
+        },
+        ValueExpression::Unary { operator, operand } => ValueExpression::Unary {
+            operator,
+            operand: fold(operand),
+        },
+        // Aggregates have a value for each group, but their arguments can still be folded
+        ValueExpression::Aggregate { function, argument } => {
+            return ValueExpression::Aggregate {
+                function,
+                argument: argument.map(fold),
+            }
+        }
+        ValueExpression::Extract { field, source } => ValueExpression::Extract {
+            field,
+            source: fold(source),
+        },
+    };
+
+    let is_constant = match &folded {
+        ValueExpression::Binary { left, right, .. } => {
+            matches!(
+                (left.as_ref(), right.as_ref()),
+                (ValueExpression::Value(_), ValueExpression::Value(_))
+            )
+        }
+        ValueExpression::Unary { operand, .. } => {
+            matches!(operand.as_ref(), ValueExpression::Value(_))
+        }
+        ValueExpression::Extract { source, .. } => {
+            matches!(source.as_ref(), ValueExpression::Value(_))
+        }
+        _ => false,
+    };
+
+    // Mistakes like dividing by zero are left for when rows are evaluated, so queries without rows still work
+    return match is_constant {
+        true => match folded.evaluate(
+            &|index| Err(SqlError::IndexOutOfBounds(*index, 0)),
+            &StatementContext::default(),
+        ) {
+            Ok(value) => ValueExpression::Value(value),
+            Err(_) => folded,
+        },
+        false => folded,
+    };
+}
+
+/// Moves the parts of conditions that only use the columns of one side of a join below it,
+/// so that side has fewer rows to join.
+fn push_down_conditions(plan: LogicalPlan) -> LogicalPlan {
+    return match plan {
+        LogicalPlan::Filter { input, condition } => match *input {
+            LogicalPlan::Join {
+                left,
+                right,
+                kind,
+                on,
+                schema,
+            } => {
+                let width = left.width();
+
+                let mut kept = vec![];
+                let mut to_left = vec![];
+                let mut to_right = vec![];
+
+                for part in conjuncts(condition) {
+                    match columns_range(&part) {
+                        Some((_, last)) if last < width => to_left.push(part),
+                        // Filtering the right side first would pad rows of a left join that get filtered out now
+                        Some((first, _)) if first >= width && kind != JoinType::Left => {
+                            to_right.push(shift_columns(part, width))
+                        }
+                        _ => kept.push(part),
+                    }
+                }
+
+                let join = LogicalPlan::Join {
+                    left: Box::new(filter(*left, to_left)),
+                    right: Box::new(filter(*right, to_right)),
+                    kind,
+                    on,
+                    schema,
+                };
+
+                filter(push_down_conditions(join), kept)
+            }
+            input => LogicalPlan::Filter {
+                input: Box::new(push_down_conditions(input)),
+                condition,
+            },
+        },
+        LogicalPlan::Join {
+            left,
+            right,
+            kind,
+            on,
+            schema,
+        } => {
+            let width = left.width();
+
+            let mut kept = vec![];
+            let mut to_left = vec![];
+            let mut to_right = vec![];
+
+            for part in on.map(conjuncts).unwrap_or_default() {
+                match columns_range(&part) {
+                    // A left join keeps all rows of the left side, even the ones that don't match
+                    Some((_, last)) if last < width && kind != JoinType::Left => to_left.push(part),
+                    Some((first, _)) if first >= width => to_right.push(shift_columns(part, width)),
+                    _ => kept.push(part),
+                }
+            }
+
+            LogicalPlan::Join {
+                left: Box::new(push_down_conditions(filter(*left, to_left))),
+                right: Box::new(push_down_conditions(filter(*right, to_right))),
+                kind,
+                on: conjoin(kept),
+                schema,
+            }
+        }
+        other => other.map_inputs(&mut push_down_conditions),
+    };
+}
+
+/// The parts of `condition` that all have to be true for it to be.
+fn conjuncts(condition: PreparedWhere) -> Vec<PreparedWhere> {
+    return match condition {
+        Where::And(left, right) => {
+            let mut result = conjuncts(*left);
+
+            result.extend(conjuncts(*right));
+
+            result
+        }
+        other => vec![other],
+    };
+}
+
+/// The opposite of [`conjuncts`], `None` if there are no parts.
+fn conjoin(parts: Vec<PreparedWhere>) -> Option<PreparedWhere> {
+    return parts
+        .into_iter()
+        .reduce(|left, right| Where::And(Box::new(left), Box::new(right)));
+}
+
+fn filter(input: LogicalPlan, parts: Vec<PreparedWhere>) -> LogicalPlan {
+    return match conjoin(parts) {
+        Some(condition) => LogicalPlan::Filter {
+            input: Box::new(input),
+            condition,
+        },
+        None => input,
+    };
+}
+
+/// The lowest and highest column `condition` uses, or `None` if it doesn't use any.
+fn columns_range(condition: &PreparedWhere) -> Option<(usize, usize)> {
+    let mut columns = vec![];
+
+    condition_columns(condition, &mut columns);
+
+    return Some((*columns.iter().min()?, *columns.iter().max()?));
+}
+
+fn condition_columns(condition: &PreparedWhere, columns: &mut Vec<usize>) {
+    match condition {
+        Where::Comparison { left, right, .. } => {
+            expression_columns(left, columns);
+
+            expression_columns(right, columns);
+        }
+        Where::IsNull { expression, .. } => expression_columns(expression, columns),
+        Where::And(left, right) | Where::Or(left, right) => {
+            condition_columns(left, columns);
+
+            condition_columns(right, columns);
+        }
+        Where::Not(inner) => condition_columns(inner, columns),
+    }
+}
+
+fn expression_columns(expression: &PreparedValueExpression, columns: &mut Vec<usize>) {
+    match expression {
+        ValueExpression::Column(index) => columns.push(*index),
+        ValueExpression::Value(_) | ValueExpression::Function(_) => {}
+        ValueExpression::Binary { left, right, .. } => {
+            expression_columns(left, columns);
+
+            expression_columns(right, columns);
+        }
+        ValueExpression::Unary { operand, .. } => expression_columns(operand, columns),
+        ValueExpression::Aggregate { argument, .. } => {
+            if let Some(argument) = argument {
+                expression_columns(argument, columns);
+            }
+        }
+        ValueExpression::Extract { source, .. } => expression_columns(source, columns),
+    }
+}
+
+/// Moves the columns of `condition` `width` to the left, for the right side of a join.
+fn shift_columns(condition: PreparedWhere, width: usize) -> PreparedWhere {
+    return condition.map_expressions(&mut |expression| {
+        // Only called for conditions about the right side, whose columns all come after the left side
+        return expression
+            .map_columns(&|index: usize| Ok(index - width))
+            .unwrap_or_else(|_| unreachable!());
+    });
+}
+
+/// Has scans that are filtered look up their rows with an index, if one helps.
+fn select_indexes(plan: LogicalPlan) -> LogicalPlan {
+    return match plan {
+        LogicalPlan::Filter { input, condition } => {
+            let input = match *input {
+                LogicalPlan::Scan { table, index: None } => LogicalPlan::Scan {
+                    table,
+                    index: table.choose_index(&condition),
+                },
+                input => select_indexes(input),
+            };
+
+            LogicalPlan::Filter {
+                input: Box::new(input),
+                condition,
+            }
+        }
+        other => other.map_inputs(&mut select_indexes),
+    };
+}
@@ -0,0 +1,472 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+use sql_parse::parser::{JoinType, SortDirection};
+
+use super::super::aggregate::group_rows;
+use super::super::index::IndexLookup;
+use super::super::{Row, StatementContext, Table};
+use super::LogicalPlan;
+use crate::types::{ColumnName, ColumnValue, PreparedValueExpression, PreparedWhere};
+use crate::{Result, SqlError};
+
+/// Rows that are only found when they are asked for, see [`PhysicalPlan::open`].
+pub type Rows<'a> = Box<dyn Iterator<Item = Result<Cow<'a, Row>>> + Send + 'a>;
+
+/// How a [`LogicalPlan`] gets executed, as a tree of operators that each pass rows to the one above them.
+#[derive(Debug)]
+pub enum PhysicalPlan<'a> {
+    SeqScan(&'a Table),
+    /// The rows an index finds, in the order they are in the table
+    IndexScan {
+        table: &'a Table,
+        lookup: IndexLookup,
+    },
+    Filter {
+        input: Box<PhysicalPlan<'a>>,
+        condition: PreparedWhere,
+    },
+    /// Compares every row of the left side with every row of the right side
+    NestedLoopJoin {
+        left: Box<PhysicalPlan<'a>>,
+        right: Box<PhysicalPlan<'a>>,
+        kind: JoinType,
+        on: Option<PreparedWhere>,
+        names: Vec<ColumnName>, // Of the joined rows
+    },
+    /// Sorts the rows into groups, see [`group_rows`]
+    GroupAggregate {
+        input: Box<PhysicalPlan<'a>>,
+        group_by: Vec<PreparedValueExpression>,
+        aggregates: Vec<PreparedValueExpression>,
+        width: usize, // Of the input rows
+    },
+    Sort {
+        input: Box<PhysicalPlan<'a>>,
+        keys: Vec<(PreparedValueExpression, SortDirection)>,
+    },
+    Limit {
+        input: Box<PhysicalPlan<'a>>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    },
+    Project {
+        input: Box<PhysicalPlan<'a>>,
+        expressions: Vec<PreparedValueExpression>,
+    },
+}
+
+impl<'a> LogicalPlan<'a> {
+    pub fn into_physical(self) -> PhysicalPlan<'a> {
+        return match self {
+            LogicalPlan::Scan { table, index: None } => PhysicalPlan::SeqScan(table),
+            LogicalPlan::Scan {
+                table,
+                index: Some(lookup),
+            } => PhysicalPlan::IndexScan { table, lookup },
+            LogicalPlan::Filter { input, condition } => PhysicalPlan::Filter {
+                input: Box::new(input.into_physical()),
+                condition,
+            },
+            LogicalPlan::Join {
+                left,
+                right,
+                kind,
+                on,
+                schema,
+            } => PhysicalPlan::NestedLoopJoin {
+                left: Box::new(left.into_physical()),
+                right: Box::new(right.into_physical()),
+                kind,
+                on,
+                names: schema.schema.column_names,
+            },
+            LogicalPlan::Aggregate {
+                input,
+                group_by,
+                aggregates,
+            } => PhysicalPlan::GroupAggregate {
+                width: input.width(),
+                input: Box::new(input.into_physical()),
+                group_by,
+                aggregates,
+            },
+            LogicalPlan::Sort { input, keys } => PhysicalPlan::Sort {
+                input: Box::new(input.into_physical()),
+                keys,
+            },
+            LogicalPlan::Limit {
+                input,
+                limit,
+                offset,
+            } => PhysicalPlan::Limit {
+                input: Box::new(input.into_physical()),
+                limit,
+                offset,
+            },
+            LogicalPlan::Project { input, expressions } => PhysicalPlan::Project {
+                input: Box::new(input.into_physical()),
+                expressions,
+            },
+        };
+    }
+}
+
+impl<'a> PhysicalPlan<'a> {
+    /// Starts executing the plan, returning its rows as they are asked for,
+    /// borrowed from the tables where they aren't changed.
+    ///
+    /// Operators that need all rows of their input before they can return any,
+    /// like sorting or the right side of a join, read them here.
+    pub fn open(self, context: &'a StatementContext) -> Result<Rows<'a>> {
+        return Ok(match self {
+            PhysicalPlan::SeqScan(table) => {
+                Box::new(table.values.iter().map(Cow::Borrowed).map(Ok))
+            }
+            PhysicalPlan::IndexScan { table, lookup } => Box::new(
+                table
+                    .look_up(&lookup)
+                    .into_iter()
+                    .map(|position| Ok(Cow::Borrowed(&table.values[position]))),
+            ),
+            PhysicalPlan::Filter { input, condition } => {
+                Box::new(input.open(context)?.filter_map(move |row| {
+                    let row = match row {
+                        Ok(row) => row,
+                        Err(error) => return Some(Err(error)),
+                    };
+
+                    // Unknown (NULL) counts as not matching
+                    return match row.evaluate_condition(&condition, context) {
+                        Ok(Some(true)) => Some(Ok(row)),
+                        Ok(_) => None,
+                        Err(error) => Some(Err(error)),
+                    };
+                }))
+            }
+            PhysicalPlan::NestedLoopJoin {
+                left,
+                right,
+                kind,
+                on,
+                ..
+            } => {
+                let right_width = right.column_names().len();
+
+                // Gone through again for every row of the left side
+                let right = right.open(context)?.collect::<Result<Vec<_>>>()?;
+
+                Box::new(left.open(context)?.flat_map(move |left| {
+                    let left = match left {
+                        Ok(left) => left,
+                        Err(error) => return vec![Err(error)],
+                    };
+
+                    let mut result = vec![];
+
+                    for right in &right {
+                        let row = Row(left.0.iter().chain(&right.0).cloned().collect());
+
+                        match row.matches(&on, context) {
+                            Ok(true) => result.push(Ok(Cow::Owned(row))),
+                            Ok(false) => {}
+                            Err(error) => return vec![Err(error)],
+                        }
+                    }
+
+                    // Left joins keep unmatched rows, with NULL for all of the right side's columns
+                    if result.is_empty() && kind == JoinType::Left {
+                        let padding = std::iter::repeat_n(ColumnValue::Null, right_width);
+
+                        let row = Row(left.0.iter().cloned().chain(padding).collect());
+
+                        result.push(Ok(Cow::Owned(row)));
+                    }
+
+                    return result;
+                }))
+            }
+            PhysicalPlan::GroupAggregate {
+                input,
+                group_by,
+                aggregates,
+                width,
+            } => {
+                let rows = input.open(context)?.collect::<Result<Vec<_>>>()?;
+
+                // Only grouped columns can be used outside of aggregates, which an empty group doesn't have
+                let empty_row = Row(vec![ColumnValue::Null; width]);
+
+                let mut result = vec![];
+
+                let rows = rows.iter().map(AsRef::as_ref).collect();
+
+                for group in group_rows(rows, &group_by, context)? {
+                    // Non-aggregated columns are grouped, so any row of the group will do
+                    let representative = group.first().copied().unwrap_or(&empty_row);
+
+                    let mut values = representative.0.clone();
+
+                    for aggregate in &aggregates {
+                        let aggregate = aggregate.resolve_aggregates(&group, context)?;
+
+                        values.push(representative.evaluate(&aggregate, context)?);
+                    }
+
+                    result.push(Ok(Cow::Owned(Row(values))));
+                }
+
+                Box::new(result.into_iter())
+            }
+            PhysicalPlan::Sort { input, keys } => {
+                let mut rows = input
+                    .open(context)?
+                    .map(|row| {
+                        let row = row?;
+
+                        let values = keys
+                            .iter()
+                            .map(|(expression, _)| row.evaluate(expression, context))
+                            .collect::<Result<Vec<_>>>()?;
+
+                        Ok((values, row))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                // Stable, so rows that compare equal stay in the order they were in
+                rows.sort_by(|(left, _), (right, _)| {
+                    for ((left, right), (_, direction)) in left.iter().zip(right).zip(&keys) {
+                        let ordering = match direction {
+                            SortDirection::Ascending => left.total_cmp(right),
+                            SortDirection::Descending => right.total_cmp(left),
+                        };
+
+                        if ordering != Ordering::Equal {
+                            return ordering;
+                        }
+                    }
+
+                    return Ordering::Equal;
+                });
+
+                Box::new(rows.into_iter().map(|(_, row)| Ok(row)))
+            }
+            PhysicalPlan::Limit {
+                input,
+                limit,
+                offset,
+            } => {
+                let mut skipped = 0;
+
+                let offset = offset.unwrap_or(0);
+
+                // Stops asking for rows once it has enough, and errors aren't skipped like rows are
+                Box::new(
+                    input
+                        .open(context)?
+                        .filter(move |row| {
+                            if row.is_err() || skipped == offset {
+                                return true;
+                            }
+
+                            skipped += 1;
+
+                            return false;
+                        })
+                        .take(limit.unwrap_or(usize::MAX)),
+                )
+            }
+            PhysicalPlan::Project { input, expressions } => {
+                Box::new(input.open(context)?.map(move |row| {
+                    let row = row?;
+
+                    let values = expressions
+                        .iter()
+                        .map(|expression| row.evaluate(expression, context))
+                        .collect::<Result<Vec<_>>>()?;
+
+                    Ok(Cow::Owned(Row(values)))
+                }))
+            }
+        });
+    }
+
+    /// The names of the columns of the rows the plan returns, which are what expressions above it refer to.
+    fn column_names(&self) -> Vec<ColumnName> {
+        return match self {
+            PhysicalPlan::SeqScan(table) | PhysicalPlan::IndexScan { table, .. } => {
+                table.schema.column_names.clone()
+            }
+            PhysicalPlan::NestedLoopJoin { names, .. } => names.clone(),
+            PhysicalPlan::Filter { input, .. }
+            | PhysicalPlan::Sort { input, .. }
+            | PhysicalPlan::Limit { input, .. } => input.column_names(),
+            // Aggregates are named after themselves, like unnamed columns of a `SELECT`
+            PhysicalPlan::GroupAggregate {
+                input, aggregates, ..
+            } => {
+                let mut names = input.column_names();
+
+                let aggregates: Vec<_> = aggregates
+                    .iter()
+                    .map(|aggregate| ColumnName(show(aggregate, &names)))
+                    .collect();
+
+                names.extend(aggregates);
+
+                names
+            }
+            PhysicalPlan::Project { input, expressions } => {
+                let names = input.column_names();
+
+                expressions
+                    .iter()
+                    .map(|expression| ColumnName(show(expression, &names)))
+                    .collect()
+            }
+        };
+    }
+
+    /// Describes each operator of the plan on a line of its own,
+    /// followed by the operators it uses the rows of, indented by two more spaces.
+    pub fn explain(&self) -> Vec<String> {
+        let (line, inputs): (String, Vec<&PhysicalPlan>) = match self {
+            PhysicalPlan::SeqScan(table) => {
+                (format!("Seq Scan on {}", table.schema.name.0), vec![])
+            }
+            PhysicalPlan::IndexScan { table, lookup } => {
+                let (index, comparison) = table.describe_lookup(lookup);
+
+                (
+                    format!(
+                        "Index Scan using {} on {} ({comparison})",
+                        index.0, table.schema.name.0
+                    ),
+                    vec![],
+                )
+            }
+            PhysicalPlan::Filter { input, condition } => (
+                format!(
+                    "Filter ({})",
+                    show_condition(condition, &input.column_names())
+                ),
+                vec![input],
+            ),
+            PhysicalPlan::NestedLoopJoin {
+                left,
+                right,
+                kind,
+                on,
+                names,
+            } => {
+                let kind = match kind {
+                    JoinType::Inner => "",
+                    JoinType::Left => " Left",
+                    JoinType::Cross => " Cross",
+                };
+
+                let on = match on {
+                    Some(on) => format!(" on ({})", show_condition(on, names)),
+                    None => String::new(),
+                };
+
+                (format!("Nested Loop{kind} Join{on}"), vec![left, right])
+            }
+            PhysicalPlan::GroupAggregate {
+                input,
+                group_by,
+                aggregates,
+                ..
+            } => {
+                let names = input.column_names();
+
+                let mut line = String::from("Group Aggregate");
+
+                if !aggregates.is_empty() {
+                    line = format!("{line} {}", show_all(aggregates, &names));
+                }
+
+                if !group_by.is_empty() {
+                    line = format!("{line} by {}", show_all(group_by, &names));
+                }
+
+                (line, vec![input])
+            }
+            PhysicalPlan::Sort { input, keys } => {
+                let names = input.column_names();
+
+                let keys: Vec<_> = keys
+                    .iter()
+                    .map(|(expression, direction)| match direction {
+                        SortDirection::Ascending => show(expression, &names),
+                        SortDirection::Descending => format!("{} DESC", show(expression, &names)),
+                    })
+                    .collect();
+
+                (format!("Sort by {}", keys.join(", ")), vec![input])
+            }
+            PhysicalPlan::Limit {
+                input,
+                limit,
+                offset,
+            } => {
+                let line = match (limit, offset) {
+                    (Some(limit), Some(offset)) => format!("Limit {limit} offset {offset}"),
+                    (Some(limit), None) => format!("Limit {limit}"),
+                    (None, offset) => format!("Offset {}", offset.unwrap_or(0)),
+                };
+
+                (line, vec![input])
+            }
+            PhysicalPlan::Project { input, expressions } => (
+                format!("Project {}", show_all(expressions, &input.column_names())),
+                vec![input],
+            ),
+        };
+
+        let mut lines = vec![line];
+
+        for input in inputs {
+            lines.extend(input.explain().into_iter().map(|line| format!("  {line}")));
+        }
+
+        return lines;
+    }
+}
+
+/// `expression` as SQL, with the columns it uses named by `names`.
+fn show(expression: &PreparedValueExpression, names: &[ColumnName]) -> String {
+    return match expression
+        .clone()
+        .map_columns(&|index: usize| name(names, index))
+    {
+        Ok(expression) => expression.to_string(),
+        Err(_) => format!("{expression:?}"),
+    };
+}
+
+fn show_all(expressions: &[PreparedValueExpression], names: &[ColumnName]) -> String {
+    let expressions: Vec<_> = expressions
+        .iter()
+        .map(|expression| show(expression, names))
+        .collect();
+
+    return expressions.join(", ");
+}
+
+fn show_condition(condition: &PreparedWhere, names: &[ColumnName]) -> String {
+    return match condition
+        .clone()
+        .map_columns(&|index: usize| name(names, index))
+    {
+        Ok(condition) => condition.to_string(),
+        Err(_) => format!("{condition:?}"),
+    };
+}
+
+fn name(names: &[ColumnName], index: usize) -> Result<ColumnName> {
+    return names
+        .get(index)
+        .cloned()
+        .ok_or(SqlError::IndexOutOfBounds(index, names.len()));
+}
@@ -0,0 +1,384 @@
+use sql_parse::parser::{ArithmeticOperator, ColumnType, InfixOperator, JoinType};
+
+use crate::types::{AggregateFunction, ColumnDefinition, IndexDefinition, OrderBy};
+use crate::utils::tests::{test_db_with_values, test_table};
+
+use super::*;
+
+fn test_plan_db() -> Database {
+    let mut db = test_db_with_values();
+
+    let mut pets = Table::new(
+        "pets".into(),
+        vec![
+            ColumnDefinition("id".into(), ColumnType::Int, vec![]),
+            ColumnDefinition("owner".into(), ColumnType::Int, vec![]),
+        ],
+        vec![],
+        vec![],
+        vec![],
+    )
+    .unwrap();
+
+    pets.insert_multiple(
+        &None,
+        vec![
+            vec![1.into(), 5.into()],
+            vec![2.into(), 6.into()],
+            vec![3.into(), 5.into()],
+            vec![4.into(), 7.into()],
+        ],
+    )
+    .unwrap();
+
+    db.create(pets).unwrap();
+
+    return db;
+}
+
+fn plan_lines(row_set: RowSet) -> Vec<String> {
+    assert_eq!(row_set.names, vec!["plan".into()]);
+
+    return row_set
+        .values
+        .into_iter()
+        .map(|row| match &row.0[..] {
+            [ColumnValue::Str(line)] => line.clone(),
+            other => panic!("Not a line of a plan: {other:?}"),
+        })
+        .collect();
+}
+
+fn comparison(left: ValueExpression, operator: InfixOperator, right: ValueExpression) -> Where {
+    return Where::Comparison {
+        left,
+        operator,
+        right,
+    };
+}
+
+fn add(left: ValueExpression, right: ValueExpression) -> ValueExpression {
+    return ValueExpression::Binary {
+        left: Box::new(left),
+        operator: ArithmeticOperator::Add,
+        right: Box::new(right),
+    };
+}
+
+fn join(kind: JoinType, on: Option<Where>) -> TableReference {
+    return TableReference::Join {
+        left: Box::new("test_table".into()),
+        right: "pets".into(),
+        kind,
+        on,
+    };
+}
+
+#[test]
+fn fold_constants() {
+    let condition: PreparedWhere = Where::And(
+        Box::new(Where::Comparison {
+            left: ValueExpression::Column(0),
+            operator: InfixOperator::GreaterThan,
+            right: ValueExpression::Binary {
+                left: Box::new(ValueExpression::Value(2.into())),
+                operator: ArithmeticOperator::Multiply,
+                right: Box::new(ValueExpression::Binary {
+                    left: Box::new(ValueExpression::Value(1.into())),
+                    operator: ArithmeticOperator::Add,
+                    right: Box::new(ValueExpression::Value(2.into())),
+                }),
+            },
+        }),
+        // Left for when rows are evaluated
+        Box::new(Where::Comparison {
+            left: ValueExpression::Column(1),
+            operator: InfixOperator::Equals,
+            right: ValueExpression::Binary {
+                left: Box::new(ValueExpression::Value(1.into())),
+                operator: ArithmeticOperator::Divide,
+                right: Box::new(ValueExpression::Value(0.into())),
+            },
+        }),
+    );
+
+    let expected: PreparedWhere = Where::And(
+        Box::new(Where::Comparison {
+            left: ValueExpression::Column(0),
+            operator: InfixOperator::GreaterThan,
+            right: ValueExpression::Value(6.into()),
+        }),
+        Box::new(Where::Comparison {
+            left: ValueExpression::Column(1),
+            operator: InfixOperator::Equals,
+            right: ValueExpression::Binary {
+                left: Box::new(ValueExpression::Value(1.into())),
+                operator: ArithmeticOperator::Divide,
+                right: Box::new(ValueExpression::Value(0.into())),
+            },
+        }),
+    );
+
+    assert_eq!(fold_condition(condition), expected);
+}
+
+#[test]
+fn push_down_conditions() {
+    let db = test_plan_db();
+
+    let on = comparison(
+        "test_table.first".into(),
+        InfixOperator::Equals,
+        "pets.owner".into(),
+    );
+
+    let condition = Where::And(
+        Box::new(Where::And(
+            Box::new(comparison(
+                "second".into(),
+                InfixOperator::Equals,
+                true.into(),
+            )),
+            Box::new(comparison(
+                "id".into(),
+                InfixOperator::GreaterThan,
+                add(1.into(), 1.into()),
+            )),
+        )),
+        Box::new(comparison(
+            "first".into(),
+            InfixOperator::GreaterThan,
+            "id".into(),
+        )),
+    );
+
+    let query = SelectQuery::new(ColumnSelector::AllColumns, Some(condition.clone()));
+
+    let plan = db
+        .plan_query(join(JoinType::Inner, Some(on.clone())), query)
+        .unwrap();
+
+    assert_eq!(
+        plan_lines(plan.explain().unwrap()),
+        vec![
+            "Project test_table.first, test_table.second, pets.id, pets.owner",
+            "  Filter (test_table.first > pets.id)",
+            "    Nested Loop Join on (test_table.first = pets.owner)",
+            "      Filter (second = true)",
+            "        Seq Scan on test_table",
+            "      Filter (id > 2)",
+            "        Seq Scan on pets",
+        ]
+    );
+
+    let query = SelectQuery::new(ColumnSelector::AllColumns, Some(condition.clone()));
+
+    let result = db
+        .plan_query(join(JoinType::Inner, Some(on.clone())), query)
+        .unwrap()
+        .execute(&StatementContext::default())
+        .unwrap();
+
+    assert_eq!(
+        result.values,
+        vec![Row(vec![5.into(), true.into(), 3.into(), 5.into()])]
+    );
+
+    // Rows of the right side of a left join that don't match are padded with NULLs, not left out
+    let query = SelectQuery::new(ColumnSelector::AllColumns, Some(condition));
+
+    let plan = db
+        .plan_query(join(JoinType::Left, Some(on)), query)
+        .unwrap();
+
+    assert_eq!(
+        plan_lines(plan.explain().unwrap()),
+        vec![
+            "Project test_table.first, test_table.second, pets.id, pets.owner",
+            "  Filter (pets.id > 2 AND test_table.first > pets.id)",
+            "    Nested Loop Left Join on (test_table.first = pets.owner)",
+            "      Filter (second = true)",
+            "        Seq Scan on test_table",
+            "      Seq Scan on pets",
+        ]
+    );
+}
+
+#[test]
+fn index_scan() {
+    let mut db = test_plan_db();
+
+    let index = IndexDefinition {
+        name: "pets_by_owner".into(),
+        columns: vec!["owner".into()],
+        unique: false,
+    };
+
+    db.create_index(&"pets".into(), index).unwrap();
+
+    let condition = Where::And(
+        Box::new(comparison(
+            "owner".into(),
+            InfixOperator::Equals,
+            add(2.into(), 3.into()),
+        )),
+        Box::new(comparison(
+            "id".into(),
+            InfixOperator::GreaterThan,
+            1.into(),
+        )),
+    );
+
+    let mut query = SelectQuery::new(
+        ColumnSelector::Expressions(vec!["id".into()]),
+        Some(condition.clone()),
+    );
+
+    query.order_by = vec![OrderBy {
+        expression: "id".into(),
+        direction: SortDirection::Descending,
+    }];
+
+    query.limit = Some(1);
+
+    let plan = db.plan_query("pets".into(), query).unwrap();
+
+    assert_eq!(
+        plan_lines(plan.explain().unwrap()),
+        vec![
+            "Project id",
+            "  Limit 1",
+            "    Sort by id DESC",
+            "      Filter (owner = 5 AND id > 1)",
+            "        Index Scan using pets_by_owner on pets (owner = 5)",
+        ]
+    );
+
+    let result = db
+        .plan_query(
+            "pets".into(),
+            SelectQuery::new(ColumnSelector::AllColumns, Some(condition.clone())),
+        )
+        .unwrap()
+        .execute(&StatementContext::default())
+        .unwrap();
+
+    assert_eq!(result.values, vec![Row(vec![3.into(), 5.into()])]);
+
+    assert_eq!(
+        plan_lines(
+            db.explain_change("Delete", "pets".into(), Some(condition))
+                .unwrap()
+        ),
+        vec![
+            "Delete on pets",
+            "  Filter (owner = 5 AND id > 1)",
+            "    Index Scan using pets_by_owner on pets (owner = 5)",
+        ]
+    );
+
+    assert_eq!(
+        plan_lines(db.explain_change("Update", "pets".into(), None).unwrap()),
+        vec!["Update on pets", "  Seq Scan on pets"]
+    );
+}
+
+#[test]
+fn aggregate_plan() {
+    let db = test_plan_db();
+
+    let query = || {
+        let count = ValueExpression::Aggregate {
+            function: AggregateFunction::Count,
+            argument: None,
+        };
+
+        let mut query = SelectQuery::new(
+            ColumnSelector::Expressions(vec!["owner".into(), count.clone()]),
+            None,
+        );
+
+        query.group_by = vec!["owner".into()];
+
+        query.having = Some(comparison(count, InfixOperator::GreaterThan, 1.into()));
+
+        query.offset = Some(0);
+
+        return query;
+    };
+
+    let plan = db.plan_query("pets".into(), query()).unwrap();
+
+    assert_eq!(
+        plan_lines(plan.explain().unwrap()),
+        vec![
+            "Project owner, COUNT(*)",
+            "  Offset 0",
+            "    Filter (COUNT(*) > 1)",
+            "      Group Aggregate COUNT(*) by owner",
+            "        Seq Scan on pets",
+        ]
+    );
+
+    let result = db
+        .plan_query("pets".into(), query())
+        .unwrap()
+        .execute(&StatementContext::default())
+        .unwrap();
+
+    assert_eq!(result.values, vec![Row(vec![5.into(), 2.into()])]);
+}
+
+#[test]
+fn stream_rows() {
+    let mut table = test_table();
+
+    table
+        .insert_multiple(
+            &None,
+            vec![
+                vec![1.into(), true.into()],
+                vec![2.into(), false.into()],
+                vec![0.into(), true.into()],
+            ],
+        )
+        .unwrap();
+
+    let query = |limit| {
+        let mut query = SelectQuery::new(
+            ColumnSelector::Expressions(vec![ValueExpression::Binary {
+                left: Box::new(10.into()),
+                operator: ArithmeticOperator::Divide,
+                right: Box::new("first".into()),
+            }]),
+            None,
+        );
+
+        query.limit = limit;
+
+        return query;
+    };
+
+    let context = StatementContext::default();
+
+    let mut rows = QueryPlan::new(LogicalPlan::scan(&table), query(None))
+        .unwrap()
+        .stream(&context)
+        .unwrap();
+
+    assert_eq!(rows.next().unwrap().unwrap(), Row(vec![10.into()]));
+    assert_eq!(rows.next().unwrap().unwrap(), Row(vec![5.into()]));
+    assert!(matches!(rows.next(), Some(Err(SqlError::DivisionByZero))));
+
+    // The row that can't be divided by is never asked for
+    let result = table.query(query(Some(2))).unwrap();
+
+    assert_eq!(
+        result.values,
+        vec![Row(vec![10.into()]), Row(vec![5.into()])]
+    );
+
+    let result = table.query(query(Some(3)));
+
+    assert!(matches!(result, Err(SqlError::DivisionByZero)));
+}
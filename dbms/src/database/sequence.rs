@@ -0,0 +1,253 @@
+use sql_parse::parser::{ColumnConstraint, Expression};
+
+use super::expression::operands;
+use super::{Database, Table};
+use crate::types::{ColumnDefinition, SequenceName, TableName};
+use crate::{Result, SqlError};
+
+/// A named counter made by `CREATE SEQUENCE`, or for a `SERIAL` column.
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct Sequence {
+    pub name: SequenceName,
+    pub start: i64,
+    pub increment: i64,           // Never 0, can be negative
+    pub last_value: Option<i64>,  // None until the first `nextval`
+    pub owner: Option<TableName>, // The table of a `SERIAL` column, which drops the sequence with it
+}
+
+impl Sequence {
+    pub fn new(
+        name: SequenceName,
+        start: Option<i64>,
+        increment: Option<i64>,
+        owner: Option<TableName>,
+    ) -> Result<Self> {
+        let increment = increment.unwrap_or(1);
+
+        if increment == 0 {
+            return Err(SqlError::InvalidParameter);
+        }
+
+        return Ok(Self {
+            name,
+            start: start.unwrap_or(1),
+            increment,
+            last_value: None,
+            owner,
+        });
+    }
+
+    pub(super) fn next_value(&mut self) -> Result<i64> {
+        let value = match self.last_value {
+            Some(last_value) => last_value
+                .checked_add(self.increment)
+                .ok_or_else(|| SqlError::SequenceExhausted(self.name.clone()))?,
+            None => self.start,
+        };
+
+        self.last_value = Some(value);
+
+        return Ok(value);
+    }
+
+    pub(super) fn current_value(&self) -> Result<i64> {
+        return self
+            .last_value
+            .ok_or_else(|| SqlError::SequenceNotStarted(self.name.clone()));
+    }
+}
+
+/// The name of the sequence if `expression` is a call to `nextval` or `currval`.
+fn sequence_call(expression: &Expression) -> Option<&str> {
+    let Expression::FunctionCall { name, argument } = expression else {
+        return None;
+    };
+
+    if !matches!(name.to_uppercase().as_str(), "NEXTVAL" | "CURRVAL") {
+        return None;
+    }
+
+    return match argument.as_deref() {
+        Some(Expression::Str(sequence_name)) => Some(sequence_name),
+        _ => None,
+    };
+}
+
+/// The names of the sequences `expression` calls `nextval` or `currval` of.
+pub(crate) fn used_sequences(expression: &Expression) -> Vec<&str> {
+    if let Some(name) = sequence_call(expression) {
+        return vec![name];
+    }
+
+    return operands(expression)
+        .into_iter()
+        .flat_map(used_sequences)
+        .collect();
+}
+
+/// Turns the `SERIAL` columns of a new table into INT columns that take their values from a new sequence,
+/// which is named `<table>_<column>_seq`.
+pub fn serial_sequences(
+    table_name: &TableName,
+    columns: Vec<ColumnDefinition>,
+) -> Result<(Vec<ColumnDefinition>, Vec<Sequence>)> {
+    let mut sequences = vec![];
+
+    let mut result = vec![];
+
+    for ColumnDefinition(name, column_type, constraints) in columns {
+        if !constraints.contains(&ColumnConstraint::Serial) {
+            result.push(ColumnDefinition(name, column_type, constraints));
+
+            continue;
+        }
+
+        let sequence_name = format!("{}_{}_seq", table_name.0, name.0);
+
+        let mut constraints: Vec<ColumnConstraint> = constraints
+            .into_iter()
+            .filter(|constraint| *constraint != ColumnConstraint::Serial)
+            .collect();
+
+        constraints.push(ColumnConstraint::NotNull);
+
+        constraints.push(ColumnConstraint::Default(Expression::FunctionCall {
+            name: "nextval".into(),
+            argument: Some(Expression::Str(sequence_name.clone()).into()),
+        }));
+
+        sequences.push(Sequence::new(
+            SequenceName(sequence_name),
+            None,
+            None,
+            Some(table_name.clone()),
+        )?);
+
+        result.push(ColumnDefinition(name, column_type, constraints));
+    }
+
+    return Ok((result, sequences));
+}
+
+impl Database {
+    pub fn create_sequence(&mut self, sequence: Sequence) -> Result<()> {
+        if self.sequences.contains_key(&sequence.name.0) {
+            return Err(SqlError::DuplicateSequence(sequence.name));
+        }
+
+        self.sequences.insert(sequence.name.0.clone(), sequence);
+
+        return Ok(());
+    }
+
+    /// Creates a table together with the sequences of its `SERIAL` columns, see [`serial_sequences`].
+    pub fn create_with_sequences(&mut self, table: Table, sequences: Vec<Sequence>) -> Result<()> {
+        // The defaults of the table use the sequences, so they have to exist when it's created
+        return self.with_sequences(sequences, |database| database.create(table));
+    }
+
+    /// Adds `sequences` for a change that uses them, and removes them again if it fails.
+    pub(crate) fn with_sequences(
+        &mut self,
+        sequences: Vec<Sequence>,
+        change: impl FnOnce(&mut Self) -> Result<()>,
+    ) -> Result<()> {
+        for sequence in &sequences {
+            if self.sequences.contains_key(&sequence.name.0) {
+                return Err(SqlError::DuplicateSequence(sequence.name.clone()));
+            }
+        }
+
+        let names: Vec<String> = sequences
+            .iter()
+            .map(|sequence| sequence.name.0.clone())
+            .collect();
+
+        for sequence in sequences {
+            self.sequences.insert(sequence.name.0.clone(), sequence);
+        }
+
+        let result = change(self);
+
+        if result.is_err() {
+            for name in names {
+                self.sequences.remove(&name);
+            }
+        }
+
+        return result;
+    }
+
+    /// Drops a sequence, unless the default of a column uses it.
+    pub fn drop_sequence(&mut self, name: SequenceName) -> Result<Sequence> {
+        if let Some(table) = self
+            .tables
+            .values()
+            .find(|table| table.uses_sequence(&name))
+        {
+            return Err(SqlError::SequenceIsUsed(name, table.schema.name.clone()));
+        }
+
+        return self
+            .sequences
+            .remove(&name.0)
+            .ok_or(SqlError::SequenceDoesNotExist(name));
+    }
+
+    /// Fails if a default of `table` uses a sequence that doesn't exist.
+    pub(crate) fn validate_sequences(&self, table: &Table) -> Result<()> {
+        for default in table.schema.defaults.iter().flatten() {
+            for name in used_sequences(default) {
+                if !self.sequences.contains_key(name) {
+                    return Err(SqlError::SequenceDoesNotExist(SequenceName(name.into())));
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Drops the sequences made for the `SERIAL` columns of a table that is being dropped,
+    /// unless another table uses them as well.
+    pub(crate) fn drop_owned_sequences(&mut self, table_name: &TableName) -> Result<()> {
+        let is_owned = |sequence: &Sequence| {
+            sequence
+                .owner
+                .as_ref()
+                .is_some_and(|owner| owner.0 == table_name.0)
+        };
+
+        for sequence in self
+            .sequences
+            .values()
+            .filter(|sequence| is_owned(sequence))
+        {
+            let other = self.tables.values().find(|table| {
+                table.schema.name.0 != table_name.0 && table.uses_sequence(&sequence.name)
+            });
+
+            if let Some(other) = other {
+                return Err(SqlError::SequenceIsUsed(
+                    sequence.name.clone(),
+                    other.schema.name.clone(),
+                ));
+            }
+        }
+
+        self.sequences.retain(|_, sequence| !is_owned(sequence));
+
+        return Ok(());
+    }
+}
+
+impl Table {
+    pub(super) fn uses_sequence(&self, name: &SequenceName) -> bool {
+        return self
+            .schema
+            .defaults
+            .iter()
+            .flatten()
+            .any(|default| used_sequences(default).contains(&name.0.as_str()));
+    }
+}
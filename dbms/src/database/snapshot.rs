@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+use super::{Database, Sequence, Table};
+use crate::types::TableName;
+
+/// Copies of the tables a statement can change, as they were before it, so it can be undone if it fails.
+#[derive(Debug)]
+pub struct Snapshot {
+    // `None` for a table that didn't exist yet
+    tables: HashMap<String, Option<Table>>,
+    sequences: HashMap<String, Sequence>,
+}
+
+impl Database {
+    /// Copies the tables called `names`, which are all that [`Database::restore`] puts back.
+    ///
+    /// All sequences are copied, since there are few and they are small.
+    pub fn snapshot(&self, names: Vec<TableName>) -> Snapshot {
+        let tables = names
+            .into_iter()
+            .map(|name| {
+                let table = self.tables.get(&name.0).cloned();
+
+                (name.0, table)
+            })
+            .collect();
+
+        return Snapshot {
+            tables,
+            sequences: self.sequences.clone(),
+        };
+    }
+
+    /// Puts back the tables and sequences of `snapshot`, including removing the tables that didn't exist.
+    ///
+    /// The values the sequences gave in the meantime are lost, see [`Database::keep_sequence_values`].
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        for (name, table) in snapshot.tables {
+            match table {
+                Some(table) => self.tables.insert(name, table),
+                None => self.tables.remove(&name),
+            };
+        }
+
+        self.sequences = snapshot.sequences;
+    }
+}
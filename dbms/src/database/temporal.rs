@@ -0,0 +1,629 @@
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sql_parse::parser::{ArithmeticOperator, ColumnType, DateTimeField};
+
+use crate::types::{ColumnValue, Date, Interval, Time, Timestamp};
+use crate::{Result, SqlError};
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_MINUTE: i64 = 60 * MICROS_PER_SECOND;
+const MICROS_PER_HOUR: i64 = 60 * MICROS_PER_MINUTE;
+const MICROS_PER_DAY: i64 = 24 * MICROS_PER_HOUR;
+
+// Comparing intervals assumes a month has 30 days, so 1 month equals 30 days
+const DAYS_PER_MONTH: i64 = 30;
+
+// Years are always written with four digits
+const YEARS: RangeInclusive<i64> = 1..=9999;
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // Counting years from March puts the leap day at the end
+    let year = if month <= 2 { year - 1 } else { year };
+
+    let era = year.div_euclid(400);
+
+    let year_of_era = year.rem_euclid(400);
+
+    let month_from_march = (month as i64 + 9) % 12;
+
+    let day_of_year = (153 * month_from_march + 2) / 5 + day as i64 - 1;
+
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    return era * 146097 + day_of_era - 719468;
+}
+
+/// The inverse of [`days_from_civil`].
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+
+    let era = days.div_euclid(146097);
+
+    let day_of_era = days.rem_euclid(146097);
+
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+
+    let month_from_march = (5 * day_of_year + 2) / 153;
+
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+
+    let month = (month_from_march + 2) % 12 + 1;
+
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    return (year, month as u32, day as u32);
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+
+    return match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+}
+
+/// Parses a number of `digits` decimal digits, without a sign.
+fn parse_digits(value: &str, digits: RangeInclusive<usize>) -> Option<i64> {
+    if !digits.contains(&value.len()) || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    return value.parse().ok();
+}
+
+/// Parses up to 6 digits after the dot as a number of microseconds.
+fn parse_fraction(value: &str) -> Option<i64> {
+    let digits = parse_digits(value, 1..=6)?;
+
+    return Some(digits * 10_i64.pow(6 - value.len() as u32));
+}
+
+/// Writes microseconds as the digits after the dot, without trailing zeros.
+fn fraction(micros: i64) -> String {
+    if micros == 0 {
+        return String::new();
+    }
+
+    return format!(".{micros:06}").trim_end_matches('0').to_owned();
+}
+
+impl Date {
+    pub fn from_ymd(year: i64, month: u32, day: u32) -> Option<Date> {
+        if !YEARS.contains(&year)
+            || !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+        {
+            return None;
+        }
+
+        return Some(Date(days_from_civil(year, month, day) as i32));
+    }
+
+    /// Whether the date is in one of the years that can be written, 1 to 9999.
+    pub fn is_valid(&self) -> bool {
+        return Date::from_days(self.0 as i64).is_some();
+    }
+
+    fn from_days(days: i64) -> Option<Date> {
+        let (year, _, _) = civil_from_days(days);
+
+        if !YEARS.contains(&year) {
+            return None;
+        }
+
+        return Some(Date(days as i32));
+    }
+
+    pub fn year_month_day(&self) -> (i64, u32, u32) {
+        return civil_from_days(self.0 as i64);
+    }
+
+    /// Parses `YYYY-MM-DD`.
+    pub fn parse(value: &str) -> Option<Date> {
+        let mut parts = value.split('-');
+
+        let year = parse_digits(parts.next()?, 4..=4)?;
+        let month = parse_digits(parts.next()?, 1..=2)?;
+        let day = parse_digits(parts.next()?, 1..=2)?;
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        return Date::from_ymd(year, month as u32, day as u32);
+    }
+
+    fn add_days(&self, days: i64) -> Option<Date> {
+        return Date::from_days((self.0 as i64).checked_add(days)?);
+    }
+
+    /// Adds months, keeping the day of the month unless the new month is shorter,
+    /// so one month after January 31st is the last day of February.
+    fn add_months(&self, months: i32) -> Option<Date> {
+        let (year, month, day) = self.year_month_day();
+
+        let total = year * 12 + month as i64 - 1 + months as i64;
+
+        let (year, month) = (total.div_euclid(12), total.rem_euclid(12) as u32 + 1);
+
+        if !YEARS.contains(&year) {
+            return None;
+        }
+
+        return Date::from_ymd(year, month, day.min(days_in_month(year, month)));
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (year, month, day) = self.year_month_day();
+
+        return write!(f, "{year:04}-{month:02}-{day:02}");
+    }
+}
+
+impl Time {
+    /// Parses `HH:MM`, optionally followed by `:SS` and up to 6 digits of fractional seconds.
+    pub fn parse(value: &str) -> Option<Time> {
+        let (value, micros) = match value.split_once('.') {
+            Some((value, fraction)) => (value, parse_fraction(fraction)?),
+            None => (value, 0),
+        };
+
+        let mut parts = value.split(':');
+
+        let hours = parse_digits(parts.next()?, 1..=2)?;
+        let minutes = parse_digits(parts.next()?, 2..=2)?;
+
+        let seconds = match parts.next() {
+            Some(seconds) => parse_digits(seconds, 2..=2)?,
+            // Fractions need seconds to be fractions of
+            None if micros != 0 => return None,
+            None => 0,
+        };
+
+        if parts.next().is_some() || hours > 23 || minutes > 59 || seconds > 59 {
+            return None;
+        }
+
+        return Some(Time(
+            hours * MICROS_PER_HOUR
+                + minutes * MICROS_PER_MINUTE
+                + seconds * MICROS_PER_SECOND
+                + micros,
+        ));
+    }
+
+    pub fn is_valid(&self) -> bool {
+        return (0..MICROS_PER_DAY).contains(&self.0);
+    }
+
+    fn add(&self, interval: &Interval) -> Time {
+        // Wraps around midnight, and a time has no months to add
+        let micros = self.0 + interval.micros.rem_euclid(MICROS_PER_DAY);
+
+        return Time(micros.rem_euclid(MICROS_PER_DAY));
+    }
+}
+
+impl std::fmt::Display for Time {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(
+            f,
+            "{:02}:{:02}:{:02}{}",
+            self.0 / MICROS_PER_HOUR,
+            self.0 % MICROS_PER_HOUR / MICROS_PER_MINUTE,
+            self.0 % MICROS_PER_MINUTE / MICROS_PER_SECOND,
+            fraction(self.0 % MICROS_PER_SECOND)
+        );
+    }
+}
+
+impl From<Date> for Timestamp {
+    /// Midnight at the start of the date.
+    fn from(value: Date) -> Self {
+        return Timestamp(value.0 as i64 * MICROS_PER_DAY);
+    }
+}
+
+impl Timestamp {
+    pub fn now() -> Timestamp {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        return Timestamp(since_epoch.as_micros() as i64);
+    }
+
+    fn from_date_and_time(date: Date, time: Time) -> Timestamp {
+        return Timestamp(Timestamp::from(date).0 + time.0);
+    }
+
+    pub fn is_valid(&self) -> bool {
+        return self.date().is_valid();
+    }
+
+    fn from_micros(micros: i64) -> Option<Timestamp> {
+        Date::from_days(micros.div_euclid(MICROS_PER_DAY))?;
+
+        return Some(Timestamp(micros));
+    }
+
+    pub fn date(&self) -> Date {
+        return Date(self.0.div_euclid(MICROS_PER_DAY) as i32);
+    }
+
+    pub fn time(&self) -> Time {
+        return Time(self.0.rem_euclid(MICROS_PER_DAY));
+    }
+
+    /// Parses a date, optionally followed by a time separated by a space or `T`.
+    pub fn parse(value: &str) -> Option<Timestamp> {
+        let (date, time) = match value.split_once([' ', 'T']) {
+            Some((date, time)) => (date, Time::parse(time)?),
+            None => (value, Time(0)),
+        };
+
+        return Some(Timestamp::from_date_and_time(Date::parse(date)?, time));
+    }
+
+    fn add(&self, interval: &Interval) -> Option<Timestamp> {
+        let date = self.date().add_months(interval.months)?;
+
+        let micros = Timestamp::from_date_and_time(date, self.time())
+            .0
+            .checked_add(interval.micros)?;
+
+        return Timestamp::from_micros(micros);
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{} {}", self.date(), self.time());
+    }
+}
+
+impl Interval {
+    fn from_micros(micros: i64) -> Interval {
+        return Interval { months: 0, micros };
+    }
+
+    /// Parses amounts followed by their unit, like `1 year 2 months 3.5 seconds`.
+    ///
+    /// Units are years, months, weeks, days, hours, minutes and seconds, in singular or plural.
+    /// Only seconds can have a fraction.
+    pub fn parse(value: &str) -> Option<Interval> {
+        let mut words = value.split_whitespace().peekable();
+
+        words.peek()?;
+
+        let mut result = Interval::from_micros(0);
+
+        while let Some(amount) = words.next() {
+            let unit = words.next()?.to_lowercase();
+
+            let unit = unit.strip_suffix('s').unwrap_or(&unit);
+
+            let (negative, amount) = match amount.strip_prefix('-') {
+                Some(amount) => (true, amount),
+                None => (false, amount),
+            };
+
+            let (whole, micros) = match amount.split_once('.') {
+                Some((whole, fraction)) if unit == "second" => (whole, parse_fraction(fraction)?),
+                Some(_) => return None,
+                None => (amount, 0),
+            };
+
+            let whole = parse_digits(whole, 1..=18)?;
+
+            let part = match unit {
+                "year" => Interval {
+                    months: i32::try_from(whole.checked_mul(12)?).ok()?,
+                    micros: 0,
+                },
+                "month" => Interval {
+                    months: i32::try_from(whole).ok()?,
+                    micros: 0,
+                },
+                "week" => Interval::from_micros(whole.checked_mul(7 * MICROS_PER_DAY)?),
+                "day" => Interval::from_micros(whole.checked_mul(MICROS_PER_DAY)?),
+                "hour" => Interval::from_micros(whole.checked_mul(MICROS_PER_HOUR)?),
+                "minute" => Interval::from_micros(whole.checked_mul(MICROS_PER_MINUTE)?),
+                "second" => Interval::from_micros(
+                    whole.checked_mul(MICROS_PER_SECOND)?.checked_add(micros)?,
+                ),
+                _ => return None,
+            };
+
+            let part = match negative {
+                true => part.checked_neg()?,
+                false => part,
+            };
+
+            result = result.checked_add(&part)?;
+        }
+
+        return Some(result);
+    }
+
+    fn checked_add(&self, other: &Interval) -> Option<Interval> {
+        return Some(Interval {
+            months: self.months.checked_add(other.months)?,
+            micros: self.micros.checked_add(other.micros)?,
+        });
+    }
+
+    fn checked_neg(&self) -> Option<Interval> {
+        return Some(Interval {
+            months: self.months.checked_neg()?,
+            micros: self.micros.checked_neg()?,
+        });
+    }
+
+    fn checked_mul(&self, factor: i64) -> Option<Interval> {
+        return Some(Interval {
+            months: i32::try_from((self.months as i64).checked_mul(factor)?).ok()?,
+            micros: self.micros.checked_mul(factor)?,
+        });
+    }
+
+    fn compare(&self, other: &Interval) -> Ordering {
+        let length = |interval: &Interval| {
+            interval.months as i128 * (DAYS_PER_MONTH * MICROS_PER_DAY) as i128
+                + interval.micros as i128
+        };
+
+        return length(self).cmp(&length(other));
+    }
+}
+
+impl std::fmt::Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |amount: i64, unit: &str| match amount.abs() {
+            1 => format!("{amount} {unit}"),
+            _ => format!("{amount} {unit}s"),
+        };
+
+        let mut parts = vec![];
+
+        let (years, months) = (self.months / 12, self.months % 12);
+
+        let days = self.micros / MICROS_PER_DAY;
+        let hours = self.micros % MICROS_PER_DAY / MICROS_PER_HOUR;
+        let minutes = self.micros % MICROS_PER_HOUR / MICROS_PER_MINUTE;
+        let seconds = self.micros % MICROS_PER_MINUTE;
+
+        for (amount, unit) in [
+            (years as i64, "year"),
+            (months as i64, "month"),
+            (days, "day"),
+            (hours, "hour"),
+            (minutes, "minute"),
+        ] {
+            if amount != 0 {
+                parts.push(plural(amount, unit));
+            }
+        }
+
+        if seconds % MICROS_PER_SECOND != 0 {
+            // The whole part of e.g. -0.5 is 0, so the sign has to be written separately
+            let sign = if seconds < 0 { "-" } else { "" };
+
+            let seconds = seconds.abs();
+
+            parts.push(format!(
+                "{sign}{}{} seconds",
+                seconds / MICROS_PER_SECOND,
+                fraction(seconds % MICROS_PER_SECOND)
+            ));
+        } else if seconds != 0 || parts.is_empty() {
+            parts.push(plural(seconds / MICROS_PER_SECOND, "second"));
+        }
+
+        return write!(f, "{}", parts.join(" "));
+    }
+}
+
+/// The type of arithmetic involving dates, times or intervals, or `None` if the operation isn't supported.
+pub fn temporal_arithmetic_type(
+    operator: ArithmeticOperator,
+    left: ColumnType,
+    right: ColumnType,
+) -> Option<ColumnType> {
+    use ArithmeticOperator::*;
+    use ColumnType::*;
+
+    return match (left, operator, right) {
+        (Date, Add | Subtract, SmallInt | Int | BigInt) => Some(Date),
+        (SmallInt | Int | BigInt, Add, Date) => Some(Date),
+        (Date, Subtract, Date) => Some(BigInt),
+        (Date | Timestamp, Add | Subtract, Interval) => Some(Timestamp),
+        (Interval, Add, Date | Timestamp) => Some(Timestamp),
+        (Date | Timestamp, Subtract, Date | Timestamp) => Some(Interval),
+        (Time, Add | Subtract, Interval) | (Interval, Add, Time) => Some(Time),
+        (Time, Subtract, Time) => Some(Interval),
+        (Interval, Add | Subtract, Interval) => Some(Interval),
+        (Interval, Multiply, SmallInt | Int | BigInt) => Some(Interval),
+        (SmallInt | Int | BigInt, Multiply, Interval) => Some(Interval),
+        _ => None,
+    };
+}
+
+/// The type `EXTRACT` returns for `field` of a value of type `source`.
+pub fn extract_type(field: DateTimeField, source: ColumnType) -> Result<ColumnType> {
+    use DateTimeField::*;
+
+    return match (field, source) {
+        (Year | Month | Day, ColumnType::Date) => Ok(ColumnType::Int),
+        (Hour | Minute | Second, ColumnType::Time) => Ok(ColumnType::Int),
+        (_, ColumnType::Timestamp | ColumnType::Interval) => Ok(ColumnType::Int),
+        _ => Err(SqlError::InvalidExtract(field, source)),
+    };
+}
+
+impl ColumnValue {
+    /// Parses the string of a typed literal like `DATE '2026-01-01'`.
+    pub fn parse(value: &str, column_type: ColumnType) -> Result<ColumnValue> {
+        let result = match column_type {
+            ColumnType::Date => Date::parse(value).map(ColumnValue::Date),
+            ColumnType::Time => Time::parse(value).map(ColumnValue::Time),
+            ColumnType::Timestamp => Timestamp::parse(value).map(ColumnValue::Timestamp),
+            ColumnType::Interval => Interval::parse(value).map(ColumnValue::Interval),
+            // Mostly for NaN and infinity, which can't be written as numbers
+            ColumnType::Float => value.trim().parse().ok().map(ColumnValue::Float),
+            _ => None,
+        };
+
+        return result.ok_or_else(|| SqlError::InvalidLiteral(column_type, value.to_owned()));
+    }
+
+    /// Arithmetic where at least one side is a date, time or interval,
+    /// whose types have been checked with [`temporal_arithmetic_type`].
+    ///
+    /// Returns `None` if the result is out of range.
+    pub(super) fn apply_temporal(
+        &self,
+        operator: ArithmeticOperator,
+        other: &ColumnValue,
+    ) -> Option<ColumnValue> {
+        use ArithmeticOperator::*;
+        use ColumnValue as V;
+
+        let timestamp = |value: &ColumnValue| match value {
+            V::Date(date) => Timestamp::from(*date),
+            V::Timestamp(timestamp) => *timestamp,
+            _ => unreachable!(),
+        };
+
+        return match (self, operator, other) {
+            (V::Date(date), Add, V::Int(days)) | (V::Int(days), Add, V::Date(date)) => {
+                date.add_days(*days).map(V::Date)
+            }
+            (V::Date(date), Subtract, V::Int(days)) => {
+                date.add_days(days.checked_neg()?).map(V::Date)
+            }
+            (V::Date(left), Subtract, V::Date(right)) => {
+                Some(V::Int(left.0 as i64 - right.0 as i64))
+            }
+            (V::Date(_) | V::Timestamp(_), Add, V::Interval(interval)) => {
+                timestamp(self).add(interval).map(V::Timestamp)
+            }
+            (V::Interval(interval), Add, V::Date(_) | V::Timestamp(_)) => {
+                timestamp(other).add(interval).map(V::Timestamp)
+            }
+            (V::Date(_) | V::Timestamp(_), Subtract, V::Interval(interval)) => timestamp(self)
+                .add(&interval.checked_neg()?)
+                .map(V::Timestamp),
+            (V::Date(_) | V::Timestamp(_), Subtract, V::Date(_) | V::Timestamp(_)) => {
+                let micros = timestamp(self).0.checked_sub(timestamp(other).0)?;
+
+                Some(V::Interval(Interval::from_micros(micros)))
+            }
+            (V::Time(time), Add, V::Interval(interval))
+            | (V::Interval(interval), Add, V::Time(time)) => Some(V::Time(time.add(interval))),
+            (V::Time(time), Subtract, V::Interval(interval)) => {
+                Some(V::Time(time.add(&interval.checked_neg()?)))
+            }
+            (V::Time(left), Subtract, V::Time(right)) => {
+                Some(V::Interval(Interval::from_micros(left.0 - right.0)))
+            }
+            (V::Interval(left), Add, V::Interval(right)) => {
+                left.checked_add(right).map(V::Interval)
+            }
+            (V::Interval(left), Subtract, V::Interval(right)) => {
+                left.checked_add(&right.checked_neg()?).map(V::Interval)
+            }
+            (V::Interval(interval), Multiply, V::Int(factor))
+            | (V::Int(factor), Multiply, V::Interval(interval)) => {
+                interval.checked_mul(*factor).map(V::Interval)
+            }
+            _ => unreachable!(),
+        };
+    }
+
+    /// Compares dates, times and intervals, where dates are the midnight at their start.
+    ///
+    /// Returns `None` if the values aren't both of these types, or can't be compared with each other.
+    pub(super) fn compare_temporal(&self, other: &ColumnValue) -> Option<Ordering> {
+        use ColumnValue as V;
+
+        return match (self, other) {
+            (V::Date(left), V::Date(right)) => Some(left.cmp(right)),
+            (V::Date(_) | V::Timestamp(_), V::Date(_) | V::Timestamp(_)) => {
+                let timestamp = |value: &ColumnValue| match value {
+                    V::Date(date) => Timestamp::from(*date),
+                    V::Timestamp(timestamp) => *timestamp,
+                    _ => unreachable!(),
+                };
+
+                Some(timestamp(self).cmp(&timestamp(other)))
+            }
+            (V::Time(left), V::Time(right)) => Some(left.cmp(right)),
+            (V::Interval(left), V::Interval(right)) => Some(left.compare(right)),
+            _ => None,
+        };
+    }
+
+    /// Gets the `field` of a date, time or interval, see [`extract_type`] for which fields each of them has.
+    ///
+    /// Fractions of a second are dropped.
+    pub fn extract(&self, field: DateTimeField) -> Result<ColumnValue> {
+        use DateTimeField::*;
+
+        let (date, time) = match self {
+            ColumnValue::Null => return Ok(ColumnValue::Null),
+            ColumnValue::Date(date) => (Some(*date), None),
+            ColumnValue::Time(time) => (None, Some(*time)),
+            ColumnValue::Timestamp(timestamp) => (Some(timestamp.date()), Some(timestamp.time())),
+            ColumnValue::Interval(interval) => {
+                let micros = interval.micros;
+
+                let value = match field {
+                    Year => interval.months as i64 / 12,
+                    Month => interval.months as i64 % 12,
+                    Day => micros / MICROS_PER_DAY,
+                    Hour => micros % MICROS_PER_DAY / MICROS_PER_HOUR,
+                    Minute => micros % MICROS_PER_HOUR / MICROS_PER_MINUTE,
+                    Second => micros % MICROS_PER_MINUTE / MICROS_PER_SECOND,
+                };
+
+                return Ok(ColumnValue::Int(value));
+            }
+            _ => (None, None),
+        };
+
+        let value = match (field, date, time) {
+            (Year | Month | Day, Some(date), _) => {
+                let (year, month, day) = date.year_month_day();
+
+                match field {
+                    Year => year,
+                    Month => month as i64,
+                    _ => day as i64,
+                }
+            }
+            (Hour, _, Some(time)) => time.0 / MICROS_PER_HOUR,
+            (Minute, _, Some(time)) => time.0 % MICROS_PER_HOUR / MICROS_PER_MINUTE,
+            (Second, _, Some(time)) => time.0 % MICROS_PER_MINUTE / MICROS_PER_SECOND,
+            _ => return Err(SqlError::InvalidExtract(field, self.column_type().unwrap())),
+        };
+
+        return Ok(ColumnValue::Int(value));
+    }
+}
@@ -1,4 +1,7 @@
-use crate::utils::tests::{test_row_set, test_table, test_table_with_values};
+use sql_parse::parser::{ArithmeticOperator, SortDirection};
+
+use crate::types::{AggregateFunction, IndexDefinition, OrderBy, UniqueConstraint};
+use crate::utils::tests::{decimal, test_row_set, test_table, test_table_with_values};
 
 use super::*;
 
@@ -42,6 +45,47 @@ fn insert_check_types() {
     assert_eq!(table.values, vec![]);
 }
 
+#[test]
+fn insert_with_column_names() {
+    let mut table = test_table();
+
+    table
+        .insert(
+            &Some(vec!["second".into(), "first".into()]),
+            vec![true.into(), 5.into()],
+        )
+        .unwrap();
+
+    table
+        .insert(&Some(vec!["test_table.first".into()]), vec![6.into()])
+        .unwrap();
+
+    assert_eq!(
+        table.values,
+        vec![
+            Row(vec![5.into(), true.into()]),
+            Row(vec![6.into(), ColumnValue::Null]),
+        ]
+    );
+
+    let result = table.insert(&Some(vec!["third".into()]), vec![7.into()]);
+
+    assert!(matches!(result, Err(SqlError::NameDoesNotExist(_, _))));
+
+    let result = table.insert(
+        &Some(vec!["first".into(), "first".into()]),
+        vec![7.into(), 8.into()],
+    );
+
+    assert!(matches!(result, Err(SqlError::ColumnNameNotUnique(_))));
+
+    let result = table.insert(&Some(vec!["first".into()]), vec![7.into(), true.into()]);
+
+    assert!(matches!(result, Err(SqlError::UnequalLengths(1, 2))));
+
+    assert_eq!(table.values.len(), 2);
+}
+
 #[test]
 fn select_basic() {
     let (table, (row1, row2)) = test_table_with_values();
@@ -56,7 +100,7 @@ fn select_basic() {
     let where_bool_true = table
         .select(
             ColumnSelector::AllColumns,
-            Some(Where {
+            Some(Where::Comparison {
                 left: "second".into(),
                 operator: InfixOperator::Equals,
                 right: true.into(),
@@ -74,18 +118,54 @@ fn evaluate_equal() {
     let row1 = Row(vec![Int(5), Bool(true)]);
 
     let inputs = [
-        (row1.evaluate_equal(0, &Int(5)).unwrap(), true),
-        (row1.evaluate_equal(0, &Int(6)).unwrap(), false),
-        (row1.evaluate_equal(0, &Decimal(5, 0)).unwrap(), true),
-        (row1.evaluate_equal(0, &Decimal(5, 1)).unwrap(), false),
+        (
+            row1.evaluate_equal(
+                &ValueExpression::Column(0),
+                &Int(5).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            true,
+        ),
+        (
+            row1.evaluate_equal(
+                &ValueExpression::Column(0),
+                &Int(6).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
+        (
+            row1.evaluate_equal(
+                &ValueExpression::Column(0),
+                &decimal(50, 1).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            true,
+        ),
+        (
+            row1.evaluate_equal(
+                &ValueExpression::Column(0),
+                &decimal(51, 1).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
     ];
 
     inputs.iter().for_each(|(result, expected)| {
-        assert_eq!(result, expected);
+        assert_eq!(*result, Some(*expected));
     });
 
     assert!(matches!(
-        row1.evaluate_equal(0, &Bool(true)),
+        row1.evaluate_equal(
+            &ValueExpression::Column(0),
+            &Bool(true).into(),
+            &StatementContext::default()
+        ),
         Err(SqlError::ImpossibleComparison(Int(5), Bool(true)))
     ));
 }
@@ -97,18 +177,54 @@ fn evaluate_not_equal() {
     let row1 = Row(vec![Int(5), Bool(true)]);
 
     let inputs = [
-        (row1.evaluate_not_equal(0, &Int(5)).unwrap(), false),
-        (row1.evaluate_not_equal(0, &Int(6)).unwrap(), true),
-        (row1.evaluate_not_equal(0, &Decimal(5, 0)).unwrap(), false),
-        (row1.evaluate_not_equal(0, &Decimal(5, 1)).unwrap(), true),
+        (
+            row1.evaluate_not_equal(
+                &ValueExpression::Column(0),
+                &Int(5).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
+        (
+            row1.evaluate_not_equal(
+                &ValueExpression::Column(0),
+                &Int(6).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            true,
+        ),
+        (
+            row1.evaluate_not_equal(
+                &ValueExpression::Column(0),
+                &decimal(50, 1).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
+        (
+            row1.evaluate_not_equal(
+                &ValueExpression::Column(0),
+                &decimal(51, 1).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            true,
+        ),
     ];
 
     inputs.iter().for_each(|(result, expected)| {
-        assert_eq!(result, expected);
+        assert_eq!(*result, Some(*expected));
     });
 
     assert!(matches!(
-        row1.evaluate_not_equal(0, &Bool(true)),
+        row1.evaluate_not_equal(
+            &ValueExpression::Column(0),
+            &Bool(true).into(),
+            &StatementContext::default()
+        ),
         Err(SqlError::ImpossibleComparison(Int(5), Bool(true)))
     ));
 }
@@ -121,22 +237,86 @@ fn evaluate_less_than() {
     let row2 = Row(vec![Int(6), Bool(false)]);
 
     let inputs = [
-        (row1.evaluate_less_than(0, &Int(5)).unwrap(), false),
-        (row1.evaluate_less_than(0, &Int(6)).unwrap(), true),
-        (row1.evaluate_less_than(0, &Int(4)).unwrap(), false),
-        (row1.evaluate_less_than(0, &Decimal(5, 0)).unwrap(), false),
-        (row1.evaluate_less_than(1, &Bool(false)).unwrap(), false),
-        (row1.evaluate_less_than(1, &Bool(true)).unwrap(), false),
-        (row2.evaluate_less_than(1, &Bool(true)).unwrap(), true),
+        (
+            row1.evaluate_less_than(
+                &ValueExpression::Column(0),
+                &Int(5).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
+        (
+            row1.evaluate_less_than(
+                &ValueExpression::Column(0),
+                &Int(6).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            true,
+        ),
+        (
+            row1.evaluate_less_than(
+                &ValueExpression::Column(0),
+                &Int(4).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
+        (
+            row1.evaluate_less_than(
+                &ValueExpression::Column(0),
+                &decimal(50, 1).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
+        (
+            row1.evaluate_less_than(
+                &ValueExpression::Column(1),
+                &Bool(false).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
+        (
+            row1.evaluate_less_than(
+                &ValueExpression::Column(1),
+                &Bool(true).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
+        (
+            row2.evaluate_less_than(
+                &ValueExpression::Column(1),
+                &Bool(true).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            true,
+        ),
     ];
 
     inputs.iter().for_each(|(result, expected)| {
-        assert_eq!(result, expected);
+        assert_eq!(*result, Some(*expected));
     });
 
     let failing_inputs = [
-        (row1.evaluate_less_than(1, &Decimal(5, 0))),
-        (row1.evaluate_less_than(0, &Bool(false))),
+        (row1.evaluate_less_than(
+            &ValueExpression::Column(1),
+            &decimal(50, 1).into(),
+            &StatementContext::default(),
+        )),
+        (row1.evaluate_less_than(
+            &ValueExpression::Column(0),
+            &Bool(false).into(),
+            &StatementContext::default(),
+        )),
     ];
 
     failing_inputs
@@ -152,28 +332,86 @@ fn evaluate_less_than_equal() {
     let row2 = Row(vec![Int(6), Bool(false)]);
 
     let inputs = [
-        (row1.evaluate_less_than_equal(0, &Int(5)).unwrap(), true),
-        (row1.evaluate_less_than_equal(0, &Int(6)).unwrap(), true),
-        (row1.evaluate_less_than_equal(0, &Int(4)).unwrap(), false),
         (
-            row1.evaluate_less_than_equal(0, &Decimal(5, 0)).unwrap(),
+            row1.evaluate_less_than_equal(
+                &ValueExpression::Column(0),
+                &Int(5).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            true,
+        ),
+        (
+            row1.evaluate_less_than_equal(
+                &ValueExpression::Column(0),
+                &Int(6).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            true,
+        ),
+        (
+            row1.evaluate_less_than_equal(
+                &ValueExpression::Column(0),
+                &Int(4).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
+        (
+            row1.evaluate_less_than_equal(
+                &ValueExpression::Column(0),
+                &decimal(50, 1).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
             true,
         ),
         (
-            row1.evaluate_less_than_equal(1, &Bool(false)).unwrap(),
+            row1.evaluate_less_than_equal(
+                &ValueExpression::Column(1),
+                &Bool(false).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
             false,
         ),
-        (row1.evaluate_less_than_equal(1, &Bool(true)).unwrap(), true),
-        (row2.evaluate_less_than_equal(1, &Bool(true)).unwrap(), true),
+        (
+            row1.evaluate_less_than_equal(
+                &ValueExpression::Column(1),
+                &Bool(true).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            true,
+        ),
+        (
+            row2.evaluate_less_than_equal(
+                &ValueExpression::Column(1),
+                &Bool(true).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            true,
+        ),
     ];
 
     inputs.iter().for_each(|(result, expected)| {
-        assert_eq!(result, expected);
+        assert_eq!(*result, Some(*expected));
     });
 
     let failing_inputs = [
-        (row1.evaluate_less_than_equal(1, &Decimal(5, 0))),
-        (row1.evaluate_less_than_equal(0, &Bool(false))),
+        (row1.evaluate_less_than_equal(
+            &ValueExpression::Column(1),
+            &decimal(50, 1).into(),
+            &StatementContext::default(),
+        )),
+        (row1.evaluate_less_than_equal(
+            &ValueExpression::Column(0),
+            &Bool(false).into(),
+            &StatementContext::default(),
+        )),
     ];
 
     failing_inputs
@@ -189,25 +427,86 @@ fn evaluate_greater_than() {
     let row2 = Row(vec![Int(6), Bool(false)]);
 
     let inputs = [
-        (row1.evaluate_greater_than(0, &Int(5)).unwrap(), false),
-        (row1.evaluate_greater_than(0, &Int(6)).unwrap(), false),
-        (row1.evaluate_greater_than(0, &Int(4)).unwrap(), true),
         (
-            row1.evaluate_greater_than(0, &Decimal(5, 0)).unwrap(),
+            row1.evaluate_greater_than(
+                &ValueExpression::Column(0),
+                &Int(5).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
+        (
+            row1.evaluate_greater_than(
+                &ValueExpression::Column(0),
+                &Int(6).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
+        (
+            row1.evaluate_greater_than(
+                &ValueExpression::Column(0),
+                &Int(4).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            true,
+        ),
+        (
+            row1.evaluate_greater_than(
+                &ValueExpression::Column(0),
+                &decimal(50, 1).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
+        (
+            row1.evaluate_greater_than(
+                &ValueExpression::Column(1),
+                &Bool(false).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            true,
+        ),
+        (
+            row1.evaluate_greater_than(
+                &ValueExpression::Column(1),
+                &Bool(true).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
+        (
+            row2.evaluate_greater_than(
+                &ValueExpression::Column(1),
+                &Bool(false).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
             false,
         ),
-        (row1.evaluate_greater_than(1, &Bool(false)).unwrap(), true),
-        (row1.evaluate_greater_than(1, &Bool(true)).unwrap(), false),
-        (row2.evaluate_greater_than(1, &Bool(false)).unwrap(), false),
     ];
 
     inputs.iter().for_each(|(result, expected)| {
-        assert_eq!(result, expected);
+        assert_eq!(*result, Some(*expected));
     });
 
     let failing_inputs = [
-        (row1.evaluate_greater_than(1, &Decimal(5, 0))),
-        (row1.evaluate_greater_than(0, &Bool(false))),
+        (row1.evaluate_greater_than(
+            &ValueExpression::Column(1),
+            &decimal(50, 1).into(),
+            &StatementContext::default(),
+        )),
+        (row1.evaluate_greater_than(
+            &ValueExpression::Column(0),
+            &Bool(false).into(),
+            &StatementContext::default(),
+        )),
     ];
 
     failing_inputs
@@ -223,34 +522,86 @@ fn evaluate_greater_than_equal() {
     let row2 = Row(vec![Int(6), Bool(false)]);
 
     let inputs = [
-        (row1.evaluate_greater_than_equal(0, &Int(5)).unwrap(), true),
-        (row1.evaluate_greater_than_equal(0, &Int(6)).unwrap(), false),
-        (row1.evaluate_greater_than_equal(0, &Int(4)).unwrap(), true),
         (
-            row1.evaluate_greater_than_equal(0, &Decimal(5, 0)).unwrap(),
+            row1.evaluate_greater_than_equal(
+                &ValueExpression::Column(0),
+                &Int(5).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            true,
+        ),
+        (
+            row1.evaluate_greater_than_equal(
+                &ValueExpression::Column(0),
+                &Int(6).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            false,
+        ),
+        (
+            row1.evaluate_greater_than_equal(
+                &ValueExpression::Column(0),
+                &Int(4).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
             true,
         ),
         (
-            row1.evaluate_greater_than_equal(1, &Bool(false)).unwrap(),
+            row1.evaluate_greater_than_equal(
+                &ValueExpression::Column(0),
+                &decimal(50, 1).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
             true,
         ),
         (
-            row1.evaluate_greater_than_equal(1, &Bool(true)).unwrap(),
+            row1.evaluate_greater_than_equal(
+                &ValueExpression::Column(1),
+                &Bool(false).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
             true,
         ),
         (
-            row2.evaluate_greater_than_equal(1, &Bool(false)).unwrap(),
+            row1.evaluate_greater_than_equal(
+                &ValueExpression::Column(1),
+                &Bool(true).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
+            true,
+        ),
+        (
+            row2.evaluate_greater_than_equal(
+                &ValueExpression::Column(1),
+                &Bool(false).into(),
+                &StatementContext::default(),
+            )
+            .unwrap(),
             true,
         ),
     ];
 
     inputs.iter().for_each(|(result, expected)| {
-        assert_eq!(result, expected);
+        assert_eq!(*result, Some(*expected));
     });
 
     let failing_inputs = [
-        (row1.evaluate_greater_than_equal(1, &Decimal(5, 0))),
-        (row1.evaluate_greater_than_equal(0, &Bool(false))),
+        (row1.evaluate_greater_than_equal(
+            &ValueExpression::Column(1),
+            &decimal(50, 1).into(),
+            &StatementContext::default(),
+        )),
+        (row1.evaluate_greater_than_equal(
+            &ValueExpression::Column(0),
+            &Bool(false).into(),
+            &StatementContext::default(),
+        )),
     ];
 
     failing_inputs
@@ -264,8 +615,8 @@ fn select_with_where() {
 
     let only_int_five = table
         .select(
-            ColumnSelector::Name(vec![ColumnName("first".into())]),
-            Some(Where {
+            ColumnSelector::Expressions(vec!["first".into()]),
+            Some(Where::Comparison {
                 left: "first".into(),
                 operator: InfixOperator::Equals,
                 right: 5.into(),
@@ -278,7 +629,9 @@ fn select_with_where() {
         test_row_set(vec![Row(vec![5.into()])]).unwrap()
     );
 
-    let none = table.select(ColumnSelector::Name(vec![]), None).unwrap();
+    let none = table
+        .select(ColumnSelector::Expressions(vec![]), None)
+        .unwrap();
 
     assert_eq!(none, test_row_set(vec![Row(vec![]), Row(vec![]),]).unwrap())
 }
@@ -288,7 +641,12 @@ fn update_basic() {
     let (mut table, _) = test_table_with_values();
 
     table
-        .update(vec![ColumnName("first".into())], vec![69.into()], None)
+        .update(
+            vec![ColumnName("first".into())],
+            vec![ColumnValue::from(69).into()],
+            None,
+            &StatementContext::default(),
+        )
         .unwrap();
 
     assert_eq!(
@@ -302,12 +660,13 @@ fn update_basic() {
     table
         .update(
             vec![ColumnName("first".into())],
-            vec![420.into()],
-            Some(Where {
+            vec![ColumnValue::from(420).into()],
+            Some(Where::Comparison {
                 left: "second".into(),
                 operator: InfixOperator::Equals,
                 right: true.into(),
             }),
+            &StatementContext::default(),
         )
         .unwrap();
 
@@ -324,19 +683,1981 @@ fn update_basic() {
 fn delete_basic() {
     let (mut table, _) = test_table_with_values();
 
-    table.delete(None).unwrap();
+    table.delete(None, &StatementContext::default()).unwrap();
 
     assert_eq!(table.values, vec![]);
 
     let (mut table, _) = test_table_with_values();
 
     table
-        .delete(Some(Where {
-            left: "second".into(),
-            operator: InfixOperator::Equals,
-            right: false.into(),
-        }))
+        .delete(
+            Some(Where::Comparison {
+                left: "second".into(),
+                operator: InfixOperator::Equals,
+                right: false.into(),
+            }),
+            &StatementContext::default(),
+        )
         .unwrap();
 
     assert_eq!(table.values, vec![Row(vec![5.into(), true.into()])])
 }
+
+#[test]
+fn select_with_boolean_where() {
+    let (mut table, (row1, row2)) = test_table_with_values();
+
+    let row3 = vec![7.into(), true.into()];
+
+    table.insert(&None, row3.clone()).unwrap();
+
+    let comparison = |column: &str, operator, value: ColumnValue| Where::Comparison {
+        left: column.into(),
+        operator,
+        right: value.into(),
+    };
+
+    let inputs = [
+        (
+            Where::And(
+                comparison("first", InfixOperator::GreaterThan, 5.into()).into(),
+                comparison("second", InfixOperator::Equals, true.into()).into(),
+            ),
+            vec![Row(row3.clone())],
+        ),
+        (
+            Where::Or(
+                comparison("first", InfixOperator::Equals, 5.into()).into(),
+                comparison("second", InfixOperator::Equals, false.into()).into(),
+            ),
+            vec![Row(row1.clone()), Row(row2.clone())],
+        ),
+        (
+            Where::Not(comparison("first", InfixOperator::Equals, 6.into()).into()),
+            vec![Row(row1.clone()), Row(row3.clone())],
+        ),
+        (
+            // NOT (first = 5 OR first = 7) AND second = false
+            Where::And(
+                Where::Not(
+                    Where::Or(
+                        comparison("first", InfixOperator::Equals, 5.into()).into(),
+                        comparison("first", InfixOperator::Equals, 7.into()).into(),
+                    )
+                    .into(),
+                )
+                .into(),
+                comparison("second", InfixOperator::Equals, false.into()).into(),
+            ),
+            vec![Row(row2)],
+        ),
+    ];
+
+    inputs.into_iter().for_each(|(condition, expected)| {
+        let result = table
+            .select(ColumnSelector::AllColumns, Some(condition))
+            .unwrap();
+
+        assert_eq!(result, test_row_set(expected).unwrap());
+    });
+
+    // Unknown columns are caught anywhere in the tree
+    let result = table.select(
+        ColumnSelector::AllColumns,
+        Some(Where::Or(
+            comparison("first", InfixOperator::Equals, 5.into()).into(),
+            comparison("third", InfixOperator::Equals, 5.into()).into(),
+        )),
+    );
+
+    assert!(matches!(result, Err(SqlError::NameDoesNotExist(_, _))));
+}
+
+fn binary(
+    left: ValueExpression,
+    operator: ArithmeticOperator,
+    right: ValueExpression,
+) -> ValueExpression {
+    return ValueExpression::Binary {
+        left: left.into(),
+        operator,
+        right: right.into(),
+    };
+}
+
+#[test]
+fn arithmetic_on_values() {
+    use ArithmeticOperator as A;
+    use ColumnValue::*;
+
+    let inputs = [
+        (Int(5), A::Add, Int(3), Int(8)),
+        (Int(5), A::Subtract, Int(3), Int(2)),
+        (Int(5), A::Multiply, Int(3), Int(15)),
+        (Int(7), A::Divide, Int(2), Int(3)),
+        // Ints get promoted to decimals
+        (Int(5), A::Add, decimal(15, 1), decimal(65, 1)),
+        (decimal(25, 1), A::Multiply, Int(2), decimal(50, 1)),
+        (
+            decimal(125, 2),
+            A::Multiply,
+            decimal(12, 1),
+            decimal(1500, 3),
+        ),
+        (decimal(70, 1), A::Divide, Int(2), decimal(3500000, 6)),
+        (
+            decimal(375, 2),
+            A::Subtract,
+            decimal(15, 1),
+            decimal(225, 2),
+        ),
+        // Leading zeros in the fractional part matter
+        (decimal(105, 2), A::Add, decimal(15, 1), decimal(255, 2)),
+        (decimal(1, 1), A::Divide, decimal(3, 0), decimal(33333, 6)),
+        // Anything that meets a float becomes a float
+        (Float(1.5), A::Multiply, Int(2), Float(3.0)),
+        (decimal(25, 1), A::Subtract, Float(0.5), Float(2.0)),
+        (Int(1), A::Divide, Float(4.0), Float(0.25)),
+        (
+            decimal(5, 1),
+            A::Divide,
+            decimal(3, 7),
+            decimal(16666666666666, 7),
+        ),
+    ];
+
+    inputs
+        .into_iter()
+        .for_each(|(left, operator, right, expected)| {
+            assert_eq!(left.apply(operator, &right).unwrap(), expected);
+        });
+
+    assert_eq!(Int(3).apply(A::Subtract, &Int(5)).unwrap(), Int(-2));
+    assert_eq!(Int(-7).apply(A::Divide, &Int(2)).unwrap(), Int(-3));
+    assert_eq!(
+        decimal(-15, 1).apply(A::Multiply, &Int(-3)).unwrap(),
+        decimal(45, 1)
+    );
+
+    assert!(matches!(
+        Int(i64::MAX).apply(A::Add, &Int(1)),
+        Err(SqlError::ArithmeticOverflow(_, _, _))
+    ));
+    assert!(matches!(
+        Int(i64::MIN).apply(A::Subtract, &Int(1)),
+        Err(SqlError::ArithmeticOverflow(_, _, _))
+    ));
+    assert!(matches!(
+        Int(i64::MIN).apply(A::Divide, &Int(-1)),
+        Err(SqlError::ArithmeticOverflow(_, _, _))
+    ));
+    assert!(matches!(
+        Int(1).apply(A::Divide, &Int(0)),
+        Err(SqlError::DivisionByZero)
+    ));
+    assert!(matches!(
+        decimal(15, 1).apply(A::Divide, &decimal(0, 2)),
+        Err(SqlError::DivisionByZero)
+    ));
+    assert!(matches!(
+        Float(1.0).apply(A::Divide, &Int(0)),
+        Err(SqlError::DivisionByZero)
+    ));
+    assert!(matches!(
+        Float(f64::MAX).apply(A::Multiply, &Int(2)),
+        Err(SqlError::ArithmeticOverflow(_, _, _))
+    ));
+    assert!(matches!(
+        Float(f64::NAN).apply(A::Add, &Int(1)),
+        Ok(Float(value)) if value.is_nan()
+    ));
+    assert!(matches!(
+        Int(1).apply(A::Add, &Bool(true)),
+        Err(SqlError::InvalidOperation(A::Add, _, _))
+    ));
+
+    assert_eq!(Int(0).negate().unwrap(), Int(0));
+    assert_eq!(Int(1).negate().unwrap(), Int(-1));
+    assert_eq!(decimal(-105, 2).negate().unwrap(), decimal(105, 2));
+    assert!(matches!(
+        Int(i64::MIN).negate(),
+        Err(SqlError::ArithmeticOverflow(_, _, _))
+    ));
+}
+
+#[test]
+fn select_with_arithmetic() {
+    let (table, _) = test_table_with_values();
+
+    let result = table
+        .select(
+            ColumnSelector::Expressions(vec![
+                binary(
+                    "first".into(),
+                    ArithmeticOperator::Multiply,
+                    ColumnValue::from(2).into(),
+                ),
+                binary(
+                    "first".into(),
+                    ArithmeticOperator::Add,
+                    decimal(5, 1).into(),
+                ),
+            ]),
+            Some(Where::Comparison {
+                left: binary(
+                    "first".into(),
+                    ArithmeticOperator::Subtract,
+                    ColumnValue::from(1).into(),
+                ),
+                operator: InfixOperator::GreaterThan,
+                right: 4.into(),
+            }),
+        )
+        .unwrap();
+
+    assert_eq!(result.values, vec![Row(vec![12.into(), decimal(65, 1)])]);
+    assert_eq!(
+        result.types,
+        vec![ColumnType::BigInt, ColumnType::Decimal(None)]
+    );
+    assert_eq!(
+        result.names,
+        vec![
+            ColumnName("first * 2".into()),
+            ColumnName("first + 0.5".into())
+        ]
+    );
+
+    let result = table.select(
+        ColumnSelector::Expressions(vec![binary(
+            "first".into(),
+            ArithmeticOperator::Add,
+            "second".into(),
+        )]),
+        None,
+    );
+
+    assert!(matches!(result, Err(SqlError::InvalidOperation(_, _, _))));
+}
+
+#[test]
+fn update_with_arithmetic() {
+    let (mut table, _) = test_table_with_values();
+
+    table
+        .update(
+            vec!["first".into()],
+            vec![binary(
+                "first".into(),
+                ArithmeticOperator::Add,
+                ColumnValue::from(1).into(),
+            )],
+            Some(Where::Comparison {
+                left: "second".into(),
+                operator: InfixOperator::Equals,
+                right: true.into(),
+            }),
+            &StatementContext::default(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        table.values,
+        vec![
+            Row(vec![6.into(), true.into()]),
+            Row(vec![6.into(), false.into()]),
+        ]
+    );
+
+    // The new value has to have the column's type, even if no rows match
+    let result = table.update(
+        vec!["first".into()],
+        vec![binary(
+            "first".into(),
+            ArithmeticOperator::Divide,
+            decimal(25, 1).into(),
+        )],
+        Some(Where::Comparison {
+            left: "first".into(),
+            operator: InfixOperator::Equals,
+            right: 0.into(),
+        }),
+        &StatementContext::default(),
+    );
+
+    assert!(matches!(result, Err(SqlError::IncompatibleTypes(_, _))));
+}
+
+#[test]
+fn select_with_column_comparison() {
+    let mut table = Table::new(
+        "test_table".into(),
+        vec![
+            ColumnDefinition("a".into(), ColumnType::Int, vec![]),
+            ColumnDefinition("b".into(), ColumnType::Int, vec![]),
+            ColumnDefinition("c".into(), ColumnType::Text, vec![]),
+        ],
+        vec![],
+        vec![],
+        vec![],
+    )
+    .unwrap();
+
+    let row1 = vec![1.into(), 1.into(), "x".into()];
+    let row2 = vec![2.into(), 3.into(), "y".into()];
+
+    table
+        .insert_multiple(&None, vec![row1.clone(), row2.clone()])
+        .unwrap();
+
+    let inputs = [
+        (
+            Where::Comparison {
+                left: "a".into(),
+                operator: InfixOperator::Equals,
+                right: "b".into(),
+            },
+            vec![Row(row1.clone())],
+        ),
+        // Literal on the left
+        (
+            Where::Comparison {
+                left: 1.into(),
+                operator: InfixOperator::LessThan,
+                right: "b".into(),
+            },
+            vec![Row(row2.clone())],
+        ),
+        (
+            Where::Comparison {
+                left: ValueExpression::Value("y".into()),
+                operator: InfixOperator::Equals,
+                right: "c".into(),
+            },
+            vec![Row(row2.clone())],
+        ),
+        (
+            Where::Comparison {
+                left: binary("a".into(), ArithmeticOperator::Add, 1.into()),
+                operator: InfixOperator::Equals,
+                right: "b".into(),
+            },
+            vec![Row(row2)],
+        ),
+    ];
+
+    inputs.into_iter().for_each(|(condition, expected)| {
+        let result = table
+            .select(ColumnSelector::AllColumns, Some(condition))
+            .unwrap();
+
+        assert_eq!(result, test_row_set(expected).unwrap());
+    });
+
+    let result = table.select(
+        ColumnSelector::AllColumns,
+        Some(Where::Comparison {
+            left: 1.into(),
+            operator: InfixOperator::Equals,
+            right: "d".into(),
+        }),
+    );
+
+    assert!(matches!(result, Err(SqlError::NameDoesNotExist(_, _))));
+}
+
+#[test]
+fn join_basic() {
+    let (table, (row1, row2)) = test_table_with_values();
+
+    let mut other = Table::new(
+        "other_table".into(),
+        vec![
+            ColumnDefinition("first".into(), ColumnType::Int, vec![]),
+            ColumnDefinition("name".into(), ColumnType::Text, vec![]),
+        ],
+        vec![],
+        vec![],
+        vec![],
+    )
+    .unwrap();
+
+    other.insert(&None, vec![6.into(), "six".into()]).unwrap();
+
+    let joined = table
+        .join(
+            &other,
+            JoinType::Inner,
+            Some(Where::Comparison {
+                left: "test_table.first".into(),
+                operator: InfixOperator::Equals,
+                right: "other_table.first".into(),
+            }),
+        )
+        .unwrap();
+
+    assert_eq!(
+        joined.schema.column_names,
+        vec![
+            ColumnName("test_table.first".into()),
+            ColumnName("test_table.second".into()),
+            ColumnName("other_table.first".into()),
+            ColumnName("other_table.name".into()),
+        ]
+    );
+    assert_eq!(
+        joined.values,
+        vec![Row(vec![6.into(), false.into(), 6.into(), "six".into()])]
+    );
+
+    // Unqualified names work as long as they're unambiguous
+    let result = joined.select(ColumnSelector::Expressions(vec!["name".into()]), None);
+
+    assert!(result.is_ok());
+
+    let result = joined.select(ColumnSelector::Expressions(vec!["first".into()]), None);
+
+    assert!(matches!(result, Err(SqlError::AmbiguousColumnName(_))));
+
+    let crossed = table.join(&other, JoinType::Cross, None).unwrap();
+
+    assert_eq!(
+        crossed.values,
+        vec![
+            Row(row1
+                .into_iter()
+                .chain(vec![6.into(), "six".into()])
+                .collect()),
+            Row(row2
+                .into_iter()
+                .chain(vec![6.into(), "six".into()])
+                .collect()),
+        ]
+    );
+
+    let left_joined = table
+        .join(
+            &other,
+            JoinType::Left,
+            Some(Where::Comparison {
+                left: "test_table.first".into(),
+                operator: InfixOperator::Equals,
+                right: "other_table.first".into(),
+            }),
+        )
+        .unwrap();
+
+    // Unmatched rows are padded with NULLs
+    assert_eq!(
+        left_joined.values,
+        vec![
+            Row(vec![
+                5.into(),
+                true.into(),
+                ColumnValue::Null,
+                ColumnValue::Null
+            ]),
+            Row(vec![6.into(), false.into(), 6.into(), "six".into()]),
+        ]
+    );
+}
+
+#[test]
+fn total_ordering_of_values() {
+    use ColumnValue::*;
+    use Ordering::*;
+
+    let inputs = [
+        (Int(5), Int(6), Less),
+        (Int(5), decimal(50, 1), Equal),
+        (decimal(51, 1), Int(5), Greater),
+        (decimal(525, 2), decimal(53, 1), Less),
+        (decimal(499, 2), decimal(51, 1), Less),
+        (decimal(105, 2), decimal(15, 1), Less),
+        (decimal(150, 2), decimal(15, 1), Equal),
+        ("b".into(), "ab".into(), Greater),
+        (Bool(false), Bool(true), Less),
+        (Float(0.5), Int(1), Less),
+        (Float(1.5), decimal(15, 1), Equal),
+        (Float(-0.0), Float(0.0), Equal),
+        // NaN is equal to itself and larger than any other number
+        (Float(f64::NAN), Float(f64::NAN), Equal),
+        (Float(f64::NAN), Float(f64::INFINITY), Greater),
+        (Int(i64::MAX), Float(f64::NAN), Less),
+        (
+            Blob(crate::types::Blob(vec![1, 2])),
+            Blob(crate::types::Blob(vec![1, 2, 0])),
+            Less,
+        ),
+        // Different types are ordered by type
+        (Bool(true), Int(0), Less),
+        (Int(100), "a".into(), Less),
+    ];
+
+    inputs.into_iter().for_each(|(left, right, expected)| {
+        assert_eq!(left.total_cmp(&right), expected);
+        assert_eq!(right.total_cmp(&left), expected.reverse());
+    });
+
+    assert!(Int(5).compare(&decimal(50, 1)).is_ok());
+    assert!(matches!(
+        Int(5).compare(&Bool(true)),
+        Err(SqlError::ImpossibleComparison(_, _))
+    ));
+}
+
+#[test]
+fn temporal_values() {
+    use sql_parse::parser::DateTimeField;
+    use ArithmeticOperator as A;
+    use ColumnType as T;
+
+    let value = |value: &str, column_type: ColumnType| {
+        return ColumnValue::parse(value, column_type).unwrap();
+    };
+
+    for (input, column_type, expected) in [
+        ("2024-02-29", T::Date, "DATE '2024-02-29'"),
+        ("0001-01-01", T::Date, "DATE '0001-01-01'"),
+        ("23:59:59.999999", T::Time, "TIME '23:59:59.999999'"),
+        (
+            "1969-12-31 23:59:59.5",
+            T::Timestamp,
+            "TIMESTAMP '1969-12-31 23:59:59.5'",
+        ),
+        (
+            "1 year 14 months 3 days 1.5 seconds",
+            T::Interval,
+            "INTERVAL '2 years 2 months 3 days 1.5 seconds'",
+        ),
+    ] {
+        assert_eq!(value(input, column_type).to_string(), expected);
+    }
+
+    assert_eq!(
+        value("2026-01-01T10:00:00", T::Timestamp),
+        value("2026-01-01 10:00", T::Timestamp)
+    );
+    assert_eq!(
+        value("1 week 2 days", T::Interval),
+        value("9 days", T::Interval)
+    );
+
+    for (input, column_type) in [
+        ("2023-02-29", T::Date),
+        ("2026-13-01", T::Date),
+        ("10000-01-01", T::Date),
+        ("24:00:00", T::Time),
+        ("2026-01-01 12:60:00", T::Timestamp),
+        ("3 fortnights", T::Interval),
+    ] {
+        assert!(matches!(
+            ColumnValue::parse(input, column_type),
+            Err(SqlError::InvalidLiteral(_, _))
+        ));
+    }
+
+    let inputs = [
+        (
+            value("2026-01-31", T::Date),
+            A::Add,
+            value("1 month", T::Interval),
+            value("2026-02-28", T::Timestamp),
+        ),
+        (
+            value("2026-03-01", T::Date),
+            A::Subtract,
+            value("2026-02-01", T::Date),
+            ColumnValue::Int(28),
+        ),
+        (
+            value("2026-01-01 00:00:00", T::Timestamp),
+            A::Subtract,
+            value("1 second", T::Interval),
+            value("2025-12-31 23:59:59", T::Timestamp),
+        ),
+        (
+            value("2026-01-02 06:00:00", T::Timestamp),
+            A::Subtract,
+            value("2026-01-01 00:00:00", T::Timestamp),
+            value("30 hours", T::Interval),
+        ),
+        (
+            value("23:00:00", T::Time),
+            A::Add,
+            value("2 hours", T::Interval),
+            value("01:00:00", T::Time),
+        ),
+        (
+            value("1 day", T::Interval),
+            A::Multiply,
+            ColumnValue::Int(3),
+            value("3 days", T::Interval),
+        ),
+    ];
+
+    inputs
+        .into_iter()
+        .for_each(|(left, operator, right, expected)| {
+            assert_eq!(left.apply(operator, &right).unwrap(), expected);
+        });
+
+    assert!(matches!(
+        value("9999-12-31", T::Date).apply(A::Add, &value("1 day", T::Interval)),
+        Err(SqlError::ArithmeticOverflow(_, _, _))
+    ));
+    assert!(matches!(
+        value("2026-01-01", T::Date).apply(A::Add, &value("2026-01-01", T::Date)),
+        Err(SqlError::InvalidOperation(A::Add, _, _))
+    ));
+
+    assert_eq!(
+        value("2026-01-01", T::Date)
+            .compare(&value("2026-01-01 00:00:00", T::Timestamp))
+            .unwrap(),
+        Ordering::Equal
+    );
+    assert_eq!(
+        value("1 month", T::Interval)
+            .compare(&value("29 days", T::Interval))
+            .unwrap(),
+        Ordering::Greater
+    );
+    assert!(matches!(
+        value("2026-01-01", T::Date).compare(&value("10:00:00", T::Time)),
+        Err(SqlError::ImpossibleComparison(_, _))
+    ));
+
+    let timestamp = value("2026-10-18 13:45:30.25", T::Timestamp);
+
+    for (field, expected) in [
+        (DateTimeField::Year, 2026),
+        (DateTimeField::Month, 10),
+        (DateTimeField::Day, 18),
+        (DateTimeField::Hour, 13),
+        (DateTimeField::Minute, 45),
+        (DateTimeField::Second, 30),
+    ] {
+        assert_eq!(
+            timestamp.extract(field).unwrap(),
+            ColumnValue::Int(expected)
+        );
+    }
+
+    assert!(matches!(
+        value("10:00:00", T::Time).extract(DateTimeField::Year),
+        Err(SqlError::InvalidExtract(DateTimeField::Year, T::Time))
+    ));
+}
+
+#[test]
+fn insert_and_update_decimal() {
+    let mut table = Table::new(
+        "test_table".into(),
+        vec![
+            ColumnDefinition("a".into(), ColumnType::Decimal(Some((4, 2))), vec![]),
+            ColumnDefinition("b".into(), ColumnType::Decimal(None), vec![]),
+        ],
+        vec![],
+        vec![],
+        vec![],
+    )
+    .unwrap();
+
+    table
+        .insert_multiple(
+            &None,
+            vec![
+                vec![decimal(105, 2), decimal(105, 2)],
+                vec![decimal(15, 1), decimal(15, 1)],
+                // Rounded to the scale of the column, the other one is kept as is
+                vec![decimal(1995, 3), decimal(1995, 3)],
+                vec![3.into(), 3.into()],
+            ],
+        )
+        .unwrap();
+
+    assert_eq!(
+        table.values,
+        vec![
+            Row(vec![decimal(105, 2), decimal(105, 2)]),
+            Row(vec![decimal(150, 2), decimal(15, 1)]),
+            Row(vec![decimal(200, 2), decimal(1995, 3)]),
+            Row(vec![decimal(300, 2), decimal(3, 0)]),
+        ]
+    );
+
+    // Only two digits are left before the dot
+    let result = table.insert(&None, vec![decimal(100, 0), ColumnValue::Null]);
+
+    assert!(matches!(result, Err(SqlError::ValueOutOfRange(_, _))));
+
+    table
+        .update(
+            vec!["a".into()],
+            vec![binary(
+                "a".into(),
+                ArithmeticOperator::Divide,
+                ColumnValue::from(3).into(),
+            )],
+            None,
+            &StatementContext::default(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        table
+            .values
+            .iter()
+            .map(|row| row.0[0].clone())
+            .collect::<Vec<_>>(),
+        vec![
+            decimal(35, 2),
+            decimal(50, 2),
+            decimal(67, 2),
+            decimal(100, 2)
+        ]
+    );
+
+    let result = table.update(
+        vec!["a".into()],
+        vec![ColumnValue::from("hey").into()],
+        None,
+        &StatementContext::default(),
+    );
+
+    assert!(matches!(result, Err(SqlError::IncompatibleTypes(_, _))));
+}
+
+#[test]
+fn select_with_order_by_and_limit() {
+    let mut table = Table::new(
+        "test_table".into(),
+        vec![
+            ColumnDefinition("a".into(), ColumnType::Decimal(None), vec![]),
+            ColumnDefinition("b".into(), ColumnType::Int, vec![]),
+        ],
+        vec![],
+        vec![],
+        vec![],
+    )
+    .unwrap();
+
+    table
+        .insert_multiple(
+            &None,
+            vec![
+                vec![decimal(25, 1), 1.into()],
+                vec![decimal(10, 1), 2.into()],
+                vec![decimal(250, 2), 3.into()],
+                vec![decimal(100, 1), 4.into()],
+            ],
+        )
+        .unwrap();
+
+    let query = |order_by: Vec<(&str, SortDirection)>, limit, offset| SelectQuery {
+        columns: ColumnSelector::Expressions(vec!["b".into()]),
+        condition: None,
+        group_by: vec![],
+        having: None,
+        order_by: order_by
+            .into_iter()
+            .map(|(column, direction)| OrderBy {
+                expression: column.into(),
+                direction,
+            })
+            .collect(),
+        limit,
+        offset,
+    };
+
+    let inputs = [
+        (query(vec![], None, None), vec![1, 2, 3, 4]),
+        // Stable for equal keys
+        (
+            query(vec![("a", SortDirection::Ascending)], None, None),
+            vec![2, 1, 3, 4],
+        ),
+        (
+            query(
+                vec![
+                    ("a", SortDirection::Descending),
+                    ("b", SortDirection::Descending),
+                ],
+                None,
+                None,
+            ),
+            vec![4, 3, 1, 2],
+        ),
+        (
+            query(vec![("a", SortDirection::Ascending)], Some(2), Some(1)),
+            vec![1, 3],
+        ),
+        (query(vec![], Some(0), None), vec![]),
+        (query(vec![], None, Some(10)), vec![]),
+    ];
+
+    inputs.into_iter().for_each(|(query, expected)| {
+        let result = table.query(query).unwrap();
+
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|value: i64| Row(vec![value.into()]))
+            .collect();
+
+        assert_eq!(result.values, expected);
+    });
+
+    let result = table.query(query(vec![("c", SortDirection::Ascending)], None, None));
+
+    assert!(matches!(result, Err(SqlError::NameDoesNotExist(_, _))));
+}
+
+#[test]
+fn select_with_group_by() {
+    let mut table = Table::new(
+        "test_table".into(),
+        vec![
+            ColumnDefinition("a".into(), ColumnType::Text, vec![]),
+            ColumnDefinition("b".into(), ColumnType::Int, vec![]),
+        ],
+        vec![],
+        vec![],
+        vec![],
+    )
+    .unwrap();
+
+    table
+        .insert_multiple(
+            &None,
+            vec![
+                vec!["y".into(), 1.into()],
+                vec!["x".into(), 2.into()],
+                vec!["y".into(), 4.into()],
+                vec!["x".into(), 3.into()],
+                vec!["z".into(), 10.into()],
+            ],
+        )
+        .unwrap();
+
+    let aggregate = |function, column: Option<&str>| ValueExpression::Aggregate {
+        function,
+        argument: column.map(|column| Box::new(column.into())),
+    };
+
+    let query = |columns, group_by: Vec<&str>, having| SelectQuery {
+        columns: ColumnSelector::Expressions(columns),
+        condition: None,
+        group_by: group_by.into_iter().map(|column| column.into()).collect(),
+        having,
+        order_by: vec![],
+        limit: None,
+        offset: None,
+    };
+
+    let result = table
+        .query(query(
+            vec![
+                "a".into(),
+                aggregate(AggregateFunction::Count, None),
+                aggregate(AggregateFunction::Sum, Some("b")),
+                aggregate(AggregateFunction::Average, Some("b")),
+                aggregate(AggregateFunction::Max, Some("b")),
+            ],
+            vec!["a"],
+            None,
+        ))
+        .unwrap();
+
+    assert_eq!(
+        result.types,
+        vec![
+            ColumnType::Text,
+            ColumnType::BigInt,
+            ColumnType::BigInt,
+            ColumnType::Decimal(None),
+            ColumnType::Int
+        ]
+    );
+
+    assert_eq!(
+        result.names,
+        vec![
+            "a".into(),
+            "COUNT(*)".into(),
+            "SUM(b)".into(),
+            "AVG(b)".into(),
+            "MAX(b)".into()
+        ]
+    );
+
+    // Groups are ordered by their key
+    assert_eq!(
+        result.values,
+        vec![
+            Row(vec![
+                "x".into(),
+                2.into(),
+                5.into(),
+                decimal(2500000, 6),
+                3.into()
+            ]),
+            Row(vec![
+                "y".into(),
+                2.into(),
+                5.into(),
+                decimal(2500000, 6),
+                4.into()
+            ]),
+            Row(vec![
+                "z".into(),
+                1.into(),
+                10.into(),
+                decimal(10000000, 6),
+                10.into()
+            ]),
+        ]
+    );
+
+    let having = Where::Comparison {
+        left: aggregate(AggregateFunction::Sum, Some("b")),
+        operator: InfixOperator::GreaterThan,
+        right: 5.into(),
+    };
+
+    let result = table
+        .query(query(vec!["a".into()], vec!["a"], Some(having)))
+        .unwrap();
+
+    assert_eq!(result.values, vec![Row(vec!["z".into()])]);
+
+    // Without GROUP BY, everything is one group, even if there are no rows
+    let result = table
+        .query(query(
+            vec![aggregate(AggregateFunction::Min, Some("b"))],
+            vec![],
+            None,
+        ))
+        .unwrap();
+
+    assert_eq!(result.values, vec![Row(vec![1.into()])]);
+
+    let mut empty_query = query(
+        vec![aggregate(AggregateFunction::Count, None)],
+        vec![],
+        None,
+    );
+
+    empty_query.condition = Some(Where::Comparison {
+        left: "b".into(),
+        operator: InfixOperator::GreaterThan,
+        right: 100.into(),
+    });
+
+    let result = table.query(empty_query).unwrap();
+
+    assert_eq!(result.values, vec![Row(vec![0.into()])]);
+}
+
+#[test]
+fn select_with_group_by_errors() {
+    let (table, _) = test_table_with_values();
+
+    let aggregate = |function, column: &str| ValueExpression::Aggregate {
+        function,
+        argument: Some(Box::new(column.into())),
+    };
+
+    let ungrouped = || {
+        SelectQuery::new(
+            ColumnSelector::Expressions(vec![
+                "first".into(),
+                aggregate(AggregateFunction::Count, "second"),
+            ]),
+            None,
+        )
+    };
+
+    let result = table.query(ungrouped());
+
+    assert!(matches!(result, Err(SqlError::NotGrouped(_))));
+
+    let mut grouped = ungrouped();
+
+    grouped.group_by = vec!["first".into()];
+
+    assert!(table.query(grouped).is_ok());
+
+    let invalid = SelectQuery::new(
+        ColumnSelector::Expressions(vec![aggregate(AggregateFunction::Sum, "second")]),
+        None,
+    );
+
+    let result = table.query(invalid);
+
+    assert!(matches!(
+        result,
+        Err(SqlError::InvalidAggregate(
+            AggregateFunction::Sum,
+            ColumnType::Bool
+        ))
+    ));
+
+    let misplaced = SelectQuery::new(
+        ColumnSelector::AllColumns,
+        Some(Where::Comparison {
+            left: aggregate(AggregateFunction::Max, "first"),
+            operator: InfixOperator::Equals,
+            right: 6.into(),
+        }),
+    );
+
+    let result = table.query(misplaced);
+
+    assert!(matches!(
+        result,
+        Err(SqlError::MisplacedAggregate(AggregateFunction::Max))
+    ));
+}
+
+fn nullable_test_table() -> Table {
+    let mut table = Table::new(
+        "test_table".into(),
+        vec![
+            ColumnDefinition(
+                "id".into(),
+                ColumnType::Int,
+                vec![ColumnConstraint::NotNull],
+            ),
+            ColumnDefinition("name".into(), ColumnType::Text, vec![]),
+        ],
+        vec![],
+        vec![],
+        vec![],
+    )
+    .unwrap();
+
+    // Columns that aren't listed are NULL
+    table
+        .insert(&Some(vec!["id".into()]), vec![1.into()])
+        .unwrap();
+
+    table.insert(&None, vec![2.into(), "two".into()]).unwrap();
+
+    return table;
+}
+
+#[test]
+fn insert_and_update_null() {
+    let mut table = nullable_test_table();
+
+    table
+        .insert(&None, vec![3.into(), ColumnValue::Null])
+        .unwrap();
+
+    assert_eq!(
+        table.values,
+        vec![
+            Row(vec![1.into(), ColumnValue::Null]),
+            Row(vec![2.into(), "two".into()]),
+            Row(vec![3.into(), ColumnValue::Null]),
+        ]
+    );
+
+    let result = table.insert(&Some(vec!["name".into()]), vec!["no id".into()]);
+
+    assert!(matches!(result, Err(SqlError::NullNotAllowed(_))));
+
+    let result = table.insert(&None, vec![ColumnValue::Null, "no id".into()]);
+
+    assert!(matches!(result, Err(SqlError::NullNotAllowed(_))));
+
+    table
+        .update(
+            vec!["name".into()],
+            vec![ColumnValue::Null.into()],
+            None,
+            &StatementContext::default(),
+        )
+        .unwrap();
+
+    assert!(table.values.iter().all(|row| row.0[1].is_null()));
+
+    let result = table.update(
+        vec!["id".into()],
+        vec![ColumnValue::Null.into()],
+        None,
+        &StatementContext::default(),
+    );
+
+    assert!(matches!(result, Err(SqlError::NullNotAllowed(_))));
+}
+
+#[test]
+fn select_with_three_valued_logic() {
+    let table = nullable_test_table();
+
+    let name_is = |operator, value: ColumnValue| Where::Comparison {
+        left: "name".into(),
+        operator,
+        right: value.into(),
+    };
+
+    let is_null = |negated| Where::IsNull {
+        expression: "name".into(),
+        negated,
+    };
+
+    let id_is_one = || Where::Comparison {
+        left: "id".into(),
+        operator: InfixOperator::Equals,
+        right: 1.into(),
+    };
+
+    let inputs = [
+        (name_is(InfixOperator::Equals, "two".into()), vec![2]),
+        // Comparing with NULL is unknown, and so is its negation
+        (name_is(InfixOperator::NotEqual, "two".into()), vec![]),
+        (
+            Where::Not(name_is(InfixOperator::Equals, "two".into()).into()),
+            vec![],
+        ),
+        (name_is(InfixOperator::Equals, ColumnValue::Null), vec![]),
+        (is_null(false), vec![1]),
+        (is_null(true), vec![2]),
+        // Unknown OR true is true
+        (
+            Where::Or(
+                name_is(InfixOperator::Equals, "two".into()).into(),
+                id_is_one().into(),
+            ),
+            vec![1, 2],
+        ),
+        // Unknown AND true is unknown
+        (
+            Where::Not(
+                Where::And(
+                    name_is(InfixOperator::Equals, "x".into()).into(),
+                    id_is_one().into(),
+                )
+                .into(),
+            ),
+            vec![2],
+        ),
+    ];
+
+    inputs.into_iter().for_each(|(condition, expected)| {
+        let result = table
+            .select(
+                ColumnSelector::Expressions(vec!["id".into()]),
+                Some(condition),
+            )
+            .unwrap();
+
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|value: i64| Row(vec![value.into()]))
+            .collect();
+
+        assert_eq!(result.values, expected);
+    });
+}
+
+#[test]
+fn aggregates_ignore_null() {
+    let table = nullable_test_table();
+
+    let aggregate = |function| ValueExpression::Aggregate {
+        function,
+        argument: Some(Box::new("name".into())),
+    };
+
+    let query = |condition| {
+        SelectQuery::new(
+            ColumnSelector::Expressions(vec![
+                ValueExpression::Aggregate {
+                    function: AggregateFunction::Count,
+                    argument: None,
+                },
+                aggregate(AggregateFunction::Count),
+                aggregate(AggregateFunction::Max),
+            ]),
+            condition,
+        )
+    };
+
+    let result = table.query(query(None)).unwrap();
+
+    assert_eq!(
+        result.values,
+        vec![Row(vec![2.into(), 1.into(), "two".into()])]
+    );
+
+    // Aggregates over nothing are NULL, apart from COUNT
+    let result = table
+        .query(query(Some(Where::IsNull {
+            expression: "name".into(),
+            negated: false,
+        })))
+        .unwrap();
+
+    assert_eq!(
+        result.values,
+        vec![Row(vec![1.into(), 0.into(), ColumnValue::Null])]
+    );
+}
+
+fn keyed_test_table() -> Table {
+    let mut table = Table::new(
+        "test_table".into(),
+        vec![
+            ColumnDefinition("order_id".into(), ColumnType::Int, vec![]),
+            ColumnDefinition("line".into(), ColumnType::Int, vec![]),
+            ColumnDefinition(
+                "sku".into(),
+                ColumnType::Text,
+                vec![ColumnConstraint::Unique],
+            ),
+        ],
+        vec![],
+        vec![UniqueConstraint {
+            columns: vec!["order_id".into(), "line".into()],
+            primary: true,
+        }],
+        vec![],
+    )
+    .unwrap();
+
+    table
+        .insert_multiple(
+            &None,
+            vec![
+                vec![1.into(), 1.into(), "a".into()],
+                vec![1.into(), 2.into(), "b".into()],
+                vec![2.into(), 1.into(), ColumnValue::Null],
+            ],
+        )
+        .unwrap();
+
+    return table;
+}
+
+#[test]
+fn insert_with_unique_constraints() {
+    let mut table = keyed_test_table();
+
+    // Primary key columns can't be NULL
+    assert_eq!(table.schema.nullable, vec![false, false, true]);
+
+    let result = table.insert(&None, vec![1.into(), 2.into(), "c".into()]);
+
+    assert!(matches!(
+        result,
+        Err(SqlError::DuplicateKey(columns, values))
+            if columns == vec!["order_id".into(), "line".into()] && values == vec![1.into(), 2.into()]
+    ));
+
+    let result = table.insert(&None, vec![3.into(), 1.into(), "a".into()]);
+
+    assert!(matches!(
+        result,
+        Err(SqlError::DuplicateKey(columns, _)) if columns == vec!["sku".into()]
+    ));
+
+    // NULLs are never equal, so they don't clash
+    table
+        .insert(&None, vec![2.into(), 2.into(), ColumnValue::Null])
+        .unwrap();
+
+    assert_eq!(table.values.len(), 4);
+
+    // Deleting a row frees its key
+    table
+        .delete(
+            Some(Where::Comparison {
+                left: "sku".into(),
+                operator: InfixOperator::Equals,
+                right: ColumnValue::from("a").into(),
+            }),
+            &StatementContext::default(),
+        )
+        .unwrap();
+
+    table
+        .insert(&None, vec![1.into(), 1.into(), "a".into()])
+        .unwrap();
+}
+
+#[test]
+fn update_with_unique_constraints() {
+    let mut table = keyed_test_table();
+
+    let before = table.clone();
+
+    let result = table.update(
+        vec!["line".into()],
+        vec![ColumnValue::from(1).into()],
+        None,
+        &StatementContext::default(),
+    );
+
+    assert!(matches!(result, Err(SqlError::DuplicateKey(_, _))));
+    assert_eq!(table, before);
+
+    // The new primary keys are unique, but the SKUs aren't, so neither index changes
+    let result = table.update(
+        vec!["line".into(), "sku".into()],
+        vec![
+            ValueExpression::Binary {
+                left: Box::new("line".into()),
+                operator: ArithmeticOperator::Add,
+                right: Box::new(ColumnValue::from(10).into()),
+            },
+            ColumnValue::from("z").into(),
+        ],
+        None,
+        &StatementContext::default(),
+    );
+
+    assert!(matches!(result, Err(SqlError::DuplicateKey(_, _))));
+    assert_eq!(table, before);
+
+    // Keys only have to be unique after the whole update
+    table
+        .update(
+            vec!["line".into()],
+            vec![ValueExpression::Binary {
+                left: Box::new("line".into()),
+                operator: ArithmeticOperator::Add,
+                right: Box::new(ColumnValue::from(1).into()),
+            }],
+            Some(Where::Comparison {
+                left: "order_id".into(),
+                operator: InfixOperator::Equals,
+                right: ColumnValue::from(1).into(),
+            }),
+            &StatementContext::default(),
+        )
+        .unwrap();
+
+    // Only the keys of the updated rows are replaced, which ends up the same as building them again
+    assert_eq!(
+        table.indexes,
+        UniqueIndex::build_all(&table.schema, &table.values).unwrap()
+    );
+
+    table
+        .insert(&None, vec![1.into(), 1.into(), "c".into()])
+        .unwrap();
+}
+
+#[test]
+fn create_table_with_invalid_keys() {
+    let columns = || {
+        vec![
+            ColumnDefinition(
+                "id".into(),
+                ColumnType::Int,
+                vec![ColumnConstraint::PrimaryKey],
+            ),
+            ColumnDefinition("name".into(), ColumnType::Text, vec![]),
+        ]
+    };
+
+    let key = |columns: &[&str], primary| UniqueConstraint {
+        columns: columns.iter().map(|&name| name.into()).collect(),
+        primary,
+    };
+
+    let result = Table::new(
+        "t".into(),
+        columns(),
+        vec![],
+        vec![key(&["name"], true)],
+        vec![],
+    );
+
+    assert!(matches!(result, Err(SqlError::MultiplePrimaryKeys(_))));
+
+    let result = Table::new(
+        "t".into(),
+        columns(),
+        vec![],
+        vec![key(&["other"], false)],
+        vec![],
+    );
+
+    assert!(matches!(result, Err(SqlError::NameDoesNotExist(_, _))));
+
+    let result = Table::new(
+        "t".into(),
+        columns(),
+        vec![],
+        vec![key(&["name", "name"], false)],
+        vec![],
+    );
+
+    assert!(matches!(result, Err(SqlError::ColumnNameNotUnique(_))));
+}
+
+fn checked_test_table() -> Table {
+    let expression = |sql| sql_parse::parse_expression(sql).unwrap();
+
+    return Table::new(
+        "checked".into(),
+        vec![
+            ColumnDefinition(
+                "id".into(),
+                ColumnType::Int,
+                vec![ColumnConstraint::Check(expression("id > 0"))],
+            ),
+            ColumnDefinition(
+                "amount".into(),
+                ColumnType::Decimal(Some((5, 2))),
+                vec![
+                    ColumnConstraint::NotNull,
+                    ColumnConstraint::Default(expression("2 * 1.5")),
+                ],
+            ),
+            ColumnDefinition(
+                "created".into(),
+                ColumnType::Timestamp,
+                vec![ColumnConstraint::Default(expression("NOW()"))],
+            ),
+        ],
+        vec![],
+        vec![],
+        vec![expression("amount < id * 10")],
+    )
+    .unwrap();
+}
+
+#[test]
+fn insert_with_defaults() {
+    let mut table = checked_test_table();
+
+    table
+        .insert(&Some(vec!["id".into()]), vec![1.into()])
+        .unwrap();
+
+    let row = &table.values[0].0;
+
+    assert_eq!(row[0], 1.into());
+    assert_eq!(row[1], decimal(300, 2));
+    assert!(matches!(row[2], ColumnValue::Timestamp(_)));
+
+    // Explicit values, including NULL, replace the default
+    let result = table.insert(
+        &Some(vec!["id".into(), "amount".into()]),
+        vec![2.into(), ColumnValue::Null],
+    );
+
+    assert!(matches!(result, Err(SqlError::NullNotAllowed(_))));
+}
+
+#[test]
+fn insert_and_update_with_checks() {
+    let mut table = checked_test_table();
+
+    let columns = Some(vec!["id".into(), "amount".into()]);
+
+    let result = table.insert(&columns, vec![0.into(), decimal(1, 0)]);
+
+    assert!(
+        matches!(&result, Err(SqlError::CheckViolation(_, check)) if check == "id > 0"),
+        "{result:?}"
+    );
+
+    let result = table.insert(&columns, vec![1.into(), decimal(10, 0)]);
+
+    assert!(
+        matches!(&result, Err(SqlError::CheckViolation(_, check)) if check == "amount < id * 10"),
+        "{result:?}"
+    );
+
+    // Only false violates a check, unknown (NULL) passes it
+    table
+        .insert(&columns, vec![ColumnValue::Null, decimal(1, 0)])
+        .unwrap();
+    table
+        .insert(&columns, vec![2.into(), decimal(1, 0)])
+        .unwrap();
+
+    let result = table.update(
+        vec!["amount".into()],
+        vec![ValueExpression::Value(decimal(25, 0))],
+        None,
+        &StatementContext::default(),
+    );
+
+    assert!(matches!(result, Err(SqlError::CheckViolation(_, _))));
+
+    // Failed updates leave the table as it was
+    assert_eq!(table.values[1].0[1], decimal(100, 2));
+
+    table
+        .update(
+            vec!["amount".into()],
+            vec![ValueExpression::Value(decimal(15, 0))],
+            Some(Where::IsNull {
+                expression: ValueExpression::Column("id".into()),
+                negated: false,
+            }),
+            &StatementContext::default(),
+        )
+        .unwrap();
+
+    assert_eq!(table.values[0].0[1], decimal(1500, 2));
+}
+
+#[test]
+fn create_table_with_invalid_defaults_and_checks() {
+    let expression = |sql| sql_parse::parse_expression(sql).unwrap();
+
+    let column = |constraint| {
+        vec![ColumnDefinition(
+            "a".into(),
+            ColumnType::SmallInt,
+            vec![constraint],
+        )]
+    };
+
+    for (constraint, check) in [
+        (ColumnConstraint::Default(expression("'text'")), None),
+        (ColumnConstraint::Default(expression("100000")), None),
+        (ColumnConstraint::Default(expression("a + 1")), None),
+        (ColumnConstraint::Check(expression("b > 1")), None),
+        (ColumnConstraint::NotNull, Some(expression("a + 1"))),
+        (ColumnConstraint::NotNull, Some(expression("SUM(a) > 1"))),
+    ] {
+        let result = Table::new(
+            "t".into(),
+            column(constraint.clone()),
+            vec![],
+            vec![],
+            check.into_iter().collect(),
+        );
+
+        assert!(result.is_err(), "{constraint:?}");
+    }
+}
+
+#[test]
+fn sequence_values() {
+    let mut database = Database::new("db".into());
+
+    database
+        .create_sequence(Sequence::new("down".into(), Some(i64::MIN + 3), Some(-2), None).unwrap())
+        .unwrap();
+
+    let next = sql_parse::parse_expression("nextval('down')").unwrap();
+    let current = sql_parse::parse_expression("currval('down') - 1").unwrap();
+
+    let context = StatementContext::new(&database);
+
+    let result = context.evaluate(&current);
+
+    assert!(
+        matches!(result, Err(SqlError::SequenceNotStarted(_))),
+        "{result:?}"
+    );
+
+    for expected in [i64::MIN + 3, i64::MIN + 1] {
+        let result = context.evaluate(&next).unwrap();
+
+        assert_eq!(result, ColumnValue::Int(expected));
+    }
+
+    let result = context.evaluate(&current).unwrap();
+
+    assert_eq!(result, ColumnValue::Int(i64::MIN));
+
+    let result = context.evaluate(&next);
+
+    assert!(
+        matches!(result, Err(SqlError::SequenceExhausted(_))),
+        "{result:?}"
+    );
+
+    // The database only gets the values once they're kept
+    assert_eq!(database.sequences["down"].last_value, None);
+
+    database.keep_sequence_values(&context);
+
+    assert_eq!(database.sequences["down"].last_value, Some(i64::MIN + 1));
+
+    let result = Sequence::new("zero".into(), None, Some(0), None);
+
+    assert!(
+        matches!(result, Err(SqlError::InvalidParameter)),
+        "{result:?}"
+    );
+
+    let result = database.create_sequence(Sequence::new("down".into(), None, None, None).unwrap());
+
+    assert!(
+        matches!(result, Err(SqlError::DuplicateSequence(_))),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn snapshot_and_restore() {
+    let mut database = Database::new("db".into());
+
+    database.create(test_table_with_values().0).unwrap();
+
+    database
+        .rename_table(&"test_table".into(), "kept".into())
+        .unwrap();
+
+    database.create(test_table_with_values().0).unwrap();
+
+    let snapshot = database.snapshot(vec!["test_table".into(), "new".into()]);
+
+    let context = StatementContext::default();
+
+    database.drop_table("test_table".into()).unwrap();
+
+    database.delete("kept".into(), None, &context).unwrap();
+
+    database.rename_table(&"kept".into(), "new".into()).unwrap();
+
+    database
+        .create_sequence(Sequence::new("seq".into(), None, None, None).unwrap())
+        .unwrap();
+
+    database.restore(snapshot);
+
+    assert_eq!(database.tables["test_table"].values.len(), 2);
+
+    assert!(!database.tables.contains_key("new"));
+
+    // Tables that weren't copied aren't put back
+    assert!(!database.tables.contains_key("kept"));
+
+    assert!(database.sequences.is_empty());
+}
+
+#[test]
+fn alter_checked_table() {
+    let mut database = Database::new("db".into());
+
+    database.create(checked_test_table()).unwrap();
+
+    let name: TableName = "checked".into();
+
+    database
+        .rename_column(&name, &"id".into(), "code".into())
+        .unwrap();
+
+    let table = &database.tables["checked"];
+
+    assert_eq!(
+        table.schema.checks,
+        vec![
+            sql_parse::parse_expression("code > 0").unwrap(),
+            sql_parse::parse_expression("amount < code * 10").unwrap(),
+        ]
+    );
+
+    database.drop_column(&name, &"code".into()).unwrap();
+
+    let table = &database.tables["checked"];
+
+    assert_eq!(
+        table.schema.column_names,
+        vec!["amount".into(), "created".into()]
+    );
+
+    assert!(table.schema.checks.is_empty());
+
+    assert_eq!(
+        table.schema.defaults[0],
+        Some(sql_parse::parse_expression("2 * 1.5").unwrap())
+    );
+
+    database
+        .add_column(
+            &name,
+            ColumnDefinition("id".into(), ColumnType::Int, vec![ColumnConstraint::Serial]),
+        )
+        .unwrap();
+
+    database.rename_table(&name, "renamed".into()).unwrap();
+
+    assert!(!database.tables.contains_key("checked"));
+
+    assert_eq!(database.tables["renamed"].schema.name, "renamed".into());
+
+    assert_eq!(
+        database.sequences["checked_id_seq"].owner,
+        Some("renamed".into())
+    );
+}
+
+#[test]
+fn secondary_index() {
+    let mut table = test_table();
+
+    let rows = (0..20)
+        .map(|value: i64| vec![(value % 7).into(), (value % 2 == 0).into()])
+        .collect();
+
+    table.insert_multiple(&None, rows).unwrap();
+
+    table
+        .insert(&None, vec![ColumnValue::Null, true.into()])
+        .unwrap();
+
+    let mut unindexed = table.clone();
+
+    table
+        .create_index(IndexDefinition {
+            name: "by_first".into(),
+            columns: vec!["first".into(), "second".into()],
+            unique: false,
+        })
+        .unwrap();
+
+    let comparison = |operator, value: i64| Where::Comparison {
+        left: "first".into(),
+        operator,
+        right: value.into(),
+    };
+
+    let conditions = [
+        comparison(InfixOperator::Equals, 3),
+        comparison(InfixOperator::Equals, 10),
+        comparison(InfixOperator::NotEqual, 3),
+        comparison(InfixOperator::LessThan, 2),
+        comparison(InfixOperator::LessThanEqual, 2),
+        comparison(InfixOperator::GreaterThan, 5),
+        comparison(InfixOperator::GreaterThanEqual, 5),
+        Where::And(
+            Box::new(comparison(InfixOperator::GreaterThanEqual, 2)),
+            Box::new(Where::Comparison {
+                left: 4.into(),
+                operator: InfixOperator::GreaterThan,
+                right: "first".into(),
+            }),
+        ),
+        Where::IsNull {
+            expression: "first".into(),
+            negated: false,
+        },
+    ];
+
+    let assert_same_results = |table: &Table, unindexed: &Table| {
+        for condition in &conditions {
+            assert_eq!(
+                table
+                    .select(ColumnSelector::AllColumns, Some(condition.clone()))
+                    .unwrap(),
+                unindexed
+                    .select(ColumnSelector::AllColumns, Some(condition.clone()))
+                    .unwrap(),
+                "{condition:?}"
+            );
+        }
+    };
+
+    assert_same_results(&table, &unindexed);
+
+    let condition = table
+        .prepare_where_clause(comparison(InfixOperator::Equals, 3))
+        .unwrap();
+
+    assert_eq!(table.candidate_rows(&Some(condition)), vec![3, 10, 17]);
+
+    // The index is kept up to date, as if it was built again after every change
+    for table in [&mut table, &mut unindexed] {
+        table
+            .delete(
+                Some(comparison(InfixOperator::LessThan, 2)),
+                &StatementContext::default(),
+            )
+            .unwrap();
+
+        table
+            .update(
+                vec!["first".into()],
+                vec![10.into()],
+                Some(comparison(InfixOperator::Equals, 4)),
+                &StatementContext::default(),
+            )
+            .unwrap();
+
+        table
+            .update(
+                vec!["first".into()],
+                vec![ValueExpression::Binary {
+                    left: Box::new(ColumnValue::from(8).into()),
+                    operator: ArithmeticOperator::Subtract,
+                    right: Box::new("first".into()),
+                }],
+                Some(comparison(InfixOperator::GreaterThan, 5)),
+                &StatementContext::default(),
+            )
+            .unwrap();
+
+        table.insert(&None, vec![3.into(), false.into()]).unwrap();
+    }
+
+    assert_eq!(
+        table.secondary_indexes,
+        SecondaryIndex::build_all(&table.schema, &table.values).unwrap()
+    );
+
+    assert_same_results(&table, &unindexed);
+
+    assert!(matches!(
+        table.create_index(IndexDefinition {
+            name: "unique_first".into(),
+            columns: vec!["first".into()],
+            unique: true,
+        }),
+        Err(SqlError::DuplicateKey(_, _))
+    ));
+
+    assert!(matches!(
+        table.create_index(IndexDefinition {
+            name: "by_third".into(),
+            columns: vec!["third".into()],
+            unique: false,
+        }),
+        Err(SqlError::NameDoesNotExist(_, _))
+    ));
+
+    assert!(table.drop_index(&"by_first".into()));
+
+    assert!(!table.drop_index(&"by_first".into()));
+
+    assert_same_results(&table, &unindexed);
+}
+
+#[test]
+fn choose_index_most_selective() {
+    let mut table = test_table();
+
+    let rows = (0..20)
+        .map(|value: i64| vec![(value % 7).into(), (value % 2 == 0).into()])
+        .collect();
+
+    table.insert_multiple(&None, rows).unwrap();
+
+    for (name, column) in [("by_first", "first"), ("by_second", "second")] {
+        table
+            .create_index(IndexDefinition {
+                name: name.into(),
+                columns: vec![column.into()],
+                unique: false,
+            })
+            .unwrap();
+    }
+
+    let condition = |operator, value: i64| {
+        let condition = Where::And(
+            Box::new(Where::Comparison {
+                left: "second".into(),
+                operator: InfixOperator::Equals,
+                right: true.into(),
+            }),
+            Box::new(Where::Comparison {
+                left: "first".into(),
+                operator,
+                right: value.into(),
+            }),
+        );
+
+        table.prepare_where_clause(condition).unwrap()
+    };
+
+    // 3 rows have a first of 3, but 10 have a second that is true
+    let lookup = table
+        .choose_index(&condition(InfixOperator::Equals, 3))
+        .unwrap();
+
+    assert_eq!(lookup.index, 0);
+
+    assert_eq!(table.look_up(&lookup), vec![3, 10, 17]);
+
+    // 17 rows have a first above 0
+    let lookup = table
+        .choose_index(&condition(InfixOperator::GreaterThan, 0))
+        .unwrap();
+
+    assert_eq!(lookup.index, 1);
+
+    assert_eq!(table.look_up(&lookup).len(), 10);
+}
+
+#[test]
+fn unique_index() {
+    let mut database = Database::new("db".into());
+
+    database.create(test_table()).unwrap();
+
+    let name: TableName = "test_table".into();
+
+    let definition = IndexDefinition {
+        name: "unique_first".into(),
+        columns: vec!["first".into()],
+        unique: true,
+    };
+
+    database.create_index(&name, definition.clone()).unwrap();
+
+    assert!(matches!(
+        database.create_index(&name, definition),
+        Err(SqlError::DuplicateIndex(_))
+    ));
+
+    database
+        .insert(
+            name.clone(),
+            None,
+            vec![
+                vec![1.into(), true.into()],
+                vec![ColumnValue::Null, true.into()],
+                vec![ColumnValue::Null, false.into()],
+            ],
+            &StatementContext::default(),
+        )
+        .unwrap();
+
+    assert!(matches!(
+        database.insert(
+            name.clone(),
+            None,
+            vec![vec![1.into(), false.into()]],
+            &StatementContext::default()
+        ),
+        Err(SqlError::DuplicateKey(_, _))
+    ));
+
+    database
+        .rename_column(&name, &"first".into(), "id".into())
+        .unwrap();
+
+    assert_eq!(
+        database.tables["test_table"].schema.indexes[0].columns,
+        vec!["id".into()]
+    );
+
+    database.drop_index(&"unique_first".into()).unwrap();
+
+    assert!(matches!(
+        database.drop_index(&"unique_first".into()),
+        Err(SqlError::IndexDoesNotExist(_))
+    ));
+
+    database
+        .insert(
+            name,
+            None,
+            vec![vec![1.into(), false.into()]],
+            &StatementContext::default(),
+        )
+        .unwrap();
+}
@@ -1,13 +1,18 @@
 #[cfg(test)]
 mod tests;
 
-use sql_parse::parser::{CreateType, Expression, Statement};
-
-use super::database::{Database, RowSet, Table};
-use super::types::{ColumnName, ColumnSelector, ColumnValue, DatabaseName, TableName, Where};
+use sql_parse::parser::{AlterTableAction, CreateType, Expression, Statement};
+
+use super::database::{
+    serial_sequences, Database, RowSet, RowStream, Sequence, StatementContext, Table,
+};
+use super::types::{
+    ColumnName, ColumnSelector, ColumnValue, DatabaseName, IndexDefinition, IndexName, SelectQuery,
+    SequenceName, TableName, TableReference, ValueExpression, Where,
+};
 use super::SqlError;
 use crate::server::Runtime;
-use crate::types::{ColumnDefinition, ForeignKeyConstraint};
+use crate::types::{ColumnDefinition, ForeignKeyConstraint, UniqueConstraint};
 use crate::Result;
 
 impl Database {
@@ -16,6 +21,10 @@ impl Database {
             return Err(SqlError::DuplicateTable(table.schema.name.0.clone()));
         }
 
+        self.validate_foreign_keys(&table)?;
+
+        self.validate_sequences(&table)?;
+
         self.tables.insert(table.schema.name.0.clone(), table);
 
         return Ok(());
@@ -26,54 +35,96 @@ impl Database {
         table_name: TableName,
         columns: Option<Vec<ColumnName>>,
         values: Vec<Vec<ColumnValue>>,
+        context: &StatementContext,
     ) -> Result<()> {
-        let table = self
+        let checks = self
             .tables
-            .get_mut(&table_name.0)
-            .ok_or(SqlError::TableDoesNotExist(table_name))?;
+            .get(&table_name.0)
+            .ok_or_else(|| SqlError::TableDoesNotExist(table_name.clone()))?
+            .prepare_checks()?;
 
-        return table.insert_multiple(&columns, values);
+        for row in values {
+            let table = self
+                .tables
+                .get(&table_name.0)
+                .ok_or_else(|| SqlError::TableDoesNotExist(table_name.clone()))?;
+
+            let row = table.prepare_row(&columns, row, &checks, context)?;
+
+            self.check_references(table, std::slice::from_ref(&row))?;
+
+            // Looked up again, because checking the references borrows the other tables
+            self.tables
+                .get_mut(&table_name.0)
+                .ok_or_else(|| SqlError::TableDoesNotExist(table_name.clone()))?
+                .push_row(row);
+        }
+
+        return Ok(());
     }
 
     pub fn select(
         &self,
-        table_name: TableName,
+        from: TableReference,
         columns: ColumnSelector,
         condition: Option<Where>,
+        context: &StatementContext,
     ) -> Result<RowSet> {
-        let table = self
-            .tables
-            .get(&table_name.0)
-            .ok_or(SqlError::TableDoesNotExist(table_name))?;
+        return self.query(from, SelectQuery::new(columns, condition), context);
+    }
 
-        return table.select(columns, condition);
+    pub fn query(
+        &self,
+        from: TableReference,
+        query: SelectQuery,
+        context: &StatementContext,
+    ) -> Result<RowSet> {
+        return self.stream_query(from, query, context)?.into_row_set();
+    }
+
+    /// Like [`Database::query`], but the rows are found as they are taken from the stream.
+    pub fn stream_query<'a>(
+        &'a self,
+        from: TableReference,
+        query: SelectQuery,
+        context: &'a StatementContext,
+    ) -> Result<RowStream<'a>> {
+        return self.plan_query(from, query)?.stream(context);
     }
 
     pub fn update(
         &mut self,
         table_name: TableName,
         column_names: Vec<ColumnName>,
-        new_values: Vec<ColumnValue>,
+        new_values: Vec<ValueExpression>,
         condition: Option<Where>,
+        context: &StatementContext,
     ) -> Result<()> {
-        let table = self
-            .tables
-            .get_mut(&table_name.0)
-            .ok_or(SqlError::TableDoesNotExist(table_name))?;
-
-        return table.update(column_names, new_values, condition);
+        return self.change_rows(&table_name, context, |table| {
+            table.update(column_names, new_values, condition, context)
+        });
     }
 
-    pub fn delete(&mut self, table_name: TableName, condition: Option<Where>) -> Result<()> {
-        let table = self
-            .tables
-            .get_mut(&table_name.0)
-            .ok_or(SqlError::TableDoesNotExist(table_name))?;
-
-        return table.delete(condition);
+    pub fn delete(
+        &mut self,
+        table_name: TableName,
+        condition: Option<Where>,
+        context: &StatementContext,
+    ) -> Result<()> {
+        return self.change_rows(&table_name, context, |table| {
+            table.delete(condition, context)
+        });
     }
 
     pub fn drop_table(&mut self, table_name: TableName) -> Result<Table> {
+        self.check_not_referenced(&table_name)?;
+
+        if !self.tables.contains_key(&table_name.0) {
+            return Err(SqlError::TableDoesNotExist(table_name));
+        }
+
+        self.drop_owned_sequences(&table_name)?;
+
         return self
             .tables
             .remove(&table_name.0)
@@ -85,7 +136,7 @@ impl Database {
 #[cfg_attr(test, derive(PartialEq))]
 pub enum ExecutionResult {
     None,
-    Table(Table),
+    Table(Box<Table>),
     Select(RowSet),
     CreateDatabase(DatabaseName),
     DropDatabase(DatabaseName),
@@ -107,6 +158,62 @@ pub trait Execute {
         &self,
         runtime: &mut Runtime,
     ) -> impl futures::Future<Output = Result<ExecutionResult>> + Send;
+
+    /// Like [`Execute::execute`], but the rows of a `SELECT` are sent to `sink` in chunks,
+    /// instead of all at once as an [`ExecutionResult::Select`].
+    ///
+    /// They are only sent once the database is unlocked again, so other connections don't wait for the client.
+    fn execute_into(
+        &self,
+        runtime: &mut Runtime,
+        sink: &mut impl RowSink,
+    ) -> impl futures::Future<Output = Result<ExecutionResult>> + Send;
+}
+
+/// Where [`Execute::execute_into`] sends the rows of a `SELECT`, a few at a time.
+pub trait RowSink: Send {
+    /// How many rows are sent at once, only the last rows can be fewer.
+    fn rows_per_chunk(&self) -> usize;
+
+    /// Takes the next rows, there's always at least one chunk so the columns are known.
+    fn send(&mut self, rows: RowSet) -> impl futures::Future<Output = Result<()>> + Send;
+}
+
+/// Collects all rows into one [`RowSet`], for [`Execute::execute`].
+#[derive(Default)]
+struct CollectRows(Option<RowSet>);
+
+/// Keeps the chunks of a `SELECT` until the database is unlocked, see [`execute_in_database`].
+struct Chunks {
+    rows_per_chunk: usize,
+    chunks: Vec<RowSet>,
+}
+
+impl RowSink for Chunks {
+    fn rows_per_chunk(&self) -> usize {
+        return self.rows_per_chunk;
+    }
+
+    async fn send(&mut self, rows: RowSet) -> Result<()> {
+        self.chunks.push(rows);
+
+        return Ok(());
+    }
+}
+
+impl RowSink for CollectRows {
+    fn rows_per_chunk(&self) -> usize {
+        return usize::MAX;
+    }
+
+    async fn send(&mut self, rows: RowSet) -> Result<()> {
+        match &mut self.0 {
+            Some(collected) => collected.values.extend(rows.values),
+            None => self.0 = Some(rows),
+        }
+
+        return Ok(());
+    }
 }
 
 // Helper to destructure Array expressions
@@ -126,50 +233,261 @@ fn map_option_where_clause(input: &Option<Expression>) -> Result<Option<Where>>
     };
 }
 
+fn map_option_count(input: &Option<Expression>) -> Result<Option<usize>> {
+    return match input {
+        Some(Expression::Int(count)) => match usize::try_from(*count) {
+            Ok(count) => Ok(Some(count)),
+            Err(_) => Err(SqlError::ImpossibleConversion(
+                Expression::Int(*count),
+                "usize",
+            )),
+        },
+        Some(other) => Err(SqlError::ImpossibleConversion(other.clone(), "usize")),
+        None => Ok(None),
+    };
+}
+
 impl Execute for Statement {
     async fn execute(&self, runtime: &mut Runtime) -> Result<ExecutionResult> {
-        let result = execute_statement(self, runtime).await;
-
-        // I guess an error could mean invalid state, so don't persist an invalid state
-        // I could go through all the ways this could fail and check if it actually leaves invalid states,
-        // but ykno
-        // I guess not saving still leaves it in the old invalid state in memory, should refresh that or something
-        // TODO
-        if result.is_ok() {
-            runtime.save().await?;
+        let mut rows = CollectRows::default();
+
+        let result = self.execute_into(runtime, &mut rows).await?;
+
+        return Ok(match rows.0 {
+            Some(rows) => ExecutionResult::Select(rows),
+            None => result,
+        });
+    }
+
+    async fn execute_into(
+        &self,
+        runtime: &mut Runtime,
+        sink: &mut impl RowSink,
+    ) -> Result<ExecutionResult> {
+        let result = match self {
+            Statement::Begin => runtime.begin().await,
+            Statement::Commit => runtime.commit().await,
+            Statement::Rollback => runtime.rollback(),
+            _ => return execute_in_database(self, runtime, sink).await,
+        };
+
+        return result.map(|_| ExecutionResult::None);
+    }
+}
+
+/// Executes a statement that isn't about transactions, and saves its changes unless a transaction is going on.
+///
+/// A statement that fails is undone entirely, even the changes it made before it failed,
+/// but the statements before it in a transaction are kept.
+async fn execute_in_database(
+    statement: &Statement,
+    runtime: &mut Runtime,
+    sink: &mut impl RowSink,
+) -> Result<ExecutionResult> {
+    // Kept locked until the changes are saved, so other connections can't change it in between
+    let mut database = runtime.lock_database().await;
+
+    // Only the tables the statement can change are copied, to undo it if it fails
+    let snapshot = database
+        .as_deref()
+        .map(|database| database.snapshot(changed_tables(statement, database)));
+
+    let context = match database.as_deref() {
+        Some(database) => StatementContext::new(database),
+        None => StatementContext::default(),
+    };
+
+    // Sent once the database is unlocked, so a slow client doesn't keep other connections waiting
+    let mut chunks = Chunks {
+        rows_per_chunk: sink.rows_per_chunk(),
+        chunks: vec![],
+    };
+
+    let mut result = execute_statement(
+        statement,
+        runtime,
+        database.as_deref_mut(),
+        &context,
+        &mut chunks,
+    )
+    .await;
+
+    if let (Ok(_), Some(database)) = (&result, &mut database) {
+        let gave_values = database.keep_sequence_values(&context);
+
+        // Saving tells the transactions of other connections the database changed, which reading doesn't
+        let reads_only = matches!(statement, Statement::Select { .. } | Statement::Explain(_));
+
+        if !reads_only || gave_values {
+            if let Err(error) = runtime.save(database).await {
+                result = Err(error);
+            }
         }
+    }
+
+    if let (Err(_), Some(database), Some(snapshot)) = (&result, &mut database, snapshot) {
+        database.restore(snapshot);
 
-        return result;
+        database.keep_sequence_values(&context);
     }
+
+    runtime.unlock_database(database);
+
+    let result = result?;
+
+    for chunk in chunks.chunks {
+        sink.send(chunk).await?;
+    }
+
+    return Ok(result);
+}
+
+/// The tables `statement` can change, which are the only ones that have to be kept to undo it.
+fn changed_tables(statement: &Statement, database: &Database) -> Vec<TableName> {
+    // A name that can't be converted fails the statement before it changes anything
+    let table = |name: &Expression| TableName::try_from(name).ok();
+
+    let (names, with_references) = match statement {
+        Statement::Insert { into, .. } => (vec![table(into)], false),
+        // The actions of the foreign keys referencing the table change other tables
+        Statement::Update { from, .. } | Statement::Delete { from, .. } => {
+            (vec![table(from)], true)
+        }
+        Statement::Create {
+            what: CreateType::Table,
+            name,
+            ..
+        }
+        | Statement::Drop {
+            what: CreateType::Table,
+            name,
+        } => (vec![table(name)], false),
+        Statement::CreateIndex { table: name, .. } => (vec![table(name)], false),
+        Statement::Drop {
+            what: CreateType::Index,
+            name,
+        } => {
+            let index = IndexName::try_from(name).ok();
+
+            (
+                vec![index.and_then(|index| database.index_table(&index))],
+                false,
+            )
+        }
+        // Renames change the foreign keys referencing the table
+        Statement::AlterTable {
+            name,
+            action: AlterTableAction::RenameTable(new_name),
+        } => (vec![table(name), table(new_name)], true),
+        Statement::AlterTable { name, .. } => (vec![table(name)], true),
+        _ => (vec![], false),
+    };
+
+    let names = names.into_iter().flatten();
+
+    if !with_references {
+        return names.collect();
+    }
+
+    return names
+        .flat_map(|name| database.referencing_tables(&name))
+        .collect();
+}
+
+/// The table and query of a `SELECT` statement
+fn select_query(statement: &Statement) -> Result<(TableReference, SelectQuery)> {
+    let Statement::Select {
+        table,
+        columns,
+        where_clause,
+        group_by,
+        having,
+        order_by,
+        limit,
+        offset,
+    } = statement
+    else {
+        return Err(SqlError::InvalidParameter);
+    };
+
+    let table: TableReference = table.try_into()?;
+
+    let columns: ColumnSelector = columns.try_into()?;
+
+    let where_clause = map_option_where_clause(where_clause)?;
+
+    let group_by = match group_by {
+        Some(expressions) => try_destructure_array(expressions)?
+            .iter()
+            .map(|expression| expression.try_into())
+            .collect::<Result<Vec<_>>>()?,
+        None => vec![],
+    };
+
+    let having = map_option_where_clause(having)?;
+
+    let order_by = match order_by {
+        Some(keys) => try_destructure_array(keys)?
+            .iter()
+            .map(|key| key.try_into())
+            .collect::<Result<Vec<_>>>()?,
+        None => vec![],
+    };
+
+    let query = SelectQuery {
+        columns,
+        condition: where_clause,
+        group_by,
+        having,
+        order_by,
+        limit: map_option_count(limit)?,
+        offset: map_option_count(offset)?,
+    };
+
+    return Ok((table, query));
 }
 
 async fn execute_statement(
     statement: &Statement,
     runtime: &mut Runtime,
+    database: Option<&mut Database>,
+    context: &StatementContext,
+    sink: &mut impl RowSink,
 ) -> Result<ExecutionResult> {
-    let database = runtime.get_database();
-
     match statement {
-        Statement::Select {
-            table,
-            columns,
-            where_clause,
-        } => {
+        Statement::Select { .. } => {
             if database.is_none() {
                 return Err(SqlError::NoDatabaseSelected);
             }
 
             let database = database.unwrap();
 
-            let table: TableName = table.try_into()?;
+            let (table, query) = select_query(statement)?;
 
-            let columns: ColumnSelector = columns.try_into()?;
+            let mut rows = database.stream_query(table, query, context)?;
 
-            let where_clause = map_option_where_clause(where_clause)?;
+            let rows_per_chunk = sink.rows_per_chunk();
 
-            return database
-                .select(table, columns, where_clause)
-                .map(ExecutionResult::Select);
+            loop {
+                let values = rows
+                    .by_ref()
+                    .take(rows_per_chunk)
+                    .collect::<Result<Vec<_>>>()?;
+
+                let is_last = values.len() < rows_per_chunk;
+
+                let chunk = RowSet {
+                    types: rows.types.clone(),
+                    names: rows.names.clone(),
+                    values,
+                };
+
+                sink.send(chunk).await?;
+
+                if is_last {
+                    return Ok(ExecutionResult::None);
+                }
+            }
         }
 
         Statement::Create {
@@ -183,7 +501,7 @@ async fn execute_statement(
 
                     let name = database.name.clone();
 
-                    runtime.create_database(database);
+                    runtime.create_database(database).await?;
 
                     return Ok(ExecutionResult::CreateDatabase(name));
                 }
@@ -199,8 +517,16 @@ async fn execute_statement(
 
                     let mut column_definitions = vec![];
                     let mut constraints = vec![];
+                    let mut keys = vec![];
+                    let mut checks = vec![];
 
                     for column in columns {
+                        if let Expression::CheckConstraint(condition) = column {
+                            checks.push(condition.as_ref().clone());
+
+                            continue;
+                        }
+
                         let column_definition: Result<ColumnDefinition> = column.try_into();
                         if let Ok(column_definition) = column_definition {
                             column_definitions.push(column_definition);
@@ -215,16 +541,90 @@ async fn execute_statement(
                             continue;
                         }
 
-                        return Err(SqlError::ImpossibleConversion(column.clone(), "A column definiton or foreign key constraint"))
+                        let key: Result<UniqueConstraint> = column.try_into();
+                        if let Ok(key) = key {
+                            keys.push(key);
+
+                            continue;
+                        }
+
+                        return Err(SqlError::ImpossibleConversion(
+                            column.clone(),
+                            "A column definiton, key, foreign key or check constraint",
+                        ));
                     }
 
+                    let name: TableName = name.try_into()?;
+
+                    let (column_definitions, sequences) =
+                        serial_sequences(&name, column_definitions)?;
+
+                    let table = Table::new(name, column_definitions, constraints, keys, checks)?;
+
                     return database
-                        .create(Table::new(name.try_into()?, column_definitions, constraints)?)
+                        .create_with_sequences(table, sequences)
                         .map(|_| ExecutionResult::None);
                 }
+                // `CREATE SEQUENCE` and `CREATE INDEX` are statements of their own, since they don't have columns
+                CreateType::Sequence | CreateType::Index => return Err(SqlError::InvalidParameter),
             };
         }
 
+        Statement::CreateSequence {
+            name,
+            start,
+            increment,
+        } => {
+            if database.is_none() {
+                return Err(SqlError::NoDatabaseSelected);
+            }
+
+            let database = database.unwrap();
+
+            let to_int = |option: &Option<Expression>| match option {
+                Some(expression) => match ColumnValue::try_from(expression)? {
+                    ColumnValue::Int(value) => Ok(Some(value)),
+                    _ => Err(SqlError::ImpossibleConversion(expression.clone(), "i64")),
+                },
+                None => Ok(None),
+            };
+
+            let sequence =
+                Sequence::new(name.try_into()?, to_int(start)?, to_int(increment)?, None)?;
+
+            return database
+                .create_sequence(sequence)
+                .map(|_| ExecutionResult::None);
+        }
+
+        Statement::CreateIndex {
+            name,
+            table,
+            columns,
+            unique,
+        } => {
+            if database.is_none() {
+                return Err(SqlError::NoDatabaseSelected);
+            }
+
+            let database = database.unwrap();
+
+            let columns = try_destructure_array(columns)?
+                .iter()
+                .map(|column| column.try_into())
+                .collect::<Result<Vec<_>>>()?;
+
+            let definition = IndexDefinition {
+                name: name.try_into()?,
+                columns,
+                unique: *unique,
+            };
+
+            return database
+                .create_index(&table.try_into()?, definition)
+                .map(|_| ExecutionResult::None);
+        }
+
         Statement::Insert {
             into,
             columns,
@@ -247,7 +647,7 @@ async fn execute_statement(
 
                 let row_values = row
                     .iter()
-                    .map(|value| value.try_into())
+                    .map(|value| context.evaluate(value))
                     .collect::<Result<Vec<_>>>()?;
 
                 result.push(row_values);
@@ -268,7 +668,7 @@ async fn execute_statement(
             };
 
             return database
-                .insert(into, columns, result)
+                .insert(into, columns, result, context)
                 .map(|_| ExecutionResult::None);
         }
 
@@ -303,7 +703,7 @@ async fn execute_statement(
             let where_clause = map_option_where_clause(where_clause)?;
 
             return database
-                .update(from, column_names, values, where_clause)
+                .update(from, column_names, values, where_clause, context)
                 .map(|_| ExecutionResult::None);
         }
 
@@ -319,15 +719,78 @@ async fn execute_statement(
             let where_clause = map_option_where_clause(where_clause)?;
 
             return database
-                .delete(from, where_clause)
+                .delete(from, where_clause, context)
                 .map(|_| ExecutionResult::None);
         }
 
+        Statement::Explain(statement) => {
+            if database.is_none() {
+                return Err(SqlError::NoDatabaseSelected);
+            }
+
+            let database = database.unwrap();
+
+            return match statement.as_ref() {
+                Statement::Select { .. } => {
+                    let (table, query) = select_query(statement)?;
+
+                    database.plan_query(table, query)?.explain()
+                }
+                Statement::Update {
+                    from, where_clause, ..
+                } => database.explain_change(
+                    "Update",
+                    from.try_into()?,
+                    map_option_where_clause(where_clause)?,
+                ),
+                Statement::Delete { from, where_clause } => database.explain_change(
+                    "Delete",
+                    from.try_into()?,
+                    map_option_where_clause(where_clause)?,
+                ),
+                _ => Err(SqlError::InvalidParameter),
+            }
+            .map(ExecutionResult::Select);
+        }
+
+        Statement::AlterTable { name, action } => {
+            if database.is_none() {
+                return Err(SqlError::NoDatabaseSelected);
+            }
+
+            let database = database.unwrap();
+
+            let name: TableName = name.try_into()?;
+
+            match action {
+                AlterTableAction::AddColumn(column) => {
+                    database.add_column(&name, column.try_into()?)?
+                }
+                AlterTableAction::DropColumn(column) => {
+                    database.drop_column(&name, &column.try_into()?)?
+                }
+                AlterTableAction::RenameColumn { from, to } => {
+                    database.rename_column(&name, &from.try_into()?, to.try_into()?)?
+                }
+                AlterTableAction::RenameTable(new_name) => {
+                    let new_name: TableName = new_name.try_into()?;
+
+                    database.rename_table(&name, new_name.clone())?;
+
+                    runtime.rename_table(database, &name, &new_name).await?;
+                }
+            };
+
+            return Ok(ExecutionResult::None);
+        }
+
         Statement::Drop { what, name } => match what {
             CreateType::Database => {
                 let name = DatabaseName::try_from(name)?;
 
-                runtime.drop().await?;
+                let database = database.ok_or(SqlError::NoDatabaseSelected)?;
+
+                runtime.drop(database).await?;
 
                 return Ok(ExecutionResult::DropDatabase(name));
             }
@@ -340,8 +803,37 @@ async fn execute_statement(
 
                 let name: TableName = name.try_into()?;
 
-                return database.drop_table(name).map(ExecutionResult::Table);
+                return database
+                    .drop_table(name)
+                    .map(|table| ExecutionResult::Table(Box::new(table)));
+            }
+            CreateType::Sequence => {
+                if database.is_none() {
+                    return Err(SqlError::NoDatabaseSelected);
+                }
+
+                let database = database.unwrap();
+
+                let name: SequenceName = name.try_into()?;
+
+                return database.drop_sequence(name).map(|_| ExecutionResult::None);
+            }
+            CreateType::Index => {
+                if database.is_none() {
+                    return Err(SqlError::NoDatabaseSelected);
+                }
+
+                let database = database.unwrap();
+
+                let name: IndexName = name.try_into()?;
+
+                return database.drop_index(&name).map(|_| ExecutionResult::None);
             }
         },
+
+        // These are about the connection rather than the database, so are executed before it is locked
+        Statement::Begin | Statement::Commit | Statement::Rollback => {
+            return Err(SqlError::InvalidParameter);
+        }
     }
 }
@@ -1,5 +1,5 @@
 use super::super::database::{Database, Row};
-use super::super::types::ColumnDefinition;
+use super::super::types::{Blob, ColumnDefinition};
 use super::*;
 use crate::evaluate::{Execute, ExecutionResult};
 use crate::utils::tests::*;
@@ -12,10 +12,12 @@ fn create_and_drop_tables_basic() {
     let table = Table::new(
         "test_table".into(),
         vec![
-            ColumnDefinition("a".into(), ColumnType::Int),
-            ColumnDefinition("b".into(), ColumnType::Decimal),
+            ColumnDefinition("a".into(), ColumnType::Int, vec![]),
+            ColumnDefinition("b".into(), ColumnType::Decimal(None), vec![]),
         ],
         vec![],
+        vec![],
+        vec![],
     )
     .unwrap();
 
@@ -36,7 +38,7 @@ fn create_and_drop_tables_basic() {
 fn create_table_duplicate_name() {
     let mut db = Database::new("test_db".into());
 
-    let table = Table::new("test_table1".into(), vec![], vec![]).unwrap();
+    let table = Table::new("test_table1".into(), vec![], vec![], vec![], vec![]).unwrap();
 
     db.create(table.clone()).unwrap();
 
@@ -77,8 +79,8 @@ async fn create_table_statement() {
         what: CreateType::Table,
         name: Expression::Ident("other_test_table".into()),
         columns: Some(Expression::Array(vec![
-            Expression::ColumnDefinition("first".into(), ColumnType::Int),
-            Expression::ColumnDefinition("second".into(), ColumnType::Bool),
+            Expression::ColumnDefinition("first".into(), ColumnType::Int, vec![]),
+            Expression::ColumnDefinition("second".into(), ColumnType::Bool, vec![]),
         ])),
     };
 
@@ -96,16 +98,17 @@ async fn test_create_table_statement_no_db(statement: &Statement) {
     assert!(matches!(result, Err(SqlError::NoDatabaseSelected),));
 }
 
-#[test]
-fn insert_into_table_basic() {
+#[tokio::test]
+async fn insert_into_table_basic() {
     let mut runtime = test_runtime_with_values();
 
-    let db = runtime.get_database().unwrap();
+    let mut db = runtime.lock_database().await.unwrap();
 
     db.insert(
         "test_table".into(),
         None,
         vec![vec![ColumnValue::Int(69), ColumnValue::Bool(false)]],
+        &StatementContext::default(),
     )
     .unwrap();
 
@@ -146,25 +149,31 @@ async fn test_insert_statement_no_db(statement: &Statement) {
     assert!(matches!(result, Err(SqlError::NoDatabaseSelected)));
 }
 
-#[test]
-fn select_from_table_basic() {
+#[tokio::test]
+async fn select_from_table_basic() {
     let mut runtime = test_runtime_with_values();
 
-    let db = runtime.get_database().unwrap();
+    let db = runtime.lock_database().await.unwrap();
 
     let (_, (row1, row2)) = test_table_with_values();
 
     assert_eq!(
-        db.select("test_table".into(), ColumnSelector::AllColumns, None)
-            .unwrap(),
+        db.select(
+            "test_table".into(),
+            ColumnSelector::AllColumns,
+            None,
+            &StatementContext::default()
+        )
+        .unwrap(),
         test_row_set(vec![Row(row1.clone()), Row(row2.clone())]).unwrap()
     );
 
     assert_eq!(
         db.select(
             "test_table".into(),
-            ColumnSelector::Name(vec![ColumnName("first".into())]),
-            None
+            ColumnSelector::Expressions(vec![ValueExpression::Column("first".into())]),
+            None,
+            &StatementContext::default(),
         )
         .unwrap(),
         test_row_set(vec![
@@ -178,11 +187,12 @@ fn select_from_table_basic() {
         db.select(
             "test_table".into(),
             ColumnSelector::AllColumns,
-            Some(Where {
+            Some(Where::Comparison {
                 left: "second".into(),
                 operator: InfixOperator::Equals,
                 right: true.into(),
-            })
+            }),
+            &StatementContext::default(),
         )
         .unwrap(),
         test_row_set(vec![Row(vec![
@@ -195,12 +205,13 @@ fn select_from_table_basic() {
     assert_eq!(
         db.select(
             "test_table".into(),
-            ColumnSelector::Name(vec![ColumnName("first".into())]),
-            Some(Where {
+            ColumnSelector::Expressions(vec![ValueExpression::Column("first".into())]),
+            Some(Where::Comparison {
                 left: "second".into(),
                 operator: InfixOperator::Equals,
                 right: true.into(),
-            })
+            }),
+            &StatementContext::default(),
         )
         .unwrap(),
         test_row_set(vec![Row(vec![ColumnValue::Int(5)]),]).unwrap()
@@ -214,11 +225,16 @@ async fn select_statement() {
     let statement = Statement::Select {
         table: Expression::Ident("test_table".into()),
         columns: Expression::AllColumns,
-        where_clause: Some(Expression::Where {
+        where_clause: Some(Expression::Comparison {
             left: Box::new(Expression::Ident("second".into())),
             operator: InfixOperator::Equals,
             right: Box::new(Expression::Bool(true)),
         }),
+        group_by: None,
+        having: None,
+        order_by: None,
+        limit: None,
+        offset: None,
     };
 
     let result = statement.execute(&mut runtime).await.unwrap();
@@ -240,14 +256,20 @@ fn delete_from_table_basic() {
     let (table, (row1, row2)) = test_table_with_values();
     db.create(table).unwrap();
 
-    db.delete("test_table".into(), None).unwrap();
+    db.delete("test_table".into(), None, &StatementContext::default())
+        .unwrap();
 
     assert_eq!(db.tables.len(), 1);
 
     assert_eq!(db.tables.get("test_table").unwrap().values, vec![]);
 
-    db.insert("test_table".into(), None, vec![row1.clone(), row2.clone()])
-        .unwrap();
+    db.insert(
+        "test_table".into(),
+        None,
+        vec![row1.clone(), row2.clone()],
+        &StatementContext::default(),
+    )
+    .unwrap();
 
     assert_eq!(
         db.tables.get("test_table").unwrap().values,
@@ -256,11 +278,12 @@ fn delete_from_table_basic() {
 
     db.delete(
         "test_table".into(),
-        Some(Where {
+        Some(Where::Comparison {
             left: "second".into(),
             operator: InfixOperator::Equals,
             right: false.into(),
         }),
+        &StatementContext::default(),
     )
     .unwrap();
 
@@ -273,7 +296,7 @@ async fn delete_statement() {
 
     let statement = Statement::Delete {
         from: Expression::Ident("test_table".into()),
-        where_clause: Some(Expression::Where {
+        where_clause: Some(Expression::Comparison {
             left: Box::new(Expression::Ident("first".into())),
             operator: InfixOperator::Equals,
             right: Box::new(Expression::Int(5)),
@@ -282,7 +305,7 @@ async fn delete_statement() {
 
     let result = statement.execute(&mut runtime).await.unwrap();
 
-    let db = runtime.get_database().unwrap();
+    let db = runtime.lock_database().await.unwrap();
 
     assert_eq!(result, ExecutionResult::None,);
 
@@ -305,8 +328,9 @@ fn update_table_basic() {
     db.update(
         "test_table".into(),
         vec![ColumnName("first".into())],
-        vec![ColumnValue::Int(69)],
+        vec![ColumnValue::Int(69).into()],
         None,
+        &StatementContext::default(),
     )
     .unwrap();
 
@@ -321,8 +345,9 @@ fn update_table_basic() {
     db.update(
         "test_table".into(),
         vec![ColumnName("first".into()), ColumnName("second".into())],
-        vec![ColumnValue::Int(420), ColumnValue::Bool(true)],
+        vec![ColumnValue::Int(420).into(), ColumnValue::Bool(true).into()],
         None,
+        &StatementContext::default(),
     )
     .unwrap();
 
@@ -338,8 +363,14 @@ fn update_table_basic() {
     db.drop_table("test_table".into()).unwrap();
     db.create(table).unwrap();
 
-    db.update("test_table".into(), vec![], vec![], None)
-        .unwrap();
+    db.update(
+        "test_table".into(),
+        vec![],
+        vec![],
+        None,
+        &StatementContext::default(),
+    )
+    .unwrap();
 
     assert_eq!(
         db.tables.get("test_table").unwrap().values,
@@ -349,12 +380,13 @@ fn update_table_basic() {
     db.update(
         "test_table".into(),
         vec![ColumnName("first".into())],
-        vec![ColumnValue::Int(0)],
-        Some(Where {
+        vec![ColumnValue::Int(0).into()],
+        Some(Where::Comparison {
             left: "second".into(),
             operator: InfixOperator::Equals,
             right: false.into(),
         }),
+        &StatementContext::default(),
     )
     .unwrap();
 
@@ -378,7 +410,7 @@ async fn update_statement() {
             Expression::Ident("second".into()),
         ]),
         values: Expression::Array(vec![Expression::Int(69), Expression::Bool(true)]),
-        where_clause: Some(Expression::Where {
+        where_clause: Some(Expression::Comparison {
             left: Box::new(Expression::Ident("second".into())),
             operator: InfixOperator::Equals,
             right: Box::new(Expression::Bool(false)),
@@ -389,7 +421,7 @@ async fn update_statement() {
 
     assert_eq!(result, ExecutionResult::None,);
 
-    let db = runtime.get_database().unwrap();
+    let db = runtime.lock_database().await.unwrap();
 
     assert_eq!(
         db.tables.get("test_table").unwrap().values,
@@ -399,3 +431,1687 @@ async fn update_statement() {
         ]
     );
 }
+
+#[tokio::test]
+async fn update_statement_with_arithmetic() {
+    let mut runtime = test_runtime_with_values();
+
+    let statement =
+        sql_parse::parse_statement("UPDATE test_table SET first = first * 2 + 1;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    assert_eq!(result, ExecutionResult::None);
+
+    let db = runtime.lock_database().await.unwrap();
+
+    assert_eq!(
+        db.tables.get("test_table").unwrap().values,
+        vec![
+            Row(vec![ColumnValue::Int(11), ColumnValue::Bool(true)]),
+            Row(vec![ColumnValue::Int(13), ColumnValue::Bool(false)]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn select_statement_with_literal_on_the_left() {
+    let mut runtime = test_runtime_with_values();
+
+    let statement =
+        sql_parse::parse_statement("SELECT first FROM test_table WHERE 6 <= first;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    assert_eq!(
+        result,
+        ExecutionResult::Select(test_row_set(vec![Row(vec![ColumnValue::Int(6)])]).unwrap())
+    );
+}
+
+async fn execute_all(runtime: &mut Runtime, statements: &[&str]) {
+    for statement in statements {
+        let statement = sql_parse::parse_statement(statement).unwrap();
+
+        statement.execute(runtime).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn select_statement_with_join() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE other_table (id INT, name TEXT);",
+            "INSERT INTO other_table VALUES (5, 'five'), (7, 'seven');",
+        ],
+    )
+    .await;
+
+    let statement = sql_parse::parse_statement(
+        "SELECT name, test_table.first FROM test_table JOIN other_table ON first = other_table.id;",
+    )
+    .unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.values,
+        vec![Row(vec![
+            ColumnValue::Str("five".into()),
+            ColumnValue::Int(5)
+        ])]
+    );
+    assert_eq!(row_set.types, vec![ColumnType::Text, ColumnType::Int]);
+    assert_eq!(
+        row_set.names,
+        vec![
+            ColumnName("name".into()),
+            ColumnName("test_table.first".into())
+        ]
+    );
+
+    let statement =
+        sql_parse::parse_statement("SELECT * FROM test_table CROSS JOIN other_table;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(row_set.values.len(), 4);
+    assert_eq!(
+        row_set.names,
+        vec![
+            ColumnName("test_table.first".into()),
+            ColumnName("test_table.second".into()),
+            ColumnName("other_table.id".into()),
+            ColumnName("other_table.name".into()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn select_statement_with_order_by_and_limit() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &["INSERT INTO test_table VALUES (7, true), (4, false);"],
+    )
+    .await;
+
+    let statement = sql_parse::parse_statement(
+        "SELECT first FROM test_table ORDER BY second, first DESC LIMIT 2 OFFSET 1;",
+    )
+    .unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    assert_eq!(
+        result,
+        ExecutionResult::Select(
+            test_row_set(vec![
+                Row(vec![ColumnValue::Int(4)]),
+                Row(vec![ColumnValue::Int(7)]),
+            ])
+            .unwrap()
+        )
+    );
+}
+
+#[tokio::test]
+async fn select_statement_with_group_by() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &["INSERT INTO test_table VALUES (7, true), (4, false), (1, true);"],
+    )
+    .await;
+
+    let statement = sql_parse::parse_statement(
+        "SELECT second, COUNT(*), SUM(first) FROM test_table GROUP BY second HAVING MAX(first) > 6 ORDER BY COUNT(*) DESC;",
+    )
+    .unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.values,
+        vec![Row(vec![
+            ColumnValue::Bool(true),
+            ColumnValue::Int(3),
+            ColumnValue::Int(13)
+        ])]
+    );
+    assert_eq!(
+        row_set.names,
+        vec![
+            ColumnName("second".into()),
+            ColumnName("COUNT(*)".into()),
+            ColumnName("SUM(first)".into())
+        ]
+    );
+
+    let statement = sql_parse::parse_statement("SELECT AVG(first) FROM test_table;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    assert_eq!(
+        result,
+        ExecutionResult::Select(test_row_set(vec![Row(vec![decimal(4600000, 6)])]).unwrap())
+    );
+
+    let statement = sql_parse::parse_statement("SELECT first, COUNT(*) FROM test_table;").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::NotGrouped(_))));
+
+    let statement = sql_parse::parse_statement("SELECT LENGTH(first) FROM test_table;").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::UnknownFunction(_))));
+}
+
+#[tokio::test]
+async fn null_values() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE other_table (id INT NOT NULL, name TEXT);",
+            "INSERT INTO other_table (id) VALUES (5);",
+            "INSERT INTO other_table VALUES (7, NULL);",
+        ],
+    )
+    .await;
+
+    let statement =
+        sql_parse::parse_statement("INSERT INTO other_table (name) VALUES ('x');").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::NullNotAllowed(_))));
+
+    let statement =
+        sql_parse::parse_statement("SELECT id FROM other_table WHERE name IS NULL AND id > 5;")
+            .unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    assert_eq!(
+        result,
+        ExecutionResult::Select(test_row_set(vec![Row(vec![ColumnValue::Int(7)])]).unwrap())
+    );
+
+    let statement = sql_parse::parse_statement(
+        "SELECT first, id FROM test_table LEFT JOIN other_table ON first = id ORDER BY first;",
+    )
+    .unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    assert_eq!(
+        result,
+        ExecutionResult::Select(
+            test_row_set(vec![
+                Row(vec![ColumnValue::Int(5), ColumnValue::Int(5)]),
+                Row(vec![ColumnValue::Int(6), ColumnValue::Null]),
+            ])
+            .unwrap()
+        )
+    );
+}
+
+#[tokio::test]
+async fn insert_statement_with_column_names() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE other_table (id INT, name TEXT, active BOOL);",
+            "INSERT INTO other_table (name, id) VALUES ('one', 1), ('two', 2);",
+        ],
+    )
+    .await;
+
+    let statement =
+        sql_parse::parse_statement("INSERT INTO other_table (id, id) VALUES (3, 4);").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::ColumnNameNotUnique(_))));
+
+    let statement = sql_parse::parse_statement("SELECT * FROM other_table;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    assert_eq!(
+        result,
+        ExecutionResult::Select(
+            test_row_set(vec![
+                Row(vec![1.into(), "one".into(), ColumnValue::Null]),
+                Row(vec![2.into(), "two".into(), ColumnValue::Null]),
+            ])
+            .unwrap()
+        )
+    );
+}
+
+#[tokio::test]
+async fn decimal_columns() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE prices (id INT, amount DECIMAL(5, 2));",
+            "INSERT INTO prices VALUES (1, 1.05), (2, 1.5), (3, 2.005), (4, 7);",
+        ],
+    )
+    .await;
+
+    let statement = sql_parse::parse_statement("INSERT INTO prices VALUES (5, 1000.0);").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::ValueOutOfRange(_, _))));
+
+    let statement = sql_parse::parse_statement(
+        "SELECT id, amount, amount * 2 FROM prices WHERE amount = 1.05 OR amount = 2.01;",
+    )
+    .unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    // Values are stored with the scale of the column
+    assert_eq!(
+        row_set.values,
+        vec![
+            Row(vec![1.into(), decimal(105, 2), decimal(210, 2)]),
+            Row(vec![3.into(), decimal(201, 2), decimal(402, 2)]),
+        ]
+    );
+    assert_eq!(
+        row_set.types,
+        vec![
+            ColumnType::Int,
+            ColumnType::Decimal(Some((5, 2))),
+            ColumnType::Decimal(None)
+        ]
+    );
+
+    let statement = sql_parse::parse_statement("SELECT SUM(amount) FROM prices;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    assert_eq!(
+        result,
+        ExecutionResult::Select(test_row_set(vec![Row(vec![decimal(1156, 2)])]).unwrap())
+    );
+}
+
+#[tokio::test]
+async fn integer_columns() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE accounts (id SMALLINT, balance INT, total BIGINT);",
+            "INSERT INTO accounts VALUES (1, -50, 9223372036854775807), (2, 100, -9223372036854775808);",
+            "UPDATE accounts SET balance = balance - 200 WHERE id = 1;",
+        ],
+    )
+    .await;
+
+    for (statement, expected_type) in [
+        (
+            "INSERT INTO accounts VALUES (32768, 0, 0);",
+            ColumnType::SmallInt,
+        ),
+        (
+            "INSERT INTO accounts VALUES (3, -2147483649, 0);",
+            ColumnType::Int,
+        ),
+        (
+            "UPDATE accounts SET balance = balance * 10000000;",
+            ColumnType::Int,
+        ),
+    ] {
+        let statement = sql_parse::parse_statement(statement).unwrap();
+
+        let result = statement.execute(&mut runtime).await;
+
+        assert!(
+            matches!(result, Err(SqlError::ValueOutOfRange(_, column_type)) if column_type == expected_type)
+        );
+    }
+
+    let statement = sql_parse::parse_statement("SELECT total + 1 FROM accounts;").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::ArithmeticOverflow(_, _, _))));
+
+    let statement =
+        sql_parse::parse_statement("SELECT id, balance, -total FROM accounts WHERE balance < 0;")
+            .unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.values,
+        vec![Row(vec![1.into(), (-250).into(), (-i64::MAX).into()])]
+    );
+    assert_eq!(
+        row_set.types,
+        vec![ColumnType::SmallInt, ColumnType::Int, ColumnType::BigInt]
+    );
+}
+
+#[tokio::test]
+async fn temporal_columns() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE events (id INT, created_at TIMESTAMP, day DATE, starts TIME);",
+            "INSERT INTO events VALUES (1, TIMESTAMP '2026-01-31 12:00:00', DATE '2026-01-31', TIME '09:30:00');",
+            "INSERT INTO events VALUES (2, DATE '2025-12-24', DATE '2025-12-24', TIME '23:59:59.5');",
+            "INSERT INTO events VALUES (3, NOW(), DATE '2026-10-18', TIME '00:00:00');",
+        ],
+    )
+    .await;
+
+    let statement = sql_parse::parse_statement(
+        "SELECT id, created_at + INTERVAL '1 month', day - DATE '2025-12-24', EXTRACT(YEAR FROM day) FROM events WHERE created_at < TIMESTAMP '2026-06-01 00:00:00';",
+    )
+    .unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.values,
+        vec![
+            Row(vec![
+                1.into(),
+                ColumnValue::parse("2026-02-28 12:00:00", ColumnType::Timestamp).unwrap(),
+                38.into(),
+                2026.into(),
+            ]),
+            Row(vec![
+                2.into(),
+                ColumnValue::parse("2026-01-24", ColumnType::Timestamp).unwrap(),
+                0.into(),
+                2025.into(),
+            ]),
+        ]
+    );
+    assert_eq!(
+        row_set.types,
+        vec![
+            ColumnType::Int,
+            ColumnType::Timestamp,
+            ColumnType::BigInt,
+            ColumnType::Int,
+        ]
+    );
+
+    let statement =
+        sql_parse::parse_statement("SELECT id FROM events WHERE starts > TIME '12:00:00';")
+            .unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(row_set.values, vec![Row(vec![2.into()])]);
+
+    let statement = sql_parse::parse_statement(
+        "INSERT INTO events VALUES (4, TIMESTAMP '2026-02-30 00:00:00', NULL, NULL);",
+    )
+    .unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(
+        result,
+        Err(SqlError::InvalidLiteral(ColumnType::Timestamp, _))
+    ));
+
+    let statement =
+        sql_parse::parse_statement("SELECT EXTRACT(HOUR FROM day) FROM events;").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(
+        result,
+        Err(SqlError::InvalidExtract(_, ColumnType::Date))
+    ));
+
+    let statement =
+        sql_parse::parse_statement("INSERT INTO events VALUES (4, NULL, NULL, DATE '2026-01-01');")
+            .unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::IncompatibleTypes(_, _))));
+}
+
+#[tokio::test]
+async fn now_is_the_same_within_a_statement() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE events (id INT, created_at TIMESTAMP);",
+            "INSERT INTO events VALUES (1, NOW()), (2, NOW()), (3, NOW());",
+        ],
+    )
+    .await;
+
+    let statement =
+        sql_parse::parse_statement("SELECT created_at, NOW(), NOW() FROM events;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.names,
+        vec![
+            ColumnName("created_at".into()),
+            ColumnName("NOW()".into()),
+            ColumnName("NOW()".into())
+        ]
+    );
+
+    let inserted = &row_set.values[0].0[0];
+    let selected = &row_set.values[0].0[1];
+
+    assert!(matches!(inserted, ColumnValue::Timestamp(_)));
+
+    for row in &row_set.values {
+        assert_eq!(&row.0[0], inserted);
+        assert_eq!(&row.0[1], selected);
+        assert_eq!(&row.0[2], selected);
+    }
+}
+
+#[tokio::test]
+async fn blob_columns() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE files (name TEXT, hash BLOB);",
+            "INSERT INTO files VALUES ('a.png', X'DEADBEEF'), ('b.png', x'cafe'), ('empty', X'');",
+        ],
+    )
+    .await;
+
+    let statement =
+        sql_parse::parse_statement("SELECT name, hash FROM files WHERE hash = X'CAFE';").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    let hash = ColumnValue::Blob(Blob(vec![0xca, 0xfe]));
+
+    assert_eq!(
+        row_set.values,
+        vec![Row(vec!["b.png".into(), hash.clone()])]
+    );
+    assert_eq!(row_set.types, vec![ColumnType::Text, ColumnType::Blob]);
+    assert_eq!(format!("{hash:?}"), "Blob(X'CAFE')");
+
+    let statement =
+        sql_parse::parse_statement("SELECT name FROM files WHERE hash <> X'DEADBEEF';").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.values,
+        vec![Row(vec!["b.png".into()]), Row(vec!["empty".into()])]
+    );
+
+    for statement in [
+        "INSERT INTO files VALUES ('c.png', 'DEADBEEF');",
+        "SELECT name FROM files WHERE hash = 'DEADBEEF';",
+    ] {
+        let statement = sql_parse::parse_statement(statement).unwrap();
+
+        let result = statement.execute(&mut runtime).await;
+
+        assert!(matches!(
+            result,
+            Err(SqlError::IncompatibleTypes(_, _) | SqlError::ImpossibleComparison(_, _))
+        ));
+    }
+}
+
+#[tokio::test]
+async fn float_columns() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE readings (sensor INT, value REAL);",
+            "INSERT INTO readings VALUES (1, 1.5e-3), (2, 2), (3, FLOAT 'NaN'), (4, 0.25);",
+        ],
+    )
+    .await;
+
+    let statement = sql_parse::parse_statement(
+        "SELECT sensor, value * 2 FROM readings WHERE value < 1 ORDER BY value DESC;",
+    )
+    .unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.values,
+        vec![
+            Row(vec![4.into(), ColumnValue::Float(0.5)]),
+            Row(vec![1.into(), ColumnValue::Float(3e-3)]),
+        ]
+    );
+    assert_eq!(row_set.types, vec![ColumnType::Int, ColumnType::Float]);
+
+    // NaN sorts after every other number
+    let statement =
+        sql_parse::parse_statement("SELECT sensor FROM readings WHERE value > 1.5;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.values,
+        vec![Row(vec![2.into()]), Row(vec![3.into()])]
+    );
+
+    let statement =
+        sql_parse::parse_statement("INSERT INTO readings VALUES (5, 1e308 * 10);").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::ArithmeticOverflow(_, _, _))));
+
+    let statement =
+        sql_parse::parse_statement("CREATE TABLE totals (amount DECIMAL(10, 2));").unwrap();
+
+    statement.execute(&mut runtime).await.unwrap();
+
+    let statement = sql_parse::parse_statement("INSERT INTO totals VALUES (1.5e0);").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::IncompatibleTypes(_, _))));
+}
+
+#[tokio::test]
+async fn primary_key_and_unique_columns() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE users (id INT PRIMARY KEY, email TEXT UNIQUE, name TEXT);",
+            "INSERT INTO users VALUES (1, 'a@example.com', 'A'), (2, NULL, 'B'), (3, NULL, 'C');",
+        ],
+    )
+    .await;
+
+    for statement in [
+        "INSERT INTO users VALUES (1, 'b@example.com', 'D');",
+        "INSERT INTO users VALUES (4, 'a@example.com', 'D');",
+        "UPDATE users SET email = 'c@example.com';",
+        "UPDATE users SET id = 1 WHERE id = 2;",
+    ] {
+        let statement = sql_parse::parse_statement(statement).unwrap();
+
+        let result = statement.execute(&mut runtime).await;
+
+        assert!(matches!(result, Err(SqlError::DuplicateKey(_, _))));
+    }
+
+    let statement =
+        sql_parse::parse_statement("INSERT INTO users (email) VALUES ('d@example.com');").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::NullNotAllowed(_))));
+
+    let statement = sql_parse::parse_statement(
+        "CREATE TABLE lines (order_id INT PRIMARY KEY, line INT, PRIMARY KEY (order_id, line));",
+    )
+    .unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::MultiplePrimaryKeys(_))));
+
+    execute_all(
+        &mut runtime,
+        &[
+            "UPDATE users SET id = id + 1;",
+            "INSERT INTO users VALUES (1, 'b@example.com', 'D');",
+        ],
+    )
+    .await;
+
+    let statement = sql_parse::parse_statement("SELECT id, name FROM users ORDER BY id;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.values,
+        vec![
+            Row(vec![1.into(), "D".into()]),
+            Row(vec![2.into(), "A".into()]),
+            Row(vec![3.into(), "B".into()]),
+            Row(vec![4.into(), "C".into()]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn foreign_keys() {
+    let mut runtime = test_runtime_with_values();
+
+    for (statement, is_expected) in [
+        (
+            "CREATE TABLE orders (id INT, FOREIGN KEY (id) REFERENCES missing(id));",
+            (|error| matches!(error, SqlError::TableDoesNotExist(_))) as fn(&SqlError) -> bool,
+        ),
+        (
+            "CREATE TABLE orders (id INT, FOREIGN KEY (id) REFERENCES test_table(missing));",
+            |error| matches!(error, SqlError::NameDoesNotExist(_, _)),
+        ),
+        (
+            "CREATE TABLE orders (id INT, FOREIGN KEY (id) REFERENCES test_table(first));",
+            |error| matches!(error, SqlError::NotAKey(_, _)),
+        ),
+    ] {
+        let statement = sql_parse::parse_statement(statement).unwrap();
+
+        let error = statement.execute(&mut runtime).await.unwrap_err();
+
+        assert!(is_expected(&error), "Unexpected error: {error:?}");
+    }
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE customers (id INT PRIMARY KEY, name TEXT);",
+            "CREATE TABLE orders (id INT PRIMARY KEY, customer_id INT, FOREIGN KEY (customer_id) REFERENCES customers(id));",
+            "INSERT INTO customers VALUES (1, 'A'), (2, 'B');",
+            "INSERT INTO orders VALUES (10, 1), (11, 1), (12, NULL);",
+        ],
+    )
+    .await;
+
+    let statement = sql_parse::parse_statement(
+        "CREATE TABLE wrong (name TEXT, FOREIGN KEY (name) REFERENCES customers(id));",
+    )
+    .unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::IncompatibleTypes(_, _))));
+
+    for statement in [
+        "INSERT INTO orders VALUES (13, 3);",
+        "UPDATE orders SET customer_id = 3 WHERE id = 12;",
+    ] {
+        let statement = sql_parse::parse_statement(statement).unwrap();
+
+        let result = statement.execute(&mut runtime).await;
+
+        assert!(matches!(
+            result,
+            Err(SqlError::ForeignKeyViolation(_, _, _))
+        ));
+    }
+
+    for statement in [
+        "DELETE FROM customers WHERE id = 1;",
+        "UPDATE customers SET id = 3 WHERE id = 1;",
+    ] {
+        let statement = sql_parse::parse_statement(statement).unwrap();
+
+        let result = statement.execute(&mut runtime).await;
+
+        assert!(matches!(result, Err(SqlError::RowIsReferenced(_, _, _))));
+    }
+
+    let statement = sql_parse::parse_statement("DROP TABLE customers;").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::TableIsReferenced(_, _))));
+
+    // Rows that nothing references can still be changed
+    execute_all(
+        &mut runtime,
+        &[
+            "UPDATE customers SET id = 3 WHERE id = 2;",
+            "DELETE FROM customers WHERE id = 3;",
+            "UPDATE orders SET customer_id = 1 WHERE id = 12;",
+        ],
+    )
+    .await;
+
+    let statement = sql_parse::parse_statement("SELECT * FROM orders ORDER BY id;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.values,
+        vec![
+            Row(vec![10.into(), 1.into()]),
+            Row(vec![11.into(), 1.into()]),
+            Row(vec![12.into(), 1.into()]),
+        ]
+    );
+
+    execute_all(
+        &mut runtime,
+        &["DROP TABLE orders;", "DROP TABLE customers;"],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn foreign_key_actions() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE customers (id INT PRIMARY KEY, name TEXT);",
+            "CREATE TABLE orders (
+                id INT PRIMARY KEY,
+                customer_id INT,
+                FOREIGN KEY (customer_id) REFERENCES customers(id) ON DELETE CASCADE ON UPDATE CASCADE
+            );",
+            "CREATE TABLE lines (
+                order_id INT,
+                sku TEXT,
+                FOREIGN KEY (order_id) REFERENCES orders(id) ON DELETE CASCADE
+            );",
+            "CREATE TABLE notes (
+                customer_id INT,
+                note TEXT,
+                FOREIGN KEY (customer_id) REFERENCES customers(id) ON DELETE SET NULL ON UPDATE SET NULL
+            );",
+            "INSERT INTO customers VALUES (1, 'A'), (2, 'B');",
+            "INSERT INTO orders VALUES (10, 1), (11, 2);",
+            "INSERT INTO lines VALUES (10, 'x'), (10, 'y'), (11, 'z');",
+            "INSERT INTO notes VALUES (1, 'first'), (2, 'second');",
+            "UPDATE customers SET id = 3 WHERE id = 2;",
+            // Cascades from orders to lines too
+            "DELETE FROM customers WHERE id = 1;",
+        ],
+    )
+    .await;
+
+    for (query, expected) in [
+        (
+            "SELECT * FROM customers;",
+            vec![Row(vec![3.into(), "B".into()])],
+        ),
+        (
+            "SELECT * FROM orders;",
+            vec![Row(vec![11.into(), 3.into()])],
+        ),
+        (
+            "SELECT * FROM lines;",
+            vec![Row(vec![11.into(), "z".into()])],
+        ),
+        (
+            "SELECT * FROM notes ORDER BY note;",
+            vec![
+                Row(vec![ColumnValue::Null, "first".into()]),
+                Row(vec![ColumnValue::Null, "second".into()]),
+            ],
+        ),
+    ] {
+        let statement = sql_parse::parse_statement(query).unwrap();
+
+        let result = statement.execute(&mut runtime).await.unwrap();
+
+        let ExecutionResult::Select(row_set) = result else {
+            panic!("Wrong result type: {result:?}");
+        };
+
+        assert_eq!(row_set.values, expected, "{query}");
+    }
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE employees (
+                id INT PRIMARY KEY,
+                manager_id INT,
+                FOREIGN KEY (manager_id) REFERENCES employees(id) ON DELETE CASCADE
+            );",
+            "INSERT INTO employees VALUES (1, NULL), (2, 1), (3, 2), (4, NULL);",
+            "CREATE TABLE badges (employee_id INT, FOREIGN KEY (employee_id) REFERENCES employees(id));",
+            "INSERT INTO badges VALUES (3);",
+        ],
+    )
+    .await;
+
+    // The cascade reaches a row that is restricted, which fails the whole statement
+    let statement = sql_parse::parse_statement("DELETE FROM employees WHERE id = 1;").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::RowIsReferenced(_, _, _))));
+
+    execute_all(
+        &mut runtime,
+        &["DELETE FROM badges;", "DELETE FROM employees WHERE id = 1;"],
+    )
+    .await;
+
+    let statement = sql_parse::parse_statement("SELECT id FROM employees;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(row_set.values, vec![Row(vec![4.into()])]);
+}
+
+#[tokio::test]
+async fn defaults_and_checks() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE products (
+                id INT PRIMARY KEY CHECK (id > 0),
+                name TEXT DEFAULT 'unnamed',
+                price DECIMAL(6, 2) NOT NULL DEFAULT 0.5 * 2,
+                stock INT DEFAULT 0,
+                CHECK (stock >= 0 AND price >= 0)
+            );",
+            "INSERT INTO products (id) VALUES (1);",
+            "INSERT INTO products (id, name, stock) VALUES (2, 'Widget', 5);",
+        ],
+    )
+    .await;
+
+    let statement = sql_parse::parse_statement("SELECT * FROM products;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.values,
+        vec![
+            Row(vec![1.into(), "unnamed".into(), decimal(100, 2), 0.into()]),
+            Row(vec![2.into(), "Widget".into(), decimal(100, 2), 5.into()]),
+        ]
+    );
+
+    for query in [
+        "INSERT INTO products (id) VALUES (0);",
+        "INSERT INTO products (id, stock) VALUES (3, -1);",
+        "UPDATE products SET stock = stock - 1;",
+    ] {
+        let statement = sql_parse::parse_statement(query).unwrap();
+
+        let result = statement.execute(&mut runtime).await;
+
+        assert!(
+            matches!(result, Err(SqlError::CheckViolation(_, _))),
+            "{query}: {result:?}"
+        );
+    }
+
+    for query in [
+        "CREATE TABLE wrong (a INT DEFAULT 'a');",
+        "CREATE TABLE wrong (a INT, CHECK (b > 0));",
+    ] {
+        let statement = sql_parse::parse_statement(query).unwrap();
+
+        assert!(statement.execute(&mut runtime).await.is_err(), "{query}");
+    }
+}
+
+#[tokio::test]
+async fn serial_columns_and_sequences() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE items (id SERIAL PRIMARY KEY, name TEXT);",
+            "CREATE SEQUENCE codes START WITH 100 INCREMENT BY 10;",
+            "CREATE TABLE labels (code INT DEFAULT nextval('codes'), label TEXT);",
+            "INSERT INTO items (name) VALUES ('first');",
+            "INSERT INTO items (name) VALUES ('second');",
+            "INSERT INTO labels (label) VALUES ('a');",
+            "INSERT INTO labels (code, label) VALUES (currval('codes') + 1, 'b');",
+            "INSERT INTO labels (label) VALUES ('c');",
+        ],
+    )
+    .await;
+
+    for (query, expected) in [
+        (
+            "SELECT * FROM items;",
+            vec![
+                Row(vec![1.into(), "first".into()]),
+                Row(vec![2.into(), "second".into()]),
+            ],
+        ),
+        (
+            "SELECT * FROM labels;",
+            vec![
+                Row(vec![100.into(), "a".into()]),
+                Row(vec![101.into(), "b".into()]),
+                Row(vec![110.into(), "c".into()]),
+            ],
+        ),
+    ] {
+        let statement = sql_parse::parse_statement(query).unwrap();
+
+        let result = statement.execute(&mut runtime).await.unwrap();
+
+        let ExecutionResult::Select(row_set) = result else {
+            panic!("Wrong result type: {result:?}");
+        };
+
+        assert_eq!(row_set.values, expected, "{query}");
+    }
+
+    let statement = sql_parse::parse_statement("CREATE SEQUENCE fresh;").unwrap();
+
+    statement.execute(&mut runtime).await.unwrap();
+
+    for query in [
+        "CREATE SEQUENCE codes;",
+        "INSERT INTO labels VALUES (currval('fresh'), 'd');",
+        "INSERT INTO labels VALUES (nextval('missing'), 'e');",
+        "DROP SEQUENCE codes;",
+    ] {
+        let statement = sql_parse::parse_statement(query).unwrap();
+
+        let result = statement.execute(&mut runtime).await;
+
+        assert!(
+            matches!(
+                result,
+                Err(SqlError::DuplicateSequence(_)
+                    | SqlError::SequenceNotStarted(_)
+                    | SqlError::SequenceDoesNotExist(_)
+                    | SqlError::SequenceIsUsed(_, _))
+            ),
+            "{query}: {result:?}"
+        );
+    }
+
+    execute_all(
+        &mut runtime,
+        &[
+            "DROP TABLE items;",
+            "DROP TABLE labels;",
+            "DROP SEQUENCE codes;",
+        ],
+    )
+    .await;
+
+    let database = runtime.lock_database().await.unwrap();
+
+    let sequences = &database.sequences;
+
+    assert!(sequences.contains_key("fresh"));
+
+    assert!(!sequences.contains_key("items_id_seq"));
+
+    assert!(!sequences.contains_key("codes"));
+}
+
+/// Runs a `SELECT` and returns its rows.
+async fn select_values(runtime: &mut Runtime, query: &str) -> Vec<Row> {
+    let statement = sql_parse::parse_statement(query).unwrap();
+
+    let result = statement.execute(runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    return row_set.values;
+}
+
+#[tokio::test]
+async fn sequence_calls_in_select() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE SEQUENCE seq;",
+            "CREATE TABLE t (id INT, v INT);",
+            "INSERT INTO t VALUES (1, 0), (2, 0), (3, 0);",
+        ],
+    )
+    .await;
+
+    let statement = sql_parse::parse_statement("SELECT id, nextval('seq') FROM t;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.names,
+        vec![ColumnName("id".into()), ColumnName("nextval('seq')".into())]
+    );
+
+    assert_eq!(
+        row_set.values,
+        vec![
+            Row(vec![1.into(), 1.into()]),
+            Row(vec![2.into(), 2.into()]),
+            Row(vec![3.into(), 3.into()]),
+        ]
+    );
+
+    // The values are kept for the next statement
+    let values = select_values(&mut runtime, "SELECT currval('seq') FROM t WHERE id = 1;").await;
+
+    assert_eq!(values, vec![Row(vec![3.into()])]);
+}
+
+#[tokio::test]
+async fn sequence_calls_in_update() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE SEQUENCE seq START WITH 10;",
+            "CREATE TABLE t (id INT, v INT CHECK (v < 13));",
+            "INSERT INTO t VALUES (1, 0), (2, 0);",
+            "UPDATE t SET v = nextval('seq');",
+        ],
+    )
+    .await;
+
+    let values = select_values(&mut runtime, "SELECT id, v FROM t;").await;
+
+    assert_eq!(
+        values,
+        vec![
+            Row(vec![1.into(), 10.into()]),
+            Row(vec![2.into(), 11.into()]),
+        ]
+    );
+
+    let statement = sql_parse::parse_statement("UPDATE t SET v = nextval('seq');").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(
+        matches!(result, Err(SqlError::CheckViolation(_, _))),
+        "{result:?}"
+    );
+
+    // The update is undone, but the sequence still advanced
+    let values = select_values(&mut runtime, "SELECT v, currval('seq') FROM t;").await;
+
+    assert_eq!(
+        values,
+        vec![
+            Row(vec![10.into(), 13.into()]),
+            Row(vec![11.into(), 13.into()]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn failed_statements_are_undone() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE t (id SERIAL, v INT CHECK (v < 3));",
+            "CREATE TABLE other (v INT);",
+            "INSERT INTO t (v) VALUES (1);",
+            "INSERT INTO other VALUES (1);",
+        ],
+    )
+    .await;
+
+    // Fails on the last row, after the first one was inserted
+    let statement = sql_parse::parse_statement("INSERT INTO t (v) VALUES (2), (3);").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(
+        matches!(result, Err(SqlError::CheckViolation(_, _))),
+        "{result:?}"
+    );
+
+    // The rows are gone, but the values of the sequence aren't given out again
+    execute_all(&mut runtime, &["INSERT INTO t (v) VALUES (2);"]).await;
+
+    let values = select_values(&mut runtime, "SELECT id, v FROM t;").await;
+
+    assert_eq!(
+        values,
+        vec![Row(vec![1.into(), 1.into()]), Row(vec![4.into(), 2.into()])]
+    );
+
+    let values = select_values(&mut runtime, "SELECT v FROM other;").await;
+
+    assert_eq!(values, vec![Row(vec![1.into()])]);
+
+    let statement = sql_parse::parse_statement("DROP TABLE missing;").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(
+        matches!(result, Err(SqlError::TableDoesNotExist(_))),
+        "{result:?}"
+    );
+
+    let values = select_values(&mut runtime, "SELECT v FROM t;").await;
+
+    assert_eq!(values, vec![Row(vec![1.into()]), Row(vec![2.into()])]);
+}
+
+#[tokio::test]
+async fn sequence_calls_in_where() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE SEQUENCE seq;",
+            "CREATE TABLE t (id SERIAL, name TEXT);",
+            "INSERT INTO t (name) VALUES ('a'), ('b'), ('c');",
+            "SELECT nextval('seq') FROM t WHERE name = 'a';",
+            "SELECT nextval('seq') FROM t WHERE name = 'a';",
+        ],
+    )
+    .await;
+
+    let values = select_values(
+        &mut runtime,
+        "SELECT name FROM t WHERE id = currval('seq');",
+    )
+    .await;
+
+    assert_eq!(values, vec![Row(vec!["b".into()])]);
+
+    execute_all(&mut runtime, &["DELETE FROM t WHERE id > currval('seq');"]).await;
+
+    let values = select_values(&mut runtime, "SELECT name FROM t;").await;
+
+    assert_eq!(values, vec![Row(vec!["a".into()]), Row(vec!["b".into()])]);
+}
+
+#[tokio::test]
+async fn alter_table() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE people (id SERIAL PRIMARY KEY, name TEXT, age INT CHECK (age >= 0));",
+            "CREATE TABLE pets (name TEXT, owner INT, FOREIGN KEY (owner) REFERENCES people (id));",
+            "INSERT INTO people (name, age) VALUES ('Ann', 30), ('Bob', 40);",
+            "INSERT INTO pets VALUES ('Rex', 2);",
+            "ALTER TABLE people ADD COLUMN active BOOL NOT NULL DEFAULT TRUE;",
+            "ALTER TABLE people ADD nickname TEXT;",
+            "ALTER TABLE people ADD COLUMN number SERIAL UNIQUE;",
+            "ALTER TABLE people RENAME COLUMN age TO years;",
+            "ALTER TABLE people RENAME TO persons;",
+        ],
+    )
+    .await;
+
+    let statement = sql_parse::parse_statement("SELECT * FROM persons;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.names,
+        ["id", "name", "years", "active", "nickname", "number"]
+            .map(ColumnName::from)
+            .to_vec()
+    );
+
+    assert_eq!(
+        row_set.values,
+        vec![
+            Row(vec![
+                1.into(),
+                "Ann".into(),
+                30.into(),
+                true.into(),
+                ColumnValue::Null,
+                1.into()
+            ]),
+            Row(vec![
+                2.into(),
+                "Bob".into(),
+                40.into(),
+                true.into(),
+                ColumnValue::Null,
+                2.into()
+            ]),
+        ]
+    );
+
+    for query in [
+        // The check and the foreign key follow the renames
+        "INSERT INTO persons (name, years) VALUES ('Cid', -1);",
+        "INSERT INTO pets VALUES ('Tom', 3);",
+        "ALTER TABLE persons ADD COLUMN code INT NOT NULL;",
+        "ALTER TABLE persons ADD COLUMN code INT CHECK (code > 0) DEFAULT 0;",
+        "ALTER TABLE persons DROP COLUMN id;",
+        "ALTER TABLE persons RENAME COLUMN name TO years;",
+        "ALTER TABLE persons RENAME TO pets;",
+        "ALTER TABLE persons DROP COLUMN age;",
+        "ALTER TABLE people ADD COLUMN code INT;",
+    ] {
+        let statement = sql_parse::parse_statement(query).unwrap();
+
+        let result = statement.execute(&mut runtime).await;
+
+        assert!(
+            matches!(
+                result,
+                Err(SqlError::CheckViolation(_, _)
+                    | SqlError::ForeignKeyViolation(_, _, _)
+                    | SqlError::NullNotAllowed(_)
+                    | SqlError::ColumnIsReferenced(_, _)
+                    | SqlError::ColumnNameNotUnique(_)
+                    | SqlError::DuplicateTable(_)
+                    | SqlError::NameDoesNotExist(_, _)
+                    | SqlError::TableDoesNotExist(_))
+            ),
+            "{query}: {result:?}"
+        );
+    }
+
+    execute_all(
+        &mut runtime,
+        &[
+            "ALTER TABLE persons DROP COLUMN years;",
+            "ALTER TABLE persons DROP COLUMN number;",
+            "INSERT INTO persons (id, name) VALUES (3, 'Cid');",
+            "INSERT INTO pets VALUES ('Tom', 3);",
+        ],
+    )
+    .await;
+
+    let statement = sql_parse::parse_statement("SELECT * FROM persons WHERE id = 3;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.values,
+        vec![Row(vec![
+            3.into(),
+            "Cid".into(),
+            true.into(),
+            ColumnValue::Null
+        ])]
+    );
+
+    let database = runtime.lock_database().await.unwrap();
+
+    let sequences = &database.sequences;
+
+    assert_eq!(sequences["people_id_seq"].owner, Some("persons".into()));
+
+    assert!(!sequences.contains_key("people_number_seq"));
+}
+
+#[tokio::test]
+async fn indexes() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE people (id INT PRIMARY KEY, name TEXT, age INT);",
+            "INSERT INTO people VALUES (1, 'Ann', 30), (2, 'Bob', 40), (3, 'Cid', 50), (4, 'Dan', 60), (5, 'Eve', NULL);",
+            "CREATE INDEX people_by_age ON people (age);",
+            "CREATE UNIQUE INDEX people_by_name ON people (name);",
+            "UPDATE people SET age = age + 1 WHERE age = 30;",
+            "DELETE FROM people WHERE age >= 60;",
+        ],
+    )
+    .await;
+
+    let statement =
+        sql_parse::parse_statement("SELECT id FROM people WHERE age > 30 AND 50 >= age;").unwrap();
+
+    let result = statement.execute(&mut runtime).await.unwrap();
+
+    let ExecutionResult::Select(row_set) = result else {
+        panic!("Wrong result type: {result:?}");
+    };
+
+    assert_eq!(
+        row_set.values,
+        vec![
+            Row(vec![1.into()]),
+            Row(vec![2.into()]),
+            Row(vec![3.into()])
+        ]
+    );
+
+    for query in [
+        "INSERT INTO people VALUES (6, 'Ann', 20);",
+        "CREATE INDEX people_by_age ON people (id);",
+        "CREATE INDEX people_by_height ON people (height);",
+        "CREATE INDEX people_by_id ON pets (id);",
+        "DROP INDEX people_by_height;",
+    ] {
+        let statement = sql_parse::parse_statement(query).unwrap();
+
+        let result = statement.execute(&mut runtime).await;
+
+        assert!(
+            matches!(
+                result,
+                Err(SqlError::DuplicateKey(_, _)
+                    | SqlError::DuplicateIndex(_)
+                    | SqlError::NameDoesNotExist(_, _)
+                    | SqlError::TableDoesNotExist(_)
+                    | SqlError::IndexDoesNotExist(_))
+            ),
+            "{query}: {result:?}"
+        );
+    }
+
+    execute_all(
+        &mut runtime,
+        &[
+            "ALTER TABLE people RENAME COLUMN age TO years;",
+            "ALTER TABLE people DROP COLUMN name;",
+            "DROP INDEX people_by_age;",
+        ],
+    )
+    .await;
+
+    let database = runtime.lock_database().await.unwrap();
+
+    let table = &database.tables["people"];
+
+    assert!(table.schema.indexes.is_empty());
+}
+
+#[tokio::test]
+async fn explain() {
+    let mut runtime = test_runtime_with_values();
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE pets (id INT PRIMARY KEY, owner INT);",
+            "INSERT INTO pets VALUES (1, 5), (2, 6), (3, 5);",
+            "CREATE INDEX pets_by_owner ON pets (owner);",
+        ],
+    )
+    .await;
+
+    let cases = [
+        (
+            "EXPLAIN SELECT id FROM test_table JOIN pets ON first = owner WHERE second = TRUE AND id > 1 + 1;",
+            vec![
+                "Project pets.id",
+                "  Nested Loop Join on (test_table.first = pets.owner)",
+                "    Filter (second = true)",
+                "      Seq Scan on test_table",
+                "    Filter (id > 2)",
+                "      Seq Scan on pets",
+            ],
+        ),
+        (
+            "EXPLAIN UPDATE pets SET id = id + 10 WHERE owner = 5;",
+            vec![
+                "Update on pets",
+                "  Filter (owner = 5)",
+                "    Index Scan using pets_by_owner on pets (owner = 5)",
+            ],
+        ),
+        (
+            "EXPLAIN DELETE FROM pets;",
+            vec!["Delete on pets", "  Seq Scan on pets"],
+        ),
+        (
+            // Every aggregate is only computed once, wherever it's used
+            "EXPLAIN SELECT owner, COUNT(*), SUM(id) + 1 FROM pets GROUP BY owner HAVING COUNT(*) > 1 AND SUM(id) > 0 ORDER BY SUM(id) DESC;",
+            vec![
+                "Project owner, COUNT(*), SUM(id) + 1",
+                "  Sort by SUM(id) DESC",
+                "    Filter (COUNT(*) > 1 AND SUM(id) > 0)",
+                "      Group Aggregate COUNT(*), SUM(id) by owner",
+                "        Seq Scan on pets",
+            ],
+        ),
+    ];
+
+    for (query, expected) in cases {
+        let statement = sql_parse::parse_statement(query).unwrap();
+
+        let result = statement.execute(&mut runtime).await.unwrap();
+
+        let ExecutionResult::Select(row_set) = result else {
+            panic!("Wrong result type: {result:?}");
+        };
+
+        let lines: Vec<ColumnValue> = expected.into_iter().map(|line| line.into()).collect();
+
+        assert_eq!(
+            row_set
+                .values
+                .into_iter()
+                .map(|row| row.0[0].clone())
+                .collect::<Vec<_>>(),
+            lines,
+            "{query}"
+        );
+    }
+
+    // Explaining doesn't change anything
+    let database = runtime.lock_database().await.unwrap();
+
+    let table = &database.tables["pets"];
+
+    assert_eq!(table.values.len(), 3);
+
+    let statement = sql_parse::parse_statement("EXPLAIN CREATE TABLE t (a INT);");
+
+    assert!(statement.is_err());
+}
+
+#[tokio::test]
+async fn transactions() {
+    let mut runtime = test_runtime_with_values();
+
+    async fn ids(runtime: &mut Runtime) -> Vec<Row> {
+        let statement = sql_parse::parse_statement("SELECT id FROM people;").unwrap();
+
+        let result = statement.execute(runtime).await.unwrap();
+
+        let ExecutionResult::Select(row_set) = result else {
+            panic!("Wrong result type: {result:?}");
+        };
+
+        return row_set.values;
+    }
+
+    execute_all(
+        &mut runtime,
+        &[
+            "CREATE TABLE people (id INT PRIMARY KEY, name TEXT);",
+            "BEGIN;",
+            "INSERT INTO people VALUES (1, 'Ann');",
+            "UPDATE people SET name = 'Bob' WHERE id = 1;",
+            "COMMIT;",
+            "BEGIN TRANSACTION;",
+            "INSERT INTO people VALUES (2, 'Cid');",
+            "DELETE FROM people WHERE id = 1;",
+            "DROP TABLE test_table;",
+        ],
+    )
+    .await;
+
+    assert_eq!(ids(&mut runtime).await, vec![Row(vec![2.into()])]);
+
+    execute_all(&mut runtime, &["ROLLBACK;"]).await;
+
+    assert_eq!(ids(&mut runtime).await, vec![Row(vec![1.into()])]);
+
+    let database = runtime.lock_database().await.unwrap();
+
+    assert!(database.tables.contains_key("test_table"));
+
+    drop(database);
+
+    // The rows before the duplicate key aren't inserted either, but the earlier statements are kept
+    execute_all(
+        &mut runtime,
+        &["BEGIN;", "INSERT INTO people VALUES (3, 'Dan');"],
+    )
+    .await;
+
+    let statement =
+        sql_parse::parse_statement("INSERT INTO people VALUES (4, 'Eve'), (1, 'Fay');").unwrap();
+
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::DuplicateKey(_, _))));
+
+    execute_all(&mut runtime, &["COMMIT;"]).await;
+
+    assert_eq!(
+        ids(&mut runtime).await,
+        vec![Row(vec![1.into()]), Row(vec![3.into()])]
+    );
+
+    // Same without a transaction
+    let result = statement.execute(&mut runtime).await;
+
+    assert!(matches!(result, Err(SqlError::DuplicateKey(_, _))));
+
+    assert_eq!(
+        ids(&mut runtime).await,
+        vec![Row(vec![1.into()]), Row(vec![3.into()])]
+    );
+
+    execute_all(&mut runtime, &["BEGIN;"]).await;
+
+    for query in ["BEGIN;", "CREATE DATABASE other;", "DROP DATABASE test_db;"] {
+        let statement = sql_parse::parse_statement(query).unwrap();
+
+        let result = statement.execute(&mut runtime).await;
+
+        assert!(
+            matches!(result, Err(SqlError::TransactionInProgress)),
+            "{query}: {result:?}"
+        );
+    }
+
+    execute_all(&mut runtime, &["ROLLBACK;"]).await;
+
+    for query in ["COMMIT;", "ROLLBACK;"] {
+        let statement = sql_parse::parse_statement(query).unwrap();
+
+        let result = statement.execute(&mut runtime).await;
+
+        assert!(
+            matches!(result, Err(SqlError::NoTransaction)),
+            "{query}: {result:?}"
+        );
+    }
+}
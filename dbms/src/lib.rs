@@ -9,9 +9,9 @@ pub mod server;
 pub mod types;
 pub mod utils;
 
-use sql_parse::parser::{ColumnType, Expression, InfixOperator};
+use sql_parse::parser::{ArithmeticOperator, ColumnType, DateTimeField, Expression, ParseError};
 use types::DatabaseName;
-use types::{ColumnName, ColumnValue, TableName};
+use types::{AggregateFunction, ColumnName, ColumnValue, IndexName, SequenceName, TableName};
 
 pub use database::Database;
 
@@ -22,17 +22,47 @@ pub enum SqlError {
     NameDoesNotExist(ColumnName, Vec<ColumnName>),
     IncompatibleTypes(Vec<ColumnType>, Vec<ColumnType>),
     ImpossibleConversion(Expression, &'static str),
-    InvalidOperation(InfixOperator, &'static str, &'static str),
+    InvalidOperation(ArithmeticOperator, ColumnType, ColumnType),
+    NullNotAllowed(ColumnName),
     ColumnNameNotUnique(ColumnName),
+    DuplicateKey(Vec<ColumnName>, Vec<ColumnValue>),
+    MultiplePrimaryKeys(TableName),
+    NotAKey(TableName, ColumnName),
+    ForeignKeyViolation(ColumnName, ColumnValue, TableName),
+    RowIsReferenced(TableName, ColumnName, ColumnValue),
+    TableIsReferenced(TableName, TableName),
+    ColumnIsReferenced(ColumnName, TableName), // The table with the foreign key
+    CheckViolation(TableName, String),         // The check as SQL
+    AmbiguousColumnName(ColumnName),
     InvalidParameter,
 
     ImpossibleComparison(ColumnValue, ColumnValue),
+    UnknownFunction(String),
+    InvalidArguments(String),
+    InvalidAggregate(AggregateFunction, ColumnType),
+    MisplacedAggregate(AggregateFunction),
+    NotGrouped(ColumnName),
+    ArithmeticOverflow(ColumnValue, ArithmeticOperator, ColumnValue),
+    DivisionByZero,
+    ValueOutOfRange(ColumnValue, ColumnType),
+    InvalidLiteral(ColumnType, String),
+    InvalidExtract(DateTimeField, ColumnType),
 
     DuplicateDatabase(DatabaseName),
     DuplicateTable(String),
     TableDoesNotExist(TableName),
+    DuplicateSequence(SequenceName),
+    SequenceDoesNotExist(SequenceName),
+    SequenceIsUsed(SequenceName, TableName),
+    SequenceNotStarted(SequenceName), // `currval` before `nextval`
+    SequenceExhausted(SequenceName),
+    DuplicateIndex(IndexName),
+    IndexDoesNotExist(IndexName),
     NoDatabaseSelected,
     DatabaseDoesNotExist(DatabaseName),
+    TransactionInProgress,
+    NoTransaction,       // `COMMIT` or `ROLLBACK` without `BEGIN`
+    TransactionConflict, // Another connection changed the database since `BEGIN`
 
     FSError(std::io::Error),
     CouldNotStoreDatabase(DatabaseName, std::io::Error),
@@ -41,6 +71,8 @@ pub enum SqlError {
     CouldNotRemoveTable(TableName, std::io::Error),
     CouldNotStoreSchemas(DatabaseName, std::io::Error),
     CouldNotReadSchemas(std::io::Error),
+    CouldNotStoreSequences(DatabaseName, std::io::Error),
+    CouldNotReadSequences(std::io::Error),
     SchemaDoesNotExist(DatabaseName),
 
     SliceConversionError(std::array::TryFromSliceError),
@@ -48,8 +80,19 @@ pub enum SqlError {
     NotAValidString(std::string::FromUtf8Error),
     NotATypeDiscriminator(u8),
     NotABoolean(u8),
+    NotADecimalScale(u8),
+    NotADecimalSize(u8, u8),
+    NotADecimal(u64, u64), // Whole and fractional part, stored by V1 or V2
+    NotAnInt(u64),
+    NotADate(i32),
+    NotATime(i64),
+    NotATimestamp(i64),
+    NotAConstraintDiscriminator(u8),
+    NotAReferentialAction(u8),
 
     IncompatibleVersion(u8),
+    IncompatibleType(ColumnType), // Can't be stored by an older serialiser
+    IncompatibleValue(ColumnValue), // Same
 
     InvalidHeader(&'static str),
     InvalidMessageType(u8),
@@ -58,8 +101,18 @@ pub enum SqlError {
     CouldNotWriteToConnection(std::io::Error),
     CouldNotReadFromConnection(std::io::Error),
 
-    ParseError,
+    ParseError(ParseError),
     InvalidCommand(String),
 }
 
+impl std::fmt::Display for SqlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            SqlError::ParseError(error) => write!(f, "Failed to parse: {error}"),
+            // The Debug output is informative enough for most errors
+            other => write!(f, "{other:?}"),
+        };
+    }
+}
+
 pub type Result<T> = std::result::Result<T, SqlError>;
@@ -8,7 +8,7 @@ use std::path::{Path, PathBuf};
 use async_trait::async_trait;
 use futures::future::try_join_all;
 
-use super::database::{Database, Table};
+use super::database::{Database, Sequence, Table};
 use super::serialisation::SerialisationManager;
 use super::types::DatabaseName;
 use super::SqlError;
@@ -21,13 +21,23 @@ pub trait PersistenceManager: std::fmt::Debug + Send + Sync {
     async fn save_database(&self, database: &Database) -> Result<()>;
     async fn load_database(&self, name: &DatabaseName) -> Result<Database>;
     async fn drop_database(&self, name: &DatabaseName) -> Result<()>;
+    async fn database_exists(&self, name: &DatabaseName) -> bool;
 
     async fn save_table(&self, database_name: &DatabaseName, table: &Table) -> Result<()>;
     async fn load_table(&self, database_name: &DatabaseName, name: TableName) -> Result<Table>;
     async fn drop_table(&self, database_name: &DatabaseName, name: &TableName) -> Result<()>;
+    async fn rename_table(
+        &self,
+        database: &Database,
+        from: &TableName,
+        to: &TableName,
+    ) -> Result<()>;
 
     async fn save_schemas(&self, database: &Database) -> Result<()>;
     async fn load_schemas(&self, database_name: &DatabaseName) -> Result<Vec<TableSchema>>;
+
+    async fn save_sequences(&self, database: &Database) -> Result<()>;
+    async fn load_sequences(&self, database_name: &DatabaseName) -> Result<Vec<Sequence>>;
 }
 
 fn database_path(path: &Path, name: &DatabaseName) -> PathBuf {
@@ -48,6 +58,12 @@ fn schema_path(path: &Path, database_name: &DatabaseName) -> PathBuf {
     return result;
 }
 
+fn sequences_path(path: &Path, database_name: &DatabaseName) -> PathBuf {
+    let result = path.to_path_buf().join(&database_name.0).join(".sequences");
+
+    return result;
+}
+
 #[derive(Debug)]
 pub struct FileSystem(SerialisationManager, PathBuf);
 
@@ -66,6 +82,9 @@ impl PersistenceManager for FileSystem {
             .create(database_path(&self.1, &database.name))
             .map_err(|error| SqlError::CouldNotStoreDatabase(database.name.clone(), error))?;
 
+        // Before the tables, so rows never hold values that the stored sequences would hand out again
+        self.save_sequences(database).await?;
+
         let mut futures = vec![self.save_schemas(database)];
 
         // The C in ACID stands for "can't be fucked" right?
@@ -107,6 +126,15 @@ impl PersistenceManager for FileSystem {
             database.tables.insert(table.schema.name.0.clone(), table);
         });
 
+        // Databases stored before sequences existed don't have any
+        if sequences_path(&self.1, name).exists() {
+            let sequences = self.load_sequences(name).await?;
+
+            sequences.into_iter().for_each(|sequence| {
+                database.sequences.insert(sequence.name.0.clone(), sequence);
+            });
+        }
+
         return Ok(database);
     }
 
@@ -117,10 +145,14 @@ impl PersistenceManager for FileSystem {
             .map_err(|error| SqlError::CouldNotRemoveDatabase(name.clone(), error));
     }
 
+    async fn database_exists(&self, name: &DatabaseName) -> bool {
+        return database_path(&self.1, name).exists();
+    }
+
     async fn save_table(&self, database_name: &DatabaseName, table: &Table) -> Result<()> {
         let path = table_path(&self.1, database_name, &table.schema.name);
 
-        let data = self.0.serialise_table(table);
+        let data = self.0.serialise_table(table)?;
 
         return fs::write(path, data)
             .map_err(|error| SqlError::CouldNotStoreTable(table.schema.name.clone(), error));
@@ -147,6 +179,30 @@ impl PersistenceManager for FileSystem {
             .map_err(|error| SqlError::CouldNotRemoveTable(name.clone(), error));
     }
 
+    async fn rename_table(
+        &self,
+        database: &Database,
+        from: &TableName,
+        to: &TableName,
+    ) -> Result<()> {
+        let table = database
+            .tables
+            .get(&to.0)
+            .ok_or_else(|| SqlError::TableDoesNotExist(to.clone()))?;
+
+        // Stored under the new name before the old file is removed,
+        // so that the schema never lists a table whose file is missing
+        self.save_table(&database.name, table).await?;
+
+        self.save_schemas(database).await?;
+
+        if table_path(&self.1, &database.name, from).exists() {
+            self.drop_table(&database.name, from).await?;
+        }
+
+        return Ok(());
+    }
+
     async fn save_schemas(&self, database: &Database) -> Result<()> {
         let path = schema_path(&self.1, &database.name);
 
@@ -156,7 +212,7 @@ impl PersistenceManager for FileSystem {
             .map(|table| &table.schema)
             .collect::<Vec<_>>();
 
-        let data = self.0.serialise_schemas(schemas);
+        let data = self.0.serialise_schemas(schemas)?;
 
         return fs::write(path, data)
             .map_err(|error| SqlError::CouldNotStoreSchemas(database.name.clone(), error));
@@ -175,6 +231,29 @@ impl PersistenceManager for FileSystem {
 
         return Ok(schemas);
     }
+
+    async fn save_sequences(&self, database: &Database) -> Result<()> {
+        let path = sequences_path(&self.1, &database.name);
+
+        let mut sequences = database.sequences.values().collect::<Vec<_>>();
+
+        sequences.sort_by(|left, right| left.name.0.cmp(&right.name.0));
+
+        let data = self.0.serialise_sequences(sequences);
+
+        return fs::write(path, data)
+            .map_err(|error| SqlError::CouldNotStoreSequences(database.name.clone(), error));
+    }
+
+    async fn load_sequences(&self, database_name: &DatabaseName) -> Result<Vec<Sequence>> {
+        let path = sequences_path(&self.1, database_name);
+
+        let data = fs::read(path).map_err(SqlError::CouldNotReadSequences)?;
+
+        let sequences = self.0.deserialise_sequences(data.as_slice())?;
+
+        return Ok(sequences);
+    }
 }
 
 #[cfg(test)]
@@ -196,12 +275,16 @@ impl PersistenceManager for NoOp {
         return Ok(());
     }
 
+    async fn database_exists(&self, _: &DatabaseName) -> bool {
+        return false;
+    }
+
     async fn save_table(&self, _: &DatabaseName, _: &Table) -> Result<()> {
         return Ok(());
     }
 
     async fn load_table(&self, _: &DatabaseName, name: TableName) -> Result<Table> {
-        let result = Table::new(name, vec![], vec![]).unwrap();
+        let result = Table::new(name, vec![], vec![], vec![], vec![]).unwrap();
 
         return Ok(result);
     }
@@ -210,6 +293,10 @@ impl PersistenceManager for NoOp {
         return Ok(());
     }
 
+    async fn rename_table(&self, _: &Database, _: &TableName, _: &TableName) -> Result<()> {
+        return Ok(());
+    }
+
     async fn save_schemas(&self, _: &Database) -> Result<()> {
         return Ok(());
     }
@@ -217,4 +304,12 @@ impl PersistenceManager for NoOp {
     async fn load_schemas(&self, _: &DatabaseName) -> Result<Vec<TableSchema>> {
         return Ok(vec![]);
     }
+
+    async fn save_sequences(&self, _: &Database) -> Result<()> {
+        return Ok(());
+    }
+
+    async fn load_sequences(&self, _: &DatabaseName) -> Result<Vec<Sequence>> {
+        return Ok(vec![]);
+    }
 }
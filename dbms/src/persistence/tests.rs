@@ -44,10 +44,12 @@ mod filesystem {
         let table = Table::new(
             "tbl".into(),
             vec![
-                ColumnDefinition("col1".into(), ColumnType::Int),
-                ColumnDefinition("col2".into(), ColumnType::Bool),
+                ColumnDefinition("col1".into(), ColumnType::Int, vec![]),
+                ColumnDefinition("col2".into(), ColumnType::Bool, vec![]),
             ],
             vec![],
+            vec![],
+            vec![],
         )
         .unwrap();
 
@@ -88,6 +90,122 @@ mod filesystem {
         assert_eq!(result, db);
     }
 
+    #[tokio::test]
+    async fn load_database_with_sequences() {
+        let mut db = test_db_with_values();
+
+        let mut sequence = Sequence::new("ids".into(), Some(10), Some(-2), None).unwrap();
+
+        sequence.last_value = Some(8);
+
+        db.create_sequence(sequence).unwrap();
+
+        db.create_sequence(
+            Sequence::new(
+                "test_table_id_seq".into(),
+                None,
+                None,
+                Some("test_table".into()),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let (persistence_manager, ref path) = new_filesystem_manager();
+
+        persistence_manager.save_database(&db).await.unwrap();
+
+        assert!(sequences_path(path, &db.name).exists());
+
+        let result = persistence_manager.load_database(&db.name).await.unwrap();
+
+        assert_eq!(result, db);
+    }
+
+    #[tokio::test]
+    async fn load_database_from_original_v2() {
+        let (persistence_manager, path) = new_filesystem_manager();
+
+        let name = DatabaseName("old".into());
+
+        // Written by V2 before it stored anything but INT, DECIMAL, TEXT and BOOL:
+        // items (id INT, price DECIMAL, name TEXT, sold BOOL)
+        #[rustfmt::skip]
+        let schemas: Vec<u8> = vec![
+            2, // Version
+            1, 0, 0, 0, 0, 0, 0, 0, // Schema count
+            5, 0, 0, 0, 0, 0, 0, 0, 105, 116, 101, 109, 115, // items
+            4, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, // Types
+            4, 0, 0, 0, 0, 0, 0, 0, // Column names
+            2, 0, 0, 0, 0, 0, 0, 0, 105, 100,
+            5, 0, 0, 0, 0, 0, 0, 0, 112, 114, 105, 99, 101,
+            4, 0, 0, 0, 0, 0, 0, 0, 110, 97, 109, 101,
+            4, 0, 0, 0, 0, 0, 0, 0, 115, 111, 108, 100,
+        ];
+
+        // (1, 2.5, 'pen', true), (20, 13, 'ink', false)
+        #[rustfmt::skip]
+        let table: Vec<u8> = vec![
+            2, // Version
+            5, 0, 0, 0, 0, 0, 0, 0, 105, 116, 101, 109, 115, // items
+            4, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, // Types
+            4, 0, 0, 0, 0, 0, 0, 0, // Column names
+            2, 0, 0, 0, 0, 0, 0, 0, 105, 100,
+            5, 0, 0, 0, 0, 0, 0, 0, 112, 114, 105, 99, 101,
+            4, 0, 0, 0, 0, 0, 0, 0, 110, 97, 109, 101,
+            4, 0, 0, 0, 0, 0, 0, 0, 115, 111, 108, 100,
+            2, 0, 0, 0, 0, 0, 0, 0, // Row count
+            4, 0, 0, 0, 0, 0, 0, 0, // Value count
+            1, 0, 0, 0, 0, 0, 0, 0,
+            2, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0,
+            3, 0, 0, 0, 0, 0, 0, 0, 112, 101, 110,
+            1,
+            4, 0, 0, 0, 0, 0, 0, 0, // Value count
+            20, 0, 0, 0, 0, 0, 0, 0,
+            13, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            3, 0, 0, 0, 0, 0, 0, 0, 105, 110, 107,
+            0,
+        ];
+
+        std::fs::create_dir_all(database_path(&path, &name)).unwrap();
+
+        std::fs::write(schema_path(&path, &name), schemas).unwrap();
+
+        std::fs::write(path.join("old").join("items"), table).unwrap();
+
+        let result = persistence_manager.load_database(&name).await.unwrap();
+
+        let table = &result.tables["items"];
+
+        assert_eq!(
+            table.schema.types,
+            vec![
+                ColumnType::Int,
+                ColumnType::Decimal(None),
+                ColumnType::Text,
+                ColumnType::Bool
+            ]
+        );
+
+        assert_eq!(
+            table.values,
+            vec![
+                crate::database::Row(vec![
+                    ColumnValue::Int(1),
+                    decimal(25, 1),
+                    ColumnValue::Str("pen".into()),
+                    ColumnValue::Bool(true)
+                ]),
+                crate::database::Row(vec![
+                    ColumnValue::Int(20),
+                    decimal(13, 0),
+                    ColumnValue::Str("ink".into()),
+                    ColumnValue::Bool(false)
+                ]),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn load_database_nonexistent() {
         let persistence_manager = new_filesystem_manager().0;
@@ -195,6 +313,31 @@ mod filesystem {
         }
     }
 
+    #[tokio::test]
+    async fn rename_table_basic() {
+        let (persistence_manager, ref path) = new_filesystem_manager();
+
+        let mut db = test_db_with_values();
+
+        persistence_manager.save_database(&db).await.unwrap();
+
+        db.rename_table(&"test_table".into(), "renamed".into())
+            .unwrap();
+
+        persistence_manager
+            .rename_table(&db, &"test_table".into(), &"renamed".into())
+            .await
+            .unwrap();
+
+        assert!(!table_path(path, &db.name, &"test_table".into()).exists());
+
+        assert!(table_path(path, &db.name, &"renamed".into()).exists());
+
+        let result = persistence_manager.load_database(&db.name).await.unwrap();
+
+        assert_eq!(result, db);
+    }
+
     #[tokio::test]
     async fn drop_table_basic() {
         let (persistence_manager, ref path) = new_filesystem_manager();
@@ -227,6 +370,12 @@ async fn fix_coverage_noop() {
         .await
         .unwrap();
     NoOp.drop_table(&db.name, &table.schema.name).await.unwrap();
+    NoOp.rename_table(&db, &table.schema.name, &table.schema.name)
+        .await
+        .unwrap();
+
+    NoOp.save_sequences(&db).await.unwrap();
+    NoOp.load_sequences(&db.name).await.unwrap();
 
     NoOp.save_database(&db).await.unwrap();
     NoOp.load_database(&db.name).await.unwrap();
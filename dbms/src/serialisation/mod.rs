@@ -1,21 +1,29 @@
 mod v1;
 mod v2;
+mod v3;
+mod v4;
+
+use sql_parse::parser::MAX_DECIMAL_PRECISION;
 
 use super::SqlError;
 use crate::{
-    database::{RowSet, Table},
-    types::TableSchema,
+    database::{RowSet, Sequence, Table},
+    types::{ColumnValue, Decimal, TableSchema},
     Result,
 };
 
 use v1::V1;
 use v2::V2;
+use v3::V3;
+use v4::V4;
 
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(test, derive(PartialEq))]
 pub enum Serialiser {
     V1,
     V2,
+    V3,
+    V4,
 }
 
 impl From<&Serialiser> for u8 {
@@ -23,6 +31,8 @@ impl From<&Serialiser> for u8 {
         return match value {
             Serialiser::V1 => 1,
             Serialiser::V2 => 2,
+            Serialiser::V3 => 3,
+            Serialiser::V4 => 4,
         };
     }
 }
@@ -37,6 +47,8 @@ impl From<&Serialiser> for Box<dyn Serialise> {
         return match value {
             Serialiser::V1 => Box::new(V1),
             Serialiser::V2 => Box::new(V2),
+            Serialiser::V3 => Box::new(V3),
+            Serialiser::V4 => Box::new(V4),
         };
     }
 }
@@ -48,17 +60,19 @@ impl TryFrom<u8> for Serialiser {
         return match value {
             1 => Ok(Serialiser::V1),
             2 => Ok(Serialiser::V2),
+            3 => Ok(Serialiser::V3),
+            4 => Ok(Serialiser::V4),
             _ => Err(SqlError::IncompatibleVersion(value)),
         };
     }
 }
 
 trait Serialise {
-    fn serialise_table(&self, value: &Table) -> Vec<u8>;
+    fn serialise_table(&self, value: &Table) -> Result<Vec<u8>>;
 
-    fn serialise_rowset(&self, value: &RowSet) -> Vec<u8>;
+    fn serialise_rowset(&self, value: &RowSet) -> Result<Vec<u8>>;
 
-    fn serialise_schemas(&self, value: Vec<&TableSchema>) -> Vec<u8>;
+    fn serialise_schemas(&self, value: Vec<&TableSchema>) -> Result<Vec<u8>>;
 
     fn deserialise_table(&self, input: &mut &[u8]) -> Result<Table>;
 
@@ -68,19 +82,19 @@ trait Serialise {
 }
 
 impl Serialise for Serialiser {
-    fn serialise_table(&self, value: &Table) -> Vec<u8> {
+    fn serialise_table(&self, value: &Table) -> Result<Vec<u8>> {
         let implementation: Box<dyn Serialise> = self.into();
 
         return implementation.serialise_table(value);
     }
 
-    fn serialise_rowset(&self, value: &RowSet) -> Vec<u8> {
+    fn serialise_rowset(&self, value: &RowSet) -> Result<Vec<u8>> {
         let implementation: Box<dyn Serialise> = self.into();
 
         return implementation.serialise_rowset(value);
     }
 
-    fn serialise_schemas(&self, value: Vec<&TableSchema>) -> Vec<u8> {
+    fn serialise_schemas(&self, value: Vec<&TableSchema>) -> Result<Vec<u8>> {
         let implementation: Box<dyn Serialise> = self.into();
 
         return implementation.serialise_schemas(value);
@@ -105,6 +119,196 @@ impl Serialise for Serialiser {
     }
 }
 
+/// The whole part and the digits after the dot of a decimal, as V1 and V2 store it, e.g. 2.5 as `(2, 5)`.
+///
+/// `None` if it's negative, or if its digits after the dot start with a zero,
+/// since those can't be told apart from the same digits without the zero.
+fn split_decimal(value: &Decimal) -> Option<(u64, u64)> {
+    let mantissa = u128::try_from(value.mantissa).ok()?;
+
+    let unit = 10_u128.pow(value.scale);
+
+    let whole = u64::try_from(mantissa / unit).ok()?;
+
+    let mut fractional = mantissa % unit;
+
+    let mut digits = value.scale;
+
+    // Trailing zeros don't change the value, so they can be left out
+    while digits > 0 && fractional.is_multiple_of(10) {
+        fractional /= 10;
+
+        digits -= 1;
+    }
+
+    if digits > 0 && fractional < 10_u128.pow(digits - 1) {
+        return None;
+    }
+
+    return Some((whole, u64::try_from(fractional).ok()?));
+}
+
+/// Puts a decimal split by [`split_decimal`] back together.
+fn join_decimal(whole: u64, fractional: u64) -> Result<Decimal> {
+    let scale = fractional.checked_ilog10().map_or(0, |digits| digits + 1);
+
+    let mantissa = (whole as i128)
+        .checked_mul(10_i128.pow(scale))
+        .and_then(|mantissa| mantissa.checked_add(fractional as i128))
+        .filter(|mantissa| *mantissa < 10_i128.pow(MAX_DECIMAL_PRECISION));
+
+    return match mantissa {
+        Some(mantissa) => Ok(Decimal { mantissa, scale }),
+        None => Err(SqlError::NotADecimal(whole, fractional)),
+    };
+}
+
+/// An integer as V1 and V2 store it, which can't be negative.
+fn unsigned_int(value: &ColumnValue) -> Result<u64> {
+    return match value {
+        ColumnValue::Int(int) => {
+            u64::try_from(*int).map_err(|_| SqlError::IncompatibleValue(value.clone()))
+        }
+        _ => Err(SqlError::IncompatibleValue(value.clone())),
+    };
+}
+
+/// Reads an integer stored by V1 or V2, which didn't have a maximum below that of an unsigned `u64`.
+fn signed_int(value: u64) -> Result<ColumnValue> {
+    return i64::try_from(value)
+        .map(ColumnValue::Int)
+        .map_err(|_| SqlError::NotAnInt(value));
+}
+
+/// One bit per value, set if the value is NULL, starting at the least significant bit of the first byte.
+/// NULLs aren't stored otherwise, since there's nothing to store.
+fn null_bitmap(values: &[ColumnValue]) -> Vec<u8> {
+    let mut result = vec![0; values.len().div_ceil(8)];
+
+    for (index, value) in values.iter().enumerate() {
+        if value.is_null() {
+            result[index / 8] |= 1 << (index % 8);
+        }
+    }
+
+    return result;
+}
+
+/// Reads a bitmap written by [`null_bitmap`] for `count` values.
+fn read_null_bitmap(input: &mut &[u8], count: usize) -> Result<Vec<bool>> {
+    let length = count.div_ceil(8);
+
+    if input.len() < length {
+        return Err(SqlError::InputTooShort(input.len(), length));
+    }
+
+    let result = (0..count)
+        .map(|index| input[index / 8] & (1 << (index % 8)) != 0)
+        .collect();
+
+    *input = &input[length..];
+
+    return Ok(result);
+}
+
+/// An integer as 8 little-endian two's complement bytes, whatever the size of its column.
+fn int_bytes(value: i64) -> Vec<u8> {
+    return value.to_le_bytes().to_vec();
+}
+
+/// Reads an integer written by [`int_bytes`].
+fn read_int(input: &mut &[u8]) -> Result<i64> {
+    const LENGTH: usize = 8;
+
+    if input.len() < LENGTH {
+        return Err(SqlError::InputTooShort(input.len(), LENGTH));
+    }
+
+    let bytes: [u8; LENGTH] = input[..LENGTH]
+        .try_into()
+        .map_err(SqlError::SliceConversionError)?;
+
+    *input = &input[LENGTH..];
+
+    return Ok(i64::from_le_bytes(bytes));
+}
+
+/// A float as its 8 little-endian IEEE 754 bytes, so NaN and infinity survive too.
+fn float_bytes(value: f64) -> Vec<u8> {
+    return value.to_le_bytes().to_vec();
+}
+
+/// Reads a float written by [`float_bytes`].
+fn read_float(input: &mut &[u8]) -> Result<f64> {
+    // Same size and byte order as an int
+    let bits = read_int(input)?;
+
+    return Ok(f64::from_le_bytes(bits.to_le_bytes()));
+}
+
+/// The mantissa of a decimal as 16 little-endian bytes, followed by its scale as one byte.
+///
+/// The scale is stored with every value, since values that aren't in a column keep the scale they were written with.
+fn decimal_bytes(value: &Decimal) -> Vec<u8> {
+    let mut result = value.mantissa.to_le_bytes().to_vec();
+
+    result.push(value.scale as u8);
+
+    return result;
+}
+
+/// Reads a decimal written by [`decimal_bytes`].
+fn read_decimal(input: &mut &[u8]) -> Result<Decimal> {
+    const LENGTH: usize = 17;
+
+    if input.len() < LENGTH {
+        return Err(SqlError::InputTooShort(input.len(), LENGTH));
+    }
+
+    let bytes: [u8; 16] = input[..16]
+        .try_into()
+        .map_err(SqlError::SliceConversionError)?;
+
+    let scale = input[16];
+
+    if scale as u32 > MAX_DECIMAL_PRECISION {
+        return Err(SqlError::NotADecimalScale(scale));
+    }
+
+    *input = &input[LENGTH..];
+
+    return Ok(Decimal {
+        mantissa: i128::from_le_bytes(bytes),
+        scale: scale as u32,
+    });
+}
+
+/// The precision and scale of a decimal type as one byte each, where a precision of 0 means any decimal fits.
+fn decimal_size_bytes(size: Option<(u32, u32)>) -> Vec<u8> {
+    let (precision, scale) = size.unwrap_or((0, 0));
+
+    return vec![precision as u8, scale as u8];
+}
+
+/// Reads a decimal size written by [`decimal_size_bytes`].
+fn read_decimal_size(input: &mut &[u8]) -> Result<Option<(u32, u32)>> {
+    if input.len() < 2 {
+        return Err(SqlError::InputTooShort(input.len(), 2));
+    }
+
+    let (precision, scale) = (input[0], input[1]);
+
+    *input = &input[2..];
+
+    return match (precision as u32, scale as u32) {
+        (0, 0) => Ok(None),
+        (precision, scale) if precision <= MAX_DECIMAL_PRECISION && scale <= precision => {
+            Ok(Some((precision, scale)))
+        }
+        _ => Err(SqlError::NotADecimalSize(precision, scale)),
+    };
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct SerialisationManager(pub Serialiser);
 
@@ -123,26 +327,35 @@ impl SerialisationManager {
         return result;
     }
 
-    pub fn serialise_table(&self, value: &Table) -> Vec<u8> {
+    pub fn serialise_table(&self, value: &Table) -> Result<Vec<u8>> {
         let mut result = self.write_version();
 
-        result.extend(self.0.serialise_table(value));
+        result.extend(self.0.serialise_table(value)?);
 
-        return result;
+        return Ok(result);
     }
 
-    pub fn serialise_rowset(&self, value: &RowSet) -> Vec<u8> {
+    pub fn serialise_rowset(&self, value: &RowSet) -> Result<Vec<u8>> {
         let mut result = self.write_version();
 
-        result.extend(self.0.serialise_rowset(value));
+        result.extend(self.0.serialise_rowset(value)?);
 
-        return result;
+        return Ok(result);
     }
 
-    pub fn serialise_schemas(&self, value: Vec<&TableSchema>) -> Vec<u8> {
+    pub fn serialise_schemas(&self, value: Vec<&TableSchema>) -> Result<Vec<u8>> {
         let mut result = self.write_version();
 
-        result.extend(self.0.serialise_schemas(value));
+        result.extend(self.0.serialise_schemas(value)?);
+
+        return Ok(result);
+    }
+
+    /// Always written with V4, the first version that knows about sequences.
+    pub fn serialise_sequences(&self, value: Vec<&Sequence>) -> Vec<u8> {
+        let mut result = vec![Serialiser::V4.into()];
+
+        result.extend(v4::serialise_sequences(value));
 
         return result;
     }
@@ -182,6 +395,15 @@ impl SerialisationManager {
 
         return serialiser.deserialise_schemas(input);
     }
+
+    pub fn deserialise_sequences(&self, mut input: &[u8]) -> Result<Vec<Sequence>> {
+        let input = &mut input;
+
+        return match self.read_version(input)? {
+            Serialiser::V4 => v4::deserialise_sequences(input),
+            serialiser => Err(SqlError::IncompatibleVersion(serialiser.into())),
+        };
+    }
 }
 
 #[cfg(test)]
@@ -196,7 +418,7 @@ mod tests {
 
         let manager = SerialisationManager::new(Serialiser::V1);
 
-        let serialised = manager.serialise_table(&table);
+        let serialised = manager.serialise_table(&table).unwrap();
 
         assert_eq!(serialised.first().unwrap(), &1);
 
@@ -204,7 +426,7 @@ mod tests {
             .select(crate::types::ColumnSelector::AllColumns, None)
             .unwrap();
 
-        let serialised = manager.serialise_rowset(&rowset);
+        let serialised = manager.serialise_rowset(&rowset).unwrap();
 
         assert_eq!(serialised.first().unwrap(), &1);
     }
@@ -215,7 +437,7 @@ mod tests {
 
         let manager = SerialisationManager::new(Serialiser::V1);
 
-        let mut serialised = manager.serialise_table(&table);
+        let mut serialised = manager.serialise_table(&table).unwrap();
 
         let first = serialised.first_mut().unwrap();
 
@@ -230,7 +452,7 @@ mod tests {
             .select(crate::types::ColumnSelector::AllColumns, None)
             .unwrap();
 
-        let mut serialised = manager.serialise_rowset(&rowset);
+        let mut serialised = manager.serialise_rowset(&rowset).unwrap();
 
         let first = serialised.first_mut().unwrap();
 
@@ -1,3 +1,5 @@
+//! Its layout can't change anymore, since there are clients using it,
+//! so values that didn't exist yet when it was made, like NULL, can't be serialised.
 #[cfg(test)]
 mod tests;
 
@@ -8,22 +10,23 @@ use crate::{Result, SqlError};
 use crate::database::{Row, RowSet, Table};
 use crate::types::{ColumnName, ColumnValue, TableName, TableSchema};
 
-use super::Serialise;
+use super::{join_decimal, signed_int, split_decimal, unsigned_int, Serialise};
 
 #[derive(Debug)]
 pub struct V1;
 
 impl Serialise for V1 {
-    fn serialise_table(&self, value: &Table) -> Vec<u8> {
+    fn serialise_table(&self, value: &Table) -> Result<Vec<u8>> {
         return value.serialise();
     }
 
-    fn serialise_rowset(&self, value: &RowSet) -> Vec<u8> {
+    fn serialise_rowset(&self, value: &RowSet) -> Result<Vec<u8>> {
         return value.serialise();
     }
 
-    fn serialise_schemas(&self, _: Vec<&TableSchema>) -> Vec<u8> {
-        panic!("V1 can't serialise schemas");
+    // V1 can't serialise schemas
+    fn serialise_schemas(&self, _: Vec<&TableSchema>) -> Result<Vec<u8>> {
+        return Err(SqlError::IncompatibleVersion(1));
     }
 
     fn deserialise_table(&self, input: &mut &[u8]) -> Result<Table> {
@@ -35,7 +38,7 @@ impl Serialise for V1 {
     }
 
     fn deserialise_schemas(&self, _: &mut &[u8]) -> Result<Vec<TableSchema>> {
-        panic!("V1 can't deserialise schemas");
+        return Err(SqlError::IncompatibleVersion(1));
     }
 }
 
@@ -57,7 +60,7 @@ impl From<Option<DO>> for DO {
 }
 
 trait V1Serialise {
-    fn serialise(&self) -> Vec<u8>;
+    fn serialise(&self) -> Result<Vec<u8>>;
 }
 
 trait V1Deserialise {
@@ -69,56 +72,58 @@ trait V1Deserialise {
 const SIZEOF_USIZE: usize = std::mem::size_of::<usize>();
 
 impl V1Serialise for Table {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         let mut result = vec![];
 
-        let name = self.schema.name.serialise();
+        let name = self.schema.name.serialise()?;
 
         result.extend(name);
 
-        let types = self.schema.types.serialise();
+        let types = self.schema.types.serialise()?;
 
         result.extend(types);
 
-        let names = self.schema.column_names.serialise();
+        let names = self.schema.column_names.serialise()?;
 
         result.extend(names);
 
-        let values = self.values.serialise();
+        let values = self.values.serialise()?;
 
         result.extend(values);
 
-        return result;
+        return Ok(result);
     }
 }
 
 impl V1Serialise for TableName {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         return self.0.serialise();
     }
 }
 
 impl V1Serialise for ColumnType {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         // Start counting at 1 to make sure uninitialised data isn't a valid type
         // (for what it's worth)
         return match self {
-            ColumnType::Int => vec![1],
-            ColumnType::Decimal => vec![2],
-            ColumnType::Text => vec![3],
-            ColumnType::Bool => vec![4],
+            // Integers of any size are stored the same way, and any decimal fits
+            ColumnType::SmallInt | ColumnType::Int | ColumnType::BigInt => Ok(vec![1]),
+            ColumnType::Decimal(_) => Ok(vec![2]),
+            ColumnType::Text => Ok(vec![3]),
+            ColumnType::Bool => Ok(vec![4]),
+            _ => Err(SqlError::IncompatibleType(*self)),
         };
     }
 }
 
 impl V1Serialise for ColumnName {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         return self.0.serialise();
     }
 }
 
 impl V1Serialise for Row {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         return self.0.serialise();
     }
 }
@@ -136,30 +141,34 @@ impl V1Serialise for Row {
 // see above about negative impl. Doing it now would just be unnecessary complexity
 
 impl V1Serialise for ColumnValue {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         return match self {
-            ColumnValue::Int(value) => (*value).serialise(),
-            ColumnValue::Decimal(whole, fractional) => {
-                let mut result = whole.serialise();
+            ColumnValue::Int(_) => (unsigned_int(self)? as usize).serialise(),
+            ColumnValue::Decimal(value) => {
+                let (whole, fractional) = split_decimal(value)
+                    .ok_or_else(|| SqlError::IncompatibleValue(self.clone()))?;
+
+                let mut result = (whole as usize).serialise()?;
 
-                result.extend(fractional.serialise());
+                result.extend((fractional as usize).serialise()?);
 
-                result
+                Ok(result)
             }
             ColumnValue::Str(value) => {
-                let mut result = value.len().serialise();
+                let mut result = value.len().serialise()?;
 
                 result.extend(value.as_bytes());
 
-                result
+                Ok(result)
             }
-            ColumnValue::Bool(value) => vec![*value as u8],
+            ColumnValue::Bool(value) => Ok(vec![*value as u8]),
+            _ => Err(SqlError::IncompatibleValue(self.clone())),
         };
     }
 }
 
 impl V1Serialise for usize {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         // https://stackoverflow.com/questions/72631065/how-to-convert-a-u32-array-to-a-u8-array-in-place
         let mut result = Vec::with_capacity(SIZEOF_USIZE);
 
@@ -167,46 +176,46 @@ impl V1Serialise for usize {
             result.push(byte)
         }
 
-        return result;
+        return Ok(result);
     }
 }
 
 impl V1Serialise for String {
-    fn serialise(&self) -> Vec<u8> {
-        let mut result = self.len().serialise();
+    fn serialise(&self) -> Result<Vec<u8>> {
+        let mut result = self.len().serialise()?;
 
         result.extend(self.bytes());
 
-        return result;
+        return Ok(result);
     }
 }
 
 impl<T: V1Serialise> V1Serialise for Vec<T> {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         let mut result = vec![];
 
         // First store total count
-        result.extend(self.len().serialise());
+        result.extend(self.len().serialise()?);
 
         for t in self {
-            let bytes = t.serialise();
+            let bytes = t.serialise()?;
 
             result.extend(bytes);
         }
 
-        return result;
+        return Ok(result);
     }
 }
 
 impl V1Serialise for RowSet {
-    fn serialise(&self) -> Vec<u8> {
-        let mut result = self.types.serialise();
+    fn serialise(&self) -> Result<Vec<u8>> {
+        let mut result = self.types.serialise()?;
 
-        result.extend(self.names.serialise());
+        result.extend(self.names.serialise()?);
 
-        result.extend(self.values.serialise());
+        result.extend(self.values.serialise()?);
 
-        return result;
+        return Ok(result);
     }
 }
 
@@ -222,17 +231,24 @@ impl V1Deserialise for Table {
 
         let values = Vec::<Row>::deserialise(input, DO::ColumnTypes(types.clone()))?;
 
+        let defaults = vec![None; column_names.len()];
+
         let schema = TableSchema {
             name,
+            // NOT NULL didn't exist yet
+            nullable: vec![true; column_names.len()],
             column_names,
             types,
+            // Keys are only stored from V4 on
+            unique: vec![],
+            // Neither are defaults and checks
+            defaults,
+            checks: vec![],
+            indexes: vec![],
         };
 
-        return Ok(Table {
-            schema,
-            values,
-            constraints: todo!(),
-        });
+        // Foreign keys are only stored from V4 on
+        return Table::from_parts(schema, values, vec![]);
     }
 }
 
@@ -276,7 +292,7 @@ impl V1Deserialise for ColumnType {
 
         return match byte {
             1 => Ok(ColumnType::Int),
-            2 => Ok(ColumnType::Decimal),
+            2 => Ok(ColumnType::Decimal(None)),
             3 => Ok(ColumnType::Text),
             4 => Ok(ColumnType::Bool),
             _ => Err(SqlError::NotATypeDiscriminator(byte)),
@@ -393,15 +409,19 @@ impl V1Deserialise for ColumnValue {
         }?;
 
         let result = match column_type {
-            ColumnType::Int => ColumnValue::Int(usize::deserialise(input, None.into())?),
-            ColumnType::Decimal => {
+            ColumnType::SmallInt | ColumnType::Int | ColumnType::BigInt => {
+                signed_int(usize::deserialise(input, None.into())? as u64)?
+            }
+            ColumnType::Decimal(_) => {
                 let whole = usize::deserialise(input, None.into())?;
                 let fractional = usize::deserialise(input, None.into())?;
 
-                ColumnValue::Decimal(whole, fractional)
+                ColumnValue::Decimal(join_decimal(whole as u64, fractional as u64)?)
             }
             ColumnType::Text => ColumnValue::Str(String::deserialise(input, None.into())?),
             ColumnType::Bool => ColumnValue::Bool(bool::deserialise(input, None.into())?),
+            // Never read from the input, see ColumnType::deserialise
+            _ => return Err(SqlError::IncompatibleType(column_type)),
         };
 
         return Ok(result);
@@ -439,7 +459,7 @@ impl V1Deserialise for RowSet {
     where
         Self: Sized,
     {
-        let types = Vec::<ColumnType>::deserialise(input, None.into()).unwrap();
+        let types = Vec::<ColumnType>::deserialise(input, None.into())?;
 
         let names = Vec::<ColumnName>::deserialise(input, None.into())?;
 
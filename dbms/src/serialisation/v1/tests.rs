@@ -2,7 +2,7 @@ use super::*;
 
 use crate::database::Row;
 use crate::types::{ColumnName, ColumnValue, TableName};
-use crate::utils::tests::{test_table, test_table_with_values};
+use crate::utils::tests::{decimal, test_table, test_table_with_values};
 use sql_parse::parser::ColumnType;
 
 // value -> [value, 0, 0..] to match length of usize
@@ -17,13 +17,13 @@ fn serialised_usize(value: u8) -> Vec<u8> {
 #[test]
 fn serialise_column_types() {
     let types = vec![
-        ColumnType::Decimal,
+        ColumnType::Decimal(None),
         ColumnType::Int,
         ColumnType::Bool,
         ColumnType::Text,
     ];
 
-    let serialised = types.serialise();
+    let serialised = types.serialise().unwrap();
 
     let mut expected = serialised_usize(4);
     expected.extend([2, 1, 4, 3]);
@@ -35,7 +35,7 @@ fn serialise_column_types() {
 fn serialise_column_names() {
     let names: Vec<ColumnName> = vec!["asdf".into(), "hello".into()];
 
-    let serialised = names.serialise();
+    let serialised = names.serialise().unwrap();
 
     let mut expected = serialised_usize(2);
     expected.extend(serialised_usize(4));
@@ -50,13 +50,13 @@ fn serialise_column_names() {
 fn serialise_column_values() {
     let values: Vec<ColumnValue> = vec![
         1.into(),
-        (420, 69).into(),
+        decimal(42069, 2),
         "hey".into(),
         true.into(),
         false.into(),
     ];
 
-    let serialised = values.serialise();
+    let serialised = values.serialise().unwrap();
 
     let buffer = [0_u8; SIZEOF_USIZE];
 
@@ -103,24 +103,24 @@ fn serialise_row() {
 
     let mut expected = serialised_usize(2);
 
-    expected.extend(row1.serialise());
-    expected.extend(row2.serialise());
+    expected.extend(row1.serialise().unwrap());
+    expected.extend(row2.serialise().unwrap());
 
-    assert_eq!(input.serialise(), expected);
+    assert_eq!(input.serialise().unwrap(), expected);
 
     let input: &mut Vec<Row> = &mut vec![];
 
     // Just the length
     let expected = serialised_usize(0);
 
-    assert_eq!(input.serialise(), expected);
+    assert_eq!(input.serialise().unwrap(), expected);
 }
 
 #[test]
 fn serialise_table() {
     let table = test_table();
 
-    let serialised = V1.serialise_table(&table);
+    let serialised = V1.serialise_table(&table).unwrap();
 
     // Names
     let mut expected = serialised_usize(10);
@@ -144,7 +144,7 @@ fn serialise_table() {
 
     let (table, _) = test_table_with_values();
 
-    let serialised = V1.serialise_table(&table);
+    let serialised = V1.serialise_table(&table).unwrap();
 
     let mut expected = expected.get(0..expected.len() - 8).unwrap().to_vec();
 
@@ -183,7 +183,9 @@ fn deserialise_usize() {
 
 #[test]
 fn deserialise_column_type() {
-    let input = vec![ColumnType::Int, ColumnType::Bool, ColumnType::Text].serialise();
+    let input = vec![ColumnType::Int, ColumnType::Bool, ColumnType::Text]
+        .serialise()
+        .unwrap();
 
     let input = &mut input.as_slice();
 
@@ -213,7 +215,9 @@ fn deserialise_column_type() {
 
 #[test]
 fn deserialise_table_name() {
-    let input = vec![TableName("a".into()), "abcd".into(), "meme".into()].serialise();
+    let input = vec![TableName("a".into()), "abcd".into(), "meme".into()]
+        .serialise()
+        .unwrap();
 
     let input = &mut input.as_slice();
 
@@ -244,7 +248,7 @@ fn deserialise_table_name() {
 
 #[test]
 fn deserialise_column_name() {
-    let input = ColumnName("hey".into()).serialise();
+    let input = ColumnName("hey".into()).serialise().unwrap();
     let input = &mut input.as_slice();
 
     assert_eq!(
@@ -252,7 +256,7 @@ fn deserialise_column_name() {
         "hey".into()
     );
 
-    let input = ColumnName("".into()).serialise();
+    let input = ColumnName("".into()).serialise().unwrap();
     let input = &mut input.as_slice();
 
     assert_eq!(
@@ -266,9 +270,10 @@ fn deserialise_vector_fixed_length_item() {
         ColumnType::Int,
         ColumnType::Bool,
         ColumnType::Text,
-        ColumnType::Decimal,
+        ColumnType::Decimal(None),
     ]
-    .serialise();
+    .serialise()
+    .unwrap();
     let input = &mut input.as_slice();
 
     assert_eq!(
@@ -277,7 +282,7 @@ fn deserialise_vector_fixed_length_item() {
             ColumnType::Int,
             ColumnType::Bool,
             ColumnType::Text,
-            ColumnType::Decimal,
+            ColumnType::Decimal(None),
         ]
     );
 
@@ -290,7 +295,7 @@ fn deserialise_vector_fixed_length_item() {
     assert!(matches!(result, Err(SqlError::NotATypeDiscriminator(_))));
 
     // Too short
-    let input = vec![ColumnType::Int, ColumnType::Bool].serialise();
+    let input = vec![ColumnType::Int, ColumnType::Bool].serialise().unwrap();
     let input = &mut input.as_slice();
 
     // Length
@@ -301,7 +306,9 @@ fn deserialise_vector_fixed_length_item() {
 
 #[test]
 fn deserialise_vector_variable_length_item() {
-    let input = vec![ColumnName("a".into()), ColumnName("abc".into())].serialise();
+    let input = vec![ColumnName("a".into()), ColumnName("abc".into())]
+        .serialise()
+        .unwrap();
     let input = &mut input.as_slice();
 
     assert_eq!(
@@ -324,11 +331,11 @@ fn deserialise_bool_invalid_values() {
 fn deserialise_column_values() {
     let values = vec![
         ColumnValue::Int(1),
-        (420, 69).into(),
+        decimal(42069, 2),
         "hey".into(),
         true.into(),
     ];
-    let input = values.serialise();
+    let input = values.serialise().unwrap();
     let input = &mut input.as_slice();
 
     assert_eq!(
@@ -336,7 +343,7 @@ fn deserialise_column_values() {
             input,
             DO::ColumnTypes(vec![
                 ColumnType::Int,
-                ColumnType::Decimal,
+                ColumnType::Decimal(None),
                 ColumnType::Text,
                 ColumnType::Bool,
             ])
@@ -352,7 +359,7 @@ fn deserialise_column_values_empty_input() {
         &mut [].as_slice(),
         DO::ColumnTypes(vec![
             ColumnType::Int,
-            ColumnType::Decimal,
+            ColumnType::Decimal(None),
             ColumnType::Text,
             ColumnType::Bool,
         ]),
@@ -367,14 +374,18 @@ fn deserialise_column_values_empty_input() {
 fn deserialise_column_values_fewer_types_than_values() {
     let values = vec![
         ColumnValue::Int(1),
-        (420, 69).into(),
+        decimal(42069, 2),
         "hey".into(),
         true.into(),
     ];
 
     let result = Vec::<ColumnValue>::deserialise(
-        &mut values.serialise().as_slice(),
-        DO::ColumnTypes(vec![ColumnType::Int, ColumnType::Decimal, ColumnType::Text]),
+        &mut values.serialise().unwrap().as_slice(),
+        DO::ColumnTypes(vec![
+            ColumnType::Int,
+            ColumnType::Decimal(None),
+            ColumnType::Text,
+        ]),
     );
 
     dbg!(&result);
@@ -386,7 +397,9 @@ fn deserialise_column_values_fewer_types_than_values() {
 fn deserialise_row_vector() {
     let (_, (row1, row2)) = test_table_with_values();
 
-    let input = vec![Row(row1.clone()), Row(row2.clone())].serialise();
+    let input = vec![Row(row1.clone()), Row(row2.clone())]
+        .serialise()
+        .unwrap();
     let input = &mut input.as_slice();
 
     assert_eq!(
@@ -402,14 +415,14 @@ fn deserialise_row_vector() {
 #[test]
 fn deserialise_table() {
     // We test serialise_table separately, so this is fine I guess
-    let table = test_table().serialise();
+    let table = test_table().serialise().unwrap();
     let input = &mut table.as_slice();
 
     let result = V1.deserialise_table(input).unwrap();
 
     assert_eq!(result, test_table());
 
-    let table = test_table_with_values().0.serialise();
+    let table = test_table_with_values().0.serialise().unwrap();
     let input = &mut table.as_slice();
 
     let result = V1.deserialise_table(input).unwrap();
@@ -425,7 +438,7 @@ fn serialise_rowset() {
         .select(crate::types::ColumnSelector::AllColumns, None)
         .unwrap();
 
-    let serialised = result.serialise();
+    let serialised = result.serialise().unwrap();
 
     // Types
     let mut expected = serialised_usize(2);
@@ -458,9 +471,38 @@ fn deserialise_rowset() {
         .select(crate::types::ColumnSelector::AllColumns, None)
         .unwrap();
 
-    let serialised = result.serialise();
+    let serialised = result.serialise().unwrap();
 
     let deserialised = V1.deserialise_rowset(&mut serialised.as_slice()).unwrap();
 
     assert_eq!(result, deserialised,);
 }
+
+#[test]
+fn serialise_newer_values() {
+    let types = vec![ColumnType::BigInt, ColumnType::Decimal(Some((10, 2)))];
+
+    // Stored like the types that existed back then
+    let mut expected = serialised_usize(2);
+
+    expected.extend([1, 2]);
+
+    assert_eq!(types.serialise().unwrap(), expected);
+
+    let result = ColumnType::Blob.serialise();
+
+    assert!(matches!(result, Err(SqlError::IncompatibleType(_))));
+
+    for value in [ColumnValue::Null, ColumnValue::Int(-1), decimal(105, 2)] {
+        let result = Row(vec![value.clone()]).serialise();
+
+        assert!(
+            matches!(result, Err(SqlError::IncompatibleValue(_))),
+            "{value:?}: {result:?}"
+        );
+    }
+
+    let result = V1.serialise_schemas(vec![]);
+
+    assert!(matches!(result, Err(SqlError::IncompatibleVersion(1))));
+}
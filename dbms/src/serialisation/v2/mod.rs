@@ -1,4 +1,7 @@
 //! Essentially the same as v1, except it uses u64 instead of usize for sizes
+//!
+//! Its layout can't change anymore, since there are files and clients using it,
+//! so values that didn't exist yet when it was made, like NULL, can't be serialised.
 #[cfg(test)]
 mod tests;
 
@@ -10,21 +13,21 @@ use crate::{
     Result, SqlError,
 };
 
-use super::Serialise;
+use super::{join_decimal, signed_int, split_decimal, unsigned_int, Serialise};
 
 #[derive(Debug)]
 pub struct V2;
 
 impl Serialise for V2 {
-    fn serialise_table(&self, value: &Table) -> Vec<u8> {
+    fn serialise_table(&self, value: &Table) -> Result<Vec<u8>> {
         return value.serialise();
     }
 
-    fn serialise_rowset(&self, value: &RowSet) -> Vec<u8> {
+    fn serialise_rowset(&self, value: &RowSet) -> Result<Vec<u8>> {
         return value.serialise();
     }
 
-    fn serialise_schemas(&self, value: Vec<&TableSchema>) -> Vec<u8> {
+    fn serialise_schemas(&self, value: Vec<&TableSchema>) -> Result<Vec<u8>> {
         return value.serialise();
     }
 
@@ -59,7 +62,7 @@ impl From<Option<DO>> for DO {
 }
 
 trait V2Serialise {
-    fn serialise(&self) -> Vec<u8>;
+    fn serialise(&self) -> Result<Vec<u8>>;
 }
 
 trait V2Deserialise {
@@ -69,74 +72,76 @@ trait V2Deserialise {
 }
 
 impl V2Serialise for Table {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         let mut result = vec![];
 
-        let schema = self.schema.serialise();
+        let schema = self.schema.serialise()?;
 
         result.extend(schema);
 
-        let values = self.values.serialise();
+        let values = self.values.serialise()?;
 
         result.extend(values);
 
-        return result;
+        return Ok(result);
     }
 }
 
 impl V2Serialise for TableSchema {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         return (&self).serialise();
     }
 }
 
 impl V2Serialise for &TableSchema {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         let mut result = vec![];
 
-        let name = self.name.serialise();
+        let name = self.name.serialise()?;
 
         result.extend(name);
 
-        let types = self.types.serialise();
+        let types = self.types.serialise()?;
 
         result.extend(types);
 
-        let names = self.column_names.serialise();
+        let names = self.column_names.serialise()?;
 
         result.extend(names);
 
-        return result;
+        return Ok(result);
     }
 }
 
 impl V2Serialise for TableName {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         return self.0.serialise();
     }
 }
 
 impl V2Serialise for ColumnType {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         // Start counting at 1 to make sure uninitialised data isn't a valid type
         // (for what it's worth)
         return match self {
-            ColumnType::Int => vec![1],
-            ColumnType::Decimal => vec![2],
-            ColumnType::Text => vec![3],
-            ColumnType::Bool => vec![4],
+            // Integers of any size are stored the same way, and any decimal fits
+            ColumnType::SmallInt | ColumnType::Int | ColumnType::BigInt => Ok(vec![1]),
+            ColumnType::Decimal(_) => Ok(vec![2]),
+            ColumnType::Text => Ok(vec![3]),
+            ColumnType::Bool => Ok(vec![4]),
+            _ => Err(SqlError::IncompatibleType(*self)),
         };
     }
 }
 
 impl V2Serialise for ColumnName {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         return self.0.serialise();
     }
 }
 
 impl V2Serialise for Row {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         return self.0.serialise();
     }
 }
@@ -154,30 +159,34 @@ impl V2Serialise for Row {
 // see above about negative impl. Doing it now would just be unnecessary complexity
 
 impl V2Serialise for ColumnValue {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         return match self {
-            ColumnValue::Int(value) => (*value as u64).serialise(),
-            ColumnValue::Decimal(whole, fractional) => {
-                let mut result = (*whole as u64).serialise();
+            ColumnValue::Int(_) => unsigned_int(self)?.serialise(),
+            ColumnValue::Decimal(value) => {
+                let (whole, fractional) = split_decimal(value)
+                    .ok_or_else(|| SqlError::IncompatibleValue(self.clone()))?;
+
+                let mut result = whole.serialise()?;
 
-                result.extend((*fractional as u64).serialise());
+                result.extend(fractional.serialise()?);
 
-                result
+                Ok(result)
             }
             ColumnValue::Str(value) => {
-                let mut result = (value.len() as u64).serialise();
+                let mut result = (value.len() as u64).serialise()?;
 
                 result.extend(value.as_bytes());
 
-                result
+                Ok(result)
             }
-            ColumnValue::Bool(value) => vec![*value as u8],
+            ColumnValue::Bool(value) => Ok(vec![*value as u8]),
+            _ => Err(SqlError::IncompatibleValue(self.clone())),
         };
     }
 }
 
 impl V2Serialise for u64 {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         // https://stackoverflow.com/questions/72631065/how-to-convert-a-u32-array-to-a-u8-array-in-place
         let mut result = Vec::with_capacity(8);
 
@@ -185,46 +194,46 @@ impl V2Serialise for u64 {
             result.push(byte)
         }
 
-        return result;
+        return Ok(result);
     }
 }
 
 impl V2Serialise for String {
-    fn serialise(&self) -> Vec<u8> {
-        let mut result = (self.len() as u64).serialise();
+    fn serialise(&self) -> Result<Vec<u8>> {
+        let mut result = (self.len() as u64).serialise()?;
 
         result.extend(self.bytes());
 
-        return result;
+        return Ok(result);
     }
 }
 
 impl<T: V2Serialise> V2Serialise for Vec<T> {
-    fn serialise(&self) -> Vec<u8> {
+    fn serialise(&self) -> Result<Vec<u8>> {
         let mut result = vec![];
 
         // First store total count
-        result.extend((self.len() as u64).serialise());
+        result.extend((self.len() as u64).serialise()?);
 
         for t in self {
-            let bytes = t.serialise();
+            let bytes = t.serialise()?;
 
             result.extend(bytes);
         }
 
-        return result;
+        return Ok(result);
     }
 }
 
 impl V2Serialise for RowSet {
-    fn serialise(&self) -> Vec<u8> {
-        let mut result = self.types.serialise();
+    fn serialise(&self) -> Result<Vec<u8>> {
+        let mut result = self.types.serialise()?;
 
-        result.extend(self.names.serialise());
+        result.extend(self.names.serialise()?);
 
-        result.extend(self.values.serialise());
+        result.extend(self.values.serialise()?);
 
-        return result;
+        return Ok(result);
     }
 }
 
@@ -234,11 +243,8 @@ impl V2Deserialise for Table {
 
         let values = Vec::<Row>::deserialise(input, DO::ColumnTypes(schema.types.clone()))?;
 
-        return Ok(Table {
-            schema,
-            values,
-            constraints: todo!(),
-        });
+        // Foreign keys are only stored from V4 on
+        return Table::from_parts(schema, values, vec![]);
     }
 }
 
@@ -253,10 +259,20 @@ impl V2Deserialise for TableSchema {
 
         let column_names = Vec::<ColumnName>::deserialise(input, None.into())?;
 
+        let defaults = vec![None; column_names.len()];
+
         return Ok(TableSchema {
             name,
+            // NOT NULL didn't exist yet
+            nullable: vec![true; column_names.len()],
             column_names,
             types,
+            // Keys are only stored from V4 on
+            unique: vec![],
+            // Neither are defaults and checks
+            defaults,
+            checks: vec![],
+            indexes: vec![],
         });
     }
 }
@@ -318,7 +334,7 @@ impl V2Deserialise for ColumnType {
 
         return match byte {
             1 => Ok(ColumnType::Int),
-            2 => Ok(ColumnType::Decimal),
+            2 => Ok(ColumnType::Decimal(None)),
             3 => Ok(ColumnType::Text),
             4 => Ok(ColumnType::Bool),
             _ => Err(SqlError::NotATypeDiscriminator(byte)),
@@ -435,15 +451,19 @@ impl V2Deserialise for ColumnValue {
         }?;
 
         let result = match column_type {
-            ColumnType::Int => ColumnValue::Int(u64::deserialise(input, None.into())? as usize),
-            ColumnType::Decimal => {
+            ColumnType::SmallInt | ColumnType::Int | ColumnType::BigInt => {
+                signed_int(u64::deserialise(input, None.into())?)?
+            }
+            ColumnType::Decimal(_) => {
                 let whole = u64::deserialise(input, None.into())?;
                 let fractional = u64::deserialise(input, None.into())?;
 
-                ColumnValue::Decimal(whole as usize, fractional as usize)
+                ColumnValue::Decimal(join_decimal(whole, fractional)?)
             }
             ColumnType::Text => ColumnValue::Str(String::deserialise(input, None.into())?),
             ColumnType::Bool => ColumnValue::Bool(bool::deserialise(input, None.into())?),
+            // Never read from the input, see ColumnType::deserialise
+            _ => return Err(SqlError::IncompatibleType(column_type)),
         };
 
         return Ok(result);
@@ -481,7 +501,7 @@ impl V2Deserialise for RowSet {
     where
         Self: Sized,
     {
-        let types = Vec::<ColumnType>::deserialise(input, None.into()).unwrap();
+        let types = Vec::<ColumnType>::deserialise(input, None.into())?;
 
         let names = Vec::<ColumnName>::deserialise(input, None.into())?;
 
@@ -3,19 +3,19 @@ use super::*;
 
 use crate::database::Row;
 use crate::types::{ColumnName, ColumnValue, TableName};
-use crate::utils::tests::{test_table, test_table_with_values};
+use crate::utils::tests::{decimal, test_table, test_table_with_values};
 use sql_parse::parser::ColumnType;
 
 #[test]
 fn serialise_column_types() {
     let types = vec![
-        ColumnType::Decimal,
+        ColumnType::Decimal(None),
         ColumnType::Int,
         ColumnType::Bool,
         ColumnType::Text,
     ];
 
-    let serialised = types.serialise();
+    let serialised = types.serialise().unwrap();
 
     assert_eq!(
         serialised,
@@ -30,7 +30,7 @@ fn serialise_column_types() {
 fn serialise_column_names() {
     let names: Vec<ColumnName> = vec!["asdf".into(), "hello".into()];
 
-    let serialised = names.serialise();
+    let serialised = names.serialise().unwrap();
     assert_eq!(
         serialised,
         vec![
@@ -46,13 +46,13 @@ fn serialise_column_names() {
 fn serialise_column_values() {
     let values: Vec<ColumnValue> = vec![
         1.into(),
-        (420, 69).into(),
+        decimal(42069, 2),
         "hey".into(),
         true.into(),
         false.into(),
     ];
 
-    let serialised = values.serialise();
+    let serialised = values.serialise().unwrap();
 
     let buffer = [0_u8; 8];
 
@@ -114,24 +114,24 @@ fn serialise_row() {
 
     let mut expected = vec![2, 0, 0, 0, 0, 0, 0, 0];
 
-    expected.extend(row1.serialise());
-    expected.extend(row2.serialise());
+    expected.extend(row1.serialise().unwrap());
+    expected.extend(row2.serialise().unwrap());
 
-    assert_eq!(input.serialise(), expected);
+    assert_eq!(input.serialise().unwrap(), expected);
 
     let input: &mut Vec<Row> = &mut vec![];
 
     // Just the length
     let expected = vec![0, 0, 0, 0, 0, 0, 0, 0];
 
-    assert_eq!(input.serialise(), expected);
+    assert_eq!(input.serialise().unwrap(), expected);
 }
 
 #[test]
 fn serialise_table() {
     let table = test_table();
 
-    let serialised = V2.serialise_table(&table);
+    let serialised = V2.serialise_table(&table).unwrap();
 
     let expected = vec![
         // Name
@@ -146,7 +146,7 @@ fn serialise_table() {
 
     let (table, _) = test_table_with_values();
 
-    let serialised = V2.serialise_table(&table);
+    let serialised = V2.serialise_table(&table).unwrap();
 
     let expected = vec![
         // Name
@@ -179,7 +179,9 @@ fn deserialise_u64() {
 
 #[test]
 fn deserialise_column_type() {
-    let input = vec![ColumnType::Int, ColumnType::Bool, ColumnType::Text].serialise();
+    let input = vec![ColumnType::Int, ColumnType::Bool, ColumnType::Text]
+        .serialise()
+        .unwrap();
 
     let input = &mut input.as_slice();
 
@@ -209,7 +211,9 @@ fn deserialise_column_type() {
 
 #[test]
 fn deserialise_table_name() {
-    let input = vec![TableName("a".into()), "abcd".into(), "meme".into()].serialise();
+    let input = vec![TableName("a".into()), "abcd".into(), "meme".into()]
+        .serialise()
+        .unwrap();
 
     let input = &mut input.as_slice();
 
@@ -240,7 +244,7 @@ fn deserialise_table_name() {
 
 #[test]
 fn deserialise_column_name() {
-    let input = ColumnName("hey".into()).serialise();
+    let input = ColumnName("hey".into()).serialise().unwrap();
     let input = &mut input.as_slice();
 
     assert_eq!(
@@ -248,7 +252,7 @@ fn deserialise_column_name() {
         "hey".into()
     );
 
-    let input = ColumnName("".into()).serialise();
+    let input = ColumnName("".into()).serialise().unwrap();
     let input = &mut input.as_slice();
 
     assert_eq!(
@@ -262,9 +266,10 @@ fn deserialise_vector_fixed_length_item() {
         ColumnType::Int,
         ColumnType::Bool,
         ColumnType::Text,
-        ColumnType::Decimal,
+        ColumnType::Decimal(None),
     ]
-    .serialise();
+    .serialise()
+    .unwrap();
     let input = &mut input.as_slice();
 
     assert_eq!(
@@ -273,7 +278,7 @@ fn deserialise_vector_fixed_length_item() {
             ColumnType::Int,
             ColumnType::Bool,
             ColumnType::Text,
-            ColumnType::Decimal,
+            ColumnType::Decimal(None),
         ]
     );
 
@@ -285,7 +290,7 @@ fn deserialise_vector_fixed_length_item() {
     assert!(matches!(result, Err(SqlError::NotATypeDiscriminator(_))));
 
     // Too short
-    let input = vec![ColumnType::Int, ColumnType::Bool].serialise();
+    let input = vec![ColumnType::Int, ColumnType::Bool].serialise().unwrap();
     let input = &mut input.as_slice();
 
     // Length
@@ -296,7 +301,9 @@ fn deserialise_vector_fixed_length_item() {
 
 #[test]
 fn deserialise_vector_variable_length_item() {
-    let input = vec![ColumnName("a".into()), ColumnName("abc".into())].serialise();
+    let input = vec![ColumnName("a".into()), ColumnName("abc".into())]
+        .serialise()
+        .unwrap();
     let input = &mut input.as_slice();
 
     assert_eq!(
@@ -319,11 +326,11 @@ fn deserialise_bool_invalid_values() {
 fn deserialise_column_values() {
     let values = vec![
         ColumnValue::Int(1),
-        (420, 69).into(),
+        decimal(42069, 2),
         "hey".into(),
         true.into(),
     ];
-    let input = values.serialise();
+    let input = values.serialise().unwrap();
     let input = &mut input.as_slice();
 
     assert_eq!(
@@ -331,7 +338,7 @@ fn deserialise_column_values() {
             input,
             DO::ColumnTypes(vec![
                 ColumnType::Int,
-                ColumnType::Decimal,
+                ColumnType::Decimal(None),
                 ColumnType::Text,
                 ColumnType::Bool,
             ])
@@ -347,7 +354,7 @@ fn deserialise_column_values_empty_input() {
         &mut [].as_slice(),
         DO::ColumnTypes(vec![
             ColumnType::Int,
-            ColumnType::Decimal,
+            ColumnType::Decimal(None),
             ColumnType::Text,
             ColumnType::Bool,
         ]),
@@ -362,14 +369,18 @@ fn deserialise_column_values_empty_input() {
 fn deserialise_column_values_fewer_types_than_values() {
     let values = vec![
         ColumnValue::Int(1),
-        (420, 69).into(),
+        decimal(42069, 2),
         "hey".into(),
         true.into(),
     ];
 
     let result = Vec::<ColumnValue>::deserialise(
-        &mut values.serialise().as_slice(),
-        DO::ColumnTypes(vec![ColumnType::Int, ColumnType::Decimal, ColumnType::Text]),
+        &mut values.serialise().unwrap().as_slice(),
+        DO::ColumnTypes(vec![
+            ColumnType::Int,
+            ColumnType::Decimal(None),
+            ColumnType::Text,
+        ]),
     );
 
     dbg!(&result);
@@ -381,7 +392,9 @@ fn deserialise_column_values_fewer_types_than_values() {
 fn deserialise_row_vector() {
     let (_, (row1, row2)) = test_table_with_values();
 
-    let input = vec![Row(row1.clone()), Row(row2.clone())].serialise();
+    let input = vec![Row(row1.clone()), Row(row2.clone())]
+        .serialise()
+        .unwrap();
     let input = &mut input.as_slice();
 
     assert_eq!(
@@ -397,14 +410,14 @@ fn deserialise_row_vector() {
 #[test]
 fn deserialise_table() {
     // We test serialise_table separately, so this is fine I guess
-    let table = test_table().serialise();
+    let table = test_table().serialise().unwrap();
     let input = &mut table.as_slice();
 
     let result = V2.deserialise_table(input).unwrap();
 
     assert_eq!(result, test_table());
 
-    let table = test_table_with_values().0.serialise();
+    let table = test_table_with_values().0.serialise().unwrap();
     let input = &mut table.as_slice();
 
     let result = V2.deserialise_table(input).unwrap();
@@ -420,7 +433,7 @@ fn serialise_rowset() {
         .select(crate::types::ColumnSelector::AllColumns, None)
         .unwrap();
 
-    let serialised = V2.serialise_rowset(&result);
+    let serialised = V2.serialise_rowset(&result).unwrap();
 
     let expected = vec![
         // Types
@@ -442,9 +455,110 @@ fn deserialise_rowset() {
         .select(crate::types::ColumnSelector::AllColumns, None)
         .unwrap();
 
-    let serialised = result.serialise();
+    let serialised = result.serialise().unwrap();
 
     let deserialised = V2.deserialise_rowset(&mut serialised.as_slice()).unwrap();
 
     assert_eq!(result, deserialised,);
 }
+
+#[test]
+fn serialise_newer_types() {
+    let types = vec![
+        ColumnType::SmallInt,
+        ColumnType::BigInt,
+        ColumnType::Decimal(Some((10, 2))),
+    ];
+
+    // Stored like the types that existed back then
+    assert_eq!(
+        types.serialise().unwrap(),
+        vec![
+            3, 0, 0, 0, 0, 0, 0, 0, // Length
+            1, 1, 2
+        ]
+    );
+
+    for column_type in [ColumnType::Float, ColumnType::Blob, ColumnType::Date] {
+        let result = column_type.serialise();
+
+        assert!(
+            matches!(result, Err(SqlError::IncompatibleType(_))),
+            "{column_type:?}: {result:?}"
+        );
+    }
+}
+
+#[test]
+fn serialise_newer_values() {
+    // Trailing zeros after the dot don't matter
+    let values = vec![decimal(250, 2), decimal(7, 0), ColumnValue::Int(i64::MAX)];
+
+    let mut expected = 3_u64.serialise().unwrap();
+
+    for value in [2, 5, 7, 0, i64::MAX as u64] {
+        expected.extend(value.serialise().unwrap());
+    }
+
+    assert_eq!(values.serialise().unwrap(), expected);
+
+    for value in [
+        ColumnValue::Null,
+        ColumnValue::Int(-1),
+        decimal(-25, 1),
+        // Would be read as 1.5
+        decimal(105, 2),
+        ColumnValue::Float(1.5),
+        ColumnValue::Date(crate::types::Date(0)),
+    ] {
+        let result = Row(vec![value.clone()]).serialise();
+
+        assert!(
+            matches!(result, Err(SqlError::IncompatibleValue(_))),
+            "{value:?}: {result:?}"
+        );
+    }
+}
+
+#[test]
+fn deserialise_baseline_values() {
+    let mut input = vec![];
+
+    for value in [2, 5, 13, 0, 0, 25, u64::MAX] {
+        input.extend(value.serialise().unwrap());
+    }
+
+    let input = &mut input.as_slice();
+
+    let decimal_type = || DO::ColumnType(ColumnType::Decimal(None));
+
+    assert_eq!(
+        ColumnValue::deserialise(input, decimal_type()).unwrap(),
+        decimal(25, 1)
+    );
+
+    assert_eq!(
+        ColumnValue::deserialise(input, decimal_type()).unwrap(),
+        decimal(13, 0)
+    );
+
+    assert_eq!(
+        ColumnValue::deserialise(input, decimal_type()).unwrap(),
+        decimal(25, 2)
+    );
+
+    let result = ColumnValue::deserialise(input, DO::ColumnType(ColumnType::Int));
+
+    assert!(matches!(result, Err(SqlError::NotAnInt(u64::MAX))));
+
+    let mut input = u64::MAX.serialise().unwrap();
+
+    input.extend(u64::MAX.serialise().unwrap());
+
+    let result = ColumnValue::deserialise(&mut input.as_slice(), decimal_type());
+
+    assert!(matches!(
+        result,
+        Err(SqlError::NotADecimal(u64::MAX, u64::MAX))
+    ));
+}
@@ -0,0 +1,663 @@
+//! Like v2, except it can also store NULLs, integers of every size, exact decimals, floats, blobs, dates, times and intervals
+#[cfg(test)]
+mod tests;
+
+use sql_parse::parser::ColumnType;
+
+use crate::{
+    database::{Row, RowSet, Table},
+    types::{
+        Blob, ColumnName, ColumnValue, Date, Interval, TableName, TableSchema, Time, Timestamp,
+    },
+    Result, SqlError,
+};
+
+use super::{
+    decimal_bytes, decimal_size_bytes, float_bytes, int_bytes, null_bitmap, read_decimal,
+    read_decimal_size, read_float, read_int, read_null_bitmap, Serialise,
+};
+
+#[derive(Debug)]
+pub struct V3;
+
+impl Serialise for V3 {
+    fn serialise_table(&self, value: &Table) -> Result<Vec<u8>> {
+        return Ok(value.serialise());
+    }
+
+    fn serialise_rowset(&self, value: &RowSet) -> Result<Vec<u8>> {
+        return Ok(value.serialise());
+    }
+
+    fn serialise_schemas(&self, value: Vec<&TableSchema>) -> Result<Vec<u8>> {
+        return Ok(value.serialise());
+    }
+
+    fn deserialise_table(&self, input: &mut &[u8]) -> Result<Table> {
+        return Table::deserialise(input, None.into());
+    }
+
+    fn deserialise_rowset(&self, input: &mut &[u8]) -> Result<RowSet> {
+        return RowSet::deserialise(input, None.into());
+    }
+
+    fn deserialise_schemas(&self, input: &mut &[u8]) -> Result<Vec<TableSchema>> {
+        return Vec::<TableSchema>::deserialise(input, None.into());
+    }
+}
+
+#[derive(Debug, Clone)]
+enum DeserialisationOptions {
+    None,
+    ColumnType(ColumnType),
+    ColumnTypes(Vec<ColumnType>),
+}
+use DeserialisationOptions as DO;
+
+impl From<Option<DO>> for DO {
+    fn from(value: Option<DO>) -> Self {
+        return match value {
+            Some(option) => option,
+            None => DO::None,
+        };
+    }
+}
+
+trait V3Serialise {
+    fn serialise(&self) -> Vec<u8>;
+}
+
+trait V3Deserialise {
+    fn deserialise(input: &mut &[u8], options: DO) -> Result<Self>
+    where
+        Self: Sized;
+}
+
+impl V3Serialise for Table {
+    fn serialise(&self) -> Vec<u8> {
+        let mut result = vec![];
+
+        let schema = self.schema.serialise();
+
+        result.extend(schema);
+
+        let values = self.values.serialise();
+
+        result.extend(values);
+
+        return result;
+    }
+}
+
+impl V3Serialise for TableSchema {
+    fn serialise(&self) -> Vec<u8> {
+        return (&self).serialise();
+    }
+}
+
+impl V3Serialise for &TableSchema {
+    fn serialise(&self) -> Vec<u8> {
+        let mut result = vec![];
+
+        let name = self.name.serialise();
+
+        result.extend(name);
+
+        let types = self.types.serialise();
+
+        result.extend(types);
+
+        let names = self.column_names.serialise();
+
+        result.extend(names);
+
+        let nullable = self.nullable.serialise();
+
+        result.extend(nullable);
+
+        return result;
+    }
+}
+
+impl V3Serialise for TableName {
+    fn serialise(&self) -> Vec<u8> {
+        return self.0.serialise();
+    }
+}
+
+impl V3Serialise for ColumnType {
+    fn serialise(&self) -> Vec<u8> {
+        // Start counting at 1 to make sure uninitialised data isn't a valid type
+        // (for what it's worth)
+        return match self {
+            ColumnType::Int => vec![1],
+            ColumnType::Decimal(size) => {
+                let mut result = vec![2];
+
+                result.extend(decimal_size_bytes(*size));
+
+                result
+            }
+            ColumnType::Text => vec![3],
+            ColumnType::Bool => vec![4],
+            ColumnType::SmallInt => vec![5],
+            ColumnType::BigInt => vec![6],
+            ColumnType::Date => vec![7],
+            ColumnType::Time => vec![8],
+            ColumnType::Timestamp => vec![9],
+            ColumnType::Interval => vec![10],
+            ColumnType::Blob => vec![11],
+            ColumnType::Float => vec![12],
+        };
+    }
+}
+
+impl V3Serialise for ColumnName {
+    fn serialise(&self) -> Vec<u8> {
+        return self.0.serialise();
+    }
+}
+
+impl V3Serialise for Row {
+    fn serialise(&self) -> Vec<u8> {
+        let mut result = (self.0.len() as u64).serialise();
+
+        result.extend(null_bitmap(&self.0));
+
+        for value in &self.0 {
+            result.extend(value.serialise());
+        }
+
+        return result;
+    }
+}
+
+// Sure would be nice if negative impl was stable
+// Then I could make a custom impl for Vec<Row> that stored the types once,
+// Removing the need for DeserialisationOptions altogether.
+// But I'm not about to write four different implementations
+// I mean I guess I could idk
+//
+// Also we're storing number of values in each Vec<ColumnValue>, which isn't necessary
+// because we store the number of types already, can reuse that value technically
+//
+// Ah well fixing that means writing a specialised Vec<ColumnValue> parser as well,
+// see above about negative impl. Doing it now would just be unnecessary complexity
+
+impl V3Serialise for ColumnValue {
+    fn serialise(&self) -> Vec<u8> {
+        return match self {
+            ColumnValue::Int(value) => int_bytes(*value),
+            ColumnValue::Decimal(value) => decimal_bytes(value),
+            ColumnValue::Float(value) => float_bytes(*value),
+            ColumnValue::Str(value) => {
+                let mut result = (value.len() as u64).serialise();
+
+                result.extend(value.as_bytes());
+
+                result
+            }
+            ColumnValue::Blob(value) => value.serialise(),
+            ColumnValue::Bool(value) => vec![*value as u8],
+            ColumnValue::Date(value) => value.0.to_le_bytes().to_vec(),
+            ColumnValue::Time(value) => value.0.to_le_bytes().to_vec(),
+            ColumnValue::Timestamp(value) => value.0.to_le_bytes().to_vec(),
+            ColumnValue::Interval(value) => {
+                let mut result = value.months.to_le_bytes().to_vec();
+
+                result.extend(value.micros.to_le_bytes());
+
+                result
+            }
+            // Only stored in the null bitmap of the row
+            ColumnValue::Null => vec![],
+        };
+    }
+}
+
+impl V3Serialise for u64 {
+    fn serialise(&self) -> Vec<u8> {
+        // https://stackoverflow.com/questions/72631065/how-to-convert-a-u32-array-to-a-u8-array-in-place
+        let mut result = Vec::with_capacity(8);
+
+        for byte in self.to_le_bytes() {
+            result.push(byte)
+        }
+
+        return result;
+    }
+}
+
+impl V3Serialise for bool {
+    fn serialise(&self) -> Vec<u8> {
+        return vec![*self as u8];
+    }
+}
+
+impl V3Serialise for String {
+    fn serialise(&self) -> Vec<u8> {
+        let mut result = (self.len() as u64).serialise();
+
+        result.extend(self.bytes());
+
+        return result;
+    }
+}
+
+impl V3Serialise for Blob {
+    fn serialise(&self) -> Vec<u8> {
+        let mut result = (self.0.len() as u64).serialise();
+
+        result.extend(&self.0);
+
+        return result;
+    }
+}
+
+impl<T: V3Serialise> V3Serialise for Vec<T> {
+    fn serialise(&self) -> Vec<u8> {
+        let mut result = vec![];
+
+        // First store total count
+        result.extend((self.len() as u64).serialise());
+
+        for t in self {
+            let bytes = t.serialise();
+
+            result.extend(bytes);
+        }
+
+        return result;
+    }
+}
+
+impl V3Serialise for RowSet {
+    fn serialise(&self) -> Vec<u8> {
+        let mut result = self.types.serialise();
+
+        result.extend(self.names.serialise());
+
+        result.extend(self.values.serialise());
+
+        return result;
+    }
+}
+
+impl V3Deserialise for Table {
+    fn deserialise(input: &mut &[u8], _: DO) -> Result<Self> {
+        let schema = TableSchema::deserialise(input, DO::None)?;
+
+        let values = Vec::<Row>::deserialise(input, DO::ColumnTypes(schema.types.clone()))?;
+
+        // Foreign keys are only stored from V4 on
+        return Table::from_parts(schema, values, vec![]);
+    }
+}
+
+impl V3Deserialise for TableSchema {
+    fn deserialise(input: &mut &[u8], _: DeserialisationOptions) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let name = TableName::deserialise(input, None.into())?;
+
+        let types = Vec::<ColumnType>::deserialise(input, None.into())?;
+
+        let column_names = Vec::<ColumnName>::deserialise(input, None.into())?;
+
+        let nullable = Vec::<bool>::deserialise(input, None.into())?;
+
+        let defaults = vec![None; column_names.len()];
+
+        return Ok(TableSchema {
+            name,
+            column_names,
+            types,
+            nullable,
+            // Keys are only stored from V4 on
+            unique: vec![],
+            // Neither are defaults and checks
+            defaults,
+            checks: vec![],
+            indexes: vec![],
+        });
+    }
+}
+
+impl V3Deserialise for Vec<TableSchema> {
+    fn deserialise(input: &mut &[u8], _: DeserialisationOptions) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let count = u64::deserialise(input, None.into())?;
+
+        let mut result = vec![];
+
+        for _ in 0..count {
+            result.push(TableSchema::deserialise(input, None.into())?);
+        }
+
+        return Ok(result);
+    }
+}
+
+impl V3Deserialise for u64 {
+    fn deserialise(input: &mut &[u8], _: DO) -> Result<Self> {
+        if input.len() < 8 {
+            return Err(SqlError::InputTooShort(input.len(), 8));
+        }
+
+        // try_into to convert slice into fixed-length array
+        let bytes: [u8; 8] = input[..8]
+            .try_into()
+            .map_err(SqlError::SliceConversionError)?;
+
+        let result = u64::from_le_bytes(bytes);
+
+        *input = &input[8..];
+
+        return Ok(result);
+    }
+}
+
+impl V3Deserialise for TableName {
+    fn deserialise(input: &mut &[u8], _: DO) -> Result<Self> {
+        let result = String::deserialise(input, None.into())?;
+
+        return Ok(TableName(result));
+    }
+}
+
+impl V3Deserialise for ColumnType {
+    fn deserialise(input: &mut &[u8], _: DO) -> Result<Self> {
+        // A ColumnType is serialised as one byte
+        if input.is_empty() {
+            return Err(SqlError::InputTooShort(input.len(), 1));
+        }
+
+        let byte = *input.first().unwrap();
+
+        *input = &input[1..];
+
+        return match byte {
+            1 => Ok(ColumnType::Int),
+            2 => Ok(ColumnType::Decimal(read_decimal_size(input)?)),
+            3 => Ok(ColumnType::Text),
+            4 => Ok(ColumnType::Bool),
+            5 => Ok(ColumnType::SmallInt),
+            6 => Ok(ColumnType::BigInt),
+            7 => Ok(ColumnType::Date),
+            8 => Ok(ColumnType::Time),
+            9 => Ok(ColumnType::Timestamp),
+            10 => Ok(ColumnType::Interval),
+            11 => Ok(ColumnType::Blob),
+            12 => Ok(ColumnType::Float),
+            _ => Err(SqlError::NotATypeDiscriminator(byte)),
+        };
+    }
+}
+
+// Only reason we can't have a blanket implementation for Vec<T>
+// is that ColumnValues requires the types to be known
+impl V3Deserialise for Vec<ColumnType> {
+    fn deserialise(input: &mut &[u8], _: DO) -> Result<Self> {
+        let count = u64::deserialise(input, None.into())?;
+
+        let mut result = vec![];
+
+        for _ in 0..count {
+            result.push(ColumnType::deserialise(input, None.into())?);
+        }
+
+        return Ok(result);
+    }
+}
+
+impl V3Deserialise for ColumnName {
+    fn deserialise(input: &mut &[u8], _: DO) -> Result<Self> {
+        let result = String::deserialise(input, None.into())?;
+
+        return Ok(ColumnName(result));
+    }
+}
+
+impl V3Deserialise for Vec<ColumnName> {
+    fn deserialise(input: &mut &[u8], _: DO) -> Result<Self> {
+        let count = u64::deserialise(input, None.into())?;
+
+        let mut result = vec![];
+
+        for _ in 0..count {
+            result.push(ColumnName::deserialise(input, None.into())?);
+        }
+
+        return Ok(result);
+    }
+}
+
+impl V3Deserialise for String {
+    fn deserialise(input: &mut &[u8], _: DO) -> Result<Self> {
+        let length = u64::deserialise(input, None.into())?;
+
+        if input.len() < length as usize {
+            return Err(SqlError::InputTooShort(input.len(), length as usize));
+        }
+
+        // https://doc.rust-lang.org/book/ch08-02-strings.html
+        // strings are UTF8 in rust
+        let result = String::from_utf8(input[..length as usize].to_vec())
+            .map_err(SqlError::NotAValidString)?;
+
+        *input = &input[length as usize..];
+
+        return Ok(result);
+    }
+}
+
+impl V3Deserialise for Blob {
+    fn deserialise(input: &mut &[u8], _: DO) -> Result<Self> {
+        let length = u64::deserialise(input, None.into())?;
+
+        if input.len() < length as usize {
+            return Err(SqlError::InputTooShort(input.len(), length as usize));
+        }
+
+        let result = Blob(input[..length as usize].to_vec());
+
+        *input = &input[length as usize..];
+
+        return Ok(result);
+    }
+}
+
+impl V3Deserialise for bool {
+    fn deserialise(input: &mut &[u8], _: DO) -> Result<Self> {
+        if input.is_empty() {
+            return Err(SqlError::InputTooShort(input.len(), 1));
+        }
+
+        let byte = *input.first().unwrap();
+
+        let result = {
+            match byte {
+                0 => Ok(false),
+                1 => Ok(true),
+                _ => Err(SqlError::NotABoolean(byte)),
+            }
+        }?;
+
+        *input = &input[1..];
+
+        return Ok(result);
+    }
+}
+
+impl V3Deserialise for Vec<bool> {
+    fn deserialise(input: &mut &[u8], _: DO) -> Result<Self> {
+        let count = u64::deserialise(input, None.into())?;
+
+        let mut result = vec![];
+
+        for _ in 0..count {
+            result.push(bool::deserialise(input, None.into())?);
+        }
+
+        return Ok(result);
+    }
+}
+
+impl V3Deserialise for Row {
+    fn deserialise(input: &mut &[u8], options: DO) -> Result<Self> {
+        let types = match options {
+            DO::ColumnTypes(types) => Ok(types),
+            _ => Err(SqlError::InvalidParameter),
+        }?;
+
+        let count = u64::deserialise(input, None.into())? as usize;
+
+        if count != types.len() {
+            return Err(SqlError::UnequalLengths(count, types.len()));
+        }
+
+        let nulls = read_null_bitmap(input, count)?;
+
+        let mut result = vec![];
+
+        for (column_type, is_null) in types.into_iter().zip(nulls) {
+            if is_null {
+                result.push(ColumnValue::Null);
+            } else {
+                result.push(ColumnValue::deserialise(
+                    input,
+                    DO::ColumnType(column_type),
+                )?);
+            }
+        }
+
+        return Ok(Row(result));
+    }
+}
+
+impl V3Deserialise for Vec<Row> {
+    fn deserialise(input: &mut &[u8], options: DO) -> Result<Self> {
+        let count = u64::deserialise(input, None.into())?;
+
+        let mut result = vec![];
+
+        for _ in 0..count {
+            result.push(Row::deserialise(input, options.clone())?);
+        }
+
+        return Ok(result);
+    }
+}
+
+/// Reads the `N` bytes of a fixed size value.
+fn read_bytes<const N: usize>(input: &mut &[u8]) -> Result<[u8; N]> {
+    if input.len() < N {
+        return Err(SqlError::InputTooShort(input.len(), N));
+    }
+
+    let bytes = input[..N]
+        .try_into()
+        .map_err(SqlError::SliceConversionError)?;
+
+    *input = &input[N..];
+
+    return Ok(bytes);
+}
+
+impl V3Deserialise for ColumnValue {
+    fn deserialise(input: &mut &[u8], options: DO) -> Result<Self> {
+        let column_type = match options {
+            DO::ColumnType(column_type) => Ok(column_type),
+            _ => Err(SqlError::InvalidParameter),
+        }?;
+
+        let result = match column_type {
+            ColumnType::SmallInt | ColumnType::Int | ColumnType::BigInt => {
+                ColumnValue::Int(read_int(input)?)
+            }
+            ColumnType::Decimal(_) => ColumnValue::Decimal(read_decimal(input)?),
+            ColumnType::Float => ColumnValue::Float(read_float(input)?),
+            ColumnType::Text => ColumnValue::Str(String::deserialise(input, None.into())?),
+            ColumnType::Blob => ColumnValue::Blob(Blob::deserialise(input, None.into())?),
+            ColumnType::Bool => ColumnValue::Bool(bool::deserialise(input, None.into())?),
+            ColumnType::Date => {
+                let days = i32::from_le_bytes(read_bytes(input)?);
+
+                match Date(days).is_valid() {
+                    true => ColumnValue::Date(Date(days)),
+                    false => return Err(SqlError::NotADate(days)),
+                }
+            }
+            ColumnType::Time => {
+                let micros = i64::from_le_bytes(read_bytes(input)?);
+
+                match Time(micros).is_valid() {
+                    true => ColumnValue::Time(Time(micros)),
+                    false => return Err(SqlError::NotATime(micros)),
+                }
+            }
+            ColumnType::Timestamp => {
+                let micros = i64::from_le_bytes(read_bytes(input)?);
+
+                match Timestamp(micros).is_valid() {
+                    true => ColumnValue::Timestamp(Timestamp(micros)),
+                    false => return Err(SqlError::NotATimestamp(micros)),
+                }
+            }
+            ColumnType::Interval => ColumnValue::Interval(Interval {
+                months: i32::from_le_bytes(read_bytes(input)?),
+                micros: i64::from_le_bytes(read_bytes(input)?),
+            }),
+        };
+
+        return Ok(result);
+    }
+}
+
+impl V3Deserialise for Vec<ColumnValue> {
+    fn deserialise(input: &mut &[u8], options: DO) -> Result<Self> {
+        let types = match options {
+            DO::ColumnTypes(types) => Ok(types),
+            _ => Err(SqlError::InvalidParameter),
+        }?;
+
+        let count = u64::deserialise(input, None.into())?;
+
+        if count != types.len() as u64 {
+            return Err(SqlError::UnequalLengths(count as usize, types.len()));
+        }
+
+        let mut result = vec![];
+
+        for column_type in types {
+            result.push(ColumnValue::deserialise(
+                input,
+                DO::ColumnType(column_type),
+            )?);
+        }
+
+        return Ok(result);
+    }
+}
+
+impl V3Deserialise for RowSet {
+    fn deserialise(input: &mut &[u8], _: DO) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let types = Vec::<ColumnType>::deserialise(input, None.into()).unwrap();
+
+        let names = Vec::<ColumnName>::deserialise(input, None.into())?;
+
+        let values = Vec::<Row>::deserialise(input, DO::ColumnTypes(types.clone()))?;
+
+        return Ok(Self {
+            types,
+            names,
+            values,
+        });
+    }
+}
@@ -0,0 +1,828 @@
+//! Essentially the same tests as v3, plus keys, foreign keys and sequences
+use super::*;
+
+use crate::database::{Row, Sequence};
+use crate::types::{
+    Blob, ColumnDefinition, ColumnName, ColumnValue, Date, ForeignKeyConstraint, IndexDefinition,
+    Interval, TableName, Time, Timestamp, UniqueConstraint,
+};
+use crate::utils::tests::{decimal, test_table, test_table_with_values};
+use sql_parse::parser::{ColumnConstraint, ColumnType, ReferentialAction};
+
+#[test]
+fn serialise_column_types() {
+    let types = vec![
+        ColumnType::Decimal(Some((10, 2))),
+        ColumnType::Int,
+        ColumnType::Bool,
+        ColumnType::Text,
+        ColumnType::Decimal(None),
+        ColumnType::SmallInt,
+        ColumnType::BigInt,
+        ColumnType::Date,
+        ColumnType::Time,
+        ColumnType::Timestamp,
+        ColumnType::Interval,
+    ];
+
+    let serialised = types.serialise();
+
+    assert_eq!(
+        serialised,
+        vec![
+            11, 0, 0, 0, 0, 0, 0, 0, // Length
+            2, 10, 2, 1, 4, 3, 2, 0, 0, 5, 6, 7, 8, 9, 10
+        ]
+    )
+}
+
+#[test]
+fn serialise_column_names() {
+    let names: Vec<ColumnName> = vec!["asdf".into(), "hello".into()];
+
+    let serialised = names.serialise();
+    assert_eq!(
+        serialised,
+        vec![
+            2, 0, 0, 0, 0, 0, 0, 0, // Length
+            4, 0, 0, 0, 0, 0, 0, 0, // Length
+            97, 115, 100, 102, 5, 0, 0, 0, 0, 0, 0, 0, // Length
+            104, 101, 108, 108, 111,
+        ]
+    );
+}
+
+#[test]
+fn serialise_column_values() {
+    let values: Vec<ColumnValue> = vec![
+        1.into(),
+        decimal(42069, 2),
+        "hey".into(),
+        true.into(),
+        false.into(),
+    ];
+
+    let serialised = values.serialise();
+
+    let buffer = [0_u8; 8];
+
+    let mut expected = vec![
+        5, 0, 0, 0, 0, 0, 0, 0, // Length
+    ];
+
+    expected.extend({
+        let mut result = buffer;
+
+        // Note use of little-endian bytes in serialisation::u64_to_bytes
+        result[0] = 1;
+
+        result.to_vec()
+    });
+
+    expected.extend({
+        let mut result = [0_u8; 17];
+
+        // 42069 = 164 * 256 + 85
+        result[0] = 85;
+        result[1] = 164;
+
+        // Scale
+        result[16] = 2;
+
+        result.to_vec()
+    });
+
+    expected.extend({
+        let mut result = [0_u8; 11];
+
+        // Set length
+        result[0] = 3;
+
+        // Characters
+        result[8] = 104;
+        result[9] = 101;
+        result[10] = 121;
+
+        result.to_vec()
+    });
+
+    expected.extend(vec![1, 0]);
+
+    assert_eq!(serialised, expected)
+}
+
+#[test]
+fn serialise_row() {
+    let (_, (row1, row2)) = test_table_with_values();
+
+    let input = &mut vec![Row(row1.clone()), Row(row2.clone())];
+
+    let mut expected = vec![2, 0, 0, 0, 0, 0, 0, 0];
+
+    expected.extend(Row(row1).serialise());
+    expected.extend(Row(row2).serialise());
+
+    assert_eq!(input.serialise(), expected);
+
+    let input: &mut Vec<Row> = &mut vec![];
+
+    // Just the length
+    let expected = vec![0, 0, 0, 0, 0, 0, 0, 0];
+
+    assert_eq!(input.serialise(), expected);
+}
+
+#[test]
+fn serialise_table() {
+    let table = test_table();
+
+    let serialised = V4.serialise_table(&table).unwrap();
+
+    let expected = vec![
+        // Name
+        10, 0, 0, 0, 0, 0, 0, 0, 116, 101, 115, 116, 95, 116, 97, 98, 108, 101, // Types
+        2, 0, 0, 0, 0, 0, 0, 0, 1, 4, // Names
+        2, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 102, 105, 114, 115, 116, 6, 0, 0, 0, 0, 0,
+        0, 0, 115, 101, 99, 111, 110, 100, // Nullable
+        2, 0, 0, 0, 0, 0, 0, 0, 1, 1, // Keys
+        0, 0, 0, 0, 0, 0, 0, 0, // Defaults
+        2, 0, 0, 0, 0, 0, 0, 0, 0, 0, // Checks
+        0, 0, 0, 0, 0, 0, 0, 0, // Indexes
+        0, 0, 0, 0, 0, 0, 0, 0, // Foreign keys
+        0, 0, 0, 0, 0, 0, 0, 0, // Values
+        0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    assert_eq!(serialised, expected);
+
+    let (table, _) = test_table_with_values();
+
+    let serialised = V4.serialise_table(&table).unwrap();
+
+    let expected = vec![
+        // Name
+        10, 0, 0, 0, 0, 0, 0, 0, 116, 101, 115, 116, 95, 116, 97, 98, 108, 101, // Types
+        2, 0, 0, 0, 0, 0, 0, 0, 1, 4, // Names
+        2, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 102, 105, 114, 115, 116, 6, 0, 0, 0, 0, 0,
+        0, 0, 115, 101, 99, 111, 110, 100, // Nullable
+        2, 0, 0, 0, 0, 0, 0, 0, 1, 1, // Keys
+        0, 0, 0, 0, 0, 0, 0, 0, // Defaults
+        2, 0, 0, 0, 0, 0, 0, 0, 0, 0, // Checks
+        0, 0, 0, 0, 0, 0, 0, 0, // Indexes
+        0, 0, 0, 0, 0, 0, 0, 0, // Foreign keys
+        0, 0, 0, 0, 0, 0, 0, 0, // Values, each row has a null bitmap after its length
+        2, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 1, 2, 0, 0, 0,
+        0, 0, 0, 0, 0, 6, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    assert_eq!(serialised, expected,)
+}
+
+#[test]
+fn deserialise_u64() {
+    let input = &mut [
+        1, 0, 0, 0, 0, 0, 0, 0, // 1
+        164, 1, 0, 0, 0, 0, 0, 0, // 420
+        0, // Too few bytes
+    ]
+    .as_slice();
+
+    assert_eq!(u64::deserialise(input, None.into()).unwrap(), 1,);
+
+    assert_eq!(u64::deserialise(input, None.into()).unwrap(), 420,);
+
+    assert!(u64::deserialise(input, None.into()).is_err());
+}
+
+#[test]
+fn deserialise_column_type() {
+    let input = vec![ColumnType::Int, ColumnType::Bool, ColumnType::Text].serialise();
+
+    let input = &mut input.as_slice();
+
+    // Length
+    assert_eq!(u64::deserialise(input, None.into()).unwrap(), 3);
+
+    assert_eq!(
+        ColumnType::deserialise(input, None.into()).unwrap(),
+        ColumnType::Int,
+    );
+
+    assert_eq!(
+        ColumnType::deserialise(input, None.into()).unwrap(),
+        ColumnType::Bool,
+    );
+
+    assert_eq!(
+        ColumnType::deserialise(input, None.into()).unwrap(),
+        ColumnType::Text,
+    );
+
+    let result = ColumnType::deserialise(&mut [].as_slice(), None.into());
+
+    dbg!(&result);
+    assert!(matches!(result, Err(SqlError::InputTooShort(0, 1))));
+}
+
+#[test]
+fn deserialise_table_name() {
+    let input = vec![TableName("a".into()), "abcd".into(), "meme".into()].serialise();
+
+    let input = &mut input.as_slice();
+
+    // Length
+    assert_eq!(u64::deserialise(input, None.into()).unwrap(), 3);
+
+    assert_eq!(
+        TableName::deserialise(input, None.into()).unwrap(),
+        "a".into()
+    );
+
+    assert_eq!(
+        TableName::deserialise(input, None.into()).unwrap(),
+        "abcd".into()
+    );
+
+    assert_eq!(
+        TableName::deserialise(input, None.into()).unwrap(),
+        "meme".into()
+    );
+
+    let result = TableName::deserialise(&mut [].as_slice(), None.into());
+
+    dbg!(&result);
+    // Expect length of string 8
+    assert!(matches!(result, Err(SqlError::InputTooShort(0, 8))));
+}
+
+#[test]
+fn deserialise_column_name() {
+    let input = ColumnName("hey".into()).serialise();
+    let input = &mut input.as_slice();
+
+    assert_eq!(
+        ColumnName::deserialise(input, None.into()).unwrap(),
+        "hey".into()
+    );
+
+    let input = ColumnName("".into()).serialise();
+    let input = &mut input.as_slice();
+
+    assert_eq!(
+        ColumnName::deserialise(input, None.into()).unwrap(),
+        "".into()
+    );
+}
+#[test]
+fn deserialise_vector_fixed_length_item() {
+    let input = vec![
+        ColumnType::Int,
+        ColumnType::Bool,
+        ColumnType::Text,
+        ColumnType::Decimal(Some((38, 38))),
+        ColumnType::Decimal(None),
+    ]
+    .serialise();
+    let input = &mut input.as_slice();
+
+    assert_eq!(
+        Vec::<ColumnType>::deserialise(input, None.into()).unwrap(),
+        vec![
+            ColumnType::Int,
+            ColumnType::Bool,
+            ColumnType::Text,
+            ColumnType::Decimal(Some((38, 38))),
+            ColumnType::Decimal(None),
+        ]
+    );
+
+    // Invalid data
+    let input = &mut [1, 0, 0, 0, 0, 0, 0, 0, 69].as_slice();
+
+    let result = Vec::<ColumnType>::deserialise(input, None.into());
+    println!("{:?}", result);
+    assert!(matches!(result, Err(SqlError::NotATypeDiscriminator(_))));
+
+    // Too short
+    let input = vec![ColumnType::Int, ColumnType::Bool].serialise();
+    let input = &mut input.as_slice();
+
+    // Length
+    assert_eq!(u64::deserialise(input, None.into()).unwrap(), 2);
+
+    assert!(Vec::<ColumnType>::deserialise(input, None.into()).is_err());
+}
+
+#[test]
+fn deserialise_vector_variable_length_item() {
+    let input = vec![ColumnName("a".into()), ColumnName("abc".into())].serialise();
+    let input = &mut input.as_slice();
+
+    assert_eq!(
+        Vec::<ColumnName>::deserialise(input, None.into()).unwrap(),
+        vec![ColumnName("a".into()), ColumnName("abc".into()),]
+    );
+}
+
+#[test]
+fn deserialise_bool_invalid_values() {
+    let input = vec![2];
+
+    let result = bool::deserialise(&mut input.as_slice(), DO::None);
+
+    dbg!(&result);
+    assert!(matches!(result, Err(SqlError::NotABoolean(2))));
+}
+
+#[test]
+fn deserialise_decimals() {
+    let values = vec![
+        decimal(105, 2),
+        decimal(15, 1),
+        decimal(150, 2),
+        decimal(10_i128.pow(38) - 1, 38),
+        decimal(-(10_i128.pow(38) - 1), 38),
+        decimal(-5, 3),
+    ];
+
+    let input = values.serialise();
+
+    let result = Vec::<ColumnValue>::deserialise(
+        &mut input.as_slice(),
+        DO::ColumnTypes(vec![ColumnType::Decimal(None); 6]),
+    );
+
+    assert_eq!(result.unwrap(), values);
+
+    let mut input = vec![0_u8; 16];
+    input.push(39);
+
+    let result = ColumnValue::deserialise(
+        &mut input.as_slice(),
+        DO::ColumnType(ColumnType::Decimal(None)),
+    );
+
+    assert!(matches!(result, Err(SqlError::NotADecimalScale(39))));
+
+    let result = ColumnType::deserialise(&mut [2, 5, 6].as_slice(), None.into());
+
+    assert!(matches!(result, Err(SqlError::NotADecimalSize(5, 6))));
+}
+
+#[test]
+fn deserialise_ints() {
+    let values = vec![
+        ColumnValue::Int(-1),
+        ColumnValue::Int(i16::MIN.into()),
+        ColumnValue::Int(i64::MAX),
+    ];
+
+    let input = values.serialise();
+
+    // Every int takes 8 bytes, whatever the type of its column
+    assert_eq!(&input[8..16], &[0xff; 8]);
+
+    let result = Vec::<ColumnValue>::deserialise(
+        &mut input.as_slice(),
+        DO::ColumnTypes(vec![
+            ColumnType::Int,
+            ColumnType::SmallInt,
+            ColumnType::BigInt,
+        ]),
+    );
+
+    assert_eq!(result.unwrap(), values);
+
+    let result = ColumnValue::deserialise(
+        &mut [0_u8; 7].as_slice(),
+        DO::ColumnType(ColumnType::BigInt),
+    );
+
+    assert!(matches!(result, Err(SqlError::InputTooShort(7, 8))));
+}
+
+#[test]
+fn deserialise_temporal() {
+    let values = vec![
+        ColumnValue::Date(Date(-1)),
+        ColumnValue::Time(Time(1_000_000)),
+        ColumnValue::Timestamp(Timestamp(-1)),
+        ColumnValue::Interval(Interval {
+            months: 14,
+            micros: -1,
+        }),
+    ];
+
+    let input = values.serialise();
+
+    assert_eq!(
+        input,
+        vec![
+            4, 0, 0, 0, 0, 0, 0, 0, // Length
+            0xff, 0xff, 0xff, 0xff, // Date
+            0x40, 0x42, 0x0f, 0, 0, 0, 0, 0, // Time
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, // Timestamp
+            14, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, // Interval
+        ]
+    );
+
+    let result = Vec::<ColumnValue>::deserialise(
+        &mut input.as_slice(),
+        DO::ColumnTypes(vec![
+            ColumnType::Date,
+            ColumnType::Time,
+            ColumnType::Timestamp,
+            ColumnType::Interval,
+        ]),
+    );
+
+    assert_eq!(result.unwrap(), values);
+
+    let result = ColumnValue::deserialise(
+        &mut i32::MAX.to_le_bytes().as_slice(),
+        DO::ColumnType(ColumnType::Date),
+    );
+
+    assert!(matches!(result, Err(SqlError::NotADate(i32::MAX))));
+
+    let result = ColumnValue::deserialise(
+        &mut (-1_i64).to_le_bytes().as_slice(),
+        DO::ColumnType(ColumnType::Time),
+    );
+
+    assert!(matches!(result, Err(SqlError::NotATime(-1))));
+
+    let result = ColumnValue::deserialise(
+        &mut i64::MIN.to_le_bytes().as_slice(),
+        DO::ColumnType(ColumnType::Timestamp),
+    );
+
+    assert!(matches!(result, Err(SqlError::NotATimestamp(i64::MIN))));
+
+    let result = ColumnValue::deserialise(
+        &mut [0_u8; 11].as_slice(),
+        DO::ColumnType(ColumnType::Interval),
+    );
+
+    assert!(matches!(result, Err(SqlError::InputTooShort(7, 8))));
+}
+
+#[test]
+fn deserialise_floats() {
+    let values = vec![
+        ColumnValue::Float(1.5e-3),
+        ColumnValue::Float(-0.0),
+        ColumnValue::Float(f64::INFINITY),
+    ];
+
+    let input = values.serialise();
+
+    assert_eq!(&input[8..16], &1.5e-3_f64.to_le_bytes());
+
+    let result = Vec::<ColumnValue>::deserialise(
+        &mut input.as_slice(),
+        DO::ColumnTypes(vec![ColumnType::Float; 3]),
+    );
+
+    assert_eq!(result.unwrap(), values);
+
+    let result = ColumnValue::deserialise(
+        &mut f64::NAN.to_le_bytes().as_slice(),
+        DO::ColumnType(ColumnType::Float),
+    );
+
+    assert!(matches!(result, Ok(ColumnValue::Float(value)) if value.is_nan()));
+}
+
+#[test]
+fn deserialise_blob() {
+    let value = ColumnValue::Blob(Blob(vec![0xde, 0xad]));
+
+    let input = value.serialise();
+
+    assert_eq!(
+        input,
+        vec![
+            2, 0, 0, 0, 0, 0, 0, 0, // Length
+            0xde, 0xad
+        ]
+    );
+
+    let result = ColumnValue::deserialise(&mut input.as_slice(), DO::ColumnType(ColumnType::Blob));
+
+    assert_eq!(result.unwrap(), value);
+
+    let result =
+        ColumnValue::deserialise(&mut input[..9].as_ref(), DO::ColumnType(ColumnType::Blob));
+
+    assert!(matches!(result, Err(SqlError::InputTooShort(1, 2))));
+}
+
+#[test]
+fn deserialise_column_values() {
+    let values = vec![
+        ColumnValue::Int(1),
+        decimal(42069, 2),
+        "hey".into(),
+        true.into(),
+    ];
+    let input = values.serialise();
+    let input = &mut input.as_slice();
+
+    assert_eq!(
+        Vec::<ColumnValue>::deserialise(
+            input,
+            DO::ColumnTypes(vec![
+                ColumnType::Int,
+                ColumnType::Decimal(None),
+                ColumnType::Text,
+                ColumnType::Bool,
+            ])
+        )
+        .unwrap(),
+        values
+    );
+}
+
+#[test]
+fn deserialise_column_values_empty_input() {
+    let result = Vec::<ColumnValue>::deserialise(
+        &mut [].as_slice(),
+        DO::ColumnTypes(vec![
+            ColumnType::Int,
+            ColumnType::Decimal(None),
+            ColumnType::Text,
+            ColumnType::Bool,
+        ]),
+    );
+
+    dbg!(&result);
+    // Expect count of values 8
+    assert!(matches!(result, Err(SqlError::InputTooShort(0, 8))));
+}
+
+#[test]
+fn deserialise_column_values_fewer_types_than_values() {
+    let values = vec![
+        ColumnValue::Int(1),
+        decimal(42069, 2),
+        "hey".into(),
+        true.into(),
+    ];
+
+    let result = Vec::<ColumnValue>::deserialise(
+        &mut values.serialise().as_slice(),
+        DO::ColumnTypes(vec![
+            ColumnType::Int,
+            ColumnType::Decimal(None),
+            ColumnType::Text,
+        ]),
+    );
+
+    dbg!(&result);
+    // Values have length 4, types have length 3
+    assert!(matches!(result, Err(SqlError::UnequalLengths(4, 3))));
+}
+
+#[test]
+fn deserialise_row_vector() {
+    let (_, (row1, row2)) = test_table_with_values();
+
+    let input = vec![Row(row1.clone()), Row(row2.clone())].serialise();
+    let input = &mut input.as_slice();
+
+    assert_eq!(
+        Vec::<Row>::deserialise(
+            input,
+            DO::ColumnTypes(vec![ColumnType::Int, ColumnType::Bool,])
+        )
+        .unwrap(),
+        vec![Row(row1), Row(row2)]
+    );
+}
+
+#[test]
+fn row_with_nulls() {
+    let row = Row(vec![ColumnValue::Null, 5.into(), ColumnValue::Null]);
+
+    let serialised = row.serialise();
+
+    // Only the bitmap is stored for NULLs
+    let mut expected = vec![3, 0, 0, 0, 0, 0, 0, 0];
+    expected.extend([0b101]);
+    expected.extend(5_u64.serialise());
+
+    assert_eq!(serialised, expected);
+
+    let deserialised = Row::deserialise(
+        &mut serialised.as_slice(),
+        DO::ColumnTypes(vec![ColumnType::Bool, ColumnType::Int, ColumnType::Text]),
+    )
+    .unwrap();
+
+    assert_eq!(deserialised, row);
+
+    let result = Row::deserialise(
+        &mut serialised[..8].as_ref(),
+        DO::ColumnTypes(vec![ColumnType::Bool, ColumnType::Int, ColumnType::Text]),
+    );
+
+    assert!(matches!(result, Err(SqlError::InputTooShort(0, 1))));
+}
+
+#[test]
+fn deserialise_table() {
+    // We test serialise_table separately, so this is fine I guess
+    let table = test_table().serialise();
+    let input = &mut table.as_slice();
+
+    let result = V4.deserialise_table(input).unwrap();
+
+    assert_eq!(result, test_table());
+
+    let table = test_table_with_values().0.serialise();
+    let input = &mut table.as_slice();
+
+    let result = V4.deserialise_table(input).unwrap();
+
+    assert_eq!(result, test_table_with_values().0);
+}
+
+#[test]
+fn deserialise_constraints() {
+    let mut table = Table::new(
+        "lines".into(),
+        vec![
+            ColumnDefinition("order_id".into(), ColumnType::Int, vec![]),
+            ColumnDefinition("line".into(), ColumnType::Int, vec![]),
+            ColumnDefinition(
+                "sku".into(),
+                ColumnType::Text,
+                vec![
+                    ColumnConstraint::Unique,
+                    ColumnConstraint::Default(parse_expression("'none'").unwrap()),
+                ],
+            ),
+        ],
+        vec![ForeignKeyConstraint {
+            column: "order_id".into(),
+            foreign_table: "orders".into(),
+            foreign_column: "id".into(),
+            on_delete: ReferentialAction::Cascade,
+            on_update: ReferentialAction::SetNull,
+        }],
+        vec![UniqueConstraint {
+            columns: vec!["order_id".into(), "line".into()],
+            primary: true,
+        }],
+        vec![parse_expression("line > 0 AND (sku <> 'x' OR order_id IS NULL)").unwrap()],
+    )
+    .unwrap();
+
+    table
+        .insert(&None, vec![1.into(), 1.into(), "a".into()])
+        .unwrap();
+
+    table
+        .create_index(IndexDefinition {
+            name: "lines_by_sku".into(),
+            columns: vec!["sku".into(), "line".into()],
+            unique: false,
+        })
+        .unwrap();
+
+    let serialised = table.serialise();
+
+    let result = V4.deserialise_table(&mut serialised.as_slice()).unwrap();
+
+    // Including the indexes made by `CREATE INDEX`
+    assert_eq!(result, table);
+
+    // The indexes are rebuilt from the keys
+    let mut result = result;
+
+    assert!(matches!(
+        result.insert(&None, vec![1.into(), 1.into(), "b".into()]),
+        Err(SqlError::DuplicateKey(_, _))
+    ));
+
+    // So are the defaults and checks
+    result
+        .insert(
+            &Some(vec!["order_id".into(), "line".into()]),
+            vec![1.into(), 2.into()],
+        )
+        .unwrap();
+
+    assert_eq!(result.values[1].0[2], "none".into());
+
+    assert!(matches!(
+        result.insert(&None, vec![1.into(), 0.into(), "c".into()]),
+        Err(SqlError::CheckViolation(_, _))
+    ));
+
+    let serialised = vec![&table.schema].serialise();
+
+    let result = V4.deserialise_schemas(&mut serialised.as_slice()).unwrap();
+
+    assert_eq!(result, vec![table.schema.clone()]);
+
+    let foreign_key = table.constraints[0].serialise();
+
+    let mut wrong_kind = foreign_key.clone();
+
+    wrong_kind[0] = 2;
+
+    assert!(matches!(
+        ForeignKeyConstraint::deserialise(&mut wrong_kind.as_slice(), DO::None),
+        Err(SqlError::NotAConstraintDiscriminator(2))
+    ));
+
+    let mut wrong_action = foreign_key.clone();
+
+    *wrong_action.last_mut().unwrap() = 4;
+
+    assert!(matches!(
+        ForeignKeyConstraint::deserialise(&mut wrong_action.as_slice(), DO::None),
+        Err(SqlError::NotAReferentialAction(4))
+    ));
+
+    assert!(matches!(
+        ForeignKeyConstraint::deserialise(&mut &foreign_key[..foreign_key.len() - 1], DO::None),
+        Err(SqlError::InputTooShort(0, 1))
+    ));
+}
+
+#[test]
+fn serialise_rowset() {
+    let (table, _) = test_table_with_values();
+
+    let result = table
+        .select(crate::types::ColumnSelector::AllColumns, None)
+        .unwrap();
+
+    let serialised = V4.serialise_rowset(&result).unwrap();
+
+    let expected = vec![
+        // Types
+        2, 0, 0, 0, 0, 0, 0, 0, 1, 4, // Names
+        2, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 102, 105, 114, 115, 116, 6, 0, 0, 0, 0, 0,
+        0, 0, 115, 101, 99, 111, 110, 100,
+        // Values, each row has a null bitmap after its length
+        2, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 1, 2, 0, 0, 0, 0,
+        0, 0, 0, 0, 6, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    assert_eq!(serialised, expected)
+}
+
+#[test]
+fn deserialise_rowset() {
+    let (table, _) = test_table_with_values();
+
+    let result = table
+        .select(crate::types::ColumnSelector::AllColumns, None)
+        .unwrap();
+
+    let serialised = result.serialise();
+
+    let deserialised = V4.deserialise_rowset(&mut serialised.as_slice()).unwrap();
+
+    assert_eq!(result, deserialised,);
+}
+
+#[test]
+fn serialise_sequence_vector() {
+    let mut counting = Sequence::new("counting".into(), Some(-5), Some(3), None).unwrap();
+
+    counting.last_value = Some(1);
+
+    let owned = Sequence::new("tbl_id_seq".into(), None, None, Some("tbl".into())).unwrap();
+
+    let serialised = vec![&counting].serialise();
+
+    let mut expected = vec![1, 0, 0, 0, 0, 0, 0, 0];
+
+    expected.extend([8, 0, 0, 0, 0, 0, 0, 0]);
+    expected.extend(b"counting");
+    expected.extend((-5i64).to_le_bytes());
+    expected.extend(3i64.to_le_bytes());
+    expected.extend([1]);
+    expected.extend(1i64.to_le_bytes());
+    expected.extend([0]);
+
+    assert_eq!(serialised, expected);
+
+    let serialised = serialise_sequences(vec![&counting, &owned]);
+
+    let result = deserialise_sequences(&mut serialised.as_slice()).unwrap();
+
+    assert_eq!(result, vec![counting, owned]);
+
+    let result = deserialise_sequences(&mut &serialised[..serialised.len() - 1]);
+
+    assert!(
+        matches!(result, Err(SqlError::InputTooShort(_, _))),
+        "{result:?}"
+    );
+}
@@ -0,0 +1,199 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tokio::sync::Mutex as AsyncMutex;
+
+use crate::{
+    persistence::PersistenceManager,
+    types::{DatabaseName, TableName},
+    Database, Result, SqlError,
+};
+
+/// A loaded database, shared by every connection that uses it.
+///
+/// Connections lock it for an entire statement, including saving the changes,
+/// so they never see each other's changes half done, or save over them.
+pub type DatabaseHandle = Arc<AsyncMutex<Database>>;
+
+#[derive(Debug)]
+struct Loaded {
+    handle: DatabaseHandle,
+    version: u64, // How often it was saved, to tell whether it changed
+}
+
+/// The databases of the server, which each get loaded once, by the first connection that uses them.
+#[derive(Debug)]
+pub struct Catalog {
+    persistence_manager: Box<dyn PersistenceManager>,
+    // Only locked to look databases up, never while waiting for anything
+    databases: Mutex<HashMap<DatabaseName, Loaded>>,
+    // Locked while databases are loaded from storage, created or removed from it,
+    // so e.g. a database can't be loaded again while it is dropped
+    storage: AsyncMutex<()>,
+}
+
+impl Loaded {
+    fn new(database: Database) -> Self {
+        return Self {
+            handle: Arc::new(AsyncMutex::new(database)),
+            version: 0,
+        };
+    }
+}
+
+impl Catalog {
+    pub fn new(persistence_manager: impl PersistenceManager + 'static) -> Self {
+        return Self {
+            persistence_manager: Box::new(persistence_manager),
+            databases: Mutex::new(HashMap::new()),
+            storage: AsyncMutex::new(()),
+        };
+    }
+
+    fn databases(&self) -> MutexGuard<'_, HashMap<DatabaseName, Loaded>> {
+        // A connection that panicked while looking something up didn't leave the map half changed
+        return self
+            .databases
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+    }
+
+    /// The database called `name`, which is loaded first if no connection has used it yet.
+    pub async fn open(&self, name: &DatabaseName) -> Result<DatabaseHandle> {
+        if let Some(loaded) = self.databases().get(name) {
+            return Ok(loaded.handle.clone());
+        }
+
+        let _storage = self.storage.lock().await;
+
+        // Another connection could have loaded it while this one waited, then everyone uses that one
+        if let Some(loaded) = self.databases().get(name) {
+            return Ok(loaded.handle.clone());
+        }
+
+        let database = self.persistence_manager.load_database(name).await?;
+
+        let loaded = Loaded::new(database);
+
+        let handle = loaded.handle.clone();
+
+        self.databases().insert(name.clone(), loaded);
+
+        return Ok(handle);
+    }
+
+    #[cfg(test)]
+    pub fn insert(&self, database: Database) -> DatabaseHandle {
+        let name = database.name.clone();
+
+        let loaded = Loaded::new(database);
+
+        let handle = loaded.handle.clone();
+
+        self.databases().insert(name, loaded);
+
+        return handle;
+    }
+
+    /// Adds a new database and saves it.
+    pub async fn create(&self, database: Database) -> Result<DatabaseHandle> {
+        let name = database.name.clone();
+
+        let _storage = self.storage.lock().await;
+
+        // It may be stored without anyone having opened it yet
+        if self.persistence_manager.database_exists(&name).await {
+            return Err(SqlError::DuplicateDatabase(name));
+        }
+
+        let loaded = Loaded::new(database);
+
+        let handle = loaded.handle.clone();
+
+        // Locked before anyone else can get it, so nobody sees it before it is saved
+        let database = handle.clone().lock_owned().await;
+
+        {
+            let mut databases = self.databases();
+
+            if databases.contains_key(&name) {
+                return Err(SqlError::DuplicateDatabase(name));
+            }
+
+            databases.insert(name.clone(), loaded);
+        }
+
+        if let Err(error) = self.persistence_manager.save_database(&database).await {
+            self.databases().remove(&name);
+
+            return Err(error);
+        }
+
+        return Ok(handle);
+    }
+
+    /// Whether `handle` is still the database called `name`, which it isn't once that was dropped.
+    pub fn contains(&self, name: &DatabaseName, handle: &DatabaseHandle) -> bool {
+        return self.version(name, handle).is_some();
+    }
+
+    /// How often the database was saved, if `handle` is still the database called `name`.
+    ///
+    /// It can only change while the database is locked, see [`Catalog::save`].
+    pub fn version(&self, name: &DatabaseName, handle: &DatabaseHandle) -> Option<u64> {
+        return self
+            .databases()
+            .get(name)
+            .filter(|loaded| Arc::ptr_eq(&loaded.handle, handle))
+            .map(|loaded| loaded.version);
+    }
+
+    /// Saves the changes to a database, which the caller has locked.
+    pub async fn save(&self, database: &Database) -> Result<()> {
+        self.persistence_manager.save_database(database).await?;
+
+        if let Some(loaded) = self.databases().get_mut(&database.name) {
+            loaded.version += 1;
+        }
+
+        return Ok(());
+    }
+
+    /// Moves the stored data of a table that was renamed in the database.
+    pub async fn rename_table(
+        &self,
+        database: &Database,
+        from: &TableName,
+        to: &TableName,
+    ) -> Result<()> {
+        return self
+            .persistence_manager
+            .rename_table(database, from, to)
+            .await;
+    }
+
+    /// Removes the stored data of a table that is no longer in the database.
+    pub async fn drop_table(&self, database: &Database, name: &TableName) -> Result<()> {
+        return self
+            .persistence_manager
+            .drop_table(&database.name, name)
+            .await;
+    }
+
+    /// Removes the database, which the caller has locked,
+    /// so connections waiting for it find out it's gone once they get it.
+    pub async fn drop_database(&self, database: &Database) -> Result<()> {
+        let _storage = self.storage.lock().await;
+
+        self.persistence_manager
+            .drop_database(&database.name)
+            .await?;
+
+        self.databases().remove(&database.name);
+
+        return Ok(());
+    }
+}
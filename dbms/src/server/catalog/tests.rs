@@ -0,0 +1,360 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::{
+    database::{Row, Sequence, Table},
+    evaluate::{Execute, ExecutionResult},
+    server::Runtime,
+    types::TableSchema,
+    utils::tests::*,
+};
+
+use super::*;
+
+/// Keeps a copy of every saved database, like a file system would.
+#[derive(Debug, Default)]
+struct Memory(Mutex<HashMap<DatabaseName, Database>>);
+
+impl Memory {
+    fn saved(&self, name: &DatabaseName) -> Option<Database> {
+        return self.0.lock().unwrap().get(name).cloned();
+    }
+}
+
+#[async_trait]
+impl PersistenceManager for Arc<Memory> {
+    async fn save_database(&self, database: &Database) -> Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(database.name.clone(), database.clone());
+
+        return Ok(());
+    }
+
+    async fn load_database(&self, name: &DatabaseName) -> Result<Database> {
+        let saved = self.saved(name);
+
+        // Lets other connections go on while it's being loaded, like reading the files would
+        tokio::task::yield_now().await;
+
+        return saved.ok_or_else(|| SqlError::DatabaseDoesNotExist(name.clone()));
+    }
+
+    async fn drop_database(&self, name: &DatabaseName) -> Result<()> {
+        self.0.lock().unwrap().remove(name);
+
+        return Ok(());
+    }
+
+    async fn database_exists(&self, name: &DatabaseName) -> bool {
+        return self.0.lock().unwrap().contains_key(name);
+    }
+
+    async fn save_table(&self, _: &DatabaseName, _: &Table) -> Result<()> {
+        return Ok(());
+    }
+
+    async fn load_table(&self, _: &DatabaseName, _: TableName) -> Result<Table> {
+        unimplemented!();
+    }
+
+    async fn drop_table(&self, _: &DatabaseName, _: &TableName) -> Result<()> {
+        return Ok(());
+    }
+
+    async fn rename_table(&self, _: &Database, _: &TableName, _: &TableName) -> Result<()> {
+        return Ok(());
+    }
+
+    async fn save_schemas(&self, _: &Database) -> Result<()> {
+        return Ok(());
+    }
+
+    async fn load_schemas(&self, _: &DatabaseName) -> Result<Vec<TableSchema>> {
+        unimplemented!();
+    }
+
+    async fn save_sequences(&self, _: &Database) -> Result<()> {
+        return Ok(());
+    }
+
+    async fn load_sequences(&self, _: &DatabaseName) -> Result<Vec<Sequence>> {
+        unimplemented!();
+    }
+}
+
+async fn execute(runtime: &mut Runtime, statement: &str) -> Result<ExecutionResult> {
+    return sql_parse::parse_statement(statement)
+        .unwrap()
+        .execute(runtime)
+        .await;
+}
+
+async fn count(runtime: &mut Runtime) -> usize {
+    let ExecutionResult::Select(row_set) =
+        execute(runtime, "SELECT * FROM test_table;").await.unwrap()
+    else {
+        panic!("Not a SELECT");
+    };
+
+    return row_set.values.len();
+}
+
+#[tokio::test]
+async fn shared_between_connections() {
+    let memory = Arc::new(Memory::default());
+
+    let catalog = Arc::new(Catalog::new(memory.clone()));
+
+    let mut first = Runtime::new(catalog.clone());
+    let mut second = Runtime::new(catalog.clone());
+
+    first.create_database(test_db_with_values()).await.unwrap();
+
+    second.load(&"test_db".into()).await.unwrap();
+
+    execute(&mut first, "INSERT INTO test_table VALUES (7, TRUE);")
+        .await
+        .unwrap();
+
+    assert_eq!(count(&mut second).await, 3);
+
+    execute(&mut second, "DELETE FROM test_table WHERE first = 5;")
+        .await
+        .unwrap();
+
+    assert_eq!(count(&mut first).await, 2);
+
+    let saved = memory.saved(&"test_db".into()).unwrap();
+
+    assert_eq!(
+        saved.tables["test_table"].values,
+        vec![
+            Row(vec![6.into(), false.into()]),
+            Row(vec![7.into(), true.into()]),
+        ]
+    );
+
+    let result = first.create_database(test_db()).await;
+
+    assert!(matches!(result, Err(SqlError::DuplicateDatabase(_))));
+}
+
+#[tokio::test]
+async fn create_stored_but_not_loaded() {
+    let memory = Arc::new(Memory::default());
+
+    memory.save_database(&test_db_with_values()).await.unwrap();
+
+    let catalog = Arc::new(Catalog::new(memory.clone()));
+
+    let mut runtime = Runtime::new(catalog.clone());
+
+    let result = runtime.create_database(test_db()).await;
+
+    assert!(matches!(result, Err(SqlError::DuplicateDatabase(_))));
+
+    let saved = memory.saved(&"test_db".into()).unwrap();
+
+    assert_eq!(saved.tables["test_table"].values.len(), 2);
+
+    runtime.load(&"test_db".into()).await.unwrap();
+
+    assert_eq!(count(&mut runtime).await, 2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_writes() {
+    let memory = Arc::new(Memory::default());
+
+    let catalog = Arc::new(Catalog::new(memory.clone()));
+
+    Runtime::new(catalog.clone())
+        .create_database(test_db_with_values())
+        .await
+        .unwrap();
+
+    let writers = (0..20).map(|value| {
+        let mut runtime = Runtime::new(catalog.clone());
+
+        tokio::spawn(async move {
+            runtime.load(&"test_db".into()).await.unwrap();
+
+            let statement = format!("INSERT INTO test_table VALUES ({value}, TRUE);");
+
+            execute(&mut runtime, &statement).await.unwrap();
+        })
+    });
+
+    for writer in writers.collect::<Vec<_>>() {
+        writer.await.unwrap();
+    }
+
+    let saved = memory.saved(&"test_db".into()).unwrap();
+
+    // None of the writes were saved over by another connection
+    assert_eq!(saved.tables["test_table"].values.len(), 22);
+}
+
+#[tokio::test]
+async fn dropped_by_another_connection() {
+    let catalog = Arc::new(Catalog::new(Arc::new(Memory::default())));
+
+    let mut first = Runtime::new(catalog.clone());
+    let mut second = Runtime::new(catalog.clone());
+
+    first.create_database(test_db_with_values()).await.unwrap();
+
+    second.load(&"test_db".into()).await.unwrap();
+
+    let result = execute(&mut first, "DROP DATABASE test_db;").await.unwrap();
+
+    assert_eq!(result, ExecutionResult::DropDatabase("test_db".into()));
+
+    let result = execute(&mut second, "SELECT * FROM test_table;").await;
+
+    assert!(matches!(result, Err(SqlError::NoDatabaseSelected)));
+
+    let result = second.load(&"test_db".into()).await;
+
+    assert!(matches!(result, Err(SqlError::DatabaseDoesNotExist(_))));
+}
+
+#[tokio::test]
+async fn dropped_while_loading() {
+    let memory = Arc::new(Memory::default());
+
+    memory.save_database(&test_db_with_values()).await.unwrap();
+
+    let catalog = Arc::new(Catalog::new(memory.clone()));
+
+    let mut first = Runtime::new(catalog.clone());
+    let mut second = Runtime::new(catalog.clone());
+
+    let loading = tokio::spawn(async move {
+        second.load(&"test_db".into()).await.unwrap();
+
+        second
+    });
+
+    first.load(&"test_db".into()).await.unwrap();
+
+    execute(&mut first, "DROP DATABASE test_db;").await.unwrap();
+
+    let mut second = loading.await.unwrap();
+
+    // Got the database that was dropped, instead of loading it again
+    let result = execute(&mut second, "SELECT * FROM test_table;").await;
+
+    assert!(matches!(result, Err(SqlError::NoDatabaseSelected)));
+
+    let result = Runtime::new(catalog).load(&"test_db".into()).await;
+
+    assert!(matches!(result, Err(SqlError::DatabaseDoesNotExist(_))));
+}
+
+#[tokio::test]
+async fn transaction_isolation() {
+    let memory = Arc::new(Memory::default());
+
+    let catalog = Arc::new(Catalog::new(memory.clone()));
+
+    let mut first = Runtime::new(catalog.clone());
+    let mut second = Runtime::new(catalog.clone());
+
+    first.create_database(test_db_with_values()).await.unwrap();
+
+    second.load(&"test_db".into()).await.unwrap();
+
+    execute(&mut first, "BEGIN;").await.unwrap();
+
+    execute(&mut first, "INSERT INTO test_table VALUES (7, TRUE);")
+        .await
+        .unwrap();
+
+    assert_eq!(count(&mut first).await, 3);
+
+    let saved = memory.saved(&"test_db".into()).unwrap();
+
+    assert_eq!(saved.tables["test_table"].values.len(), 2);
+
+    // Isn't kept waiting for the transaction, and doesn't see it before it's committed
+    assert_eq!(count(&mut second).await, 2);
+
+    execute(&mut first, "DELETE FROM test_table WHERE first = 5;")
+        .await
+        .unwrap();
+
+    execute(&mut first, "COMMIT;").await.unwrap();
+
+    assert_eq!(count(&mut second).await, 2);
+
+    let saved = memory.saved(&"test_db".into()).unwrap();
+
+    assert_eq!(
+        saved.tables["test_table"].values,
+        vec![
+            Row(vec![6.into(), false.into()]),
+            Row(vec![7.into(), true.into()]),
+        ]
+    );
+
+    // A connection that closes in the middle of a transaction rolls it back
+    execute(&mut second, "BEGIN;").await.unwrap();
+
+    execute(&mut second, "DELETE FROM test_table;")
+        .await
+        .unwrap();
+
+    drop(second);
+
+    assert_eq!(count(&mut first).await, 2);
+}
+
+#[tokio::test]
+async fn transaction_conflict() {
+    let memory = Arc::new(Memory::default());
+
+    let catalog = Arc::new(Catalog::new(memory.clone()));
+
+    let mut first = Runtime::new(catalog.clone());
+    let mut second = Runtime::new(catalog.clone());
+
+    first.create_database(test_db_with_values()).await.unwrap();
+
+    second.load(&"test_db".into()).await.unwrap();
+
+    execute(&mut first, "BEGIN;").await.unwrap();
+
+    execute(&mut first, "INSERT INTO test_table VALUES (7, TRUE);")
+        .await
+        .unwrap();
+
+    execute(&mut second, "INSERT INTO test_table VALUES (8, TRUE);")
+        .await
+        .unwrap();
+
+    let result = execute(&mut first, "COMMIT;").await;
+
+    assert!(matches!(result, Err(SqlError::TransactionConflict)));
+
+    // Rolled back, instead of saving over the change of the other connection
+    let result = execute(&mut first, "ROLLBACK;").await;
+
+    assert!(matches!(result, Err(SqlError::NoTransaction)));
+
+    assert_eq!(count(&mut first).await, 3);
+
+    let saved = memory.saved(&"test_db".into()).unwrap();
+
+    assert_eq!(
+        saved.tables["test_table"].values,
+        vec![
+            Row(vec![5.into(), true.into()]),
+            Row(vec![6.into(), false.into()]),
+            Row(vec![8.into(), true.into()]),
+        ]
+    );
+}
@@ -1,22 +1,23 @@
 #[cfg(test)]
 mod tests;
 
-use std::path::PathBuf;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 
 use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
+    io::{AsyncReadExt, AsyncWrite, AsyncWriteExt},
     net::TcpStream,
-    sync::broadcast::Receiver,
+    sync::{broadcast::Receiver, OwnedMutexGuard},
 };
 
 #[cfg(test)]
 use crate::persistence::NoOp;
 
 use crate::{
-    evaluate::{Execute, ExecutionResult},
-    persistence::{FileSystem, PersistenceManager},
+    database::RowSet,
+    evaluate::{Execute, ExecutionResult, RowSink},
     serialisation::{SerialisationManager, Serialiser},
-    types::DatabaseName,
+    types::{DatabaseName, TableName},
     utils::serialiser_version_to_serialiser,
     Database, Result, SqlError,
 };
@@ -24,72 +25,189 @@ use crate::{
 use sql_parse::parse_statement;
 
 use super::{
+    catalog::{Catalog, DatabaseHandle},
     protocol::{Command, Message, MessageBody},
     Stream,
 };
 
+/// What a connection is using, the database it selected from the [`Catalog`] it shares with all others.
 #[derive(Debug)]
 pub struct Runtime {
-    persistence_manager: Box<dyn PersistenceManager>,
-    database: Option<Database>,
+    catalog: Arc<Catalog>,
+    database: Option<(DatabaseName, DatabaseHandle)>,
+    transaction: Option<Transaction>,
+}
+
+/// Changes to the selected database that are only saved once they are committed.
+///
+/// They are made to a copy of it, so other connections don't see them before that,
+/// and can keep using the database in the meantime.
+/// Dropping it rolls them back, also when the connection closes.
+#[derive(Debug)]
+struct Transaction {
+    database: Option<Database>, // Taken while one of its statements uses it
+    version: u64,               // Of the database it's a copy of, see [`Catalog::version`]
+    renamed_tables: Vec<TableName>, // Their old names
+}
+
+/// The database a statement uses, see [`Runtime::lock_database`].
+#[derive(Debug)]
+pub enum DatabaseGuard {
+    Locked(OwnedMutexGuard<Database>), // Shared with the other connections
+    Transaction(Database),             // The copy of the transaction
+}
+
+impl Deref for DatabaseGuard {
+    type Target = Database;
+
+    fn deref(&self) -> &Database {
+        return match self {
+            DatabaseGuard::Locked(database) => database,
+            DatabaseGuard::Transaction(database) => database,
+        };
+    }
+}
+
+impl DerefMut for DatabaseGuard {
+    fn deref_mut(&mut self) -> &mut Database {
+        return match self {
+            DatabaseGuard::Locked(database) => database,
+            DatabaseGuard::Transaction(database) => database,
+        };
+    }
 }
 
 #[cfg(test)]
 impl Runtime {
     pub fn new_test() -> Self {
+        return Self::new(Arc::new(Catalog::new(NoOp)));
+    }
+
+    /// A runtime with `database` selected, without saving it first.
+    pub fn new_test_with(database: Database) -> Self {
+        let catalog = Arc::new(Catalog::new(NoOp));
+
+        let name = database.name.clone();
+
+        let handle = catalog.insert(database);
+
         return Self {
-            persistence_manager: Box::new(NoOp),
-            database: None,
+            catalog,
+            database: Some((name, handle)),
+            transaction: None,
         };
     }
 }
 
 impl Runtime {
-    pub fn new(persistence_manager: impl PersistenceManager + 'static) -> Self {
+    pub fn new(catalog: Arc<Catalog>) -> Self {
         return Self {
-            persistence_manager: Box::new(persistence_manager),
+            catalog,
             database: None,
+            transaction: None,
         };
     }
 
-    pub fn create_database(&mut self, database: Database) {
-        self.database = Some(database);
+    /// Creates and selects a new database.
+    pub async fn create_database(&mut self, database: Database) -> Result<()> {
+        self.check_no_transaction()?;
+
+        let name = database.name.clone();
+
+        let handle = self.catalog.create(database).await?;
 
-        // TODO: Persistence?
+        self.database = Some((name, handle));
+
+        return Ok(());
     }
 
-    pub fn get_database(&mut self) -> Option<&mut Database> {
-        return self.database.as_mut();
+    /// Waits until no other connection is using the selected database, then keeps it from doing so until the guard is dropped.
+    ///
+    /// `None` if no database is selected, or if it was dropped by another connection, which unselects it.
+    ///
+    /// In a transaction, its copy is taken from the transaction instead, which needs it back, see [`Runtime::unlock_database`].
+    pub async fn lock_database(&mut self) -> Option<DatabaseGuard> {
+        if let Some(transaction) = &mut self.transaction {
+            return transaction.database.take().map(DatabaseGuard::Transaction);
+        }
+
+        let (name, handle) = self.database.as_ref()?;
+
+        let database = handle.clone().lock_owned().await;
+
+        if !self.catalog.contains(name, handle) {
+            self.database = None;
+
+            return None;
+        }
+
+        return Some(DatabaseGuard::Locked(database));
+    }
+
+    /// Gives the copy back to the transaction it was taken from, or lets other connections use the database again.
+    pub fn unlock_database(&mut self, database: Option<DatabaseGuard>) {
+        if let (Some(transaction), Some(DatabaseGuard::Transaction(database))) =
+            (&mut self.transaction, database)
+        {
+            transaction.database = Some(database);
+        }
+    }
+
+    /// See [`Catalog::version`].
+    fn version(&self) -> Option<u64> {
+        let (name, handle) = self.database.as_ref()?;
+
+        return self.catalog.version(name, handle);
     }
 
     pub fn clear_database(&mut self) -> Result<DatabaseName> {
+        self.check_no_transaction()?;
+
+        return match self.database.take() {
+            Some((name, _)) => Ok(name),
+            None => Err(SqlError::NoDatabaseSelected),
+        };
+    }
+
+    /// Saves the changes to the selected database, which the caller has locked.
+    pub async fn save(&self, database: &Database) -> Result<()> {
+        // Dropping the database unselects it, and there's nothing left to save then
         if self.database.is_none() {
-            return Err(SqlError::NoDatabaseSelected);
+            return Ok(());
         }
 
-        let name = self.database.as_ref().unwrap().name.clone();
-
-        self.database = None;
+        // Saved when the transaction is committed instead
+        if self.transaction.is_some() {
+            return Ok(());
+        }
 
-        return Ok(name);
+        return self.catalog.save(database).await;
     }
 
-    // I think these two methods make sense?
-    pub async fn save(&mut self) -> Result<()> {
-        if let Some(database) = &self.database {
-            return self.persistence_manager.save_database(database).await;
-        } else {
-            return Err(SqlError::NoDatabaseSelected);
+    /// Moves the stored data of a table that was renamed in the database.
+    pub async fn rename_table(
+        &mut self,
+        database: &Database,
+        from: &TableName,
+        to: &TableName,
+    ) -> Result<()> {
+        // Stored under the new name when the transaction is committed
+        if let Some(transaction) = &mut self.transaction {
+            transaction.renamed_tables.push(from.clone());
+
+            return Ok(());
         }
+
+        return self.catalog.rename_table(database, from, to).await;
     }
 
+    /// Selects the database called `database_name`, which is loaded unless another connection already did.
     pub async fn load(&mut self, database_name: &DatabaseName) -> Result<()> {
-        let result = self
-            .persistence_manager
-            .load_database(database_name)
-            .await?;
+        self.check_no_transaction()?;
 
-        self.database = Some(result);
+        let handle = self.catalog.open(database_name).await?;
+
+        self.database = Some((database_name.clone(), handle));
 
         return Ok(());
     }
@@ -100,19 +218,92 @@ impl Runtime {
     // what interface makes sense? Just naming it `drop` doesn't make sense,
     // also this function checks and clears self.database and that's not necessary in that case.
     // When I wrote this code, I wasn't thinking about how it would be used.
-    pub async fn drop(&mut self) -> Result<()> {
-        if let Some(database) = &self.database {
-            self.persistence_manager
-                .drop_database(&database.name)
-                .await?;
+    /// Drops the selected database, which the caller has locked.
+    pub async fn drop(&mut self, database: &Database) -> Result<()> {
+        self.check_no_transaction()?;
 
-            // Note: Only clears own database if dropping succeeded
-            self.database = None;
+        self.catalog.drop_database(database).await?;
+
+        // Note: Only clears own database if dropping succeeded
+        self.database = None;
+
+        return Ok(());
+    }
+
+    /// Starts a transaction on a copy of the selected database.
+    pub async fn begin(&mut self) -> Result<()> {
+        self.check_no_transaction()?;
+
+        let database = self
+            .lock_database()
+            .await
+            .ok_or(SqlError::NoDatabaseSelected)?;
+
+        // Taken while it's locked, so it's the version of the copy
+        let version = self.version().ok_or(SqlError::NoDatabaseSelected)?;
+
+        self.transaction = Some(Transaction {
+            database: Some(Database::clone(&database)),
+            version,
+            renamed_tables: vec![],
+        });
+
+        return Ok(());
+    }
+
+    /// Saves the changes of the transaction, unless another connection changed the database since it began.
+    ///
+    /// The transaction is rolled back if they can't be saved.
+    pub async fn commit(&mut self) -> Result<()> {
+        let transaction = self.transaction.take().ok_or(SqlError::NoTransaction)?;
+
+        // Only locked now, to put the changes in
+        let mut database = self
+            .lock_database()
+            .await
+            .ok_or(SqlError::NoDatabaseSelected)?;
 
+        if self.version() != Some(transaction.version) {
+            return Err(SqlError::TransactionConflict);
+        }
+
+        let Some(changed) = transaction.database else {
             return Ok(());
-        } else {
-            return Err(SqlError::NoDatabaseSelected);
+        };
+
+        // Tables made during the transaction were never stored under their old name
+        let dropped_tables: Vec<_> = transaction
+            .renamed_tables
+            .into_iter()
+            .filter(|name| {
+                database.tables.contains_key(&name.0) && !changed.tables.contains_key(&name.0)
+            })
+            .collect();
+
+        self.catalog.save(&changed).await?;
+
+        *database = changed;
+
+        for name in &dropped_tables {
+            self.catalog.drop_table(&database, name).await?;
         }
+
+        return Ok(());
+    }
+
+    /// Undoes the changes of the transaction.
+    pub fn rollback(&mut self) -> Result<()> {
+        // Dropping it rolls it back
+        self.transaction.take().ok_or(SqlError::NoTransaction)?;
+
+        return Ok(());
+    }
+
+    fn check_no_transaction(&self) -> Result<()> {
+        return match self.transaction {
+            Some(_) => Err(SqlError::TransactionInProgress),
+            None => Ok(()),
+        };
     }
 }
 
@@ -129,8 +320,12 @@ pub struct Context {
 }
 
 impl Connection {
-    pub async fn new(mut stream: TcpStream, shutdown_receiver: Receiver<()>) -> Result<Self> {
-        let context = Connection::setup_context(&mut stream).await?;
+    pub async fn new(
+        mut stream: TcpStream,
+        shutdown_receiver: Receiver<()>,
+        catalog: Arc<Catalog>,
+    ) -> Result<Self> {
+        let context = Connection::setup_context(&mut stream, catalog).await?;
 
         return Ok(Connection {
             stream,
@@ -144,25 +339,17 @@ impl Connection {
     /// Returns a [`Context`] object populated with these parameters
     /// as well as other (default) parameters.
     // I don't quite like this function name
-    async fn setup_context(stream: &mut impl Stream) -> Result<Context> {
+    async fn setup_context(stream: &mut impl Stream, catalog: Arc<Catalog>) -> Result<Context> {
         let serialiser: Serialiser = Connection::negotiate_serialiser_version(stream).await?;
 
-        let runtime = Runtime {
-            persistence_manager: Box::new(FileSystem::new(
-                SerialisationManager(serialiser),
-                PathBuf::from("/tmp/rusty-db"),
-            )),
-            database: None,
-        };
-
         return Ok(Context {
             serialiser,
-            runtime,
+            runtime: Runtime::new(catalog),
         });
     }
 
     async fn negotiate_serialiser_version(stream: &mut impl Stream) -> Result<Serialiser> {
-        let available_serialiser_versions = [1, 2];
+        let available_serialiser_versions = [1, 2, 3, 4];
 
         stream
             .write_all(
@@ -201,11 +388,13 @@ impl Connection {
                     // This breaks out of the loop
                     let message = message?;
 
+                    let mut rows = MessageSink::new(&mut self.stream, self.context.serialiser);
+
                     // Handle message
                     let result = match message.body {
                         MessageBody::Close => break,
                         MessageBody::Ok => Ok(ExecutionResult::None),
-                        MessageBody::Str(statement) => handle_statement(&statement, &mut self.context.runtime).await,
+                        MessageBody::Str(statement) => handle_statement(&statement, &mut self.context.runtime, &mut rows).await,
                         MessageBody::Command(command) => handle_special_commands(command, &mut self.context.runtime).await,
                         MessageBody::Error(error) => {
                             println!("ERROR: {error:?}");
@@ -223,9 +412,24 @@ impl Connection {
                     // Have to convert an ExecutionResult into a MessageBody
 
                     // For now, just debug printing as message and yeeting it over hell yeah
-                    let response = Message::from_message_body(MessageBody::Str(format!("{result:?}")));
+                    // Errors do get sent as errors, so the client can tell the user what went wrong
+                    let response = match result {
+                        // The rows were sent already, this tells the client there are no more
+                        Ok(ExecutionResult::None) if rows.sent => Message::from_message_body(MessageBody::Ok),
+                        Ok(result) => Message::from_message_body(MessageBody::Str(format!("{result:?}"))),
+                        Err(error) => Message::from_message_body(MessageBody::Error(error)),
+                    };
+
+                    let written = response.write(&mut self.stream, SerialisationManager(self.context.serialiser)).await;
+
+                    // Older clients can't read every value, tell them instead of hanging up
+                    if let Err(error @ (SqlError::IncompatibleType(_) | SqlError::IncompatibleValue(_))) = written {
+                        let message = Message::from_message_body(MessageBody::Error(error));
 
-                    response.write(&mut self.stream, SerialisationManager(self.context.serialiser)).await?;
+                        message.write(&mut self.stream, SerialisationManager(self.context.serialiser)).await?;
+                    } else {
+                        written?;
+                    }
                 },
                 _ = self.shutdown_receiver.recv() => {
                     let message = Message::from_message_body(MessageBody::Close);
@@ -256,25 +460,65 @@ async fn handle_special_commands(
         }
         Command::ListTables => {
             let database = runtime
-                .database
-                .as_ref()
+                .lock_database()
+                .await
                 .ok_or(SqlError::NoDatabaseSelected)?;
 
             let names = database.tables.keys().cloned().collect();
 
+            runtime.unlock_database(Some(database));
+
             return Ok(ExecutionResult::ListTables(names));
         }
     }
 }
 
-async fn handle_statement(input: &str, runtime: &mut Runtime) -> Result<ExecutionResult> {
-    let statement = parse_statement(input);
+async fn handle_statement(
+    input: &str,
+    runtime: &mut Runtime,
+    rows: &mut impl RowSink,
+) -> Result<ExecutionResult> {
+    let statement = parse_statement(input).map_err(SqlError::ParseError)?;
 
-    if statement.is_none() {
-        println!("Failed to parse: {input}");
+    return statement.execute_into(runtime, rows).await;
+}
 
-        return Err(SqlError::ParseError);
+/// How many rows of a query are sent in one message.
+const ROWS_PER_MESSAGE: usize = 1000;
+
+/// Sends the rows of a query to the client a few at a time, as [`MessageBody::RowSet`]s.
+struct MessageSink<'a, W> {
+    stream: &'a mut W,
+    serialiser: Serialiser,
+    rows_per_message: usize,
+    sent: bool,
+}
+
+impl<'a, W: AsyncWrite + Unpin + Send> MessageSink<'a, W> {
+    fn new(stream: &'a mut W, serialiser: Serialiser) -> Self {
+        return Self {
+            stream,
+            serialiser,
+            rows_per_message: ROWS_PER_MESSAGE,
+            sent: false,
+        };
+    }
+}
+
+impl<W: AsyncWrite + Unpin + Send> RowSink for MessageSink<'_, W> {
+    fn rows_per_chunk(&self) -> usize {
+        return self.rows_per_message;
     }
 
-    return statement.unwrap().execute(runtime).await;
+    async fn send(&mut self, rows: RowSet) -> Result<()> {
+        let message = Message::from_message_body(MessageBody::RowSet(rows));
+
+        message
+            .write(self.stream, SerialisationManager(self.serialiser))
+            .await?;
+
+        self.sent = true;
+
+        return Ok(());
+    }
 }
@@ -6,6 +6,7 @@ use tokio::{
 use tokio_test::io::Builder as TestIoBuilder;
 
 use crate::{
+    database::{Row, StatementContext},
     persistence::NoOp,
     serialisation::{SerialisationManager, Serialiser},
     utils::tests::*,
@@ -46,11 +47,11 @@ async fn message_read_write() {
     }
 }
 
-#[test]
-fn create_get_clear_database_basic() {
+#[tokio::test]
+async fn create_get_clear_database_basic() {
     let mut runtime = test_runtime_with_values();
 
-    let db = runtime.get_database();
+    let db = runtime.lock_database().await;
 
     // Kinda pointless but whatever
     assert_eq!(db.unwrap().name, "test_db".into(),);
@@ -62,7 +63,7 @@ fn create_get_clear_database_basic() {
 
 #[test]
 fn clear_database_none_selected() {
-    let mut runtime = Runtime::new(NoOp);
+    let mut runtime = Runtime::new(Arc::new(Catalog::new(NoOp)));
 
     let result = runtime.clear_database();
 
@@ -71,7 +72,10 @@ fn clear_database_none_selected() {
 
 #[tokio::test]
 async fn negotiate_serialiser_version_basic() {
-    let mut client = TestIoBuilder::new().write(&[2, 1, 2]).read(&[1]).build();
+    let mut client = TestIoBuilder::new()
+        .write(&[4, 1, 2, 3, 4])
+        .read(&[1])
+        .build();
 
     let negotiated_version = Connection::negotiate_serialiser_version(&mut client)
         .await
@@ -79,7 +83,10 @@ async fn negotiate_serialiser_version_basic() {
 
     assert_eq!(negotiated_version, Serialiser::V1);
 
-    let mut client = TestIoBuilder::new().write(&[2, 1, 2]).read(&[2]).build();
+    let mut client = TestIoBuilder::new()
+        .write(&[4, 1, 2, 3, 4])
+        .read(&[2])
+        .build();
 
     let negotiated_version = Connection::negotiate_serialiser_version(&mut client)
         .await
@@ -87,57 +94,181 @@ async fn negotiate_serialiser_version_basic() {
 
     assert_eq!(negotiated_version, Serialiser::V2);
 
-    let mut client = TestIoBuilder::new().write(&[2, 1, 2]).read(&[3]).build();
+    let mut client = TestIoBuilder::new()
+        .write(&[4, 1, 2, 3, 4])
+        .read(&[3])
+        .build();
+
+    let negotiated_version = Connection::negotiate_serialiser_version(&mut client)
+        .await
+        .unwrap();
+
+    assert_eq!(negotiated_version, Serialiser::V3);
+
+    let mut client = TestIoBuilder::new()
+        .write(&[4, 1, 2, 3, 4])
+        .read(&[4])
+        .build();
+
+    let negotiated_version = Connection::negotiate_serialiser_version(&mut client)
+        .await
+        .unwrap();
+
+    assert_eq!(negotiated_version, Serialiser::V4);
+
+    let mut client = TestIoBuilder::new()
+        .write(&[4, 1, 2, 3, 4])
+        .read(&[5])
+        .build();
 
     let negotiated_version = Connection::negotiate_serialiser_version(&mut client).await;
 
     assert!(matches!(
         negotiated_version,
-        Err(SqlError::IncompatibleVersion(3)),
+        Err(SqlError::IncompatibleVersion(5)),
     ));
 }
 
 #[tokio::test]
 async fn setup_context_basic() {
-    let mut client = TestIoBuilder::new().write(&[2, 1, 2]).read(&[1]).build();
+    let mut client = TestIoBuilder::new()
+        .write(&[4, 1, 2, 3, 4])
+        .read(&[1])
+        .build();
+
+    let catalog = Arc::new(Catalog::new(NoOp));
 
-    let context = Connection::setup_context(&mut client).await.unwrap();
+    let context = Connection::setup_context(&mut client, catalog.clone())
+        .await
+        .unwrap();
 
     assert_eq!(context.serialiser, Serialiser::V1,);
 
-    let mut client = TestIoBuilder::new().write(&[2, 1, 2]).read(&[0]).build();
+    let mut client = TestIoBuilder::new()
+        .write(&[4, 1, 2, 3, 4])
+        .read(&[0])
+        .build();
 
-    let result = Connection::setup_context(&mut client).await;
+    let result = Connection::setup_context(&mut client, catalog).await;
 
     assert!(matches!(result, Err(SqlError::IncompatibleVersion(0)),));
 }
 
+/// The messages a [`MessageSink`] wrote.
+async fn sent_messages(mut buffer: &[u8]) -> Vec<MessageBody> {
+    let mut result = vec![];
+
+    while !buffer.is_empty() {
+        let message = Message::read(&mut buffer, SerialisationManager(Serialiser::V4))
+            .await
+            .unwrap();
+
+        result.push(message.body);
+    }
+
+    return result;
+}
+
 #[tokio::test]
 async fn handle_statement_basic() {
     let mut runtime = test_runtime_with_values();
 
+    let mut buffer = vec![];
+
+    let mut rows = MessageSink::new(&mut buffer, Serialiser::V4);
+
     let statement = "SELECT * FROM tbl;";
 
-    let result = handle_statement(statement, &mut runtime).await;
+    let result = handle_statement(statement, &mut runtime, &mut rows).await;
 
     dbg!(&result);
     assert!(matches!(result, Err(SqlError::TableDoesNotExist(_)),));
 
     let statement = "SELECT * FROM test_table;";
 
-    let result = handle_statement(statement, &mut runtime).await.unwrap();
+    let result = handle_statement(statement, &mut runtime, &mut rows)
+        .await
+        .unwrap();
+
+    assert_eq!(result, ExecutionResult::None);
+    assert!(rows.sent);
 
     let expected = runtime
-        .database
+        .lock_database()
+        .await
         .unwrap()
         .select(
             "test_table".into(),
             crate::types::ColumnSelector::AllColumns,
             None,
+            &StatementContext::default(),
         )
         .unwrap();
 
-    assert_eq!(result, ExecutionResult::Select(expected));
+    let messages = sent_messages(&buffer).await;
+
+    assert!(matches!(&messages[..], [MessageBody::RowSet(rows)] if *rows == expected));
+}
+
+#[tokio::test]
+async fn handle_statement_streams_rows() {
+    let mut runtime = test_runtime_with_values();
+
+    for statement in [
+        "CREATE TABLE numbers (n INT);",
+        "INSERT INTO numbers VALUES (1), (2), (5), (0), (4);",
+    ] {
+        let statement = parse_statement(statement).unwrap();
+
+        statement.execute(&mut runtime).await.unwrap();
+    }
+
+    let mut buffer = vec![];
+
+    let mut rows = MessageSink::new(&mut buffer, Serialiser::V4);
+
+    rows.rows_per_message = 2;
+
+    let result = handle_statement(
+        "SELECT 10 / n FROM numbers WHERE n <> 0;",
+        &mut runtime,
+        &mut rows,
+    )
+    .await;
+
+    assert_eq!(result.unwrap(), ExecutionResult::None);
+
+    let messages = sent_messages(&buffer).await;
+
+    let sizes: Vec<_> = messages
+        .iter()
+        .map(|message| match message {
+            MessageBody::RowSet(rows) => rows.values.len(),
+            _ => panic!("Wrong messages: {messages:?}"),
+        })
+        .collect();
+
+    assert_eq!(sizes, vec![2, 2, 0]);
+
+    assert!(
+        matches!(&messages[0], MessageBody::RowSet(rows) if rows.values == vec![Row(vec![10.into()]), Row(vec![5.into()])])
+    );
+
+    let mut buffer = vec![];
+
+    let mut rows = MessageSink::new(&mut buffer, Serialiser::V4);
+
+    rows.rows_per_message = 2;
+
+    let result = handle_statement("SELECT 10 / n FROM numbers;", &mut runtime, &mut rows).await;
+
+    assert!(
+        matches!(result, Err(SqlError::DivisionByZero)),
+        "{result:?}"
+    );
+
+    // Nothing is sent before the whole query succeeded
+    assert!(sent_messages(&buffer).await.is_empty());
 }
 
 #[tokio::test]
@@ -146,29 +277,38 @@ async fn handle_statement_parse_error() {
 
     let input = "SELECT SELECT SELECT SELECT SELECT;";
 
-    let result = handle_statement(input, &mut runtime).await;
+    let result = handle_statement(
+        input,
+        &mut runtime,
+        &mut MessageSink::new(&mut vec![], Serialiser::V4),
+    )
+    .await;
 
-    assert!(matches!(result, Err(SqlError::ParseError)));
+    if let Err(SqlError::ParseError(error)) = result {
+        assert_eq!(error.expected, "an identifier or a value or `*`");
+        assert_eq!(error.found, sql_parse::lexer::Token::Select);
+        assert!(error.position.is_some());
+    } else {
+        panic!("Wrong result type: {result:?}");
+    }
 }
 
 #[tokio::test]
 async fn runtime_persistence_basic() {
-    let mut runtime = Runtime::new(NoOp);
-
-    let result = runtime.save().await;
+    let mut runtime = Runtime::new_test();
 
-    assert!(matches!(result, Err(SqlError::NoDatabaseSelected),));
+    runtime.create_database(test_db()).await.unwrap();
 
-    runtime.database = Some(test_db());
+    let database = runtime.lock_database().await.unwrap();
 
-    assert!(runtime.drop().await.is_ok());
+    assert!(runtime.drop(&database).await.is_ok());
 
-    assert_eq!(runtime.database, None,);
+    assert!(runtime.database.is_none());
 
     // Always succeeds, because NoOp persistence never fails
     runtime.load(&"test_db".into()).await.unwrap();
 
-    assert_eq!(runtime.database, Some(test_db()),);
+    assert_eq!(*runtime.lock_database().await.unwrap(), test_db());
 }
 
 #[tokio::test]
@@ -183,5 +323,5 @@ async fn special_commands_basic() {
 
     assert_eq!(result, ExecutionResult::None);
 
-    assert_eq!(runtime.database, Some(test_db()));
+    assert_eq!(*runtime.lock_database().await.unwrap(), test_db());
 }
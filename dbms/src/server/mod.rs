@@ -1,10 +1,14 @@
+mod catalog;
 mod connection;
 mod protocol;
 
+pub use catalog::{Catalog, DatabaseHandle};
 pub use connection::Runtime;
 pub use protocol::{Command, Message, MessageBody};
 
 use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use futures::future::{join_all, select_all, OptionFuture};
 use tokio::{
@@ -18,6 +22,8 @@ use tokio::{
 
 use connection::Connection;
 
+use crate::persistence::FileSystem;
+use crate::serialisation::{SerialisationManager, Serialiser};
 use crate::SqlError;
 
 // Easiest way to make a type alias, `impl` isn't stable in type aliases
@@ -28,6 +34,12 @@ pub async fn server(listen_address: impl ToSocketAddrs) {
     let listener = TcpListener::bind(listen_address).await.unwrap();
     println!("Listening on {:?}", listener.local_addr().unwrap());
 
+    // Stored in the newest format, whichever one each client talks in
+    let catalog = Arc::new(Catalog::new(FileSystem::new(
+        SerialisationManager(Serialiser::V4),
+        PathBuf::from("/tmp/rusty-db"),
+    )));
+
     let mut join_handles = vec![];
 
     let (shutdown_sender, mut shutdown_receiver_main) = channel::<()>(1);
@@ -72,7 +84,7 @@ pub async fn server(listen_address: impl ToSocketAddrs) {
                         eprintln!("Failed to accept connection: {error}");
                     },
                     Ok((stream, address)) => {
-                        join_handles.push(spawn_new_handler(stream, address, shutdown_sender.subscribe()));
+                        join_handles.push(spawn_new_handler(stream, address, shutdown_sender.subscribe(), catalog.clone()));
                     }
                 };
             },
@@ -99,11 +111,12 @@ fn spawn_new_handler(
     stream: TcpStream,
     address: SocketAddr,
     shutdown_receiver: Receiver<()>,
+    catalog: Arc<Catalog>,
 ) -> JoinHandle<Result<(), SqlError>> {
     println!("New connection established from {address:?}");
 
     return spawn(async move {
-        let connection = Connection::new(stream, shutdown_receiver).await?;
+        let connection = Connection::new(stream, shutdown_receiver, catalog).await?;
 
         connection.handle().await
     });
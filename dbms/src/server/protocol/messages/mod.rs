@@ -123,8 +123,8 @@ pub enum MessageBody {
 }
 
 impl MessageBody {
-    fn serialise(&self, serialisation_manager: SerialisationManager) -> Vec<u8> {
-        return match self {
+    fn serialise(&self, serialisation_manager: SerialisationManager) -> Result<Vec<u8>> {
+        let result = match self {
             MessageBody::Close => vec![],
             MessageBody::Ok => vec![],
             MessageBody::Str(value) => {
@@ -136,7 +136,7 @@ impl MessageBody {
             }
             MessageBody::Command(value) => value.into(),
             MessageBody::Error(value) => {
-                let message = format!("ERROR: {:?}", value);
+                let message = format!("ERROR: {value}");
 
                 let mut result = (message.len() as u64).to_le_bytes().to_vec();
 
@@ -144,8 +144,10 @@ impl MessageBody {
 
                 result
             }
-            MessageBody::RowSet(value) => serialisation_manager.serialise_rowset(value),
+            MessageBody::RowSet(value) => serialisation_manager.serialise_rowset(value)?,
         };
+
+        return Ok(result);
     }
 
     fn deserialise(
@@ -219,7 +221,7 @@ impl Message {
         stream: &mut (impl AsyncWrite + std::marker::Unpin),
         serialisation_manager: SerialisationManager,
     ) -> Result<()> {
-        let serialised = self.serialise(serialisation_manager);
+        let serialised = self.serialise(serialisation_manager)?;
 
         stream
             .write_u64_le(serialised.len() as u64)
@@ -234,14 +236,14 @@ impl Message {
         return Ok(());
     }
 
-    fn serialise(&self, serialisation_manager: SerialisationManager) -> Vec<u8> {
+    fn serialise(&self, serialisation_manager: SerialisationManager) -> Result<Vec<u8>> {
         let mut result = vec![];
 
         result.extend(self.header.to_raw().serialise());
 
-        result.extend(self.body.serialise(serialisation_manager));
+        result.extend(self.body.serialise(serialisation_manager)?);
 
-        return result;
+        return Ok(result);
     }
 
     pub async fn read(
@@ -1,6 +1,10 @@
 use tokio_test::io::Builder as TestIoBuilder;
 
+use sql_parse::parser::ColumnType;
+
+use crate::database::Row;
 use crate::serialisation::Serialiser;
+use crate::types::{ColumnValue, Date};
 
 use super::*;
 
@@ -44,7 +48,9 @@ fn close_body_to_message() {
 fn serialise_close_message() {
     let message = Message::from_message_body(MessageBody::Close);
 
-    let serialised = message.serialise(SerialisationManager(Serialiser::V2));
+    let serialised = message
+        .serialise(SerialisationManager(Serialiser::V2))
+        .unwrap();
 
     let expected = RawHeader::new(1, vec![1]).serialise();
 
@@ -97,7 +103,9 @@ fn ok_body_to_message() {
 fn serialise_ok_message() {
     let message = Message::from_message_body(MessageBody::Ok);
 
-    let serialised = message.serialise(SerialisationManager(Serialiser::V2));
+    let serialised = message
+        .serialise(SerialisationManager(Serialiser::V2))
+        .unwrap();
 
     let expected = RawHeader::new(1, vec![2]).serialise();
 
@@ -150,7 +158,9 @@ fn string_body_to_message() {
 fn serialise_string_message() {
     let message = Message::from_message_body(MessageBody::Str("deez nuts".into()));
 
-    let serialised = message.serialise(SerialisationManager(Serialiser::V2));
+    let serialised = message
+        .serialise(SerialisationManager(Serialiser::V2))
+        .unwrap();
 
     let mut expected = vec![
         // Header
@@ -213,6 +223,32 @@ fn error_body_to_message() {
 // fn serialise_error_message() {
 // }
 
+#[test]
+fn parse_error_message_is_readable() {
+    let error = sql_parse::parse_statement("SELECT FROM tbl;").unwrap_err();
+
+    let message = Message::from_message_body(MessageBody::Error(SqlError::ParseError(error)));
+
+    let serialised = message
+        .serialise(SerialisationManager(Serialiser::V2))
+        .unwrap();
+
+    let deserialised = Message::deserialise(
+        &mut serialised.as_slice(),
+        SerialisationManager(Serialiser::V2),
+    )
+    .unwrap();
+
+    if let MessageBody::Str(string) = deserialised.body {
+        assert_eq!(
+            string,
+            "ERROR: Failed to parse: Expected an identifier or a value or `*`, found `FROM` at line 1, column 8"
+        );
+    } else {
+        panic!("Body wrong type");
+    }
+}
+
 #[test]
 fn command_body_to_message() {
     let commands = [
@@ -262,7 +298,9 @@ fn serialise_command_message() {
     inputs.into_iter().for_each(|(input, extra)| {
         let message = Message::from_message_body(MessageBody::Command(input));
 
-        let serialised = message.serialise(SerialisationManager(Serialiser::V2));
+        let serialised = message
+            .serialise(SerialisationManager(Serialiser::V2))
+            .unwrap();
 
         let mut expected = vec![1, 0, 0, 0, 0, 0, 0, 0, 4];
 
@@ -283,7 +321,9 @@ fn deserialise_command_message() {
     inputs.into_iter().for_each(|input| {
         let message = Message::from_message_body(MessageBody::Command(input.clone()));
 
-        let serialised = message.serialise(SerialisationManager(Serialiser::V2));
+        let serialised = message
+            .serialise(SerialisationManager(Serialiser::V2))
+            .unwrap();
 
         let deserialised = Message::deserialise(
             &mut serialised.as_slice(),
@@ -335,7 +375,9 @@ fn serialise_rowset_message() {
         values: vec![],
     }));
 
-    let serialised = message.serialise(SerialisationManager(Serialiser::V2));
+    let serialised = message
+        .serialise(SerialisationManager(Serialiser::V2))
+        .unwrap();
 
     let mut expected = vec![
         // Header
@@ -343,11 +385,13 @@ fn serialise_rowset_message() {
     ];
 
     expected.extend(
-        SerialisationManager(Serialiser::V2).serialise_rowset(&RowSet {
-            types: vec![],
-            names: vec![],
-            values: vec![],
-        }),
+        SerialisationManager(Serialiser::V2)
+            .serialise_rowset(&RowSet {
+                types: vec![],
+                names: vec![],
+                values: vec![],
+            })
+            .unwrap(),
     );
 
     assert_eq!(serialised, expected,);
@@ -361,11 +405,13 @@ fn deserialise_rowset_message() {
     ];
 
     input.extend(
-        SerialisationManager(Serialiser::V2).serialise_rowset(&RowSet {
-            types: vec![],
-            names: vec![],
-            values: vec![],
-        }),
+        SerialisationManager(Serialiser::V2)
+            .serialise_rowset(&RowSet {
+                types: vec![],
+                names: vec![],
+                values: vec![],
+            })
+            .unwrap(),
     );
 
     let message =
@@ -398,7 +444,7 @@ async fn read_message() {
 
     let manager = SerialisationManager(Serialiser::V2);
 
-    let serialised = message.serialise(manager);
+    let serialised = message.serialise(manager).unwrap();
 
     let mut stream = TestIoBuilder::new()
         .read(&(serialised.len() as u64).to_le_bytes())
@@ -415,7 +461,45 @@ async fn write_message() {
 
     let manager = SerialisationManager(Serialiser::V2);
 
-    let serialised = message.serialise(manager);
+    let serialised = message.serialise(manager).unwrap();
+
+    let mut stream = TestIoBuilder::new()
+        .write(&(serialised.len() as u64).to_le_bytes())
+        .write(&serialised)
+        .build();
+
+    message.write(&mut stream, manager).await.unwrap();
+}
+
+#[tokio::test]
+async fn write_rowset_message_with_dates_to_old_client() {
+    let rowset = || RowSet {
+        types: vec![ColumnType::Date],
+        names: vec!["day".into()],
+        values: vec![Row(vec![ColumnValue::Date(Date(0))])],
+    };
+
+    for serialiser in [Serialiser::V1, Serialiser::V2] {
+        let message = Message::from_message_body(MessageBody::RowSet(rowset()));
+
+        // Nothing gets written, so the client doesn't get half a message
+        let mut stream = TestIoBuilder::new().build();
+
+        let result = message
+            .write(&mut stream, SerialisationManager(serialiser))
+            .await;
+
+        assert!(
+            matches!(result, Err(SqlError::IncompatibleType(ColumnType::Date))),
+            "{serialiser:?}: {result:?}"
+        );
+    }
+
+    let message = Message::from_message_body(MessageBody::RowSet(rowset()));
+
+    let manager = SerialisationManager(Serialiser::V3);
+
+    let serialised = message.serialise(manager).unwrap();
 
     let mut stream = TestIoBuilder::new()
         .write(&(serialised.len() as u64).to_le_bytes())
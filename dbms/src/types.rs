@@ -5,9 +5,13 @@
 //! (hopefully).
 use std::any::type_name;
 
-use sql_parse::parser::{ColumnType, InfixOperator};
+use sql_parse::parser::{
+    ArithmeticOperator, ColumnConstraint, ColumnType, DateTimeField, InfixOperator, JoinType,
+    ReferentialAction, SortDirection, UnaryOperator,
+};
 
 use super::{Expression, SqlError};
+use crate::database::StatementContext;
 use crate::Result;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -18,6 +22,9 @@ impl TryFrom<&Expression> for ColumnName {
     fn try_from(value: &Expression) -> Result<Self> {
         return match value {
             Expression::Ident(name) => Ok(ColumnName(name.clone())),
+            Expression::QualifiedIdent(table, column) => {
+                Ok(ColumnName(format!("{table}.{column}")))
+            }
             _ => Err(SqlError::ImpossibleConversion(
                 value.clone(),
                 type_name::<ColumnName>(),
@@ -46,6 +53,41 @@ impl TryFrom<&Expression> for TableName {
 
 #[derive(Debug, Clone)]
 #[cfg_attr(test, derive(PartialEq))]
+pub struct SequenceName(pub String);
+
+impl TryFrom<&Expression> for SequenceName {
+    type Error = SqlError;
+
+    fn try_from(value: &Expression) -> Result<Self> {
+        return match value {
+            Expression::Ident(name) => Ok(SequenceName(name.clone())),
+            _ => Err(SqlError::ImpossibleConversion(
+                value.clone(),
+                type_name::<SequenceName>(),
+            )),
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct IndexName(pub String);
+
+impl TryFrom<&Expression> for IndexName {
+    type Error = SqlError;
+
+    fn try_from(value: &Expression) -> Result<Self> {
+        return match value {
+            Expression::Ident(name) => Ok(IndexName(name.clone())),
+            _ => Err(SqlError::ImpossibleConversion(
+                value.clone(),
+                type_name::<IndexName>(),
+            )),
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 // TODO: Validating the name
 // At least that it doesn't contain slashes
 // I'm sure rust has some function to validate that a string is a valid filename
@@ -67,11 +109,114 @@ impl TryFrom<&Expression> for DatabaseName {
 
 // impl_owned!(DatabaseName);
 
+#[derive(Debug)]
+pub struct OrderBy<Column = ColumnName> {
+    pub expression: ValueExpression<Column>,
+    pub direction: SortDirection,
+}
+
+impl TryFrom<&Expression> for OrderBy {
+    type Error = SqlError;
+
+    fn try_from(value: &Expression) -> Result<Self> {
+        return match value {
+            Expression::SortKey {
+                expression,
+                direction,
+            } => Ok(OrderBy {
+                expression: expression.as_ref().try_into()?,
+                direction: *direction,
+            }),
+            _ => Err(SqlError::ImpossibleConversion(
+                value.clone(),
+                type_name::<OrderBy>(),
+            )),
+        };
+    }
+}
+
+/// Everything in a `SELECT` apart from the table(s) it reads from.
+#[derive(Debug)]
+pub struct SelectQuery {
+    pub columns: ColumnSelector,
+    pub condition: Option<Where>,
+    pub group_by: Vec<ValueExpression>,
+    pub having: Option<Where>,
+    pub order_by: Vec<OrderBy>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+impl SelectQuery {
+    /// A query that returns all matching rows in insertion order.
+    pub fn new(columns: ColumnSelector, condition: Option<Where>) -> Self {
+        return SelectQuery {
+            columns,
+            condition,
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        };
+    }
+}
+
+/// The source of the rows in a `SELECT`, either a single table or a join of tables.
+#[derive(Debug)]
+pub enum TableReference {
+    Table(TableName),
+    Join {
+        left: Box<TableReference>,
+        right: TableName,
+        kind: JoinType,
+        on: Option<Where>,
+    },
+}
+
+impl From<TableName> for TableReference {
+    fn from(value: TableName) -> Self {
+        return TableReference::Table(value);
+    }
+}
+
+impl TryFrom<&Expression> for TableReference {
+    type Error = SqlError;
+
+    fn try_from(value: &Expression) -> Result<Self> {
+        return match value {
+            Expression::Ident(_) => Ok(TableReference::Table(value.try_into()?)),
+            Expression::Join {
+                left,
+                right,
+                kind,
+                on,
+            } => {
+                let on = match on {
+                    Some(condition) => Some(condition.as_ref().try_into()?),
+                    None => None,
+                };
+
+                Ok(TableReference::Join {
+                    left: Box::new(left.as_ref().try_into()?),
+                    right: right.as_ref().try_into()?,
+                    kind: *kind,
+                    on,
+                })
+            }
+            _ => Err(SqlError::ImpossibleConversion(
+                value.clone(),
+                type_name::<TableReference>(),
+            )),
+        };
+    }
+}
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub enum ColumnSelector {
     AllColumns,
-    Name(Vec<ColumnName>),
+    Expressions(Vec<ValueExpression>),
 }
 
 impl TryFrom<&Expression> for ColumnSelector {
@@ -81,12 +226,12 @@ impl TryFrom<&Expression> for ColumnSelector {
         return match value {
             Expression::AllColumns => Ok(ColumnSelector::AllColumns),
             Expression::Array(columns) => {
-                let columns: Vec<ColumnName> = columns
+                let columns: Vec<ValueExpression> = columns
                     .iter()
                     .map(|column| column.try_into())
                     .collect::<Result<Vec<_>>>()?;
 
-                Ok(ColumnSelector::Name(columns))
+                Ok(ColumnSelector::Expressions(columns))
             }
             _ => Err(SqlError::ImpossibleConversion(
                 value.clone(),
@@ -96,13 +241,98 @@ impl TryFrom<&Expression> for ColumnSelector {
     }
 }
 
+/// An exact decimal number, stored as an integer number of `10^-scale` units.
+///
+/// The scale is kept as written, so `1.50` is 150 hundredths and `1.5` is 15 tenths.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct Decimal {
+    pub mantissa: i128,
+    pub scale: u32,
+}
+
+impl std::fmt::Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = 10u128.pow(self.scale);
+
+        // The whole part of e.g. -0.5 is 0, so the sign has to be written separately
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+
+        let whole = self.mantissa.unsigned_abs() / unit;
+
+        if self.scale == 0 {
+            return write!(f, "{sign}{whole}");
+        }
+
+        return write!(
+            f,
+            "{sign}{whole}.{:0>width$}",
+            self.mantissa.unsigned_abs() % unit,
+            width = self.scale as usize
+        );
+    }
+}
+
+/// A byte string, shown in hex like its literals, e.g. `X'DEADBEEF'`.
+#[derive(Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct Blob(pub Vec<u8>);
+
+impl std::fmt::Display for Blob {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "X'")?;
+
+        for byte in &self.0 {
+            write!(f, "{byte:02X}")?;
+        }
+
+        return write!(f, "'");
+    }
+}
+
+// Rowsets are shown with their `Debug` output, so bytes are shown in hex there too
+impl std::fmt::Debug for Blob {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{self}");
+    }
+}
+
+/// A calendar date, stored as the number of days since 1970-01-01.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date(pub i32);
+
+/// A time of day, stored as the number of microseconds since midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Time(pub i64);
+
+/// A date and time without a time zone, stored as the number of microseconds since 1970-01-01 00:00:00.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timestamp(pub i64);
+
+/// A length of time.
+///
+/// Months are kept apart from the rest, since not all of them have the same number of days.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct Interval {
+    pub months: i32,
+    pub micros: i64,
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(test, derive(PartialEq))]
 pub enum ColumnValue {
-    Int(usize),
-    Decimal(usize, usize),
+    Int(i64),
+    Decimal(Decimal),
+    Float(f64),
     Str(String),
+    Blob(Blob),
     Bool(bool),
+    Date(Date),
+    Time(Time),
+    Timestamp(Timestamp),
+    Interval(Interval),
+    Null,
 }
 impl TryFrom<&Expression> for ColumnValue {
     type Error = SqlError;
@@ -111,9 +341,20 @@ impl TryFrom<&Expression> for ColumnValue {
         use Expression as E;
         return match value {
             E::Int(value) => Ok(ColumnValue::Int(*value)),
-            E::Decimal(whole, fractional) => Ok(ColumnValue::Decimal(*whole, *fractional)),
+            E::Decimal(mantissa, scale) => Ok(ColumnValue::Decimal(Decimal {
+                mantissa: *mantissa,
+                scale: *scale,
+            })),
+            E::Float(value) => Ok(ColumnValue::Float(*value)),
             E::Str(value) => Ok(ColumnValue::Str(value.clone())),
+            E::Blob(value) => Ok(ColumnValue::Blob(Blob(value.clone()))),
             E::Bool(value) => Ok(ColumnValue::Bool(*value)),
+            E::Null => Ok(ColumnValue::Null),
+            E::TypedLiteral(column_type, value) => ColumnValue::parse(value, *column_type),
+            // Constant arithmetic gets evaluated right away, as a statement of its own
+            E::Binary { .. } | E::Unary { .. } | E::FunctionCall { .. } | E::Extract { .. } => {
+                StatementContext::default().evaluate(value)
+            }
             _ => Err(SqlError::ImpossibleConversion(
                 value.clone(),
                 type_name::<ColumnValue>(),
@@ -122,28 +363,67 @@ impl TryFrom<&Expression> for ColumnValue {
     }
 }
 
-impl From<&ColumnValue> for ColumnType {
-    fn from(value: &ColumnValue) -> Self {
-        return match value {
-            ColumnValue::Int(_) => ColumnType::Int,
-            ColumnValue::Decimal(_, _) => ColumnType::Decimal,
-            ColumnValue::Str(_) => ColumnType::Text,
-            ColumnValue::Bool(_) => ColumnType::Bool,
+impl std::fmt::Display for ColumnValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            ColumnValue::Int(value) => write!(f, "{value}"),
+            ColumnValue::Decimal(value) => write!(f, "{value}"),
+            // Debug output keeps the dot or exponent, so floats can be told apart from other numbers
+            ColumnValue::Float(value) if value.is_finite() => write!(f, "{value:?}"),
+            ColumnValue::Float(value) => write!(f, "FLOAT '{value}'"),
+            ColumnValue::Str(value) => write!(f, "'{value}'"),
+            ColumnValue::Blob(value) => write!(f, "{value}"),
+            ColumnValue::Bool(value) => write!(f, "{value}"),
+            ColumnValue::Date(value) => write!(f, "DATE '{value}'"),
+            ColumnValue::Time(value) => write!(f, "TIME '{value}'"),
+            ColumnValue::Timestamp(value) => write!(f, "TIMESTAMP '{value}'"),
+            ColumnValue::Interval(value) => write!(f, "INTERVAL '{value}'"),
+            ColumnValue::Null => write!(f, "NULL"),
+        };
+    }
+}
+
+impl ColumnValue {
+    /// The type of the value, or `None` for NULL, which fits in a column of any type.
+    pub fn column_type(&self) -> Option<ColumnType> {
+        return match self {
+            // INT if it fits in 32 bits, BIGINT otherwise
+            ColumnValue::Int(value) => match i32::try_from(*value) {
+                Ok(_) => Some(ColumnType::Int),
+                Err(_) => Some(ColumnType::BigInt),
+            },
+            // Only columns limit the size of decimals
+            ColumnValue::Decimal(_) => Some(ColumnType::Decimal(None)),
+            ColumnValue::Float(_) => Some(ColumnType::Float),
+            ColumnValue::Str(_) => Some(ColumnType::Text),
+            ColumnValue::Blob(_) => Some(ColumnType::Blob),
+            ColumnValue::Bool(_) => Some(ColumnType::Bool),
+            ColumnValue::Date(_) => Some(ColumnType::Date),
+            ColumnValue::Time(_) => Some(ColumnType::Time),
+            ColumnValue::Timestamp(_) => Some(ColumnType::Timestamp),
+            ColumnValue::Interval(_) => Some(ColumnType::Interval),
+            ColumnValue::Null => None,
         };
     }
+
+    pub fn is_null(&self) -> bool {
+        return matches!(self, ColumnValue::Null);
+    }
 }
 
 #[derive(Debug)]
-pub struct ColumnDefinition(pub ColumnName, pub ColumnType);
+pub struct ColumnDefinition(pub ColumnName, pub ColumnType, pub Vec<ColumnConstraint>);
 
 impl TryFrom<&Expression> for ColumnDefinition {
     type Error = SqlError;
 
     fn try_from(value: &Expression) -> Result<Self> {
         return match value {
-            Expression::ColumnDefinition(name, column_type) => {
-                Ok(Self(ColumnName(name.clone()), *column_type))
-            }
+            Expression::ColumnDefinition(name, column_type, constraints) => Ok(Self(
+                ColumnName(name.clone()),
+                *column_type,
+                constraints.clone(),
+            )),
             _ => Err(SqlError::ImpossibleConversion(
                 value.clone(),
                 type_name::<ColumnDefinition>(),
@@ -152,36 +432,444 @@ impl TryFrom<&Expression> for ColumnDefinition {
     }
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(Clone, PartialEq))]
-pub struct ForeignKeyConstraint(ColumnName, TableName, ColumnName);
+/// A `FOREIGN KEY`, whose values have to be present in a key column of the referenced table.
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ForeignKeyConstraint {
+    pub column: ColumnName,
+    pub foreign_table: TableName,
+    pub foreign_column: ColumnName,
+    pub on_delete: ReferentialAction,
+    pub on_update: ReferentialAction,
+}
 
 impl TryFrom<&Expression> for ForeignKeyConstraint {
     type Error = SqlError;
 
     fn try_from(value: &Expression) -> std::result::Result<Self, Self::Error> {
         return match value {
-            Expression::ForeignKeyConstraint { column, foreign_table, foreign_column } => {
+            Expression::ForeignKeyConstraint {
+                column,
+                foreign_table,
+                foreign_column,
+                on_delete,
+                on_update,
+            } => {
                 let column_name = ColumnName::try_from(column.as_ref())?;
                 let foreign_table = TableName::try_from(foreign_table.as_ref())?;
                 let foreign_column = ColumnName::try_from(foreign_column.as_ref())?;
 
-                Ok(ForeignKeyConstraint(column_name, foreign_table, foreign_column))
+                Ok(ForeignKeyConstraint {
+                    column: column_name,
+                    foreign_table,
+                    foreign_column,
+                    on_delete: *on_delete,
+                    on_update: *on_update,
+                })
+            }
+            _ => Err(SqlError::ImpossibleConversion(
+                value.clone(),
+                type_name::<ForeignKeyConstraint>(),
+            )),
+        };
+    }
+}
+
+/// A `PRIMARY KEY` or `UNIQUE` constraint over one or more columns.
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct UniqueConstraint {
+    pub columns: Vec<ColumnName>,
+    pub primary: bool,
+}
+
+impl TryFrom<&Expression> for UniqueConstraint {
+    type Error = SqlError;
+
+    fn try_from(value: &Expression) -> Result<Self> {
+        return match value {
+            Expression::UniqueConstraint { columns, primary } => match columns.as_ref() {
+                Expression::Array(columns) => Ok(UniqueConstraint {
+                    columns: columns
+                        .iter()
+                        .map(ColumnName::try_from)
+                        .collect::<Result<_>>()?,
+                    primary: *primary,
+                }),
+                _ => Err(SqlError::InvalidParameter),
             },
             _ => Err(SqlError::ImpossibleConversion(
                 value.clone(),
-                type_name::<ForeignKeyConstraint>()
+                type_name::<UniqueConstraint>(),
             )),
-        }
+        };
     }
 }
 
-#[derive(Debug)]
-// TODO: this is janky and hacky to only support <column> <op> <value> comparisons
-pub struct Where {
-    pub left: ColumnName,
-    pub operator: InfixOperator,
-    pub right: ColumnValue,
+/// An index made by `CREATE [UNIQUE] INDEX`, which speeds up finding rows by the values of its columns.
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct IndexDefinition {
+    pub name: IndexName,
+    pub columns: Vec<ColumnName>,
+    pub unique: bool,
+}
+
+/// An expression that evaluates to a single [`ColumnValue`], like `price * qty`.
+///
+/// Generic over how columns are referred to, see [`PreparedValueExpression`].
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum ValueExpression<Column = ColumnName> {
+    Column(Column),
+    Value(ColumnValue),
+    Binary {
+        left: Box<ValueExpression<Column>>,
+        operator: ArithmeticOperator,
+        right: Box<ValueExpression<Column>>,
+    },
+    Unary {
+        operator: UnaryOperator,
+        operand: Box<ValueExpression<Column>>,
+    },
+    Aggregate {
+        function: AggregateFunction,
+        argument: Option<Box<ValueExpression<Column>>>, // None for `COUNT(*)`
+    },
+    Extract {
+        field: DateTimeField,
+        source: Box<ValueExpression<Column>>,
+    },
+    Function(StatementFunction),
+}
+
+/// A [`ValueExpression`] with column names resolved to indices in a row.
+pub type PreparedValueExpression = ValueExpression<usize>;
+
+impl<Column> ValueExpression<Column> {
+    /// Converts every column reference in the expression using `resolve`.
+    pub fn map_columns<Other>(
+        self,
+        resolve: &impl Fn(Column) -> Result<Other>,
+    ) -> Result<ValueExpression<Other>> {
+        return match self {
+            ValueExpression::Column(column) => Ok(ValueExpression::Column(resolve(column)?)),
+            ValueExpression::Value(value) => Ok(ValueExpression::Value(value)),
+            ValueExpression::Binary {
+                left,
+                operator,
+                right,
+            } => Ok(ValueExpression::Binary {
+                left: Box::new(left.map_columns(resolve)?),
+                operator,
+                right: Box::new(right.map_columns(resolve)?),
+            }),
+            ValueExpression::Unary { operator, operand } => Ok(ValueExpression::Unary {
+                operator,
+                operand: Box::new(operand.map_columns(resolve)?),
+            }),
+            ValueExpression::Aggregate { function, argument } => {
+                let argument = match argument {
+                    Some(argument) => Some(Box::new(argument.map_columns(resolve)?)),
+                    None => None,
+                };
+
+                Ok(ValueExpression::Aggregate { function, argument })
+            }
+            ValueExpression::Extract { field, source } => Ok(ValueExpression::Extract {
+                field,
+                source: Box::new(source.map_columns(resolve)?),
+            }),
+            ValueExpression::Function(function) => Ok(ValueExpression::Function(function)),
+        };
+    }
+}
+
+impl<Column> From<ColumnValue> for ValueExpression<Column> {
+    fn from(value: ColumnValue) -> Self {
+        return ValueExpression::Value(value);
+    }
+}
+
+impl TryFrom<&Expression> for ValueExpression {
+    type Error = SqlError;
+
+    fn try_from(value: &Expression) -> Result<Self> {
+        return match value {
+            Expression::Ident(_) | Expression::QualifiedIdent(_, _) => {
+                Ok(ValueExpression::Column(value.try_into()?))
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+            } => Ok(ValueExpression::Binary {
+                left: Box::new(left.as_ref().try_into()?),
+                operator: *operator,
+                right: Box::new(right.as_ref().try_into()?),
+            }),
+            Expression::Unary { operator, operand } => Ok(ValueExpression::Unary {
+                operator: *operator,
+                operand: Box::new(operand.as_ref().try_into()?),
+            }),
+            Expression::FunctionCall {
+                name,
+                argument: None,
+            } if name.to_uppercase() == "NOW" => {
+                Ok(ValueExpression::Function(StatementFunction::Now))
+            }
+            Expression::FunctionCall {
+                name,
+                argument: Some(argument),
+            } if matches!(name.to_uppercase().as_str(), "NEXTVAL" | "CURRVAL") => {
+                let Expression::Str(sequence_name) = argument.as_ref() else {
+                    return Err(SqlError::ImpossibleConversion(
+                        argument.as_ref().clone(),
+                        type_name::<SequenceName>(),
+                    ));
+                };
+
+                let sequence_name = SequenceName(sequence_name.clone());
+
+                Ok(ValueExpression::Function(
+                    match name.to_uppercase().as_str() {
+                        "NEXTVAL" => StatementFunction::NextValue(sequence_name),
+                        _ => StatementFunction::CurrentValue(sequence_name),
+                    },
+                ))
+            }
+            Expression::FunctionCall { name, argument } => {
+                let function: AggregateFunction = name.as_str().try_into()?;
+
+                let argument = match (function, argument.as_deref()) {
+                    (AggregateFunction::Count, Some(Expression::AllColumns)) => None,
+                    (_, Some(argument)) => Some(Box::new(argument.try_into()?)),
+                    (_, None) => return Err(SqlError::InvalidArguments(name.clone())),
+                };
+
+                Ok(ValueExpression::Aggregate { function, argument })
+            }
+            Expression::Extract { field, source } => Ok(ValueExpression::Extract {
+                field: *field,
+                source: Box::new(source.as_ref().try_into()?),
+            }),
+            Expression::Int(_)
+            | Expression::Decimal(_, _)
+            | Expression::Float(_)
+            | Expression::Str(_)
+            | Expression::Blob(_)
+            | Expression::Bool(_)
+            | Expression::Null
+            | Expression::TypedLiteral(_, _) => Ok(ValueExpression::Value(value.try_into()?)),
+            _ => Err(SqlError::ImpossibleConversion(
+                value.clone(),
+                type_name::<ValueExpression>(),
+            )),
+        };
+    }
+}
+
+impl std::fmt::Display for ValueExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Nested operations get parenthesised so the result is unambiguous
+        let nested = |expression: &ValueExpression| match expression {
+            ValueExpression::Binary { .. } => format!("({expression})"),
+            other => format!("{other}"),
+        };
+
+        return match self {
+            ValueExpression::Column(name) => write!(f, "{}", name.0),
+            ValueExpression::Value(value) => write!(f, "{value}"),
+            ValueExpression::Binary {
+                left,
+                operator,
+                right,
+            } => {
+                let operator = match operator {
+                    ArithmeticOperator::Add => "+",
+                    ArithmeticOperator::Subtract => "-",
+                    ArithmeticOperator::Multiply => "*",
+                    ArithmeticOperator::Divide => "/",
+                };
+
+                write!(f, "{} {operator} {}", nested(left), nested(right))
+            }
+            ValueExpression::Unary {
+                operator: UnaryOperator::Minus,
+                operand,
+            } => write!(f, "-{}", nested(operand)),
+            ValueExpression::Aggregate {
+                function,
+                argument: Some(argument),
+            } => write!(f, "{function}({argument})"),
+            ValueExpression::Aggregate {
+                function,
+                argument: None,
+            } => write!(f, "{function}(*)"),
+            ValueExpression::Extract { field, source } => {
+                let field = match field {
+                    DateTimeField::Year => "YEAR",
+                    DateTimeField::Month => "MONTH",
+                    DateTimeField::Day => "DAY",
+                    DateTimeField::Hour => "HOUR",
+                    DateTimeField::Minute => "MINUTE",
+                    DateTimeField::Second => "SECOND",
+                };
+
+                write!(f, "EXTRACT({field} FROM {source})")
+            }
+            ValueExpression::Function(function) => write!(f, "{function}"),
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Average,
+    Min,
+    Max,
+}
+
+impl TryFrom<&str> for AggregateFunction {
+    type Error = SqlError;
+
+    fn try_from(value: &str) -> Result<Self> {
+        return match value.to_uppercase().as_str() {
+            "COUNT" => Ok(AggregateFunction::Count),
+            "SUM" => Ok(AggregateFunction::Sum),
+            "AVG" => Ok(AggregateFunction::Average),
+            "MIN" => Ok(AggregateFunction::Min),
+            "MAX" => Ok(AggregateFunction::Max),
+            _ => Err(SqlError::UnknownFunction(value.to_owned())),
+        };
+    }
+}
+
+/// A function that doesn't depend on the row it's evaluated for, but on the statement it's part of,
+/// see [`StatementContext`](crate::database::StatementContext).
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum StatementFunction {
+    /// The time the statement first asks for it, so it's the same for every row
+    Now,
+    /// `nextval('<sequence>')`, which advances the sequence every time it's evaluated
+    NextValue(SequenceName),
+    /// `currval('<sequence>')`
+    CurrentValue(SequenceName),
+}
+
+impl std::fmt::Display for StatementFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            StatementFunction::Now => write!(f, "NOW()"),
+            StatementFunction::NextValue(name) => write!(f, "nextval('{}')", name.0),
+            StatementFunction::CurrentValue(name) => write!(f, "currval('{}')", name.0),
+        };
+    }
+}
+
+impl std::fmt::Display for AggregateFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AggregateFunction::Count => "COUNT",
+            AggregateFunction::Sum => "SUM",
+            AggregateFunction::Average => "AVG",
+            AggregateFunction::Min => "MIN",
+            AggregateFunction::Max => "MAX",
+        };
+
+        return write!(f, "{name}");
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum Where<Column = ColumnName> {
+    Comparison {
+        left: ValueExpression<Column>,
+        operator: InfixOperator,
+        right: ValueExpression<Column>,
+    },
+    IsNull {
+        expression: ValueExpression<Column>,
+        negated: bool,
+    },
+    And(Box<Where<Column>>, Box<Where<Column>>),
+    Or(Box<Where<Column>>, Box<Where<Column>>),
+    Not(Box<Where<Column>>),
+}
+
+/// A [`Where`] with column names resolved to indices in a row.
+pub type PreparedWhere = Where<usize>;
+
+impl<Column> Where<Column> {
+    /// Converts every column reference in the condition using `resolve`.
+    pub fn map_columns<Other>(
+        self,
+        resolve: &impl Fn(Column) -> Result<Other>,
+    ) -> Result<Where<Other>> {
+        let map = |clause: Box<Where<Column>>| clause.map_columns(resolve).map(Box::new);
+
+        return match self {
+            Where::Comparison {
+                left,
+                operator,
+                right,
+            } => Ok(Where::Comparison {
+                left: left.map_columns(resolve)?,
+                operator,
+                right: right.map_columns(resolve)?,
+            }),
+            Where::IsNull {
+                expression,
+                negated,
+            } => Ok(Where::IsNull {
+                expression: expression.map_columns(resolve)?,
+                negated,
+            }),
+            Where::And(left, right) => Ok(Where::And(map(left)?, map(right)?)),
+            Where::Or(left, right) => Ok(Where::Or(map(left)?, map(right)?)),
+            Where::Not(inner) => Ok(Where::Not(map(inner)?)),
+        };
+    }
+}
+
+impl<Column> Where<Column> {
+    /// Replaces every expression in the condition with what `map` makes of it.
+    pub fn map_expressions(
+        self,
+        map: &mut impl FnMut(ValueExpression<Column>) -> ValueExpression<Column>,
+    ) -> Where<Column> {
+        return match self {
+            Where::Comparison {
+                left,
+                operator,
+                right,
+            } => Where::Comparison {
+                left: map(left),
+                operator,
+                right: map(right),
+            },
+            Where::IsNull {
+                expression,
+                negated,
+            } => Where::IsNull {
+                expression: map(expression),
+                negated,
+            },
+            Where::And(left, right) => Where::And(
+                Box::new(left.map_expressions(map)),
+                Box::new(right.map_expressions(map)),
+            ),
+            Where::Or(left, right) => Where::Or(
+                Box::new(left.map_expressions(map)),
+                Box::new(right.map_expressions(map)),
+            ),
+            Where::Not(inner) => Where::Not(Box::new(inner.map_expressions(map))),
+        };
+    }
 }
 
 impl TryFrom<&Expression> for Where {
@@ -189,22 +877,37 @@ impl TryFrom<&Expression> for Where {
 
     fn try_from(value: &Expression) -> Result<Self> {
         return match value {
-            Expression::Where {
+            Expression::Comparison {
                 left,
                 operator,
                 right,
             } => {
-                // TODO: might be a literal. I have to rework this whole Where thingy
-                let left: ColumnName = left.as_ref().try_into()?;
+                let left: ValueExpression = left.as_ref().try_into()?;
 
-                let right: ColumnValue = right.as_ref().try_into()?;
+                let right: ValueExpression = right.as_ref().try_into()?;
 
-                return Ok(Self {
+                return Ok(Self::Comparison {
                     left,
                     operator: *operator,
                     right,
                 });
             }
+            Expression::IsNull {
+                expression,
+                negated,
+            } => Ok(Self::IsNull {
+                expression: expression.as_ref().try_into()?,
+                negated: *negated,
+            }),
+            Expression::And(left, right) => Ok(Self::And(
+                Box::new(left.as_ref().try_into()?),
+                Box::new(right.as_ref().try_into()?),
+            )),
+            Expression::Or(left, right) => Ok(Self::Or(
+                Box::new(left.as_ref().try_into()?),
+                Box::new(right.as_ref().try_into()?),
+            )),
+            Expression::Not(inner) => Ok(Self::Not(Box::new(inner.as_ref().try_into()?))),
             _ => Err(SqlError::ImpossibleConversion(
                 value.clone(),
                 type_name::<Where>(),
@@ -213,19 +916,57 @@ impl TryFrom<&Expression> for Where {
     }
 }
 
-#[derive(Debug)]
-pub struct PreparedWhere {
-    pub left: usize,
-    pub operator: InfixOperator,
-    pub right: ColumnValue,
+impl std::fmt::Display for Where {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Nested conditions get parenthesised, like nested operations
+        let nested = |condition: &Where| match condition {
+            Where::And(..) | Where::Or(..) => format!("({condition})"),
+            other => format!("{other}"),
+        };
+
+        return match self {
+            Where::Comparison {
+                left,
+                operator,
+                right,
+            } => {
+                let operator = match operator {
+                    InfixOperator::Equals => "=",
+                    InfixOperator::NotEqual => "<>",
+                    InfixOperator::LessThan => "<",
+                    InfixOperator::LessThanEqual => "<=",
+                    InfixOperator::GreaterThan => ">",
+                    InfixOperator::GreaterThanEqual => ">=",
+                };
+
+                write!(f, "{left} {operator} {right}")
+            }
+            Where::IsNull {
+                expression,
+                negated: false,
+            } => write!(f, "{expression} IS NULL"),
+            Where::IsNull {
+                expression,
+                negated: true,
+            } => write!(f, "{expression} IS NOT NULL"),
+            Where::And(left, right) => write!(f, "{} AND {}", nested(left), nested(right)),
+            Where::Or(left, right) => write!(f, "{} OR {}", nested(left), nested(right)),
+            Where::Not(inner) => write!(f, "NOT ({inner})"),
+        };
+    }
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(Clone, PartialEq))]
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
 pub struct TableSchema {
     pub name: TableName,
     pub column_names: Vec<ColumnName>,
     pub types: Vec<ColumnType>,
+    pub nullable: Vec<bool>,
+    pub unique: Vec<UniqueConstraint>,
+    pub defaults: Vec<Option<Expression>>, // One for each column
+    pub checks: Vec<Expression>,           // Conditions
+    pub indexes: Vec<IndexDefinition>,
 }
 
 #[cfg(test)]
@@ -285,7 +1026,10 @@ mod tests {
 
         assert_eq!(
             selector,
-            ColumnSelector::Name(vec!["a".into(), "deez nuts".into(),])
+            ColumnSelector::Expressions(vec![
+                ValueExpression::Column("a".into()),
+                ValueExpression::Column("deez nuts".into()),
+            ])
         );
     }
 
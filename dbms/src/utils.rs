@@ -4,6 +4,8 @@ pub fn serialiser_version_to_serialiser(version: u8) -> Result<Serialiser> {
     return match version {
         1 => Ok(Serialiser::V1),
         2 => Ok(Serialiser::V2),
+        3 => Ok(Serialiser::V3),
+        4 => Ok(Serialiser::V4),
         other => Err(SqlError::IncompatibleVersion(other)),
     };
 }
@@ -11,7 +13,10 @@ pub fn serialiser_version_to_serialiser(version: u8) -> Result<Serialiser> {
 #[cfg(test)]
 pub mod tests {
     use super::super::database::{Row, RowSet, Table};
-    use super::super::types::{ColumnDefinition, ColumnName, ColumnValue, DatabaseName, TableName};
+    use super::super::types::{
+        ColumnDefinition, ColumnName, ColumnValue, DatabaseName, Decimal, IndexName, SequenceName,
+        TableName, TableReference, ValueExpression,
+    };
     use sql_parse::parser::ColumnType;
 
     use crate::server::Runtime;
@@ -23,6 +28,12 @@ pub mod tests {
         }
     }
 
+    impl From<&str> for TableReference {
+        fn from(value: &str) -> Self {
+            return Self::Table(value.into());
+        }
+    }
+
     impl From<&str> for ColumnName {
         fn from(value: &str) -> Self {
             return Self(value.into());
@@ -35,18 +46,47 @@ pub mod tests {
         }
     }
 
-    impl From<usize> for ColumnValue {
-        fn from(value: usize) -> Self {
-            return Self::Int(value);
+    impl From<&str> for SequenceName {
+        fn from(value: &str) -> Self {
+            return Self(value.into());
+        }
+    }
+
+    impl From<&str> for IndexName {
+        fn from(value: &str) -> Self {
+            return Self(value.into());
+        }
+    }
+
+    impl From<&str> for ValueExpression {
+        fn from(value: &str) -> Self {
+            return Self::Column(value.into());
         }
     }
 
-    impl From<(usize, usize)> for ColumnValue {
-        fn from(value: (usize, usize)) -> Self {
-            return Self::Decimal(value.0, value.1);
+    impl From<i64> for ValueExpression {
+        fn from(value: i64) -> Self {
+            return Self::Value(value.into());
         }
     }
 
+    impl From<bool> for ValueExpression {
+        fn from(value: bool) -> Self {
+            return Self::Value(value.into());
+        }
+    }
+
+    impl From<i64> for ColumnValue {
+        fn from(value: i64) -> Self {
+            return Self::Int(value);
+        }
+    }
+
+    /// A decimal with `scale` digits after the dot, so `decimal(105, 2)` is 1.05.
+    pub fn decimal(mantissa: i128, scale: u32) -> ColumnValue {
+        return ColumnValue::Decimal(Decimal { mantissa, scale });
+    }
+
     impl From<&str> for ColumnValue {
         fn from(value: &str) -> Self {
             return Self::Str(value.into());
@@ -63,10 +103,12 @@ pub mod tests {
         return Table::new(
             "test_table".into(),
             vec![
-                ColumnDefinition("first".into(), ColumnType::Int),
-                ColumnDefinition("second".into(), ColumnType::Bool),
+                ColumnDefinition("first".into(), ColumnType::Int, vec![]),
+                ColumnDefinition("second".into(), ColumnType::Bool, vec![]),
             ],
             vec![],
+            vec![],
+            vec![],
         )
         .unwrap();
     }
@@ -75,10 +117,12 @@ pub mod tests {
         let mut result = Table::new(
             "test_table".into(),
             vec![
-                ColumnDefinition("first".into(), ColumnType::Int),
-                ColumnDefinition("second".into(), ColumnType::Bool),
+                ColumnDefinition("first".into(), ColumnType::Int, vec![]),
+                ColumnDefinition("second".into(), ColumnType::Bool, vec![]),
             ],
             vec![],
+            vec![],
+            vec![],
         )
         .unwrap();
 
@@ -96,7 +140,7 @@ pub mod tests {
     pub fn test_row_set(values: Vec<Row>) -> Result<RowSet> {
         let types = values
             .first()
-            .map(|row| row.0.iter().map(ColumnType::from).collect())
+            .map(|row| row.0.iter().filter_map(ColumnValue::column_type).collect())
             .ok_or(SqlError::InvalidParameter)?;
 
         let names = std::iter::repeat("test_column_name".to_owned())
@@ -130,12 +174,6 @@ pub mod tests {
     }
 
     pub fn test_runtime_with_values() -> Runtime {
-        let mut runtime = Runtime::new_test();
-
-        let db = test_db_with_values();
-
-        runtime.create_database(db);
-
-        return runtime;
+        return Runtime::new_test_with(test_db_with_values());
     }
 }
@@ -1,6 +1,9 @@
+use std::fmt::Display;
 use std::str::Chars;
 
-#[derive(Debug, PartialEq)]
+use crate::parser::MAX_DECIMAL_PRECISION;
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum Token {
     // Keywords
     Select,
@@ -16,30 +19,85 @@ pub enum Token {
     Set,
     Delete,
     Drop,
+    Sequence,
+    Start,
+    With,
+    Increment,
+    Alter,
+    Add,
+    Rename,
+    To,
+    Index,
+    Explain,
+    Begin,
+    Commit,
+    Rollback,
 
     Foreign,
     Key,
     References,
+    Primary,
+    Unique,
+    Default,
+    Check,
     On,
+    Cascade,
+    Restrict,
+
+    Join,
+    Inner,
+    Left,
+    Outer,
+    Cross,
+
+    Group,
+    Having,
+    Order,
+    By,
+    Asc,
+    Desc,
+    Limit,
+    Offset,
     // Delete,
     // Update,
 
+    // Boolean operators
+    And,
+    Or,
+    Not,
+    Is,
+
+    Extract,
+
     // Types
+    TypeSmallInt,
     TypeInt,
+    TypeBigInt,
     TypeDecimal,
     TypeText,
     TypeBool,
+    TypeBlob,
+    TypeFloat,
+    TypeDate,
+    TypeTime,
+    TypeTimestamp,
+    TypeInterval,
+    TypeSerial, // An INT that gets its values from a sequence
 
     // Literals
     Ident(String),
-    Int(usize),
-    Decimal(usize, usize),
+    Int(u64),
+    Decimal(u128, u32), // Digits and scale, `1.05` is `Decimal(105, 2)`
+    Float(f64),         // Written with an exponent, like `1.5e-3`
     Str(String),
+    Blob(Vec<u8>), // `X'DEADBEEF'`
     Bool(bool),
+    Null,
 
     // Symbols
     Asterisk,
     Comma,
+    Dot,
     Semicolon,
     LParenthesis,
     RParenthesis,
@@ -84,31 +142,231 @@ impl From<String> for Token {
             "SET" => Set,
             "DELETE" => Delete,
             "DROP" => Drop,
+            "SEQUENCE" => Sequence,
+            "START" => Start,
+            "WITH" => With,
+            "INCREMENT" => Increment,
+            "ALTER" => Alter,
+            "ADD" => Add,
+            "RENAME" => Rename,
+            "TO" => To,
+            "INDEX" => Index,
+            "EXPLAIN" => Explain,
+            "BEGIN" => Begin,
+            "COMMIT" => Commit,
+            "ROLLBACK" => Rollback,
 
             "FOREIGN" => Foreign,
             "KEY" => Key,
             "REFERENCES" => References,
+            "PRIMARY" => Primary,
+            "UNIQUE" => Unique,
+            "DEFAULT" => Default,
+            "CHECK" => Check,
             "ON" => On,
-
+            "CASCADE" => Cascade,
+            "RESTRICT" => Restrict,
+
+            "JOIN" => Join,
+            "INNER" => Inner,
+            "LEFT" => Left,
+            "OUTER" => Outer,
+            "CROSS" => Cross,
+
+            "GROUP" => Group,
+            "HAVING" => Having,
+            "ORDER" => Order,
+            "BY" => By,
+            "ASC" => Asc,
+            "DESC" => Desc,
+            "LIMIT" => Limit,
+            "OFFSET" => Offset,
+
+            "AND" => And,
+            "OR" => Or,
+            "NOT" => Not,
+            "IS" => Is,
+
+            "EXTRACT" => Extract,
+
+            "SMALLINT" => TypeSmallInt,
             "INT" => TypeInt,
             "INTEGER" => TypeInt,
+            "BIGINT" => TypeBigInt,
             "DECIMAL" => TypeDecimal,
             "TEXT" => TypeText,
             "BOOL" => TypeBool,
             "BOOLEAN" => TypeBool,
-
-            // Hijacking from_identifier to parse boolean literals
+            "BLOB" => TypeBlob,
+            "BYTEA" => TypeBlob,
+            "FLOAT" => TypeFloat,
+            "REAL" => TypeFloat,
+            "DOUBLE" => TypeFloat,
+            "DATE" => TypeDate,
+            "TIME" => TypeTime,
+            "TIMESTAMP" => TypeTimestamp,
+            "INTERVAL" => TypeInterval,
+            "SERIAL" => TypeSerial,
+
+            // Hijacking from_identifier to parse boolean and null literals
             "TRUE" => Bool(true),
             "FALSE" => Bool(false),
+            "NULL" => Null,
             _ => Ident(value),
         };
     }
 }
 
+impl Display for Token {
+    /// Formats the token roughly as it appeared in the input, for use in error messages.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Token::*;
+
+        let symbol = match self {
+            Select => "SELECT",
+            From => "FROM",
+            Where => "WHERE",
+            Insert => "INSERT",
+            Into => "INTO",
+            Values => "VALUES",
+            Create => "CREATE",
+            Database => "DATABASE",
+            Table => "TABLE",
+            Update => "UPDATE",
+            Set => "SET",
+            Delete => "DELETE",
+            Drop => "DROP",
+            Sequence => "SEQUENCE",
+            Start => "START",
+            With => "WITH",
+            Increment => "INCREMENT",
+            Alter => "ALTER",
+            Add => "ADD",
+            Rename => "RENAME",
+            To => "TO",
+            Index => "INDEX",
+            Explain => "EXPLAIN",
+            Begin => "BEGIN",
+            Commit => "COMMIT",
+            Rollback => "ROLLBACK",
+
+            Foreign => "FOREIGN",
+            Key => "KEY",
+            References => "REFERENCES",
+            Primary => "PRIMARY",
+            Unique => "UNIQUE",
+            Default => "DEFAULT",
+            Check => "CHECK",
+            On => "ON",
+            Cascade => "CASCADE",
+            Restrict => "RESTRICT",
+
+            Join => "JOIN",
+            Inner => "INNER",
+            Left => "LEFT",
+            Outer => "OUTER",
+            Cross => "CROSS",
+
+            Group => "GROUP",
+            Having => "HAVING",
+            Order => "ORDER",
+            By => "BY",
+            Asc => "ASC",
+            Desc => "DESC",
+            Limit => "LIMIT",
+            Offset => "OFFSET",
+
+            And => "AND",
+            Or => "OR",
+            Not => "NOT",
+            Is => "IS",
+
+            Extract => "EXTRACT",
+
+            TypeSmallInt => "SMALLINT",
+            TypeInt => "INT",
+            TypeBigInt => "BIGINT",
+            TypeDecimal => "DECIMAL",
+            TypeText => "TEXT",
+            TypeBool => "BOOL",
+            TypeBlob => "BLOB",
+            TypeFloat => "FLOAT",
+            TypeDate => "DATE",
+            TypeTime => "TIME",
+            TypeTimestamp => "TIMESTAMP",
+            TypeInterval => "INTERVAL",
+            TypeSerial => "SERIAL",
+
+            Ident(name) => return write!(f, "identifier `{name}`"),
+            Int(value) => return write!(f, "number `{value}`"),
+            Decimal(mantissa, scale) => {
+                let unit = 10u128.pow(*scale);
+
+                return write!(
+                    f,
+                    "number `{}.{:0>width$}`",
+                    mantissa / unit,
+                    mantissa % unit,
+                    width = *scale as usize
+                );
+            }
+            Float(value) => return write!(f, "number `{value:e}`"),
+            Str(value) => return write!(f, "string '{value}'"),
+            Blob(value) => {
+                let hex: String = value.iter().map(|byte| format!("{byte:02X}")).collect();
+
+                return write!(f, "blob X'{hex}'");
+            }
+            Bool(true) => "TRUE",
+            Bool(false) => "FALSE",
+            Null => "NULL",
+
+            Asterisk => "*",
+            Comma => ",",
+            Dot => ".",
+            Semicolon => ";",
+            LParenthesis => "(",
+            RParenthesis => ")",
+
+            Equals => "=",
+            NotEquals => "<>",
+            LessThan => "<",
+            LessThanEqual => "<=",
+            GreaterThan => ">",
+            GreaterThanEqual => ">=",
+
+            Plus => "+",
+            Minus => "-",
+            Slash => "/",
+
+            Eof => return write!(f, "end of input"),
+
+            Invalid(reason) => return write!(f, "invalid token ({reason})"),
+        };
+
+        return write!(f, "`{symbol}`");
+    }
+}
+
+/// Location of a token in the input, both 1-indexed.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "line {}, column {}", self.line, self.column);
+    }
+}
+
 pub struct Lexer<'a> {
     input: std::iter::Peekable<Chars<'a>>,
     current_char: Option<char>,
     next_char: Option<char>,
+    // Position of current_char
+    position: Position,
 }
 
 impl<'a> Lexer<'a> {
@@ -117,6 +375,8 @@ impl<'a> Lexer<'a> {
             input: input.chars().peekable(),
             current_char: None,
             next_char: None,
+            // Advancing onto the first character moves to column 1
+            position: Position { line: 1, column: 0 },
         };
 
         // Prepare first character
@@ -126,6 +386,13 @@ impl<'a> Lexer<'a> {
     }
 
     fn advance(&mut self) {
+        if self.current_char == Some('\n') {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+
         self.current_char = self.input.next();
 
         self.next_char = self.input.peek().cloned();
@@ -138,23 +405,31 @@ impl<'a> Lexer<'a> {
     }
 
     pub fn lex(input: &'a str) -> Vec<Token> {
+        return Lexer::lex_with_positions(input).0;
+    }
+
+    /// Like [`Lexer::lex`], but also returns the position at which each token starts.
+    pub fn lex_with_positions(input: &'a str) -> (Vec<Token>, Vec<Position>) {
         let mut lexer = Lexer::new(input);
 
         let mut tokens = vec![];
+        let mut positions = vec![];
 
         // Skip leading whitespace
         lexer.skip_whitespace();
 
         while lexer.current_char.is_some() {
+            positions.push(lexer.position);
             tokens.push(lexer.next_token());
 
             // Skip intermediate/trailing whitespace
             lexer.skip_whitespace();
         }
 
+        positions.push(lexer.position);
         tokens.push(Token::Eof);
 
-        return tokens;
+        return (tokens, positions);
     }
 
     fn next_token(&mut self) -> Token {
@@ -163,6 +438,7 @@ impl<'a> Lexer<'a> {
         // Can unwrap because lex() checks is_some
         let token = match self.current_char.unwrap() {
             ',' => Comma,
+            '.' => Dot,
             ';' => Semicolon,
             '*' => Asterisk,
             '(' => LParenthesis,
@@ -210,6 +486,7 @@ impl<'a> Lexer<'a> {
             '-' => Minus,
             '/' => Slash,
 
+            'X' | 'x' if self.next_char == Some('\'') => return self.read_hex(),
             c if c.is_alphabetic() => return self.read_identifier(),
             c if c.is_numeric() => return self.read_number(),
 
@@ -237,6 +514,40 @@ impl<'a> Lexer<'a> {
         return Token::from(result);
     }
 
+    /// Reads a hex literal like `X'DEADBEEF'`, two digits per byte.
+    fn read_hex(&mut self) -> Token {
+        // Skip the X and the opening quote
+        self.advance();
+        self.advance();
+
+        let mut digits = String::new();
+
+        while let Some(character) = self.current_char {
+            if character == '\'' {
+                break;
+            }
+
+            digits.push(character);
+
+            self.advance();
+        }
+
+        // Skip the closing quote
+        self.advance();
+
+        if !digits.len().is_multiple_of(2) || !digits.chars().all(|digit| digit.is_ascii_hexdigit())
+        {
+            return Token::Invalid(format!("'{digits}' is not an even number of hex digits"));
+        }
+
+        let bytes = (0..digits.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&digits[index..index + 2], 16).unwrap())
+            .collect();
+
+        return Token::Blob(bytes);
+    }
+
     fn read_number(&mut self) -> Token {
         let mut result = String::new();
 
@@ -251,18 +562,38 @@ impl<'a> Lexer<'a> {
 
         let number_of_dots = result.chars().filter(|char| char == &'.').count();
 
+        if matches!(self.current_char, Some('e' | 'E'))
+            && matches!(self.next_char, Some('+' | '-' | '0'..='9'))
+        {
+            return self.read_exponent(result, number_of_dots);
+        }
+
         return match number_of_dots {
-            0 => Token::Int(result.parse().unwrap()),
+            // Negative numbers are a minus followed by a number, see the `Factor` parser
+            0 => match result.parse() {
+                Ok(value) => Token::Int(value),
+                Err(_) => Token::Invalid(format!("Number '{result}' is too large")),
+            },
             1 => {
                 let (whole, fractional) = result.split_once('.').unwrap();
 
-                let whole = whole.parse().unwrap();
-                let fractional = fractional.parse();
+                // Keeping the digits as one integer keeps leading zeros, so `1.05` isn't `1.5`
+                let mantissa = format!("{whole}{fractional}").parse::<u128>();
+                let scale = fractional.len() as u32;
 
-                if let Ok(fractional) = fractional {
-                    Token::Decimal(whole, fractional)
-                } else {
-                    Token::Invalid(format!("No number found after decimal dot in {result}"))
+                match mantissa {
+                    _ if fractional.is_empty() => {
+                        Token::Invalid(format!("No number found after decimal dot in {result}"))
+                    }
+                    Ok(mantissa)
+                        if mantissa < 10u128.pow(MAX_DECIMAL_PRECISION)
+                            && scale <= MAX_DECIMAL_PRECISION =>
+                    {
+                        Token::Decimal(mantissa, scale)
+                    }
+                    _ => Token::Invalid(format!(
+                        "Number '{result}' has more than {MAX_DECIMAL_PRECISION} digits"
+                    )),
                 }
             }
             _ => Token::Invalid(format!(
@@ -270,11 +601,46 @@ impl<'a> Lexer<'a> {
             )),
         };
     }
+
+    /// Reads the exponent of a number in scientific notation, which makes it a float.
+    /// `result` holds the digits before the exponent.
+    fn read_exponent(&mut self, mut result: String, number_of_dots: usize) -> Token {
+        if result.ends_with('.') {
+            return Token::Invalid(format!("No number found after decimal dot in {result}"));
+        }
+
+        // The `e` and the sign or first digit of the exponent
+        for _ in 0..2 {
+            result.extend(self.current_char);
+            self.advance();
+        }
+
+        while let Some(current_char) = self.current_char {
+            if !current_char.is_ascii_digit() {
+                break;
+            }
+
+            result.push(current_char);
+            self.advance();
+        }
+
+        if number_of_dots > 1 {
+            return Token::Invalid(format!(
+                "Found {number_of_dots} decimal separators in number '{result}'"
+            ));
+        }
+
+        return match result.parse::<f64>() {
+            Ok(value) if value.is_finite() => Token::Float(value),
+            Ok(_) => Token::Invalid(format!("Number '{result}' is too large")),
+            Err(_) => Token::Invalid(format!("No exponent found in number '{result}'")),
+        };
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Lexer, Token::*};
+    use super::{Lexer, Position, Token::*};
 
     #[test]
     fn lexer_advance() {
@@ -300,16 +666,164 @@ mod tests {
 
     #[test]
     fn keywords() {
-        let input = " select from table bool boolean int integer text ";
+        let input =
+            " select from table bool boolean smallint int integer bigint text blob bytea default check serial ";
 
         let result = Lexer::lex(input);
 
         assert_eq!(
             result,
-            vec![Select, From, Table, TypeBool, TypeBool, TypeInt, TypeInt, TypeText, Eof,],
+            vec![
+                Select,
+                From,
+                Table,
+                TypeBool,
+                TypeBool,
+                TypeSmallInt,
+                TypeInt,
+                TypeInt,
+                TypeBigInt,
+                TypeText,
+                TypeBlob,
+                TypeBlob,
+                Default,
+                Check,
+                TypeSerial,
+                Eof,
+            ],
         )
     }
 
+    #[test]
+    fn temporal_literals() {
+        let input =
+            "DATE '2026-01-01' + interval '3 days' < timestamp '2026-01-04 12:00:00' EXTRACT";
+
+        let result = Lexer::lex(input);
+
+        assert_eq!(
+            result,
+            vec![
+                TypeDate,
+                Str("2026-01-01".into()),
+                Plus,
+                TypeInterval,
+                Str("3 days".into()),
+                LessThan,
+                TypeTimestamp,
+                Str("2026-01-04 12:00:00".into()),
+                Extract,
+                Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn hex_literals() {
+        let input = "X'DEADbeef' x'' xylophone X'ABC' X'GG'";
+
+        let result = Lexer::lex(input);
+
+        assert_eq!(
+            result,
+            vec![
+                Blob(vec![0xde, 0xad, 0xbe, 0xef]),
+                Blob(vec![]),
+                Ident("xylophone".into()),
+                Invalid("'ABC' is not an even number of hex digits".into()),
+                Invalid("'GG' is not an even number of hex digits".into()),
+                Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn boolean_operators() {
+        let input = "a AND b or NOT c IS NOT null";
+
+        let result = Lexer::lex(input);
+
+        assert_eq!(
+            result,
+            vec![
+                Ident("a".into()),
+                And,
+                Ident("b".into()),
+                Or,
+                Not,
+                Ident("c".into()),
+                Is,
+                Not,
+                Null,
+                Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn joins() {
+        let input = "a JOIN b LEFT OUTER JOIN c inner join d CROSS JOIN e ON a.id = d.id";
+
+        let result = Lexer::lex(input);
+
+        assert_eq!(
+            result,
+            vec![
+                Ident("a".into()),
+                Join,
+                Ident("b".into()),
+                Left,
+                Outer,
+                Join,
+                Ident("c".into()),
+                Inner,
+                Join,
+                Ident("d".into()),
+                Cross,
+                Join,
+                Ident("e".into()),
+                On,
+                Ident("a".into()),
+                Dot,
+                Ident("id".into()),
+                Equals,
+                Ident("d".into()),
+                Dot,
+                Ident("id".into()),
+                Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn select_clauses() {
+        let input = "GROUP BY a HAVING ORDER BY a ASC, b desc LIMIT 5 OFFSET 10";
+
+        let result = Lexer::lex(input);
+
+        assert_eq!(
+            result,
+            vec![
+                Group,
+                By,
+                Ident("a".into()),
+                Having,
+                Order,
+                By,
+                Ident("a".into()),
+                Asc,
+                Comma,
+                Ident("b".into()),
+                Desc,
+                Limit,
+                Int(5),
+                Offset,
+                Int(10),
+                Eof,
+            ]
+        );
+    }
+
     #[test]
     fn handles_leading_and_trailing_whitespace() {
         let input = " select ";
@@ -442,6 +956,54 @@ mod tests {
         )
     }
 
+    #[test]
+    fn decimal() {
+        let result = Lexer::lex(
+            "1.05 1.5 1.50 0.001 1234567890123456789.0123456789012345678 1234567890123456789.01234567890123456789",
+        );
+
+        assert_eq!(
+            result,
+            vec![
+                Decimal(105, 2),
+                Decimal(15, 1),
+                Decimal(150, 2),
+                Decimal(1, 3),
+                Decimal(12345678901234567890123456789012345678, 19),
+                Invalid(
+                    "Number '1234567890123456789.01234567890123456789' has more than 38 digits"
+                        .into()
+                ),
+                Eof,
+            ]
+        );
+
+        assert_eq!(Decimal(105, 2).to_string(), "number `1.05`");
+    }
+
+    #[test]
+    fn float() {
+        let result = Lexer::lex("1.5e-3 2E10 3e+2 4else 1.e5 1e- 1e400");
+
+        assert_eq!(
+            result,
+            vec![
+                Float(1.5e-3),
+                Float(2e10),
+                Float(300.0),
+                Int(4),
+                Ident("else".into()),
+                Invalid("No number found after decimal dot in 1.".into()),
+                Ident("e5".into()),
+                Invalid("No exponent found in number '1e-'".into()),
+                Invalid("Number '1e400' is too large".into()),
+                Eof,
+            ]
+        );
+
+        assert_eq!(Float(1.5e-3).to_string(), "number `1.5e-3`");
+    }
+
     #[test]
     fn string() {
         let result = Lexer::lex("'asdfghjkl';");
@@ -455,7 +1017,7 @@ mod tests {
             "CREATE TABLE test_tbl (
             id INT,
             foreign_id INT,
-            FOREIGN KEY (foreign_id) REFERENCES other_tbl(id)
+            FOREIGN KEY (foreign_id) REFERENCES other_tbl(id) ON DELETE CASCADE ON UPDATE RESTRICT
         );",
         );
 
@@ -482,6 +1044,12 @@ mod tests {
                 LParenthesis,
                 Ident("id".into()),
                 RParenthesis,
+                On,
+                Delete,
+                Cascade,
+                On,
+                Update,
+                Restrict,
                 RParenthesis,
                 Semicolon,
                 Eof,
@@ -489,6 +1057,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn token_positions() {
+        let (tokens, positions) = Lexer::lex_with_positions("SELECT a\n  FROM tbl;");
+
+        assert_eq!(
+            tokens,
+            vec![
+                Select,
+                Ident("a".into()),
+                From,
+                Ident("tbl".into()),
+                Semicolon,
+                Eof
+            ]
+        );
+
+        assert_eq!(
+            positions,
+            vec![
+                Position { line: 1, column: 1 },
+                Position { line: 1, column: 8 },
+                Position { line: 2, column: 3 },
+                Position { line: 2, column: 8 },
+                Position {
+                    line: 2,
+                    column: 11
+                },
+                Position {
+                    line: 2,
+                    column: 12
+                },
+            ]
+        );
+    }
+
     #[test]
     fn handle_invalid_token() {
         assert_eq!(
@@ -496,14 +1099,154 @@ mod tests {
             vec![Invalid("Unknown character '&'".into()), Eof,]
         );
 
+        assert_eq!(
+            Lexer::lex("18446744073709551615 18446744073709551616"),
+            vec![
+                Int(u64::MAX),
+                Invalid("Number '18446744073709551616' is too large".into()),
+                Eof,
+            ]
+        );
+
         assert_eq!(
             Lexer::lex("1 1.2 1.2.3"),
             vec![
                 Int(1),
-                Decimal(1, 2),
+                Decimal(12, 1),
                 Invalid("Found 2 decimal separators in number '1.2.3'".into()),
                 Eof,
             ]
         );
     }
+
+    #[test]
+    fn sequences() {
+        let input = "CREATE SEQUENCE ids START WITH 10 INCREMENT BY -1; nextval('ids')";
+
+        let result = Lexer::lex(input);
+
+        assert_eq!(
+            result,
+            vec![
+                Create,
+                Sequence,
+                Ident("ids".into()),
+                Start,
+                With,
+                Int(10),
+                Increment,
+                By,
+                Minus,
+                Int(1),
+                Semicolon,
+                Ident("nextval".into()),
+                LParenthesis,
+                Str("ids".into()),
+                RParenthesis,
+                Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn indexes() {
+        let input = "CREATE UNIQUE INDEX idx ON t (a, b); DROP INDEX idx;";
+
+        let result = Lexer::lex(input);
+
+        assert_eq!(
+            result,
+            vec![
+                Create,
+                Unique,
+                Index,
+                Ident("idx".into()),
+                On,
+                Ident("t".into()),
+                LParenthesis,
+                Ident("a".into()),
+                Comma,
+                Ident("b".into()),
+                RParenthesis,
+                Semicolon,
+                Drop,
+                Index,
+                Ident("idx".into()),
+                Semicolon,
+                Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn explain() {
+        let input = "explain SELECT * FROM t;";
+
+        let result = Lexer::lex(input);
+
+        assert_eq!(
+            result,
+            vec![
+                Explain,
+                Select,
+                Asterisk,
+                From,
+                Ident("t".into()),
+                Semicolon,
+                Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn transactions() {
+        let input = "BEGIN; commit transaction; Rollback;";
+
+        let result = Lexer::lex(input);
+
+        // `TRANSACTION` isn't a keyword, like `COLUMN`
+        assert_eq!(
+            result,
+            vec![
+                Begin,
+                Semicolon,
+                Commit,
+                Ident("transaction".into()),
+                Semicolon,
+                Rollback,
+                Semicolon,
+                Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn alter_table() {
+        let input = "ALTER TABLE t ADD COLUMN c INT; alter table t rename column to b";
+
+        let result = Lexer::lex(input);
+
+        // `COLUMN` isn't a keyword, so that it can still name a column
+        assert_eq!(
+            result,
+            vec![
+                Alter,
+                Table,
+                Ident("t".into()),
+                Add,
+                Ident("COLUMN".into()),
+                Ident("c".into()),
+                TypeInt,
+                Semicolon,
+                Alter,
+                Table,
+                Ident("t".into()),
+                Rename,
+                Ident("column".into()),
+                To,
+                Ident("b".into()),
+                Eof,
+            ]
+        );
+    }
 }
@@ -5,21 +5,50 @@ pub mod parser;
 
 use lexer::{Lexer, Token};
 use parser::statements::{
-    Create, Delete, Drop, Insert, Select, Statement, StatementParser, Update,
+    Alter, Create, Delete, Drop, Explain, Insert, Select, Statement, StatementParser, Transaction,
+    Update,
 };
+use parser::{Arithmetic, Chain, Condition, Expression, ExpressionParser, ParseError};
 
-pub fn parse_statement(input: &str) -> Option<Statement> {
-    let tokens = &mut &Lexer::lex(input);
-
-    return match tokens.first()? {
-        Token::Create => Create.parse(tokens),
-        Token::Insert => Insert.parse(tokens),
-        Token::Select => Select.parse(tokens),
-        Token::Update => Update.parse(tokens),
-        Token::Delete => Delete.parse(tokens),
-        Token::Drop => Drop.parse(tokens),
-        _ => None,
+/// Parses a single statement.
+///
+/// On failure, the returned [`ParseError`] has its position set.
+pub fn parse_statement(input: &str) -> Result<Statement, ParseError> {
+    let (tokens, positions) = Lexer::lex_with_positions(input);
+
+    let result = match tokens.first() {
+        Some(Token::Create) => Create.parse(&tokens),
+        Some(Token::Insert) => Insert.parse(&tokens),
+        Some(Token::Select) => Select.parse(&tokens),
+        Some(Token::Update) => Update.parse(&tokens),
+        Some(Token::Delete) => Delete.parse(&tokens),
+        Some(Token::Drop) => Drop.parse(&tokens),
+        Some(Token::Alter) => Alter.parse(&tokens),
+        Some(Token::Explain) => Explain.parse(&tokens),
+        Some(Token::Begin | Token::Commit | Token::Rollback) => Transaction.parse(&tokens),
+        _ => Err(ParseError::new("a statement", &tokens)),
     };
+
+    return result.map_err(|error| error.locate(&positions));
+}
+
+/// Parses a single condition or arithmetic expression, like the ones [`Expression`]'s `Display` prints.
+///
+/// On failure, the returned [`ParseError`] has its position set.
+pub fn parse_expression(input: &str) -> Result<Expression, ParseError> {
+    let (tokens, positions) = Lexer::lex_with_positions(input);
+
+    let mut rest = tokens.as_slice();
+
+    let result = Condition
+        .or(Arithmetic)
+        .parse(&mut rest)
+        .and_then(|expression| match rest.first() {
+            Some(Token::Eof) | None => Ok(expression),
+            _ => Err(ParseError::new("end of input", rest)),
+        });
+
+    return result.map_err(|error| error.locate(&positions));
 }
 
 #[cfg(test)]
@@ -34,13 +63,61 @@ mod tests {
             ("INSERT INTO blabla (a, b, c) VALUES ('a', 'b', 'c');"),
             ("UPDATE tbl SET col1 = 1, col2 = 'bye' WHERE a = b;"),
             ("DELETE FROM tbl WHERE a = 5;"),
+            ("DELETE FROM tbl WHERE a = 5 AND NOT (b = 1 OR c = 'c');"),
             ("DROP DATABASE db;"),
+            ("ALTER TABLE blabla RENAME TO bla;"),
+            ("EXPLAIN SELECT a FROM blabla WHERE a > 1;"),
+            ("BEGIN;"),
+            ("COMMIT TRANSACTION;"),
         ];
 
         inputs.iter().for_each(|test_case| {
             let result = parse_statement(test_case);
 
-            assert!(result.is_some());
+            assert!(result.is_ok());
         })
     }
+
+    #[test]
+    fn parse_statement_error_position() {
+        let error = parse_statement("SELECT a\nFROM tbl\nWHERE a = ;").unwrap_err();
+
+        assert_eq!(error.expected, "an identifier or a value");
+        assert_eq!(error.found, Token::Semicolon);
+        assert_eq!(
+            error.position,
+            Some(lexer::Position {
+                line: 3,
+                column: 11
+            })
+        );
+
+        assert_eq!(
+            error.to_string(),
+            "Expected an identifier or a value, found `;` at line 3, column 11"
+        );
+
+        let error = parse_statement("SELEKT * FROM tbl;").unwrap_err();
+
+        assert_eq!(error.expected, "a statement");
+        assert_eq!(error.position, Some(lexer::Position { line: 1, column: 1 }));
+    }
+
+    #[test]
+    fn parse_expressions() {
+        assert!(matches!(
+            parse_expression("a + 1"),
+            Ok(parser::Expression::Binary { .. })
+        ));
+
+        assert!(matches!(
+            parse_expression("a > 1 AND b IS NULL"),
+            Ok(parser::Expression::And(..))
+        ));
+
+        let error = parse_expression("a > 1 b").unwrap_err();
+
+        assert_eq!(error.expected, "end of input");
+        assert_eq!(error.position, Some(lexer::Position { line: 1, column: 7 }));
+    }
 }
@@ -1,5 +1,6 @@
 //! Combinators for ExpressionParsers.
 
+use super::error::Result;
 use super::expressions::{Expression, ExpressionParser};
 use crate::lexer::Token;
 
@@ -22,14 +23,30 @@ impl Or {
 }
 
 impl ExpressionParser for Or {
-    fn parse(&self, input: &mut &[Token]) -> Option<Expression> {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        let mut error = None;
+
         for parser in &self.parsers {
-            if let Some(result) = parser.parse(input) {
-                return Some(result);
+            // Each alternative starts from the same input
+            let mut attempt = *input;
+
+            match parser.parse(&mut attempt) {
+                Ok(result) => {
+                    *input = attempt;
+
+                    return Ok(result);
+                }
+                Err(new_error) => {
+                    error = match error {
+                        None => Some(new_error),
+                        Some(error) => Some(error.furthest(new_error)),
+                    };
+                }
             }
         }
 
-        return None;
+        // Or always holds at least one parser, see Or::new
+        return Err(error.unwrap());
     }
 }
 
@@ -46,7 +63,7 @@ impl Multiple {
 }
 
 impl ExpressionParser for Multiple {
-    fn parse(&self, input: &mut &[Token]) -> Option<Expression> {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
         let mut expressions = vec![];
 
         expressions.push(self.parser.parse(input)?);
@@ -64,7 +81,7 @@ impl ExpressionParser for Multiple {
             expressions.push(self.parser.parse(input)?);
         }
 
-        return Some(Expression::Array(expressions));
+        return Ok(Expression::Array(expressions));
     }
 }
 
@@ -92,7 +109,7 @@ mod tests {
         Array, Expression as E, ExpressionParser, Identifier, Number, Str,
     };
     use super::Chain;
-    use crate::lexer::Lexer;
+    use crate::lexer::{Lexer, Token};
 
     #[test]
     fn or_basic() {
@@ -110,10 +127,29 @@ mod tests {
         inputs.iter().for_each(|test_case| {
             let result = test_case.0.parse(&mut Lexer::lex(test_case.1).as_slice());
 
-            assert_eq!(result, test_case.2);
+            assert_eq!(result.ok(), test_case.2);
         });
     }
 
+    #[test]
+    fn or_reports_furthest_error() {
+        let input = Lexer::lex("(1, 2 3)");
+        let input = &mut input.as_slice();
+
+        let error = Identifier.or(Array).parse(input).unwrap_err();
+
+        // Array got further than Identifier did
+        assert_eq!(error.expected, "`)`");
+        assert_eq!(error.found, Token::Int(3));
+
+        // Failed alternatives don't consume input
+        assert_eq!(input.len(), 7);
+
+        let error = Identifier.or(Number).parse(input).unwrap_err();
+
+        assert_eq!(error.expected, "an identifier or a number");
+    }
+
     #[test]
     fn list_basic() {
         let inputs: [(Box<dyn ExpressionParser>, _, _); 5] = [
@@ -128,7 +164,7 @@ mod tests {
                 E::Array(vec![
                     E::Ident("asdf".into()),
                     E::Int(1234),
-                    E::Decimal(1, 2),
+                    E::Decimal(12, 1),
                 ]),
             ),
             (
@@ -151,14 +187,14 @@ mod tests {
             (
                 Box::new(Array.multiple().or(Number)),
                 "1.2",
-                E::Decimal(1, 2),
+                E::Decimal(12, 1),
             ),
         ];
 
         inputs.into_iter().for_each(|test_case| {
             let result = test_case.0.parse(&mut Lexer::lex(test_case.1).as_slice());
 
-            assert_eq!(result, Some(test_case.2));
+            assert_eq!(result, Ok(test_case.2));
         });
     }
 }
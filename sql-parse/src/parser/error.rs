@@ -0,0 +1,74 @@
+use std::fmt::Display;
+
+use crate::lexer::{Position, Token};
+
+pub type Result<T> = std::result::Result<T, ParseError>;
+
+/// Describes why parsing failed: what the parser was looking for and what it found instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub expected: String,
+    pub found: Token,
+    /// Only known once the error has been mapped back onto the lexed input, see [`ParseError::locate`]
+    pub position: Option<Position>,
+    // Number of tokens left in the input when the error occurred.
+    // Used to find out which token the error occurred at,
+    // and by combinators to find out which alternative got furthest.
+    remaining: usize,
+}
+
+impl ParseError {
+    pub fn new(expected: impl Into<String>, input: &[Token]) -> Self {
+        return ParseError {
+            expected: expected.into(),
+            found: input.first().cloned().unwrap_or(Token::Eof),
+            position: None,
+            remaining: input.len(),
+        };
+    }
+
+    /// Of two errors, keeps the one that got furthest into the input.
+    ///
+    /// If both got equally far, their expectations are combined.
+    pub fn furthest(self, other: ParseError) -> ParseError {
+        if self.remaining < other.remaining {
+            return self;
+        }
+
+        if other.remaining < self.remaining {
+            return other;
+        }
+
+        if self.expected == other.expected {
+            return self;
+        }
+
+        return ParseError {
+            expected: format!("{} or {}", self.expected, other.expected),
+            ..self
+        };
+    }
+
+    /// Sets the position of the error, given the positions of all tokens in the input the error originated from.
+    pub fn locate(mut self, positions: &[Position]) -> ParseError {
+        let index = positions.len().saturating_sub(self.remaining);
+
+        self.position = positions.get(index).copied();
+
+        return self;
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Expected {}, found {}", self.expected, self.found)?;
+
+        if let Some(position) = self.position {
+            write!(f, " at {position}")?;
+        }
+
+        return Ok(());
+    }
+}
+
+impl std::error::Error for ParseError {}
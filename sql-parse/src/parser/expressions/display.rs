@@ -0,0 +1,161 @@
+//! Turns expressions back into SQL that parses to the same expression.
+
+use std::fmt::{Display, Formatter, Result};
+
+use super::{
+    ArithmeticOperator, ColumnType, DateTimeField, Expression, InfixOperator, UnaryOperator,
+};
+use Expression as E;
+
+impl Display for ColumnType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let name = match self {
+            ColumnType::SmallInt => "SMALLINT",
+            ColumnType::Int => "INT",
+            ColumnType::BigInt => "BIGINT",
+            ColumnType::Decimal(Some((precision, scale))) => {
+                return write!(f, "DECIMAL({precision}, {scale})")
+            }
+            ColumnType::Decimal(None) => "DECIMAL",
+            ColumnType::Float => "FLOAT",
+            ColumnType::Text => "TEXT",
+            ColumnType::Bool => "BOOL",
+            ColumnType::Blob => "BLOB",
+            ColumnType::Date => "DATE",
+            ColumnType::Time => "TIME",
+            ColumnType::Timestamp => "TIMESTAMP",
+            ColumnType::Interval => "INTERVAL",
+        };
+
+        return write!(f, "{name}");
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        // Nested operations get parenthesised, so precedence doesn't need to be taken into account
+        let nested = |expression: &Expression| match expression {
+            E::Binary { .. } | E::Unary { .. } | E::And(..) | E::Or(..) => {
+                format!("({expression})")
+            }
+            other => format!("{other}"),
+        };
+
+        return match self {
+            E::Type(column_type) => write!(f, "{column_type}"),
+            E::AllColumns => write!(f, "*"),
+            E::Ident(name) => write!(f, "{name}"),
+            E::QualifiedIdent(table, column) => write!(f, "{table}.{column}"),
+            E::Int(value) => write!(f, "{value}"),
+            E::Decimal(mantissa, 0) => write!(f, "{mantissa}"),
+            E::Decimal(mantissa, scale) => {
+                let digits = format!(
+                    "{:0>width$}",
+                    mantissa.unsigned_abs(),
+                    width = *scale as usize + 1
+                );
+
+                let (whole, fractional) = digits.split_at(digits.len() - *scale as usize);
+
+                let sign = if *mantissa < 0 { "-" } else { "" };
+
+                write!(f, "{sign}{whole}.{fractional}")
+            }
+            // Scientific notation always lexes as a float, even without a dot
+            E::Float(value) => write!(f, "{value:e}"),
+            E::Str(value) => write!(f, "'{value}'"),
+            E::Blob(bytes) => {
+                write!(f, "X'")?;
+
+                for byte in bytes {
+                    write!(f, "{byte:02X}")?;
+                }
+
+                write!(f, "'")
+            }
+            E::Bool(true) => write!(f, "TRUE"),
+            E::Bool(false) => write!(f, "FALSE"),
+            E::Null => write!(f, "NULL"),
+            E::TypedLiteral(column_type, value) => write!(f, "{column_type} '{value}'"),
+            E::Comparison {
+                left,
+                operator,
+                right,
+            } => {
+                let operator = match operator {
+                    InfixOperator::Equals => "=",
+                    InfixOperator::NotEqual => "<>",
+                    InfixOperator::LessThan => "<",
+                    InfixOperator::LessThanEqual => "<=",
+                    InfixOperator::GreaterThan => ">",
+                    InfixOperator::GreaterThanEqual => ">=",
+                };
+
+                write!(f, "{left} {operator} {right}")
+            }
+            E::IsNull {
+                expression,
+                negated: false,
+            } => write!(f, "{expression} IS NULL"),
+            E::IsNull {
+                expression,
+                negated: true,
+            } => write!(f, "{expression} IS NOT NULL"),
+            E::And(left, right) => write!(f, "{} AND {}", nested(left), nested(right)),
+            E::Or(left, right) => write!(f, "{} OR {}", nested(left), nested(right)),
+            E::Not(inner) => write!(f, "NOT ({inner})"),
+            E::Binary {
+                left,
+                operator,
+                right,
+            } => {
+                let operator = match operator {
+                    ArithmeticOperator::Add => "+",
+                    ArithmeticOperator::Subtract => "-",
+                    ArithmeticOperator::Multiply => "*",
+                    ArithmeticOperator::Divide => "/",
+                };
+
+                write!(f, "{} {operator} {}", nested(left), nested(right))
+            }
+            E::Unary {
+                operator: UnaryOperator::Minus,
+                operand,
+            } => write!(f, "-({operand})"),
+            E::Array(expressions) => {
+                let expressions: Vec<String> =
+                    expressions.iter().map(ToString::to_string).collect();
+
+                write!(f, "({})", expressions.join(", "))
+            }
+            E::FunctionCall {
+                name,
+                argument: Some(argument),
+            } => write!(f, "{name}({argument})"),
+            E::FunctionCall {
+                name,
+                argument: None,
+            } => write!(f, "{name}()"),
+            E::Extract { field, source } => {
+                let field = match field {
+                    DateTimeField::Year => "YEAR",
+                    DateTimeField::Month => "MONTH",
+                    DateTimeField::Day => "DAY",
+                    DateTimeField::Hour => "HOUR",
+                    DateTimeField::Minute => "MINUTE",
+                    DateTimeField::Second => "SECOND",
+                };
+
+                write!(f, "EXTRACT({field} FROM {source})")
+            }
+            E::CheckConstraint(condition) => write!(f, "CHECK ({condition})"),
+            // Only parts of statements, which have no use outside of them
+            E::ColumnDefinition(..)
+            | E::ForeignKeyConstraint { .. }
+            | E::UniqueConstraint { .. }
+            | E::ColumnValuePair { .. }
+            | E::SortKey { .. }
+            | E::Join { .. } => write!(f, "{self:?}"),
+        };
+    }
+}
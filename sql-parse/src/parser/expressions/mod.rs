@@ -1,41 +1,89 @@
+mod display;
 #[cfg(test)]
 mod tests;
 
+use std::ops::RangeInclusive;
+
 use super::combinators::Chain;
+use super::error::{ParseError, Result};
 use super::utils::check_and_skip;
 use crate::lexer::Token;
 
-#[derive(Debug, Clone)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Expression {
     Type(ColumnType),
-    ColumnDefinition(String, ColumnType),
+    ColumnDefinition(String, ColumnType, Vec<ColumnConstraint>),
     ForeignKeyConstraint {
-        column: Box<Expression>, // Ident for own column name
-        foreign_table: Box<Expression>, // Ident
+        column: Box<Expression>,         // Ident for own column name
+        foreign_table: Box<Expression>,  // Ident
         foreign_column: Box<Expression>, // Ident
+        on_delete: ReferentialAction,
+        on_update: ReferentialAction,
+    },
+    UniqueConstraint {
+        columns: Box<Expression>, // Array of Idents
+        primary: bool,            // `PRIMARY KEY` rather than `UNIQUE`
     },
+    CheckConstraint(Box<Expression>), // A condition
     AllColumns,
     Ident(String),
-    Int(usize),
-    Decimal(usize, usize),
+    QualifiedIdent(String, String), // table.column
+    Int(i64),
+    Decimal(i128, u32), // Digits and scale, `1.05` is `Decimal(105, 2)`
+    Float(f64),
     Str(String),
+    Blob(Vec<u8>),
     Bool(bool),
-    Where {
+    Null,
+    TypedLiteral(ColumnType, String), // e.g. `DATE '2026-01-01'`
+    Comparison {
         left: Box<Expression>,
         operator: InfixOperator,
         right: Box<Expression>,
     },
+    IsNull {
+        expression: Box<Expression>,
+        negated: bool, // `IS NOT NULL`
+    },
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+    Binary {
+        left: Box<Expression>,
+        operator: ArithmeticOperator,
+        right: Box<Expression>,
+    },
+    Unary {
+        operator: UnaryOperator,
+        operand: Box<Expression>,
+    },
     Array(Vec<Expression>),
     ColumnValuePair {
         column: Box<Expression>, // Ident for column name
         value: Box<Expression>,
     },
+    FunctionCall {
+        name: String,
+        argument: Option<Box<Expression>>, // AllColumns for e.g. `COUNT(*)`, None for e.g. `NOW()`
+    },
+    Extract {
+        field: DateTimeField,
+        source: Box<Expression>,
+    },
+    SortKey {
+        expression: Box<Expression>,
+        direction: SortDirection,
+    },
+    Join {
+        left: Box<Expression>,  // Ident or another Join
+        right: Box<Expression>, // Ident
+        kind: JoinType,
+        on: Option<Box<Expression>>, // Only None for cross joins
+    },
 }
 use Expression as E;
 
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum InfixOperator {
     Equals,
     NotEqual,
@@ -45,19 +93,87 @@ pub enum InfixOperator {
     GreaterThanEqual,
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ArithmeticOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum UnaryOperator {
+    Minus,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum JoinType {
+    Inner,
+    Left,
+    Cross,
+}
+
+/// The part of a date or time that `EXTRACT` returns.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DateTimeField {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+/// What happens to the rows referencing a row when it is deleted or its key is updated.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ReferentialAction {
+    Restrict, // The default, fails the statement
+    Cascade,  // Deletes the rows or updates their key as well
+    SetNull,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ColumnConstraint {
+    NotNull,
+    PrimaryKey,
+    Unique,
+    Default(Expression), // An arithmetic expression
+    Check(Expression),   // A condition
+    Serial,              // From a `SERIAL` column
+}
+
+const INT_RANGE: &str = "an integer between -9223372036854775808 and 9223372036854775807";
+
+/// The most digits a decimal can have, which is as many as always fit in a `u128`.
+pub const MAX_DECIMAL_PRECISION: u32 = 38;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum ColumnType {
-    Int,
-    Decimal,
+    SmallInt,                    // 16 bits
+    Int,                         // 32 bits
+    BigInt,                      // 64 bits
+    Decimal(Option<(u32, u32)>), // Precision and scale, any decimal fits if not given
+    Float,                       // 64 bit IEEE 754
     Text,
     Bool,
+    Blob, // Bytes
+    Date,
+    Time,      // Time of day, without a date
+    Timestamp, // Date and time, without a time zone
+    Interval,
 }
 
 impl InfixOperator {
-    fn parse(input: &mut &[Token]) -> Option<Self> {
+    fn parse(input: &mut &[Token]) -> Result<Self> {
         use InfixOperator as I;
 
-        let operator = match input.first()? {
+        let operator = match input.first().unwrap_or(&Token::Eof) {
             Token::Equals => Some(I::Equals),
             Token::NotEquals => Some(I::NotEqual),
             Token::LessThan => Some(I::LessThan),
@@ -70,119 +186,252 @@ impl InfixOperator {
         if let Some(operator) = operator {
             *input = &input[1..];
 
-            return Some(operator);
+            return Ok(operator);
         }
 
-        return None;
+        return Err(ParseError::new("a comparison operator", input));
     }
 }
 
 pub trait ExpressionParser: std::fmt::Debug {
-    fn parse(&self, input: &mut &[Token]) -> Option<Expression>;
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression>;
 }
 
 #[derive(Debug)]
 pub struct Int;
 impl ExpressionParser for Int {
-    fn parse(&self, input: &mut &[Token]) -> Option<E> {
-        if let Token::Int(value) = input.first()? {
+    fn parse(&self, input: &mut &[Token]) -> Result<E> {
+        if let Some(Token::Int(value)) = input.first() {
+            let value = i64::try_from(*value).map_err(|_| ParseError::new(INT_RANGE, input))?;
+
             *input = &input[1..];
 
-            return Some(E::Int(*value));
+            return Ok(E::Int(value));
         }
 
-        return None;
+        return Err(ParseError::new("an integer", input));
     }
 }
 
 #[derive(Debug)]
 pub struct Decimal;
 impl ExpressionParser for Decimal {
-    fn parse(&self, input: &mut &[Token]) -> Option<E> {
-        if let Token::Decimal(whole, fractional) = input.first()? {
+    fn parse(&self, input: &mut &[Token]) -> Result<E> {
+        if let Some(Token::Decimal(mantissa, scale)) = input.first() {
             *input = &input[1..];
 
-            return Some(E::Decimal(*whole, *fractional));
+            // Lexed decimals have at most 38 digits, so they always fit
+            return Ok(E::Decimal(*mantissa as i128, *scale));
         }
 
-        return None;
+        return Err(ParseError::new("a decimal", input));
+    }
+}
+
+#[derive(Debug)]
+pub struct Float;
+impl ExpressionParser for Float {
+    fn parse(&self, input: &mut &[Token]) -> Result<E> {
+        if let Some(Token::Float(value)) = input.first() {
+            *input = &input[1..];
+
+            return Ok(E::Float(*value));
+        }
+
+        return Err(ParseError::new("a float", input));
     }
 }
 
 #[derive(Debug)]
 pub struct Number;
 impl ExpressionParser for Number {
-    fn parse(&self, input: &mut &[Token]) -> Option<Expression> {
-        return Int.or(Decimal).parse(input);
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        return Int
+            .or(Decimal)
+            .or(Float)
+            .parse(input)
+            .map_err(|_| ParseError::new("a number", input));
     }
 }
 
 #[derive(Debug)]
 pub struct Str;
 impl ExpressionParser for Str {
-    fn parse(&self, input: &mut &[Token]) -> Option<Expression> {
-        if let Token::Str(value) = input.first()? {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        if let Some(Token::Str(value)) = input.first() {
             *input = &input[1..];
 
-            return Some(E::Str(value.clone()));
+            return Ok(E::Str(value.clone()));
         }
 
-        return None;
+        return Err(ParseError::new("a string", input));
+    }
+}
+
+#[derive(Debug)]
+pub struct Blob;
+impl ExpressionParser for Blob {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        if let Some(Token::Blob(value)) = input.first() {
+            *input = &input[1..];
+
+            return Ok(E::Blob(value.clone()));
+        }
+
+        return Err(ParseError::new("a hex string", input));
     }
 }
 
 #[derive(Debug)]
 pub struct Bool;
 impl ExpressionParser for Bool {
-    fn parse(&self, input: &mut &[Token]) -> Option<E> {
-        if let Token::Bool(value) = input.first()? {
+    fn parse(&self, input: &mut &[Token]) -> Result<E> {
+        if let Some(Token::Bool(value)) = input.first() {
             *input = &input[1..];
 
-            return Some(E::Bool(*value));
+            return Ok(E::Bool(*value));
         }
 
-        return None;
+        return Err(ParseError::new("a boolean", input));
+    }
+}
+
+#[derive(Debug)]
+pub struct Null;
+impl ExpressionParser for Null {
+    fn parse(&self, input: &mut &[Token]) -> Result<E> {
+        check_and_skip(input, Token::Null)?;
+
+        return Ok(E::Null);
+    }
+}
+
+/// A string prefixed with the type it is written in, like `DATE '2026-01-01'`, `INTERVAL '3 days'` or `FLOAT 'NaN'`.
+///
+/// The string is only checked when the literal is converted to a value.
+#[derive(Debug)]
+pub struct TypedLiteral;
+impl ExpressionParser for TypedLiteral {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        let column_type = match input.first() {
+            Some(Token::TypeDate) => ColumnType::Date,
+            Some(Token::TypeTime) => ColumnType::Time,
+            Some(Token::TypeTimestamp) => ColumnType::Timestamp,
+            Some(Token::TypeInterval) => ColumnType::Interval,
+            Some(Token::TypeFloat) => ColumnType::Float,
+            _ => {
+                return Err(ParseError::new(
+                    "a date, time, timestamp, interval or float",
+                    input,
+                ))
+            }
+        };
+
+        let Some(Token::Str(value)) = input.get(1) else {
+            return Err(ParseError::new("a string", &input[1..]));
+        };
+
+        let result = E::TypedLiteral(column_type, value.clone());
+
+        *input = &input[2..];
+
+        return Ok(result);
     }
 }
 
 #[derive(Debug)]
 pub struct Type;
 impl ExpressionParser for Type {
-    fn parse(&self, input: &mut &[Token]) -> Option<Expression> {
-        let result = match input.first()? {
-            Token::TypeInt => Some(E::Type(ColumnType::Int)),
-            Token::TypeDecimal => Some(E::Type(ColumnType::Decimal)),
-            Token::TypeBool => Some(E::Type(ColumnType::Bool)),
-            Token::TypeText => Some(E::Type(ColumnType::Text)),
-            _ => None,
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        let result = match input.first() {
+            Some(Token::TypeSmallInt) => E::Type(ColumnType::SmallInt),
+            Some(Token::TypeInt) => E::Type(ColumnType::Int),
+            Some(Token::TypeBigInt) => E::Type(ColumnType::BigInt),
+            Some(Token::TypeDecimal) => {
+                *input = &input[1..];
+
+                return Ok(E::Type(ColumnType::Decimal(parse_decimal_size(input)?)));
+            }
+            Some(Token::TypeFloat) => E::Type(ColumnType::Float),
+            Some(Token::TypeBool) => E::Type(ColumnType::Bool),
+            Some(Token::TypeText) => E::Type(ColumnType::Text),
+            Some(Token::TypeBlob) => E::Type(ColumnType::Blob),
+            Some(Token::TypeDate) => E::Type(ColumnType::Date),
+            Some(Token::TypeTime) => E::Type(ColumnType::Time),
+            Some(Token::TypeTimestamp) => E::Type(ColumnType::Timestamp),
+            Some(Token::TypeInterval) => E::Type(ColumnType::Interval),
+            _ => return Err(ParseError::new("a column type", input)),
         };
 
-        if result.is_some() {
-            *input = &input[1..];
-        }
+        *input = &input[1..];
+
+        return Ok(result);
+    }
+}
+
+/// Parses the optional `(precision, scale)` after `DECIMAL`, where the scale defaults to 0.
+fn parse_decimal_size(input: &mut &[Token]) -> Result<Option<(u32, u32)>> {
+    if input.first() != Some(&Token::LParenthesis) {
+        return Ok(None);
+    }
 
-        return result;
+    *input = &input[1..];
+
+    let precision = parse_size(
+        input,
+        1..=MAX_DECIMAL_PRECISION,
+        format!("a precision between 1 and {MAX_DECIMAL_PRECISION}"),
+    )?;
+
+    let scale = if input.first() == Some(&Token::Comma) {
+        *input = &input[1..];
+
+        parse_size(input, 0..=precision, "a scale no larger than the precision")?
+    } else {
+        0
+    };
+
+    check_and_skip(input, Token::RParenthesis)?;
+
+    return Ok(Some((precision, scale)));
+}
+
+fn parse_size(
+    input: &mut &[Token],
+    allowed: RangeInclusive<u32>,
+    expected: impl Into<String>,
+) -> Result<u32> {
+    if let Some(Token::Int(value)) = input.first() {
+        if let Ok(value) = u32::try_from(*value) {
+            if allowed.contains(&value) {
+                *input = &input[1..];
+
+                return Ok(value);
+            }
+        }
     }
+
+    return Err(ParseError::new(expected, input));
 }
 
 #[derive(Debug)]
 pub struct Identifier;
 impl ExpressionParser for Identifier {
-    fn parse(&self, input: &mut &[Token]) -> Option<Expression> {
-        if let Token::Ident(name) = input.first()? {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        if let Some(Token::Ident(name)) = input.first() {
             *input = &input[1..];
 
-            return Some(E::Ident(name.clone()));
+            return Ok(E::Ident(name.clone()));
         }
 
-        return None;
+        return Err(ParseError::new("an identifier", input));
     }
 }
 
 #[derive(Debug)]
 pub struct ForeignKeyConstraint;
 impl ExpressionParser for ForeignKeyConstraint {
-    fn parse(&self, input: &mut &[Token]) -> Option<E> {
+    fn parse(&self, input: &mut &[Token]) -> Result<E> {
         check_and_skip(input, Token::Foreign)?;
 
         check_and_skip(input, Token::Key)?;
@@ -203,71 +452,285 @@ impl ExpressionParser for ForeignKeyConstraint {
 
         check_and_skip(input, Token::RParenthesis)?;
 
-        return Some(E::ForeignKeyConstraint {
+        let mut on_delete = None;
+        let mut on_update = None;
+
+        // `ON DELETE` and `ON UPDATE` can come in either order, but only once each
+        while let Some(Token::On) = input.first() {
+            *input = &input[1..];
+
+            let action = match input.first() {
+                Some(Token::Delete) if on_delete.is_none() => &mut on_delete,
+                Some(Token::Update) if on_update.is_none() => &mut on_update,
+                _ => return Err(ParseError::new("DELETE or UPDATE", input)),
+            };
+
+            *input = &input[1..];
+
+            *action = Some(parse_referential_action(input)?);
+        }
+
+        return Ok(E::ForeignKeyConstraint {
             column: Box::new(own_column),
             foreign_table: Box::new(table_name),
             foreign_column: Box::new(column),
+            on_delete: on_delete.unwrap_or(ReferentialAction::Restrict),
+            on_update: on_update.unwrap_or(ReferentialAction::Restrict),
         });
     }
 }
 
+fn parse_referential_action(input: &mut &[Token]) -> Result<ReferentialAction> {
+    let action = match input.first() {
+        Some(Token::Cascade) => ReferentialAction::Cascade,
+        Some(Token::Restrict) => ReferentialAction::Restrict,
+        Some(Token::Set) => {
+            *input = &input[1..];
+
+            check_and_skip(input, Token::Null)?;
+
+            return Ok(ReferentialAction::SetNull);
+        }
+        _ => return Err(ParseError::new("CASCADE, SET NULL or RESTRICT", input)),
+    };
+
+    *input = &input[1..];
+
+    return Ok(action);
+}
+
+/// A table-level `PRIMARY KEY (a, b)` or `UNIQUE (a, b)`.
+#[derive(Debug)]
+pub struct UniqueConstraint;
+impl ExpressionParser for UniqueConstraint {
+    fn parse(&self, input: &mut &[Token]) -> Result<E> {
+        let primary = match input.first() {
+            Some(Token::Primary) => {
+                *input = &input[1..];
+
+                check_and_skip(input, Token::Key)?;
+
+                true
+            }
+            Some(Token::Unique) => {
+                *input = &input[1..];
+
+                false
+            }
+            _ => return Err(ParseError::new("PRIMARY KEY or UNIQUE", input)),
+        };
+
+        check_and_skip(input, Token::LParenthesis)?;
+
+        let columns = Identifier.multiple().parse(input)?;
+
+        check_and_skip(input, Token::RParenthesis)?;
+
+        return Ok(E::UniqueConstraint {
+            columns: Box::new(columns),
+            primary,
+        });
+    }
+}
+
+/// A table-level `CHECK (<condition>)`.
+#[derive(Debug)]
+pub struct CheckConstraint;
+impl ExpressionParser for CheckConstraint {
+    fn parse(&self, input: &mut &[Token]) -> Result<E> {
+        check_and_skip(input, Token::Check)?;
+
+        let condition = ParenthesisedCondition.parse(input)?;
+
+        return Ok(E::CheckConstraint(condition.into()));
+    }
+}
+
 #[derive(Debug)]
 pub struct ColumnDefinition;
 impl ExpressionParser for ColumnDefinition {
-    fn parse(&self, input: &mut &[Token]) -> Option<Expression> {
-        let name = Identifier.parse(input);
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        match input.first() {
+            Some(Token::Foreign) => return ForeignKeyConstraint.parse(input),
+            Some(Token::Primary | Token::Unique) => return UniqueConstraint.parse(input),
+            Some(Token::Check) => return CheckConstraint.parse(input),
+            _ => (),
+        }
+
+        let name = Identifier
+            .parse(input)
+            .map_err(|_| ParseError::new("a column definition or constraint", input))?;
+
+        // `SERIAL` is short for an INT that gets its values from a sequence made for the column
+        let (column_type, mut constraints) = if check_and_skip(input, Token::TypeSerial).is_ok() {
+            (E::Type(ColumnType::Int), vec![ColumnConstraint::Serial])
+        } else {
+            (Type.parse(input)?, vec![])
+        };
+
+        loop {
+            match input.first() {
+                Some(Token::Not) => {
+                    *input = &input[1..];
+
+                    check_and_skip(input, Token::Null)?;
+
+                    constraints.push(ColumnConstraint::NotNull);
+                }
+                // Columns are nullable by default, so an explicit `NULL` doesn't change anything
+                Some(Token::Null) => *input = &input[1..],
+                Some(Token::Primary) => {
+                    *input = &input[1..];
+
+                    check_and_skip(input, Token::Key)?;
 
-        if let Some(name) = name {
-            let column_type = Type.parse(input)?;
+                    constraints.push(ColumnConstraint::PrimaryKey);
+                }
+                Some(Token::Unique) => {
+                    *input = &input[1..];
 
-            if let (E::Ident(name), E::Type(column_type)) = (name, column_type) {
-                return Some(E::ColumnDefinition(name, column_type));
-            } else {
-                panic!("Return types got all messed up")
+                    constraints.push(ColumnConstraint::Unique);
+                }
+                Some(Token::Default) => {
+                    *input = &input[1..];
+
+                    constraints.push(ColumnConstraint::Default(Arithmetic.parse(input)?));
+                }
+                Some(Token::Check) => {
+                    *input = &input[1..];
+
+                    constraints.push(ColumnConstraint::Check(
+                        ParenthesisedCondition.parse(input)?,
+                    ));
+                }
+                _ => break,
             }
         }
 
-        return ForeignKeyConstraint.parse(input);
+        if let (E::Ident(name), E::Type(column_type)) = (name, column_type) {
+            return Ok(E::ColumnDefinition(name, column_type, constraints));
+        } else {
+            panic!("Return types got all messed up")
+        }
     }
 }
 
 #[derive(Debug)]
 pub struct AllColumn;
 impl ExpressionParser for AllColumn {
-    fn parse(&self, input: &mut &[Token]) -> Option<Expression> {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
         check_and_skip(input, Token::Asterisk)?;
 
-        return Some(E::AllColumns);
+        return Ok(E::AllColumns);
     }
 }
 
 #[derive(Debug)]
 pub struct Column;
 impl ExpressionParser for Column {
-    fn parse(&self, input: &mut &[Token]) -> Option<Expression> {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
         return Identifier.or(AllColumn).multiple().parse(input);
     }
 }
 
 #[derive(Debug)]
 pub struct Where;
-// TODO: This doesn't differentiate between a failed parsing of `WHERE` clause,
-// and the absence of a `WHERE` clause.
-// Again, have to move to Result<Option> or Option<Result>
-// That's gonna make me sad about not being able to use ? everywhere though :(
+// Note: this fails if there is no `WHERE` clause at all,
+// use utils::parse_optional to parse an optional `WHERE` clause
 impl ExpressionParser for Where {
-    fn parse(&self, input: &mut &[Token]) -> Option<Expression> {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
         check_and_skip(input, Token::Where)?;
 
-        let parser = Identifier.or(Value);
+        return Condition.parse(input);
+    }
+}
+
+/// A boolean expression, made of comparisons combined with `AND`, `OR` and `NOT`.
+///
+/// Precedence from loosest to tightest binding is `OR`, `AND`, `NOT`,
+/// so `a = 1 OR NOT b = 2 AND c = 3` means `a = 1 OR ((NOT b = 2) AND c = 3)`.
+#[derive(Debug)]
+pub struct Condition;
+impl ExpressionParser for Condition {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        let mut result = Conjunction.parse(input)?;
 
-        let left = parser.parse(input)?.into();
+        while check_and_skip(input, Token::Or).is_ok() {
+            let right = Conjunction.parse(input)?;
+
+            result = E::Or(result.into(), right.into());
+        }
+
+        return Ok(result);
+    }
+}
+
+#[derive(Debug)]
+pub struct Conjunction;
+impl ExpressionParser for Conjunction {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        let mut result = Negation.parse(input)?;
+
+        while check_and_skip(input, Token::And).is_ok() {
+            let right = Negation.parse(input)?;
+
+            result = E::And(result.into(), right.into());
+        }
+
+        return Ok(result);
+    }
+}
+
+#[derive(Debug)]
+pub struct Negation;
+impl ExpressionParser for Negation {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        if check_and_skip(input, Token::Not).is_ok() {
+            let inner = Negation.parse(input)?;
+
+            return Ok(E::Not(inner.into()));
+        }
+
+        return Comparison.or(ParenthesisedCondition).parse(input);
+    }
+}
+
+#[derive(Debug)]
+pub struct ParenthesisedCondition;
+impl ExpressionParser for ParenthesisedCondition {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        check_and_skip(input, Token::LParenthesis)?;
+
+        let result = Condition.parse(input)?;
+
+        check_and_skip(input, Token::RParenthesis)?;
+
+        return Ok(result);
+    }
+}
+
+#[derive(Debug)]
+pub struct Comparison;
+impl ExpressionParser for Comparison {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        let left = Arithmetic.parse(input)?.into();
+
+        if check_and_skip(input, Token::Is).is_ok() {
+            let negated = check_and_skip(input, Token::Not).is_ok();
+
+            check_and_skip(input, Token::Null)?;
+
+            return Ok(E::IsNull {
+                expression: left,
+                negated,
+            });
+        }
 
         let operator = InfixOperator::parse(input)?;
 
-        let right = parser.parse(input)?.into();
+        let right = Arithmetic.parse(input)?.into();
 
-        return Some(E::Where {
+        return Ok(E::Comparison {
             left,
             operator,
             right,
@@ -275,25 +738,367 @@ impl ExpressionParser for Where {
     }
 }
 
+/// An arithmetic expression over columns and values, e.g. `price * (1 + tax)`.
+///
+/// `*` and `/` bind tighter than `+` and `-`, and all of them are left associative.
+#[derive(Debug)]
+pub struct Arithmetic;
+impl ExpressionParser for Arithmetic {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        let mut result = Term.parse(input)?;
+
+        loop {
+            let operator = match input.first() {
+                Some(Token::Plus) => ArithmeticOperator::Add,
+                Some(Token::Minus) => ArithmeticOperator::Subtract,
+                _ => return Ok(result),
+            };
+
+            *input = &input[1..];
+
+            let right = Term.parse(input)?;
+
+            result = E::Binary {
+                left: result.into(),
+                operator,
+                right: right.into(),
+            };
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Term;
+impl ExpressionParser for Term {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        let mut result = Factor.parse(input)?;
+
+        loop {
+            let operator = match input.first() {
+                // Multiplication and <all columns> share the same token
+                Some(Token::Asterisk) => ArithmeticOperator::Multiply,
+                Some(Token::Slash) => ArithmeticOperator::Divide,
+                _ => return Ok(result),
+            };
+
+            *input = &input[1..];
+
+            let right = Factor.parse(input)?;
+
+            result = E::Binary {
+                left: result.into(),
+                operator,
+                right: right.into(),
+            };
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Factor;
+impl ExpressionParser for Factor {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        match input.first() {
+            Some(Token::Minus) => {
+                *input = &input[1..];
+
+                if let Some(number) = parse_negative_number(input)? {
+                    return Ok(number);
+                }
+
+                let operand = Factor.parse(input)?;
+
+                return Ok(E::Unary {
+                    operator: UnaryOperator::Minus,
+                    operand: operand.into(),
+                });
+            }
+            Some(Token::LParenthesis) => {
+                *input = &input[1..];
+
+                let result = Arithmetic.parse(input)?;
+
+                check_and_skip(input, Token::RParenthesis)?;
+
+                return Ok(result);
+            }
+            Some(Token::Ident(_)) if input.get(1) == Some(&Token::LParenthesis) => {
+                return FunctionCall.parse(input);
+            }
+            Some(Token::Extract) => return Extract.parse(input),
+            _ => return ColumnReference.or(Value).parse(input),
+        }
+    }
+}
+
+/// Parses the number after a unary minus as a negative number, if it is one.
+///
+/// Folding the minus into the number is the only way to write the smallest `BIGINT`,
+/// since its absolute value doesn't fit in one.
+fn parse_negative_number(input: &mut &[Token]) -> Result<Option<Expression>> {
+    let result = match input.first() {
+        Some(Token::Int(value)) => match i64::try_from(-(*value as i128)) {
+            Ok(value) => E::Int(value),
+            Err(_) => return Err(ParseError::new(INT_RANGE, input)),
+        },
+        Some(Token::Decimal(mantissa, scale)) => E::Decimal(-(*mantissa as i128), *scale),
+        _ => return Ok(None),
+    };
+
+    *input = &input[1..];
+
+    return Ok(Some(result));
+}
+
+/// A call to a function with at most one argument, like `SUM(price)`, `COUNT(*)` or `NOW()`.
+#[derive(Debug)]
+pub struct FunctionCall;
+impl ExpressionParser for FunctionCall {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        let name = Identifier.parse(input)?;
+
+        check_and_skip(input, Token::LParenthesis)?;
+
+        let argument = match input.first() {
+            Some(Token::RParenthesis) => None,
+            _ => Some(AllColumn.or(Arithmetic).parse(input)?.into()),
+        };
+
+        check_and_skip(input, Token::RParenthesis)?;
+
+        if let E::Ident(name) = name {
+            return Ok(E::FunctionCall { name, argument });
+        } else {
+            panic!("Return types got all messed up")
+        }
+    }
+}
+
+/// `EXTRACT(<field> FROM <expression>)`, where the field is e.g. `YEAR` or `HOUR`.
+#[derive(Debug)]
+pub struct Extract;
+impl ExpressionParser for Extract {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        check_and_skip(input, Token::Extract)?;
+
+        check_and_skip(input, Token::LParenthesis)?;
+
+        let field = match input.first() {
+            Some(Token::Ident(name)) => match name.to_uppercase().as_str() {
+                "YEAR" => Some(DateTimeField::Year),
+                "MONTH" => Some(DateTimeField::Month),
+                "DAY" => Some(DateTimeField::Day),
+                "HOUR" => Some(DateTimeField::Hour),
+                "MINUTE" => Some(DateTimeField::Minute),
+                "SECOND" => Some(DateTimeField::Second),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let Some(field) = field else {
+            return Err(ParseError::new(
+                "one of YEAR, MONTH, DAY, HOUR, MINUTE or SECOND",
+                input,
+            ));
+        };
+
+        *input = &input[1..];
+
+        check_and_skip(input, Token::From)?;
+
+        let source = Arithmetic.parse(input)?;
+
+        check_and_skip(input, Token::RParenthesis)?;
+
+        return Ok(E::Extract {
+            field,
+            source: source.into(),
+        });
+    }
+}
+
+#[derive(Debug)]
+pub struct GroupBy;
+impl ExpressionParser for GroupBy {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        check_and_skip(input, Token::Group)?;
+
+        check_and_skip(input, Token::By)?;
+
+        return Arithmetic.multiple().parse(input);
+    }
+}
+
+#[derive(Debug)]
+pub struct Having;
+impl ExpressionParser for Having {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        check_and_skip(input, Token::Having)?;
+
+        return Condition.parse(input);
+    }
+}
+
+#[derive(Debug)]
+pub struct OrderBy;
+impl ExpressionParser for OrderBy {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        check_and_skip(input, Token::Order)?;
+
+        check_and_skip(input, Token::By)?;
+
+        return SortKey.multiple().parse(input);
+    }
+}
+
+/// An expression to sort by, followed by an optional `ASC` (the default) or `DESC`.
+#[derive(Debug)]
+pub struct SortKey;
+impl ExpressionParser for SortKey {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        let expression = Arithmetic.parse(input)?.into();
+
+        let direction = match input.first() {
+            Some(Token::Desc) => SortDirection::Descending,
+            Some(Token::Asc) => SortDirection::Ascending,
+            _ => {
+                return Ok(E::SortKey {
+                    expression,
+                    direction: SortDirection::Ascending,
+                })
+            }
+        };
+
+        *input = &input[1..];
+
+        return Ok(E::SortKey {
+            expression,
+            direction,
+        });
+    }
+}
+
+#[derive(Debug)]
+pub struct Limit;
+impl ExpressionParser for Limit {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        check_and_skip(input, Token::Limit)?;
+
+        return Int.parse(input);
+    }
+}
+
+#[derive(Debug)]
+pub struct Offset;
+impl ExpressionParser for Offset {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        check_and_skip(input, Token::Offset)?;
+
+        return Int.parse(input);
+    }
+}
+
+/// A column name, optionally qualified with the table it belongs to, like `orders.id`.
+#[derive(Debug)]
+pub struct ColumnReference;
+impl ExpressionParser for ColumnReference {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        let name = Identifier.parse(input)?;
+
+        if check_and_skip(input, Token::Dot).is_err() {
+            return Ok(name);
+        }
+
+        let column = Identifier.parse(input)?;
+
+        if let (E::Ident(table), E::Ident(column)) = (name, column) {
+            return Ok(E::QualifiedIdent(table, column));
+        } else {
+            panic!("Return types got all messed up")
+        }
+    }
+}
+
+/// The table(s) to select from, possibly combined with (left associative) joins.
+#[derive(Debug)]
+pub struct TableReference;
+impl ExpressionParser for TableReference {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        let mut result = Identifier.parse(input)?;
+
+        loop {
+            let kind = match input.first() {
+                Some(Token::Join) => JoinType::Inner,
+                Some(Token::Inner) => {
+                    *input = &input[1..];
+
+                    JoinType::Inner
+                }
+                Some(Token::Left) => {
+                    *input = &input[1..];
+
+                    // OUTER is optional
+                    let _ = check_and_skip(input, Token::Outer);
+
+                    JoinType::Left
+                }
+                Some(Token::Cross) => {
+                    *input = &input[1..];
+
+                    JoinType::Cross
+                }
+                _ => return Ok(result),
+            };
+
+            check_and_skip(input, Token::Join)?;
+
+            let right = Identifier.parse(input)?;
+
+            let on = match kind {
+                JoinType::Cross => None,
+                JoinType::Inner | JoinType::Left => {
+                    check_and_skip(input, Token::On)?;
+
+                    Some(Condition.parse(input)?.into())
+                }
+            };
+
+            result = E::Join {
+                left: result.into(),
+                right: right.into(),
+                kind,
+                on,
+            };
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Value;
 impl ExpressionParser for Value {
-    fn parse(&self, input: &mut &[Token]) -> Option<Expression> {
-        return Str.or(Number).or(Bool).parse(input);
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
+        return Str
+            .or(Number)
+            .or(Blob)
+            .or(Bool)
+            .or(Null)
+            .or(TypedLiteral)
+            .parse(input)
+            .map_err(|_| ParseError::new("a value", input));
     }
 }
 
 #[derive(Debug)]
 pub struct Array;
 impl ExpressionParser for Array {
-    fn parse(&self, input: &mut &[Token]) -> Option<Expression> {
+    fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
         check_and_skip(input, Token::LParenthesis)?;
 
-        // TODO: Make this parse any expression rather than hardcoded `Value`
-        let expressions = Value.multiple().parse(input)?;
+        let expressions = Arithmetic.multiple().parse(input)?;
 
         check_and_skip(input, Token::RParenthesis)?;
 
-        return Some(expressions);
+        return Ok(expressions);
     }
 }
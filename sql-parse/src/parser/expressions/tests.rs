@@ -7,7 +7,7 @@ pub fn test_all_cases(parser: impl ExpressionParser, inputs: &[(&str, Option<Exp
     inputs.iter().for_each(|test_case| {
         let result = parser.parse(&mut Lexer::lex(test_case.0).as_slice());
 
-        assert_eq!(result, test_case.1);
+        assert_eq!(result.ok(), test_case.1);
     });
 }
 
@@ -17,8 +17,11 @@ fn number_parser_basic() {
         ("1", Some(E::Int(1))),
         ("69420", Some(E::Int(69420))),
         ("asdf", None),
-        ("5.321", Some(E::Decimal(5, 321))),
+        ("5.321", Some(E::Decimal(5321, 3))),
+        ("5.0321", Some(E::Decimal(50321, 4))),
         ("5.3.2.1", None),
+        ("5e3", Some(E::Float(5000.0))),
+        ("2.5E-1", Some(E::Float(0.25))),
     ];
 
     test_all_cases(Number, &inputs);
@@ -37,6 +40,18 @@ fn string_parser_basic() {
     test_all_cases(Str, &inputs);
 }
 
+#[test]
+fn blob_parser_basic() {
+    let inputs = [
+        ("X'CAFE'", Some(E::Blob(vec![0xca, 0xfe]))),
+        ("x''", Some(E::Blob(vec![]))),
+        ("'CAFE'", None),
+        ("X'CAF'", None),
+    ];
+
+    test_all_cases(Blob, &inputs);
+}
+
 #[test]
 fn bool_parser_basic() {
     let inputs = [
@@ -52,11 +67,34 @@ fn bool_parser_basic() {
 #[test]
 fn type_parser_basic() {
     let inputs = [
+        ("smallint", Some(E::Type(ColumnType::SmallInt))),
         ("INT", Some(E::Type(ColumnType::Int))),
+        ("bigint", Some(E::Type(ColumnType::BigInt))),
         ("INTeger", Some(E::Type(ColumnType::Int))),
         ("bool", Some(E::Type(ColumnType::Bool))),
-        ("decimal", Some(E::Type(ColumnType::Decimal))),
+        ("decimal", Some(E::Type(ColumnType::Decimal(None)))),
+        (
+            "decimal(10, 2)",
+            Some(E::Type(ColumnType::Decimal(Some((10, 2))))),
+        ),
+        (
+            "decimal(5)",
+            Some(E::Type(ColumnType::Decimal(Some((5, 0))))),
+        ),
+        ("decimal(39, 2)", None),
+        ("decimal(0)", None),
+        ("decimal(5, 6)", None),
+        ("decimal(5,", None),
+        ("real", Some(E::Type(ColumnType::Float))),
+        ("DOUBLE", Some(E::Type(ColumnType::Float))),
+        ("float", Some(E::Type(ColumnType::Float))),
         ("text", Some(E::Type(ColumnType::Text))),
+        ("blob", Some(E::Type(ColumnType::Blob))),
+        ("BYTEA", Some(E::Type(ColumnType::Blob))),
+        ("date", Some(E::Type(ColumnType::Date))),
+        ("TIME", Some(E::Type(ColumnType::Time))),
+        ("timestamp", Some(E::Type(ColumnType::Timestamp))),
+        ("interval", Some(E::Type(ColumnType::Interval))),
         ("asdf", None),
     ];
 
@@ -71,7 +109,7 @@ fn type_parser_list() {
 
     assert_eq!(
         result,
-        Some(E::Array(vec![
+        Ok(E::Array(vec![
             E::Type(ColumnType::Bool),
             E::Type(ColumnType::Int),
             E::Type(ColumnType::Int),
@@ -96,12 +134,46 @@ fn column_definition_parser_basic() {
     let inputs = [
         (
             "asdf INT",
-            Some(E::ColumnDefinition("asdf".into(), ColumnType::Int)),
+            Some(E::ColumnDefinition("asdf".into(), ColumnType::Int, vec![])),
         ),
         (
             "jkl TEXT",
-            Some(E::ColumnDefinition("jkl".into(), ColumnType::Text)),
+            Some(E::ColumnDefinition("jkl".into(), ColumnType::Text, vec![])),
+        ),
+        (
+            "id INT NOT NULL",
+            Some(E::ColumnDefinition(
+                "id".into(),
+                ColumnType::Int,
+                vec![ColumnConstraint::NotNull],
+            )),
+        ),
+        (
+            "comment TEXT NULL",
+            Some(E::ColumnDefinition(
+                "comment".into(),
+                ColumnType::Text,
+                vec![],
+            )),
+        ),
+        (
+            "id INT PRIMARY KEY",
+            Some(E::ColumnDefinition(
+                "id".into(),
+                ColumnType::Int,
+                vec![ColumnConstraint::PrimaryKey],
+            )),
         ),
+        (
+            "email TEXT NOT NULL UNIQUE",
+            Some(E::ColumnDefinition(
+                "email".into(),
+                ColumnType::Text,
+                vec![ColumnConstraint::NotNull, ColumnConstraint::Unique],
+            )),
+        ),
+        ("id INT NOT", None),
+        ("id INT PRIMARY", None),
     ];
 
     test_all_cases(ColumnDefinition, &inputs);
@@ -109,18 +181,130 @@ fn column_definition_parser_basic() {
 
 #[test]
 fn column_definition_foreign_key() {
-    let inputs = [(
-        "FOREIGN KEY (foreign_id) REFERENCES other_tbl(id)",
-        Some(E::ForeignKeyConstraint {
-            column: Box::new(E::Ident("foreign_id".into())),
-            foreign_table: Box::new(E::Ident("other_tbl".into())),
-            foreign_column: Box::new(E::Ident("id".into())),
-        }),
-    )];
+    let foreign_key = |on_delete, on_update| E::ForeignKeyConstraint {
+        column: Box::new(E::Ident("foreign_id".into())),
+        foreign_table: Box::new(E::Ident("other_tbl".into())),
+        foreign_column: Box::new(E::Ident("id".into())),
+        on_delete,
+        on_update,
+    };
+
+    let inputs = [
+        (
+            "FOREIGN KEY (foreign_id) REFERENCES other_tbl(id)",
+            Some(foreign_key(
+                ReferentialAction::Restrict,
+                ReferentialAction::Restrict,
+            )),
+        ),
+        (
+            "FOREIGN KEY (foreign_id) REFERENCES other_tbl(id) ON DELETE CASCADE",
+            Some(foreign_key(
+                ReferentialAction::Cascade,
+                ReferentialAction::Restrict,
+            )),
+        ),
+        (
+            "FOREIGN KEY (foreign_id) REFERENCES other_tbl(id) ON UPDATE CASCADE ON DELETE SET NULL",
+            Some(foreign_key(
+                ReferentialAction::SetNull,
+                ReferentialAction::Cascade,
+            )),
+        ),
+        (
+            "FOREIGN KEY (foreign_id) REFERENCES other_tbl(id) ON DELETE RESTRICT ON UPDATE SET NULL",
+            Some(foreign_key(
+                ReferentialAction::Restrict,
+                ReferentialAction::SetNull,
+            )),
+        ),
+        (
+            "FOREIGN KEY (foreign_id) REFERENCES other_tbl(id) ON DELETE CASCADE ON DELETE RESTRICT",
+            None,
+        ),
+        ("FOREIGN KEY (foreign_id) REFERENCES other_tbl(id) ON DELETE SET", None),
+        ("FOREIGN KEY (foreign_id) REFERENCES other_tbl(id) ON INSERT CASCADE", None),
+        ("FOREIGN KEY (foreign_id) REFERENCES other_tbl(id) ON DELETE", None),
+    ];
 
     test_all_cases(ForeignKeyConstraint, &inputs)
 }
 
+#[test]
+fn column_definition_unique() {
+    let inputs = [
+        (
+            "PRIMARY KEY (order_id, line)",
+            Some(E::UniqueConstraint {
+                columns: Box::new(E::Array(vec![
+                    E::Ident("order_id".into()),
+                    E::Ident("line".into()),
+                ])),
+                primary: true,
+            }),
+        ),
+        (
+            "UNIQUE (email)",
+            Some(E::UniqueConstraint {
+                columns: Box::new(E::Array(vec![E::Ident("email".into())])),
+                primary: false,
+            }),
+        ),
+        ("PRIMARY (id)", None),
+        ("UNIQUE ()", None),
+    ];
+
+    test_all_cases(ColumnDefinition, &inputs)
+}
+
+#[test]
+fn column_definition_default_and_check() {
+    let inputs = [
+        (
+            "amount INT NOT NULL DEFAULT 1 + 2 CHECK (amount > 0)",
+            Some(E::ColumnDefinition(
+                "amount".into(),
+                ColumnType::Int,
+                vec![
+                    ColumnConstraint::NotNull,
+                    ColumnConstraint::Default(binary(
+                        E::Int(1),
+                        ArithmeticOperator::Add,
+                        E::Int(2),
+                    )),
+                    ColumnConstraint::Check(E::Comparison {
+                        left: E::Ident("amount".into()).into(),
+                        operator: InfixOperator::GreaterThan,
+                        right: E::Int(0).into(),
+                    }),
+                ],
+            )),
+        ),
+        (
+            "created TIMESTAMP DEFAULT NOW()",
+            Some(E::ColumnDefinition(
+                "created".into(),
+                ColumnType::Timestamp,
+                vec![ColumnConstraint::Default(E::FunctionCall {
+                    name: "NOW".into(),
+                    argument: None,
+                })],
+            )),
+        ),
+        (
+            "CHECK (a = 1 OR b = 2)",
+            Some(E::CheckConstraint(
+                E::Or(comparison("a", 1).into(), comparison("b", 2).into()).into(),
+            )),
+        ),
+        ("a INT DEFAULT", None),
+        ("a INT CHECK a > 0", None),
+        ("CHECK (a + 1)", None),
+    ];
+
+    test_all_cases(ColumnDefinition, &inputs)
+}
+
 #[test]
 fn parse_all_columns_character() {
     let inputs = [("*", Some(E::AllColumns)), ("asdf", None)];
@@ -156,7 +340,7 @@ fn where_parser_basic() {
     let inputs = [
         (
             "WHERE a = 5",
-            Some(E::Where {
+            Some(E::Comparison {
                 left: E::Ident("a".into()).into(),
                 operator: InfixOperator::Equals,
                 right: E::Int(5).into(),
@@ -164,7 +348,7 @@ fn where_parser_basic() {
         ),
         (
             "WHERE column >= other_column",
-            Some(E::Where {
+            Some(E::Comparison {
                 left: E::Ident("column".into()).into(),
                 operator: InfixOperator::GreaterThanEqual,
                 right: E::Ident("other_column".into()).into(),
@@ -172,7 +356,7 @@ fn where_parser_basic() {
         ),
         (
             "WHERE 10 <> 5",
-            Some(E::Where {
+            Some(E::Comparison {
                 left: E::Int(10).into(),
                 operator: InfixOperator::NotEqual,
                 right: E::Int(5).into(),
@@ -186,6 +370,156 @@ fn where_parser_basic() {
     test_all_cases(Where, &inputs);
 }
 
+#[test]
+fn where_parser_errors() {
+    let inputs = [
+        ("WHERE column", "a comparison operator", Token::Eof),
+        (
+            "column <> other_column",
+            "`WHERE`",
+            Token::Ident("column".into()),
+        ),
+        (
+            "WHERE * = 0",
+            "an identifier or a value or `(`",
+            Token::Asterisk,
+        ),
+        (
+            "WHERE a = )",
+            "an identifier or a value",
+            Token::RParenthesis,
+        ),
+    ];
+
+    inputs.into_iter().for_each(|(input, expected, found)| {
+        let error = Where.parse(&mut Lexer::lex(input).as_slice()).unwrap_err();
+
+        assert_eq!(error.expected, expected);
+        assert_eq!(error.found, found);
+    });
+}
+
+fn comparison(column: &str, value: i64) -> Expression {
+    return E::Comparison {
+        left: E::Ident(column.into()).into(),
+        operator: InfixOperator::Equals,
+        right: E::Int(value).into(),
+    };
+}
+
+#[test]
+fn where_parser_is_null() {
+    let inputs = [
+        (
+            "WHERE a IS NULL",
+            Some(E::IsNull {
+                expression: E::Ident("a".into()).into(),
+                negated: false,
+            }),
+        ),
+        (
+            "WHERE NOT a + 1 IS NOT NULL",
+            Some(E::Not(
+                E::IsNull {
+                    expression: E::Binary {
+                        left: E::Ident("a".into()).into(),
+                        operator: ArithmeticOperator::Add,
+                        right: E::Int(1).into(),
+                    }
+                    .into(),
+                    negated: true,
+                }
+                .into(),
+            )),
+        ),
+        (
+            "WHERE a = NULL",
+            Some(E::Comparison {
+                left: E::Ident("a".into()).into(),
+                operator: InfixOperator::Equals,
+                right: E::Null.into(),
+            }),
+        ),
+        ("WHERE a IS 5", None),
+        ("WHERE a IS NOT", None),
+    ];
+
+    test_all_cases(Where, &inputs);
+}
+
+#[test]
+fn where_parser_boolean_operators() {
+    let inputs = [
+        (
+            "WHERE a = 1 AND b = 2",
+            Some(E::And(comparison("a", 1).into(), comparison("b", 2).into())),
+        ),
+        (
+            "WHERE a = 1 OR b = 2",
+            Some(E::Or(comparison("a", 1).into(), comparison("b", 2).into())),
+        ),
+        ("WHERE NOT a = 1", Some(E::Not(comparison("a", 1).into()))),
+        // Left associative
+        (
+            "WHERE a = 1 OR b = 2 OR c = 3",
+            Some(E::Or(
+                E::Or(comparison("a", 1).into(), comparison("b", 2).into()).into(),
+                comparison("c", 3).into(),
+            )),
+        ),
+        ("WHERE a = 1 AND", None),
+        ("WHERE NOT", None),
+    ];
+
+    test_all_cases(Where, &inputs);
+}
+
+#[test]
+fn where_parser_precedence() {
+    let inputs = [
+        // AND binds tighter than OR
+        (
+            "WHERE a = 1 OR b = 2 AND c = 3",
+            Some(E::Or(
+                comparison("a", 1).into(),
+                E::And(comparison("b", 2).into(), comparison("c", 3).into()).into(),
+            )),
+        ),
+        (
+            "WHERE a = 1 AND b = 2 OR c = 3",
+            Some(E::Or(
+                E::And(comparison("a", 1).into(), comparison("b", 2).into()).into(),
+                comparison("c", 3).into(),
+            )),
+        ),
+        // NOT binds tighter than AND
+        (
+            "WHERE NOT a = 1 AND b = 2",
+            Some(E::And(
+                E::Not(comparison("a", 1).into()).into(),
+                comparison("b", 2).into(),
+            )),
+        ),
+        // Parentheses override precedence
+        (
+            "WHERE (a = 1 OR b = 2) AND c = 3",
+            Some(E::And(
+                E::Or(comparison("a", 1).into(), comparison("b", 2).into()).into(),
+                comparison("c", 3).into(),
+            )),
+        ),
+        (
+            "WHERE NOT (a = 1 OR ((b = 2)))",
+            Some(E::Not(
+                E::Or(comparison("a", 1).into(), comparison("b", 2).into()).into(),
+            )),
+        ),
+        ("WHERE (a = 1 OR b = 2", None),
+    ];
+
+    test_all_cases(Where, &inputs);
+}
+
 #[test]
 fn array_basic() {
     let inputs = [
@@ -193,7 +527,7 @@ fn array_basic() {
             "(1, 2.3, 'hey', 4)",
             Some(E::Array(vec![
                 E::Int(1),
-                E::Decimal(2, 3),
+                E::Decimal(23, 1),
                 E::Str("hey".into()),
                 E::Int(4),
             ])),
@@ -204,3 +538,394 @@ fn array_basic() {
 
     test_all_cases(Array, &inputs);
 }
+
+fn binary(left: Expression, operator: ArithmeticOperator, right: Expression) -> Expression {
+    return E::Binary {
+        left: left.into(),
+        operator,
+        right: right.into(),
+    };
+}
+
+#[test]
+fn arithmetic_basic() {
+    use ArithmeticOperator as A;
+
+    let inputs = [
+        (
+            "a + 1",
+            Some(binary(E::Ident("a".into()), A::Add, E::Int(1))),
+        ),
+        (
+            "price * qty",
+            Some(binary(
+                E::Ident("price".into()),
+                A::Multiply,
+                E::Ident("qty".into()),
+            )),
+        ),
+        (
+            "a - b / 2",
+            Some(binary(
+                E::Ident("a".into()),
+                A::Subtract,
+                binary(E::Ident("b".into()), A::Divide, E::Int(2)),
+            )),
+        ),
+        (
+            "-a",
+            Some(E::Unary {
+                operator: UnaryOperator::Minus,
+                operand: E::Ident("a".into()).into(),
+            }),
+        ),
+        ("5", Some(E::Int(5))),
+        // Negative numbers are values right away
+        ("-5", Some(E::Int(-5))),
+        (
+            "a - -1.05",
+            Some(binary(
+                E::Ident("a".into()),
+                A::Subtract,
+                E::Decimal(-105, 2),
+            )),
+        ),
+        ("-9223372036854775808", Some(E::Int(i64::MIN))),
+        ("-9223372036854775809", None),
+        ("9223372036854775808", None),
+        (
+            "-(5)",
+            Some(E::Unary {
+                operator: UnaryOperator::Minus,
+                operand: E::Int(5).into(),
+            }),
+        ),
+        ("a +", None),
+        ("(a + 1", None),
+    ];
+
+    test_all_cases(Arithmetic, &inputs);
+}
+
+#[test]
+fn arithmetic_precedence() {
+    use ArithmeticOperator as A;
+
+    let inputs = [
+        // Left associative
+        (
+            "a - b - c",
+            Some(binary(
+                binary(E::Ident("a".into()), A::Subtract, E::Ident("b".into())),
+                A::Subtract,
+                E::Ident("c".into()),
+            )),
+        ),
+        // Multiplication binds tighter than addition
+        (
+            "1 + 2 * 3",
+            Some(binary(
+                E::Int(1),
+                A::Add,
+                binary(E::Int(2), A::Multiply, E::Int(3)),
+            )),
+        ),
+        // Parentheses override precedence
+        (
+            "(1 + 2) * 3",
+            Some(binary(
+                binary(E::Int(1), A::Add, E::Int(2)),
+                A::Multiply,
+                E::Int(3),
+            )),
+        ),
+        // Unary minus binds tightest
+        (
+            "-a * 2",
+            Some(binary(
+                E::Unary {
+                    operator: UnaryOperator::Minus,
+                    operand: E::Ident("a".into()).into(),
+                },
+                A::Multiply,
+                E::Int(2),
+            )),
+        ),
+        ("-1 * 2", Some(binary(E::Int(-1), A::Multiply, E::Int(2)))),
+    ];
+
+    test_all_cases(Arithmetic, &inputs);
+}
+
+#[test]
+fn where_parser_arithmetic() {
+    let inputs = [
+        (
+            "WHERE a - b > 3",
+            Some(E::Comparison {
+                left: binary(
+                    E::Ident("a".into()),
+                    ArithmeticOperator::Subtract,
+                    E::Ident("b".into()),
+                )
+                .into(),
+                operator: InfixOperator::GreaterThan,
+                right: E::Int(3).into(),
+            }),
+        ),
+        // Parenthesised arithmetic on the left of a comparison isn't mistaken for a condition
+        (
+            "WHERE (a + 1) * 2 = 4 AND (b = 1)",
+            Some(E::And(
+                E::Comparison {
+                    left: binary(
+                        binary(E::Ident("a".into()), ArithmeticOperator::Add, E::Int(1)),
+                        ArithmeticOperator::Multiply,
+                        E::Int(2),
+                    )
+                    .into(),
+                    operator: InfixOperator::Equals,
+                    right: E::Int(4).into(),
+                }
+                .into(),
+                comparison("b", 1).into(),
+            )),
+        ),
+    ];
+
+    test_all_cases(Where, &inputs);
+}
+
+#[test]
+fn column_reference_basic() {
+    let inputs = [
+        ("a", Some(E::Ident("a".into()))),
+        ("tbl.a", Some(E::QualifiedIdent("tbl".into(), "a".into()))),
+        ("tbl.", None),
+        ("tbl.*", None),
+    ];
+
+    test_all_cases(ColumnReference, &inputs);
+}
+
+#[test]
+fn table_reference_join() {
+    let on = |left: &str, right: &str| {
+        Some(Box::new(E::Comparison {
+            left: E::Ident(left.into()).into(),
+            operator: InfixOperator::Equals,
+            right: E::Ident(right.into()).into(),
+        }))
+    };
+
+    let inputs = [
+        ("a", Some(E::Ident("a".into()))),
+        (
+            "a INNER JOIN b ON x = y",
+            Some(E::Join {
+                left: E::Ident("a".into()).into(),
+                right: E::Ident("b".into()).into(),
+                kind: JoinType::Inner,
+                on: on("x", "y"),
+            }),
+        ),
+        (
+            "a LEFT JOIN b ON x = y",
+            Some(E::Join {
+                left: E::Ident("a".into()).into(),
+                right: E::Ident("b".into()).into(),
+                kind: JoinType::Left,
+                on: on("x", "y"),
+            }),
+        ),
+        // Left associative
+        (
+            "a JOIN b ON x = y JOIN c ON y = z",
+            Some(E::Join {
+                left: E::Join {
+                    left: E::Ident("a".into()).into(),
+                    right: E::Ident("b".into()).into(),
+                    kind: JoinType::Inner,
+                    on: on("x", "y"),
+                }
+                .into(),
+                right: E::Ident("c".into()).into(),
+                kind: JoinType::Inner,
+                on: on("y", "z"),
+            }),
+        ),
+        ("a INNER b ON x = y", None),
+        ("a JOIN ON x = y", None),
+    ];
+
+    test_all_cases(TableReference, &inputs);
+}
+
+#[test]
+fn order_by_parser_basic() {
+    let inputs = [
+        (
+            "ORDER BY a + 1 DESC, b",
+            Some(E::Array(vec![
+                E::SortKey {
+                    expression: binary(E::Ident("a".into()), ArithmeticOperator::Add, E::Int(1))
+                        .into(),
+                    direction: SortDirection::Descending,
+                },
+                E::SortKey {
+                    expression: E::Ident("b".into()).into(),
+                    direction: SortDirection::Ascending,
+                },
+            ])),
+        ),
+        ("ORDER BY", None),
+        ("ORDER a", None),
+    ];
+
+    test_all_cases(OrderBy, &inputs);
+}
+
+#[test]
+fn function_call_basic() {
+    let call = |name: &str, argument: Expression| E::FunctionCall {
+        name: name.into(),
+        argument: Some(argument.into()),
+    };
+
+    let inputs = [
+        ("COUNT(*)", Some(call("COUNT", E::AllColumns))),
+        ("sum(price)", Some(call("sum", E::Ident("price".into())))),
+        (
+            "MAX(a * 2) + 1",
+            Some(binary(
+                call(
+                    "MAX",
+                    binary(
+                        E::Ident("a".into()),
+                        ArithmeticOperator::Multiply,
+                        E::Int(2),
+                    ),
+                ),
+                ArithmeticOperator::Add,
+                E::Int(1),
+            )),
+        ),
+        // Not a function call
+        ("count", Some(E::Ident("count".into()))),
+        // Whether a function takes no arguments is up to the function
+        (
+            "now()",
+            Some(E::FunctionCall {
+                name: "now".into(),
+                argument: None,
+            }),
+        ),
+        ("COUNT(a", None),
+        ("COUNT(a, b)", None),
+    ];
+
+    test_all_cases(Arithmetic, &inputs);
+}
+
+#[test]
+fn typed_literals() {
+    let inputs = [
+        (
+            "DATE '2026-01-01'",
+            Some(E::TypedLiteral(ColumnType::Date, "2026-01-01".into())),
+        ),
+        (
+            "time '12:30:00'",
+            Some(E::TypedLiteral(ColumnType::Time, "12:30:00".into())),
+        ),
+        (
+            "TIMESTAMP '2026-01-01 12:30:00'",
+            Some(E::TypedLiteral(
+                ColumnType::Timestamp,
+                "2026-01-01 12:30:00".into(),
+            )),
+        ),
+        (
+            "created_at + INTERVAL '3 days'",
+            Some(binary(
+                E::Ident("created_at".into()),
+                ArithmeticOperator::Add,
+                E::TypedLiteral(ColumnType::Interval, "3 days".into()),
+            )),
+        ),
+        // The string isn't checked by the parser
+        (
+            "DATE 'tomorrow'",
+            Some(E::TypedLiteral(ColumnType::Date, "tomorrow".into())),
+        ),
+        (
+            "FLOAT 'NaN'",
+            Some(E::TypedLiteral(ColumnType::Float, "NaN".into())),
+        ),
+        ("DATE 5", None),
+        ("DATE", None),
+    ];
+
+    test_all_cases(Arithmetic, &inputs);
+}
+
+#[test]
+fn extract() {
+    let extract = |field, source| E::Extract {
+        field,
+        source: Box::new(source),
+    };
+
+    let inputs = [
+        (
+            "EXTRACT(YEAR FROM created_at)",
+            Some(extract(DateTimeField::Year, E::Ident("created_at".into()))),
+        ),
+        (
+            "extract(second from TIME '12:30:15') * 2",
+            Some(binary(
+                extract(
+                    DateTimeField::Second,
+                    E::TypedLiteral(ColumnType::Time, "12:30:15".into()),
+                ),
+                ArithmeticOperator::Multiply,
+                E::Int(2),
+            )),
+        ),
+        ("EXTRACT(WEEK FROM created_at)", None),
+        ("EXTRACT(YEAR created_at)", None),
+        ("EXTRACT(YEAR FROM created_at", None),
+    ];
+
+    test_all_cases(Arithmetic, &inputs);
+}
+
+#[test]
+fn display_round_trip() {
+    let inputs = [
+        "a + 2 * (b - 1.05)",
+        "-(a) / -3 - -0.5",
+        "price * 1.5e3 > 10 AND (NOT (name IS NULL) OR tbl.flag = TRUE)",
+        "(a = 1 OR b <> 2) AND c IS NOT NULL",
+        "EXTRACT(YEAR FROM created) >= 2026",
+        "NOW() < TIMESTAMP '2026-01-01 00:00:00'",
+        "COUNT(*) <= 5",
+        "data = X'DEADBEEF' OR data = NULL OR name = 'it'",
+    ];
+
+    for input in inputs {
+        let expression = Condition
+            .or(Arithmetic)
+            .parse(&mut Lexer::lex(input).as_slice())
+            .unwrap();
+
+        let printed = expression.to_string();
+
+        let reparsed = Condition
+            .or(Arithmetic)
+            .parse(&mut Lexer::lex(&printed).as_slice())
+            .unwrap();
+
+        assert_eq!(reparsed, expression, "{input} was printed as {printed}");
+    }
+}
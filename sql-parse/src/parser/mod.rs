@@ -1,7 +1,15 @@
 mod combinators;
+mod error;
 mod expressions;
 pub mod statements;
 mod utils;
 
-pub use expressions::{ColumnType, Expression, InfixOperator};
-pub use statements::{CreateType, Statement};
+pub use error::{ParseError, Result};
+pub use expressions::{
+    ArithmeticOperator, ColumnConstraint, ColumnType, DateTimeField, Expression, InfixOperator,
+    JoinType, ReferentialAction, SortDirection, UnaryOperator, MAX_DECIMAL_PRECISION,
+};
+pub use statements::{AlterTableAction, CreateType, Statement};
+
+pub(crate) use combinators::Chain;
+pub(crate) use expressions::{Arithmetic, Condition, ExpressionParser};
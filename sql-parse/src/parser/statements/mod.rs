@@ -2,19 +2,28 @@
 mod tests;
 
 use super::combinators::Chain;
+use super::error::{ParseError, Result};
 use super::expressions::{
-    AllColumn, Array, ColumnDefinition, Expression, ExpressionParser, Identifier, Value, Where,
+    AllColumn, Arithmetic, Array, ColumnDefinition, Expression, ExpressionParser, GroupBy, Having,
+    Identifier, Limit, Offset, OrderBy, TableReference, Where,
 };
-use super::utils::check_and_skip;
+use super::utils::{check_and_skip, parse_optional};
 use crate::lexer::Token;
 
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
+// Only one statement is alive at a time, so the size doesn't really matter
+#[allow(clippy::large_enum_variant)]
 pub enum Statement {
     Select {
         table: Expression,
         columns: Expression,
         where_clause: Option<Expression>,
+        group_by: Option<Expression>, // Array
+        having: Option<Expression>,
+        order_by: Option<Expression>, // Array of SortKeys
+        limit: Option<Expression>,    // Int
+        offset: Option<Expression>,   // Int
     },
     Create {
         what: CreateType,
@@ -40,79 +49,146 @@ pub enum Statement {
         what: CreateType,
         name: Expression,
     },
+    CreateSequence {
+        name: Expression,
+        start: Option<Expression>,     // Int
+        increment: Option<Expression>, // Int
+    },
+    AlterTable {
+        name: Expression,
+        action: AlterTableAction,
+    },
+    CreateIndex {
+        name: Expression,
+        table: Expression,
+        columns: Expression, // Array of Idents
+        unique: bool,
+    },
+    Explain(Box<Statement>), // Select, Update or Delete
+    Begin,
+    Commit,
+    Rollback,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum CreateType {
     Database,
     Table,
+    Sequence,
+    Index,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AlterTableAction {
+    AddColumn(Expression),  // ColumnDefinition
+    DropColumn(Expression), // Ident
+    RenameColumn { from: Expression, to: Expression },
+    RenameTable(Expression), // Ident
 }
 
 pub trait StatementParser {
-    fn parse(&self, input: &[Token]) -> Option<Statement>;
+    fn parse(&self, input: &[Token]) -> Result<Statement>;
 }
 
 pub struct Select;
 impl StatementParser for Select {
-    fn parse(&self, mut input: &[Token]) -> Option<Statement> {
+    fn parse(&self, mut input: &[Token]) -> Result<Statement> {
         let input = &mut input;
 
         check_and_skip(input, Token::Select)?;
 
-        let columns = Identifier.multiple().or(AllColumn).parse(input)?;
+        let columns = Arithmetic.multiple().or(AllColumn).parse(input)?;
 
         check_and_skip(input, Token::From)?;
 
-        let table = Identifier.parse(input)?;
+        let table = TableReference.parse(input)?;
+
+        let where_clause = parse_optional(input, Token::Where, Where)?;
+
+        let group_by = parse_optional(input, Token::Group, GroupBy)?;
 
-        let where_clause = Where.parse(input);
+        let having = parse_optional(input, Token::Having, Having)?;
+
+        let order_by = parse_optional(input, Token::Order, OrderBy)?;
+
+        let limit = parse_optional(input, Token::Limit, Limit)?;
+
+        let offset = parse_optional(input, Token::Offset, Offset)?;
 
         check_and_skip(input, Token::Semicolon)?;
 
-        return Some(Statement::Select {
+        return Ok(Statement::Select {
             columns,
             table,
             where_clause,
+            group_by,
+            having,
+            order_by,
+            limit,
+            offset,
         });
     }
 }
 
-fn parse_table_or_database(input: &mut &[Token]) -> Option<CreateType> {
-    let which = match input.first()? {
-        Token::Table => Some(CreateType::Table),
-        Token::Database => Some(CreateType::Database),
-        _ => None,
-    }?;
+fn parse_create_type(input: &mut &[Token]) -> Result<CreateType> {
+    let which = match input.first() {
+        Some(Token::Table) => CreateType::Table,
+        Some(Token::Database) => CreateType::Database,
+        Some(Token::Sequence) => CreateType::Sequence,
+        Some(Token::Index) => CreateType::Index,
+        _ => {
+            return Err(ParseError::new(
+                "`TABLE`, `DATABASE`, `SEQUENCE` or `INDEX`",
+                input,
+            ))
+        }
+    };
 
     *input = &input[1..];
 
-    return Some(which);
+    return Ok(which);
 }
 
 pub struct Create;
 impl StatementParser for Create {
-    fn parse(&self, mut input: &[Token]) -> Option<Statement> {
+    fn parse(&self, mut input: &[Token]) -> Result<Statement> {
         let input = &mut input;
 
         check_and_skip(input, Token::Create)?;
 
-        let what = parse_table_or_database(input)?;
+        if input.first() == Some(&Token::Unique) {
+            *input = &input[1..];
+
+            return parse_index(input, true);
+        }
+
+        let what = parse_create_type(input)?;
+
+        if what == CreateType::Index {
+            return parse_index(input, false);
+        }
 
         let name = Identifier.parse(input)?;
 
+        if what == CreateType::Sequence {
+            return parse_sequence_options(input, name);
+        }
+
         let mut columns = None;
 
         if what == CreateType::Table {
             check_and_skip(input, Token::LParenthesis)?;
 
-            columns = ColumnDefinition.multiple().parse(input);
+            if input.first() != Some(&Token::RParenthesis) {
+                columns = Some(ColumnDefinition.multiple().parse(input)?);
+            }
 
             check_and_skip(input, Token::RParenthesis)?;
         }
 
         check_and_skip(input, Token::Semicolon)?;
 
-        return Some(Statement::Create {
+        return Ok(Statement::Create {
             what,
             name,
             columns,
@@ -120,9 +196,67 @@ impl StatementParser for Create {
     }
 }
 
+/// Parses `[START [WITH] <int>] [INCREMENT [BY] <int>]` in any order, and the rest of the statement.
+fn parse_sequence_options(input: &mut &[Token], name: Expression) -> Result<Statement> {
+    let mut start = None;
+    let mut increment = None;
+
+    loop {
+        let (option, optional_word) = match input.first() {
+            Some(Token::Start) if start.is_none() => (&mut start, Token::With),
+            Some(Token::Increment) if increment.is_none() => (&mut increment, Token::By),
+            _ => break,
+        };
+
+        *input = &input[1..];
+
+        // Ignoring the error, since the word is optional
+        let _ = check_and_skip(input, optional_word);
+
+        // Negative numbers are parsed as part of arithmetic
+        *option = Some(Arithmetic.parse(input)?);
+    }
+
+    check_and_skip(input, Token::Semicolon)?;
+
+    return Ok(Statement::CreateSequence {
+        name,
+        start,
+        increment,
+    });
+}
+
+/// Parses the rest of `CREATE [UNIQUE] INDEX <name> ON <table> (<columns>);`, after `INDEX`.
+fn parse_index(input: &mut &[Token], unique: bool) -> Result<Statement> {
+    if unique {
+        check_and_skip(input, Token::Index)?;
+    }
+
+    let name = Identifier.parse(input)?;
+
+    check_and_skip(input, Token::On)?;
+
+    let table = Identifier.parse(input)?;
+
+    check_and_skip(input, Token::LParenthesis)?;
+
+    let columns = Identifier.multiple().parse(input)?;
+
+    check_and_skip(input, Token::RParenthesis)?;
+
+    check_and_skip(input, Token::Semicolon)?;
+
+    return Ok(Statement::CreateIndex {
+        name,
+        table,
+        columns,
+        unique,
+    });
+}
+
 pub struct Insert;
 impl StatementParser for Insert {
-    fn parse(&self, mut input: &[Token]) -> Option<Statement> {
+    fn parse(&self, mut input: &[Token]) -> Result<Statement> {
         let input = &mut input;
 
         check_and_skip(input, Token::Insert)?;
@@ -133,7 +267,7 @@ impl StatementParser for Insert {
 
         let mut columns = None;
 
-        if input.first()? != &Token::Values {
+        if input.first() != Some(&Token::Values) {
             check_and_skip(input, Token::LParenthesis)?;
 
             columns = Some(Identifier.multiple().parse(input)?);
@@ -147,7 +281,7 @@ impl StatementParser for Insert {
 
         check_and_skip(input, Token::Semicolon)?;
 
-        return Some(Statement::Insert {
+        return Ok(Statement::Insert {
             into,
             columns,
             values,
@@ -157,7 +291,7 @@ impl StatementParser for Insert {
 
 pub struct Update;
 impl StatementParser for Update {
-    fn parse(&self, mut input: &[Token]) -> Option<Statement> {
+    fn parse(&self, mut input: &[Token]) -> Result<Statement> {
         let input = &mut input;
 
         check_and_skip(input, Token::Update)?;
@@ -169,14 +303,14 @@ impl StatementParser for Update {
         #[derive(Debug)]
         struct ColumnValuePair;
         impl ExpressionParser for ColumnValuePair {
-            fn parse(&self, input: &mut &[Token]) -> Option<Expression> {
+            fn parse(&self, input: &mut &[Token]) -> Result<Expression> {
                 let column = Identifier.parse(input)?;
 
                 check_and_skip(input, Token::Equals)?;
 
-                let value = Value.parse(input)?;
+                let value = Arithmetic.parse(input)?;
 
-                return Some(Expression::ColumnValuePair {
+                return Ok(Expression::ColumnValuePair {
                     column: column.into(),
                     value: value.into(),
                 });
@@ -194,11 +328,11 @@ impl StatementParser for Update {
             (columns, values) = pairs.into_iter().map(destructure_column_value_pair).unzip();
         }
 
-        let where_clause = Where.parse(input);
+        let where_clause = parse_optional(input, Token::Where, Where)?;
 
         check_and_skip(input, Token::Semicolon)?;
 
-        return Some(Statement::Update {
+        return Ok(Statement::Update {
             from,
             columns: Expression::Array(columns),
             values: Expression::Array(values),
@@ -217,7 +351,7 @@ fn destructure_column_value_pair(pair: Expression) -> (Expression, Expression) {
 
 pub struct Delete;
 impl StatementParser for Delete {
-    fn parse(&self, mut input: &[Token]) -> Option<Statement> {
+    fn parse(&self, mut input: &[Token]) -> Result<Statement> {
         let input = &mut input;
 
         check_and_skip(input, Token::Delete)?;
@@ -226,28 +360,143 @@ impl StatementParser for Delete {
 
         let from = Identifier.parse(input)?;
 
-        let where_clause = Where.parse(input);
+        let where_clause = parse_optional(input, Token::Where, Where)?;
 
         check_and_skip(input, Token::Semicolon)?;
 
-        return Some(Statement::Delete { from, where_clause });
+        return Ok(Statement::Delete { from, where_clause });
     }
 }
 
 #[derive(Debug)]
 pub struct Drop;
 impl StatementParser for Drop {
-    fn parse(&self, mut input: &[Token]) -> Option<Statement> {
+    fn parse(&self, mut input: &[Token]) -> Result<Statement> {
         let input = &mut input;
 
         check_and_skip(input, Token::Drop)?;
 
-        let what = parse_table_or_database(input)?;
+        let what = parse_create_type(input)?;
 
         let name = Identifier.parse(input)?;
 
         check_and_skip(input, Token::Semicolon)?;
 
-        return Some(Statement::Drop { what, name });
+        return Ok(Statement::Drop { what, name });
+    }
+}
+
+pub struct Alter;
+impl StatementParser for Alter {
+    fn parse(&self, mut input: &[Token]) -> Result<Statement> {
+        let input = &mut input;
+
+        check_and_skip(input, Token::Alter)?;
+
+        check_and_skip(input, Token::Table)?;
+
+        let name = Identifier.parse(input)?;
+
+        let action = match input.first() {
+            Some(Token::Add) => {
+                *input = &input[1..];
+
+                skip_column_word(input);
+
+                // Table constraints would start with a keyword instead
+                if !matches!(input.first(), Some(Token::Ident(_))) {
+                    return Err(ParseError::new("a column definition", input));
+                }
+
+                AlterTableAction::AddColumn(ColumnDefinition.parse(input)?)
+            }
+            Some(Token::Drop) => {
+                *input = &input[1..];
+
+                skip_column_word(input);
+
+                AlterTableAction::DropColumn(Identifier.parse(input)?)
+            }
+            Some(Token::Rename) => {
+                *input = &input[1..];
+
+                if check_and_skip(input, Token::To).is_ok() {
+                    AlterTableAction::RenameTable(Identifier.parse(input)?)
+                } else {
+                    skip_column_word(input);
+
+                    let from = Identifier.parse(input)?;
+
+                    check_and_skip(input, Token::To)?;
+
+                    let to = Identifier.parse(input)?;
+
+                    AlterTableAction::RenameColumn { from, to }
+                }
+            }
+            _ => return Err(ParseError::new("`ADD`, `DROP` or `RENAME`", input)),
+        };
+
+        check_and_skip(input, Token::Semicolon)?;
+
+        return Ok(Statement::AlterTable { name, action });
+    }
+}
+
+pub struct Explain;
+impl StatementParser for Explain {
+    fn parse(&self, input: &[Token]) -> Result<Statement> {
+        let mut rest = input;
+
+        check_and_skip(&mut rest, Token::Explain)?;
+
+        let statement = match rest.first() {
+            Some(Token::Select) => Select.parse(rest)?,
+            Some(Token::Update) => Update.parse(rest)?,
+            Some(Token::Delete) => Delete.parse(rest)?,
+            _ => return Err(ParseError::new("`SELECT`, `UPDATE` or `DELETE`", rest)),
+        };
+
+        return Ok(Statement::Explain(Box::new(statement)));
+    }
+}
+
+/// `BEGIN`, `COMMIT` or `ROLLBACK`, each optionally followed by the word `TRANSACTION`.
+pub struct Transaction;
+impl StatementParser for Transaction {
+    fn parse(&self, mut input: &[Token]) -> Result<Statement> {
+        let input = &mut input;
+
+        let statement = match input.first() {
+            Some(Token::Begin) => Statement::Begin,
+            Some(Token::Commit) => Statement::Commit,
+            Some(Token::Rollback) => Statement::Rollback,
+            _ => return Err(ParseError::new("`BEGIN`, `COMMIT` or `ROLLBACK`", input)),
+        };
+
+        *input = &input[1..];
+
+        // Not a keyword either, like `COLUMN`
+        if let [Token::Ident(word), ..] = input {
+            if word.eq_ignore_ascii_case("transaction") {
+                *input = &input[1..];
+            }
+        }
+
+        check_and_skip(input, Token::Semicolon)?;
+
+        return Ok(statement);
+    }
+}
+
+/// Skips the optional word `COLUMN` after `ADD`, `DROP` and `RENAME`.
+///
+/// It isn't a keyword, so that columns can still be called `column`,
+/// which is why it's only skipped if the name of a column follows it.
+fn skip_column_word(input: &mut &[Token]) {
+    if let [Token::Ident(word), Token::Ident(_), ..] = input {
+        if word.eq_ignore_ascii_case("column") {
+            *input = &input[1..];
+        }
     }
 }
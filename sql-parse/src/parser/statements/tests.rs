@@ -1,4 +1,7 @@
-use super::super::expressions::{ColumnType, InfixOperator};
+use super::super::expressions::{
+    ArithmeticOperator, ColumnConstraint, ColumnType, InfixOperator, JoinType, ReferentialAction,
+    SortDirection,
+};
 use super::*;
 use crate::lexer::Lexer;
 
@@ -8,7 +11,7 @@ pub fn test_all_cases(parser: impl StatementParser, inputs: &[(&str, Option<Stat
     inputs.iter().for_each(|test_case| {
         let result = parser.parse(Lexer::lex(test_case.0).as_slice());
 
-        assert_eq!(result, test_case.1);
+        assert_eq!(result.ok(), test_case.1);
     });
 }
 
@@ -21,6 +24,11 @@ fn select_basic() {
                 columns: E::Array(vec![E::Ident("bla".into())]),
                 table: E::Ident("asdf".into()),
                 where_clause: None,
+                group_by: None,
+                having: None,
+                order_by: None,
+                limit: None,
+                offset: None,
             },
         ),
         (
@@ -29,6 +37,11 @@ fn select_basic() {
                 columns: E::AllColumns,
                 table: E::Ident("asdf".into()),
                 where_clause: None,
+                group_by: None,
+                having: None,
+                order_by: None,
+                limit: None,
+                offset: None,
             },
         ),
     ];
@@ -36,7 +49,7 @@ fn select_basic() {
     inputs.into_iter().for_each(|test_case| {
         let result = Select.parse(&Lexer::lex(test_case.0));
 
-        assert_eq!(result, Some(test_case.1));
+        assert_eq!(result, Ok(test_case.1));
     });
 }
 
@@ -48,18 +61,224 @@ fn select_with_where() {
 
     assert_eq!(
         result,
-        Some(S::Select {
+        Ok(S::Select {
             columns: E::Array(vec![E::Ident("bla".into())]),
             table: E::Ident("asdf".into()),
-            where_clause: Some(E::Where {
+            where_clause: Some(E::Comparison {
                 left: E::Ident("a".into()).into(),
                 operator: InfixOperator::GreaterThan,
                 right: E::Ident("b".into()).into(),
-            })
+            }),
+            group_by: None,
+            having: None,
+            order_by: None,
+            limit: None,
+            offset: None,
         })
     )
 }
 
+#[test]
+fn select_with_arithmetic() {
+    let inputs = [(
+        "SELECT price * qty, id FROM orders;",
+        Some(S::Select {
+            columns: E::Array(vec![
+                E::Binary {
+                    left: E::Ident("price".into()).into(),
+                    operator: ArithmeticOperator::Multiply,
+                    right: E::Ident("qty".into()).into(),
+                },
+                E::Ident("id".into()),
+            ]),
+            table: E::Ident("orders".into()),
+            where_clause: None,
+            group_by: None,
+            having: None,
+            order_by: None,
+            limit: None,
+            offset: None,
+        }),
+    )];
+
+    test_all_cases(Select, &inputs);
+}
+
+#[test]
+fn select_with_join() {
+    let inputs = [
+        (
+            "SELECT orders.id, name FROM orders JOIN customers ON orders.customer = customers.id;",
+            Some(S::Select {
+                columns: E::Array(vec![
+                    E::QualifiedIdent("orders".into(), "id".into()),
+                    E::Ident("name".into()),
+                ]),
+                table: E::Join {
+                    left: E::Ident("orders".into()).into(),
+                    right: E::Ident("customers".into()).into(),
+                    kind: JoinType::Inner,
+                    on: Some(
+                        E::Comparison {
+                            left: E::QualifiedIdent("orders".into(), "customer".into()).into(),
+                            operator: InfixOperator::Equals,
+                            right: E::QualifiedIdent("customers".into(), "id".into()).into(),
+                        }
+                        .into(),
+                    ),
+                },
+                where_clause: None,
+                group_by: None,
+                having: None,
+                order_by: None,
+                limit: None,
+                offset: None,
+            }),
+        ),
+        (
+            "SELECT * FROM a CROSS JOIN b LEFT OUTER JOIN c ON x = y WHERE z = 1;",
+            Some(S::Select {
+                columns: E::AllColumns,
+                table: E::Join {
+                    left: E::Join {
+                        left: E::Ident("a".into()).into(),
+                        right: E::Ident("b".into()).into(),
+                        kind: JoinType::Cross,
+                        on: None,
+                    }
+                    .into(),
+                    right: E::Ident("c".into()).into(),
+                    kind: JoinType::Left,
+                    on: Some(
+                        E::Comparison {
+                            left: E::Ident("x".into()).into(),
+                            operator: InfixOperator::Equals,
+                            right: E::Ident("y".into()).into(),
+                        }
+                        .into(),
+                    ),
+                },
+                where_clause: Some(E::Comparison {
+                    left: E::Ident("z".into()).into(),
+                    operator: InfixOperator::Equals,
+                    right: E::Int(1).into(),
+                }),
+                group_by: None,
+                having: None,
+                order_by: None,
+                limit: None,
+                offset: None,
+            }),
+        ),
+        // Inner and left joins need a condition
+        ("SELECT * FROM a JOIN b;", None),
+        ("SELECT * FROM a LEFT JOIN b WHERE x = y;", None),
+        ("SELECT * FROM a CROSS JOIN b ON x = y;", None),
+    ];
+
+    test_all_cases(Select, &inputs);
+}
+
+#[test]
+fn select_with_order_by_and_limit() {
+    let sort_key = |name: &str, direction| E::SortKey {
+        expression: E::Ident(name.into()).into(),
+        direction,
+    };
+
+    let inputs = [
+        (
+            "SELECT * FROM t WHERE a > 1 ORDER BY a DESC, b LIMIT 10 OFFSET 20;",
+            Some(S::Select {
+                columns: E::AllColumns,
+                table: E::Ident("t".into()),
+                where_clause: Some(E::Comparison {
+                    left: E::Ident("a".into()).into(),
+                    operator: InfixOperator::GreaterThan,
+                    right: E::Int(1).into(),
+                }),
+                group_by: None,
+                having: None,
+                order_by: Some(E::Array(vec![
+                    sort_key("a", SortDirection::Descending),
+                    sort_key("b", SortDirection::Ascending),
+                ])),
+                limit: Some(E::Int(10)),
+                offset: Some(E::Int(20)),
+            }),
+        ),
+        (
+            "SELECT * FROM t ORDER BY a ASC OFFSET 5;",
+            Some(S::Select {
+                columns: E::AllColumns,
+                table: E::Ident("t".into()),
+                where_clause: None,
+                group_by: None,
+                having: None,
+                order_by: Some(E::Array(vec![sort_key("a", SortDirection::Ascending)])),
+                limit: None,
+                offset: Some(E::Int(5)),
+            }),
+        ),
+        ("SELECT * FROM t ORDER a;", None),
+        ("SELECT * FROM t LIMIT 'five';", None),
+        // Clauses have a fixed order
+        ("SELECT * FROM t LIMIT 5 ORDER BY a;", None),
+    ];
+
+    test_all_cases(Select, &inputs);
+}
+
+#[test]
+fn select_with_group_by_and_having() {
+    let count = E::FunctionCall {
+        name: "COUNT".into(),
+        argument: Some(E::AllColumns.into()),
+    };
+
+    let inputs = [
+        (
+            "SELECT a, COUNT(*) FROM t WHERE b = 1 GROUP BY a HAVING COUNT(*) > 1 ORDER BY a;",
+            Some(S::Select {
+                columns: E::Array(vec![E::Ident("a".into()), count.clone()]),
+                table: E::Ident("t".into()),
+                where_clause: Some(E::Comparison {
+                    left: E::Ident("b".into()).into(),
+                    operator: InfixOperator::Equals,
+                    right: E::Int(1).into(),
+                }),
+                group_by: Some(E::Array(vec![E::Ident("a".into())])),
+                having: Some(E::Comparison {
+                    left: count.into(),
+                    operator: InfixOperator::GreaterThan,
+                    right: E::Int(1).into(),
+                }),
+                order_by: Some(E::Array(vec![E::SortKey {
+                    expression: E::Ident("a".into()).into(),
+                    direction: SortDirection::Ascending,
+                }])),
+                limit: None,
+                offset: None,
+            }),
+        ),
+        ("SELECT a FROM t GROUP a;", None),
+        ("SELECT a FROM t HAVING;", None),
+    ];
+
+    test_all_cases(Select, &inputs);
+}
+
+#[test]
+fn select_with_invalid_where() {
+    let input = "SELECT bla FROM asdf WHERE a >;";
+
+    let error = Select.parse(Lexer::lex(input).as_slice()).unwrap_err();
+
+    // Broken `WHERE` clause is an error rather than being treated as absent
+    assert_eq!(error.expected, "an identifier or a value");
+    assert_eq!(error.found, Token::Semicolon);
+}
+
 #[test]
 fn create_basic() {
     let inputs = [
@@ -77,8 +296,31 @@ fn create_basic() {
                 what: CreateType::Table,
                 name: E::Ident("name".into()),
                 columns: Some(E::Array(vec![
-                    E::ColumnDefinition("a".into(), ColumnType::Bool),
-                    E::ColumnDefinition("b".into(), ColumnType::Int),
+                    E::ColumnDefinition("a".into(), ColumnType::Bool, vec![]),
+                    E::ColumnDefinition("b".into(), ColumnType::Int, vec![]),
+                ])),
+            }),
+        ),
+        (
+            "CREATE TABLE prices (amount DECIMAL(10, 2) NOT NULL);",
+            Some(S::Create {
+                what: CreateType::Table,
+                name: E::Ident("prices".into()),
+                columns: Some(E::Array(vec![E::ColumnDefinition(
+                    "amount".into(),
+                    ColumnType::Decimal(Some((10, 2))),
+                    vec![ColumnConstraint::NotNull],
+                )])),
+            }),
+        ),
+        (
+            "CREATE TABLE events (created_at TIMESTAMP, day DATE);",
+            Some(S::Create {
+                what: CreateType::Table,
+                name: E::Ident("events".into()),
+                columns: Some(E::Array(vec![
+                    E::ColumnDefinition("created_at".into(), ColumnType::Timestamp, vec![]),
+                    E::ColumnDefinition("day".into(), ColumnType::Date, vec![]),
                 ])),
             }),
         ),
@@ -98,18 +340,54 @@ fn create_table_foreign_key() {
         "CREATE TABLE tbl (
                 id INT,
                 foreign_id INT,
-                FOREIGN KEY (foreign_id) REFERENCES other_tbl(id)
+                FOREIGN KEY (foreign_id) REFERENCES other_tbl(id) ON DELETE CASCADE
             );",
         Some(S::Create {
             what: CreateType::Table,
             name: E::Ident("tbl".into()),
             columns: Some(E::Array(vec![
-                E::ColumnDefinition("id".into(), ColumnType::Int),
-                E::ColumnDefinition("foreign_id".into(), ColumnType::Int),
+                E::ColumnDefinition("id".into(), ColumnType::Int, vec![]),
+                E::ColumnDefinition("foreign_id".into(), ColumnType::Int, vec![]),
                 E::ForeignKeyConstraint {
                     column: Box::new(E::Ident("foreign_id".into())),
                     foreign_table: Box::new(E::Ident("other_tbl".into())),
                     foreign_column: Box::new(E::Ident("id".into())),
+                    on_delete: ReferentialAction::Cascade,
+                    on_update: ReferentialAction::Restrict,
+                },
+            ])),
+        }),
+    )];
+
+    test_all_cases(Create, &inputs);
+}
+
+#[test]
+fn create_table_keys() {
+    let inputs = [(
+        "CREATE TABLE order_lines (
+                order_id INT,
+                line INT,
+                sku TEXT UNIQUE,
+                PRIMARY KEY (order_id, line)
+            );",
+        Some(S::Create {
+            what: CreateType::Table,
+            name: E::Ident("order_lines".into()),
+            columns: Some(E::Array(vec![
+                E::ColumnDefinition("order_id".into(), ColumnType::Int, vec![]),
+                E::ColumnDefinition("line".into(), ColumnType::Int, vec![]),
+                E::ColumnDefinition(
+                    "sku".into(),
+                    ColumnType::Text,
+                    vec![ColumnConstraint::Unique],
+                ),
+                E::UniqueConstraint {
+                    columns: Box::new(E::Array(vec![
+                        E::Ident("order_id".into()),
+                        E::Ident("line".into()),
+                    ])),
+                    primary: true,
                 },
             ])),
         }),
@@ -118,6 +396,53 @@ fn create_table_foreign_key() {
     test_all_cases(Create, &inputs);
 }
 
+#[test]
+fn create_sequence() {
+    let inputs = [
+        (
+            "CREATE SEQUENCE ids;",
+            Some(S::CreateSequence {
+                name: E::Ident("ids".into()),
+                start: None,
+                increment: None,
+            }),
+        ),
+        (
+            "CREATE SEQUENCE ids INCREMENT BY -2 START 100;",
+            Some(S::CreateSequence {
+                name: E::Ident("ids".into()),
+                start: Some(E::Int(100)),
+                increment: Some(E::Int(-2)),
+            }),
+        ),
+        (
+            "CREATE SEQUENCE ids START WITH 5 INCREMENT 1;",
+            Some(S::CreateSequence {
+                name: E::Ident("ids".into()),
+                start: Some(E::Int(5)),
+                increment: Some(E::Int(1)),
+            }),
+        ),
+        (
+            "CREATE TABLE t (id SERIAL PRIMARY KEY);",
+            Some(S::Create {
+                what: CreateType::Table,
+                name: E::Ident("t".into()),
+                columns: Some(E::Array(vec![E::ColumnDefinition(
+                    "id".into(),
+                    ColumnType::Int,
+                    vec![ColumnConstraint::Serial, ColumnConstraint::PrimaryKey],
+                )])),
+            }),
+        ),
+        ("CREATE SEQUENCE ids START 1 START 2;", None),
+        ("CREATE SEQUENCE ids INCREMENT BY;", None),
+        ("CREATE SEQUENCE ids (a INT);", None),
+    ];
+
+    test_all_cases(Create, &inputs);
+}
+
 #[test]
 fn insert_basic() {
     let inputs = [
@@ -133,7 +458,7 @@ fn insert_basic() {
                 values: E::Array(vec![E::Array(vec![
                     E::Int(1),
                     E::Str("hey".into()),
-                    E::Decimal(420, 69),
+                    E::Decimal(42069, 2),
                 ])]),
             }),
         ),
@@ -153,7 +478,7 @@ fn insert_all_columns() {
         Some(S::Insert {
             into: E::Ident("tbl".into()),
             columns: None,
-            values: E::Array(vec![E::Array(vec![E::Int(1), E::Decimal(420, 69)])]),
+            values: E::Array(vec![E::Array(vec![E::Int(1), E::Decimal(42069, 2)])]),
         }),
     )];
 
@@ -168,8 +493,8 @@ fn insert_multiple_simultaneously() {
             into: E::Ident("bla".into()),
             columns: Some(E::Array(vec![E::Ident("a".into()), E::Ident("b".into())])),
             values: E::Array(vec![
-                E::Array(vec![E::Bool(true), E::Decimal(420, 69)]),
-                E::Array(vec![E::Bool(false), E::Decimal(69, 420)]),
+                E::Array(vec![E::Bool(true), E::Decimal(42069, 2)]),
+                E::Array(vec![E::Bool(false), E::Decimal(69420, 3)]),
             ]),
         }),
     )];
@@ -195,7 +520,7 @@ fn update_basic() {
                 from: E::Ident("tbl".into()),
                 columns: E::Array(vec![E::Ident("col".into())]),
                 values: E::Array(vec![E::Int(1)]),
-                where_clause: Some(E::Where {
+                where_clause: Some(E::Comparison {
                     left: E::Ident("other".into()).into(),
                     operator: InfixOperator::Equals,
                     right: E::Int(2).into(),
@@ -211,6 +536,19 @@ fn update_basic() {
                 where_clause: None,
             }),
         ),
+        (
+            "UPDATE tbl SET n = n + 1;",
+            Some(S::Update {
+                from: E::Ident("tbl".into()),
+                columns: E::Array(vec![E::Ident("n".into())]),
+                values: E::Array(vec![E::Binary {
+                    left: E::Ident("n".into()).into(),
+                    operator: ArithmeticOperator::Add,
+                    right: E::Int(1).into(),
+                }]),
+                where_clause: None,
+            }),
+        ),
         // Must end in semicolon
         ("UPDATE tbl set col1 = 1, col2 = 'value'", None),
     ];
@@ -232,7 +570,7 @@ fn delete_basic() {
             "DELETE FROM tbl WHERE col = 1;",
             Some(S::Delete {
                 from: E::Ident("tbl".into()),
-                where_clause: Some(E::Where {
+                where_clause: Some(E::Comparison {
                     left: E::Ident("col".into()).into(),
                     operator: InfixOperator::Equals,
                     right: E::Int(1).into(),
@@ -263,9 +601,167 @@ fn drop_basic() {
                 name: E::Ident("db".into()),
             }),
         ),
+        (
+            "DROP SEQUENCE ids;",
+            Some(S::Drop {
+                what: CreateType::Sequence,
+                name: E::Ident("ids".into()),
+            }),
+        ),
         // Must end in semicolon
         ("DROP TABLE tbl", None),
     ];
 
     test_all_cases(Drop, &inputs);
 }
+
+#[test]
+fn alter_table() {
+    let alter = |action| {
+        return Some(S::AlterTable {
+            name: E::Ident("t".into()),
+            action,
+        });
+    };
+
+    let inputs = [
+        (
+            "ALTER TABLE t ADD COLUMN c INT NOT NULL DEFAULT 1;",
+            alter(AlterTableAction::AddColumn(E::ColumnDefinition(
+                "c".into(),
+                ColumnType::Int,
+                vec![
+                    ColumnConstraint::NotNull,
+                    ColumnConstraint::Default(E::Int(1)),
+                ],
+            ))),
+        ),
+        (
+            "ALTER TABLE t ADD c TEXT;",
+            alter(AlterTableAction::AddColumn(E::ColumnDefinition(
+                "c".into(),
+                ColumnType::Text,
+                vec![],
+            ))),
+        ),
+        (
+            "ALTER TABLE t ADD column BOOL;",
+            alter(AlterTableAction::AddColumn(E::ColumnDefinition(
+                "column".into(),
+                ColumnType::Bool,
+                vec![],
+            ))),
+        ),
+        (
+            "ALTER TABLE t DROP COLUMN c;",
+            alter(AlterTableAction::DropColumn(E::Ident("c".into()))),
+        ),
+        (
+            "ALTER TABLE t DROP column;",
+            alter(AlterTableAction::DropColumn(E::Ident("column".into()))),
+        ),
+        (
+            "ALTER TABLE t RENAME COLUMN a TO b;",
+            alter(AlterTableAction::RenameColumn {
+                from: E::Ident("a".into()),
+                to: E::Ident("b".into()),
+            }),
+        ),
+        (
+            "ALTER TABLE t RENAME a TO b;",
+            alter(AlterTableAction::RenameColumn {
+                from: E::Ident("a".into()),
+                to: E::Ident("b".into()),
+            }),
+        ),
+        (
+            "ALTER TABLE t RENAME TO u;",
+            alter(AlterTableAction::RenameTable(E::Ident("u".into()))),
+        ),
+        ("ALTER TABLE t ADD CHECK (a > 0);", None),
+        ("ALTER TABLE t ADD COLUMN;", None),
+        ("ALTER TABLE t DROP COLUMN a b;", None),
+        ("ALTER TABLE t RENAME a b;", None),
+        ("ALTER TABLE t SET a = 1;", None),
+        ("ALTER t RENAME TO u;", None),
+    ];
+
+    test_all_cases(Alter, &inputs);
+}
+
+#[test]
+fn create_index() {
+    let inputs = [
+        (
+            "CREATE INDEX idx ON t (a);",
+            Some(S::CreateIndex {
+                name: E::Ident("idx".into()),
+                table: E::Ident("t".into()),
+                columns: E::Array(vec![E::Ident("a".into())]),
+                unique: false,
+            }),
+        ),
+        (
+            "CREATE UNIQUE INDEX idx ON t (a, b);",
+            Some(S::CreateIndex {
+                name: E::Ident("idx".into()),
+                table: E::Ident("t".into()),
+                columns: E::Array(vec![E::Ident("a".into()), E::Ident("b".into())]),
+                unique: true,
+            }),
+        ),
+        ("CREATE UNIQUE TABLE t (a INT);", None),
+        ("CREATE INDEX idx ON t ();", None),
+        ("CREATE INDEX idx t (a);", None),
+        ("CREATE INDEX ON t (a);", None),
+    ];
+
+    test_all_cases(Create, &inputs);
+
+    let inputs = [(
+        "DROP INDEX idx;",
+        Some(S::Drop {
+            what: CreateType::Index,
+            name: E::Ident("idx".into()),
+        }),
+    )];
+
+    test_all_cases(Drop, &inputs);
+}
+
+#[test]
+fn explain() {
+    let inputs = [
+        (
+            "EXPLAIN DELETE FROM t WHERE a = 1;",
+            Some(S::Explain(Box::new(S::Delete {
+                from: E::Ident("t".into()),
+                where_clause: Some(E::Comparison {
+                    left: Box::new(E::Ident("a".into())),
+                    operator: InfixOperator::Equals,
+                    right: Box::new(E::Int(1)),
+                }),
+            }))),
+        ),
+        ("EXPLAIN DROP TABLE t;", None),
+        ("EXPLAIN EXPLAIN DELETE FROM t;", None),
+        ("EXPLAIN;", None),
+    ];
+
+    test_all_cases(Explain, &inputs);
+}
+
+#[test]
+fn transaction() {
+    let inputs = [
+        ("BEGIN;", Some(S::Begin)),
+        ("BEGIN TRANSACTION;", Some(S::Begin)),
+        ("commit;", Some(S::Commit)),
+        ("ROLLBACK transaction;", Some(S::Rollback)),
+        ("BEGIN t;", None),
+        ("COMMIT TRANSACTION TRANSACTION;", None),
+        ("ROLLBACK", None),
+    ];
+
+    test_all_cases(Transaction, &inputs);
+}
@@ -1,17 +1,34 @@
+use super::error::{ParseError, Result};
+use super::expressions::{Expression, ExpressionParser};
 use crate::lexer::Token;
 
 /// Checks whether the first token in put is `equals`.
 ///
-/// If not, returns [`None`].
-/// If so, advances input by one token and returns [`Some`].
-pub fn check_and_skip(input: &mut &[Token], equals: Token) -> Option<()> {
+/// If not, returns a [`ParseError`].
+/// If so, advances input by one token and returns [`Ok`].
+pub fn check_and_skip(input: &mut &[Token], equals: Token) -> Result<()> {
     if input.first() != Some(&equals) {
-        return None;
+        return Err(ParseError::new(equals.to_string(), input));
     }
 
     *input = &input[1..];
 
-    return Some(());
+    return Ok(());
+}
+
+/// Parses an optional clause, which is present if the first token in input is `start`.
+///
+/// Returns [`None`] if the clause is absent, and an error if it is present but failed to parse.
+pub fn parse_optional(
+    input: &mut &[Token],
+    start: Token,
+    parser: impl ExpressionParser,
+) -> Result<Option<Expression>> {
+    if input.first() != Some(&start) {
+        return Ok(None);
+    }
+
+    return parser.parse(input).map(Some);
 }
 
 #[cfg(test)]
@@ -24,9 +41,22 @@ mod tests {
         let input = Lexer::lex("DROP TABLE bla;");
         let input = &mut input.as_slice();
 
-        assert_ne!(check_and_skip(input, Token::Drop), None);
-        assert_ne!(check_and_skip(input, Token::Table), None);
-        assert_ne!(check_and_skip(input, Token::Ident("bla".into())), None);
-        assert_ne!(check_and_skip(input, Token::Semicolon), None);
+        assert!(check_and_skip(input, Token::Drop).is_ok());
+        assert!(check_and_skip(input, Token::Table).is_ok());
+        assert!(check_and_skip(input, Token::Ident("bla".into())).is_ok());
+        assert!(check_and_skip(input, Token::Semicolon).is_ok());
+    }
+
+    #[test]
+    fn check_and_skip_error() {
+        let input = Lexer::lex("DROP bla;");
+        let input = &mut input.as_slice();
+
+        assert!(check_and_skip(input, Token::Drop).is_ok());
+
+        let error = check_and_skip(input, Token::Table).unwrap_err();
+
+        assert_eq!(error.expected, "`TABLE`");
+        assert_eq!(error.found, Token::Ident("bla".into()));
     }
 }